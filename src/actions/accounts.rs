@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use crate::database::adding::DB;
+use crate::settings::api_keys::{AppConfig, Rotation};
+
+// Reddit resets its rate limit window every 10 minutes
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(600);
+
+struct PooledAccount {
+    name: String,
+    token: String,
+    limited_until: Option<Instant>,
+    requests: usize,
+    rate_limited: usize,
+}
+
+// Access tokens for every configured Reddit account, handed out in turn
+pub struct AccountPool {
+    accounts: Vec<PooledAccount>,
+    current: usize,
+    rotation: Rotation,
+}
+
+impl AccountPool {
+    // Start from the token already issued for [api_keys] and log in the extra accounts
+    pub async fn with_primary(token: String, config: &AppConfig) -> Self {
+        let mut accounts = vec![PooledAccount::new("default", token)];
+
+        for account in &config.accounts {
+            match crate::get_access_token(account.client_id.clone(), account.client_secret.clone())
+                .await
+            {
+                Ok(token) if !token.is_empty() => {
+                    accounts.push(PooledAccount::new(&account.name, token))
+                }
+                Ok(_) => eprintln!(
+                    "Account '{}' returned an empty token, skipping",
+                    account.name
+                ),
+                Err(e) => eprintln!("Account '{}' failed to log in: {}", account.name, e),
+            }
+        }
+
+        if accounts.len() > 1 {
+            println!("Using {} Reddit accounts", accounts.len());
+        }
+
+        AccountPool {
+            accounts,
+            current: 0,
+            rotation: config.fetch.rotation,
+        }
+    }
+
+    // Token for the next request
+    pub fn token(&mut self) -> String {
+        if self.rotation == Rotation::Request {
+            self.advance();
+        }
+
+        let account = &mut self.accounts[self.current];
+        account.requests += 1;
+        account.token.clone()
+    }
+
+    // Whether a rate limited request can move on to another account
+    pub fn can_rotate(&self) -> bool {
+        self.accounts.len() > 1
+    }
+
+    // Mark the current account as rate limited and switch to the next usable one.
+    // Returns false when every account is rate limited.
+    pub fn rate_limited(&mut self) -> bool {
+        let account = &mut self.accounts[self.current];
+        account.rate_limited += 1;
+        account.limited_until = Some(Instant::now() + RATE_LIMIT_WINDOW);
+        eprintln!("Account '{}' hit the Reddit rate limit", account.name);
+
+        self.advance()
+    }
+
+    // Move to the next account that is not rate limited
+    fn advance(&mut self) -> bool {
+        let now = Instant::now();
+        for offset in 1..=self.accounts.len() {
+            let index = (self.current + offset) % self.accounts.len();
+            if self.accounts[index]
+                .limited_until
+                .is_none_or(|until| until <= now)
+            {
+                self.current = index;
+                return true;
+            }
+        }
+        false
+    }
+
+    // Add this run's request counts to the account_usage table
+    pub fn save_usage(&self) {
+        let result = DB::new().and_then(|db| {
+            self.accounts.iter().try_for_each(|account| {
+                db.record_account_usage(&account.name, account.requests, account.rate_limited)
+            })
+        });
+
+        if let Err(e) = result {
+            eprintln!("Failed to record account usage: {}", e);
+        }
+    }
+}
+
+impl PooledAccount {
+    fn new(name: &str, token: String) -> Self {
+        PooledAccount {
+            name: name.to_string(),
+            token,
+            limited_until: None,
+            requests: 0,
+            rate_limited: 0,
+        }
+    }
+}
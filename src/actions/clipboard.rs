@@ -0,0 +1,93 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::arguments::modeling::CopyFormat;
+use crate::database::adding::PostDataWrapper;
+
+// `--copy`: put the posts on the clipboard for pasting into chat. Without a
+// clipboard the text is printed instead.
+pub fn copy_posts(posts: &[PostDataWrapper], format: CopyFormat) {
+    if posts.is_empty() {
+        return;
+    }
+
+    let link = super::open::stored_link;
+    let lines: Vec<String> = posts
+        .iter()
+        .map(|post| match format {
+            CopyFormat::Links => link(post),
+            CopyFormat::Markdown if post.subreddit.is_empty() => {
+                format!("- [{}]({})", escape_markdown(&post.title), link(post))
+            }
+            CopyFormat::Markdown => format!(
+                "- [{}]({}) (r/{})",
+                escape_markdown(&post.title),
+                link(post),
+                post.subreddit
+            ),
+        })
+        .collect();
+    let text = lines.join("\n");
+
+    let what = match format {
+        CopyFormat::Links => "permalinks",
+        CopyFormat::Markdown => "posts as Markdown",
+    };
+    if copy_text(&text) {
+        println!("Copied {} {} to the clipboard", posts.len(), what);
+    } else {
+        println!("\nNo clipboard available, copy these {} instead:\n", what);
+        println!("{}", text);
+    }
+}
+
+// Brackets in a title would end the link text early
+fn escape_markdown(title: &str) -> String {
+    title.replace('[', "\\[").replace(']', "\\]")
+}
+
+// Hand the text to the platform's clipboard tool. Returns false when there is
+// none, e.g. on a server without a desktop.
+fn copy_text(text: &str) -> bool {
+    #[cfg(target_os = "macos")]
+    let tools: &[(&str, &[&str])] = &[("pbcopy", &[])];
+
+    #[cfg(target_os = "windows")]
+    let tools: &[(&str, &[&str])] = &[("clip", &[])];
+
+    #[cfg(target_os = "linux")]
+    let tools: &[(&str, &[&str])] = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        &[("wl-copy", &[])]
+    } else if std::env::var_os("DISPLAY").is_some() {
+        &[
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    } else {
+        &[]
+    };
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    let tools: &[(&str, &[&str])] = &[];
+
+    tools
+        .iter()
+        .any(|(program, args)| pipe_to(program, args, text))
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> bool {
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    matches!(child.wait(), Ok(status) if status.success()) && written
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use crate::database::adding::DB;
+use crate::filters::text::ellipsize;
+
+// Characters of a lead title shown in the conflict report
+const TITLE_WIDTH: usize = 60;
+
+// `ruddit db merge`: bring another machine's posts, comments and leads into
+// this database
+pub fn merge_database(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if !path.is_file() {
+        return Err(format!("No database at {}", path.display()).into());
+    }
+
+    let mut db = DB::new()?;
+    db.create_tables()?;
+    let report = db.merge_database(path)?;
+
+    println!("Merged {}", path.display());
+    for (table, rows) in &report.copied {
+        println!("  {:<16} {} new rows", table, rows);
+    }
+    println!(
+        "  {:<16} {} new, {} updated, {} review decisions",
+        "leads", report.leads_added, report.leads_updated, report.feedback
+    );
+
+    if !report.conflicts.is_empty() {
+        println!(
+            "\n{} leads were reviewed differently on both machines, the latest decision was kept:",
+            report.conflicts.len()
+        );
+        for conflict in &report.conflicts {
+            println!(
+                "  {:<9} here, {:<9} there, kept {:<9} {}",
+                conflict.ours,
+                conflict.theirs,
+                conflict.kept,
+                ellipsize(&conflict.title, TITLE_WIDTH)
+            );
+            println!("  {}", conflict.url);
+        }
+    }
+    Ok(())
+}
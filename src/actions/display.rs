@@ -0,0 +1,72 @@
+use std::sync::OnceLock;
+
+use chrono::Utc;
+
+use crate::actions::verbosity;
+use crate::database::adding::PostDataWrapper;
+use crate::filters::text::ellipsize;
+use crate::settings::api_keys::{ConfigDirs, TimeFormat};
+
+// Width of the titles listed by -v
+const FETCHED_TITLE_WIDTH: usize = 80;
+
+static TIME_FORMAT: OnceLock<TimeFormat> = OnceLock::new();
+
+// The configured time format, read once per run
+fn time_format() -> TimeFormat {
+    *TIME_FORMAT.get_or_init(|| {
+        ConfigDirs::read_config()
+            .map(|config| config.display.time_format)
+            .unwrap_or_default()
+    })
+}
+
+// "just now", "5m ago", "2h ago", "3d ago", "4w ago", "2mo ago", "1y ago"
+pub fn relative_time(timestamp: i64) -> String {
+    let seconds = Utc::now().timestamp() - timestamp;
+
+    match seconds {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s if s < 86400 * 7 => format!("{}d ago", s / 86400),
+        s if s < 86400 * 30 => format!("{}w ago", s / (86400 * 7)),
+        s if s < 86400 * 365 => format!("{}mo ago", s / (86400 * 30)),
+        s => format!("{}y ago", s / (86400 * 365)),
+    }
+}
+
+// Date for terminal output, following `time_format` under [display]
+pub fn display_date(timestamp: i64, formatted_date: &str) -> String {
+    match time_format() {
+        TimeFormat::Absolute => formatted_date.to_string(),
+        TimeFormat::Relative => relative_time(timestamp),
+        TimeFormat::Both => format!("{} ({})", formatted_date, relative_time(timestamp)),
+    }
+}
+
+// With -v, one line per post as it came from Reddit, before any filtering
+pub fn print_fetched(posts: &[PostDataWrapper]) {
+    if !verbosity::verbose() {
+        return;
+    }
+    for post in posts {
+        println!(
+            "  {}  r/{}  {}  {}",
+            display_date(post.timestamp, &post.formatted_date),
+            post.subreddit,
+            ellipsize(&post.title, FETCHED_TITLE_WIDTH),
+            post.permalink
+        );
+    }
+}
+
+// "12 new posts, 88 already stored" after an insert that ignores duplicates
+pub fn stored_counts(noun: &str, received: usize, inserted: usize) -> String {
+    format!(
+        "{} new {}, {} already stored",
+        inserted,
+        noun,
+        received.saturating_sub(inserted)
+    )
+}
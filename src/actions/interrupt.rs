@@ -0,0 +1,53 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+// Exit code of a process stopped by Ctrl-C (128 + SIGINT)
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static WAKE: Notify = Notify::const_new();
+
+// Handle Ctrl-C: the first press lets long-running work stop at the next safe
+// point so everything fetched so far is saved, a second press quits at once
+pub fn install() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        WAKE.notify_waiters();
+        eprintln!("\r\x1b[2KStopping after the current step, press Ctrl-C again to quit now");
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprint!("\r\x1b[2K");
+            let _ = std::io::stderr().flush();
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+    });
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+// Sleep between polls, returns false when woken early by Ctrl-C
+pub async fn sleep(duration: Duration) -> bool {
+    let woken = WAKE.notified();
+    if interrupted() {
+        return false;
+    }
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => true,
+        _ = woken => false,
+    }
+}
+
+// Once the interrupted work has saved what it had, exit with the Ctrl-C status
+pub fn exit_if_interrupted() {
+    if interrupted() {
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+}
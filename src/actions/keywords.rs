@@ -0,0 +1,162 @@
+use std::io;
+
+use crate::actions::leads::prompt;
+use crate::ai;
+use crate::database::adding::DB;
+use crate::database::leads::REVIEW_ACCEPTED;
+use crate::filters::keywords::KeywordMatcher;
+use crate::settings::api_keys::ConfigDirs;
+
+// A keyword matching more than this share of the stored rows is flagged as broad
+const BROAD_SHARE: f64 = 0.5;
+
+// `ruddit keywords stats`: how often each configured keyword matched over the
+// last runs, with the dead and over-broad ones flagged
+pub fn print_keyword_stats(runs: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let stats = db.get_keyword_stats(runs)?;
+
+    if stats.keywords.is_empty() {
+        println!("No keyword hits recorded yet, they are counted at the end of every fetch");
+        return Ok(());
+    }
+
+    println!(
+        "Keyword hits over the last {} runs ({} posts, {} comments stored)\n",
+        stats.runs, stats.posts, stats.comments
+    );
+    println!(
+        "{:<16} {:<28} {:>7} {:>9} {:>6}",
+        "GROUP", "KEYWORD", "POSTS", "COMMENTS", "RUNS"
+    );
+
+    let total = (stats.posts + stats.comments) as f64;
+    let (mut dead, mut broad) = (0, 0);
+    for keyword in &stats.keywords {
+        let hits = (keyword.posts + keyword.comments) as f64;
+        let flag = if hits == 0.0 {
+            dead += 1;
+            "dead"
+        } else if total > 0.0 && hits / total > BROAD_SHARE {
+            broad += 1;
+            "broad"
+        } else {
+            ""
+        };
+        let group = if keyword.group.is_empty() {
+            "LEAD_KEYWORDS"
+        } else {
+            &keyword.group
+        };
+        println!(
+            "{:<16} {:<28} {:>7} {:>9} {:>3}/{:<2} {}",
+            group,
+            keyword.keyword,
+            keyword.posts,
+            keyword.comments,
+            keyword.runs_hit,
+            stats.runs,
+            flag
+        );
+    }
+
+    if dead > 0 || broad > 0 {
+        println!(
+            "\n{} dead keywords never matched, {} broad ones matched more than half of what was stored",
+            dead, broad
+        );
+    }
+
+    Ok(())
+}
+
+// `ruddit keywords suggest`: ask Gemini for keywords in the language of the
+// accepted leads, keep the ones that occur in them, show how often, and add
+// them to LEAD_KEYWORDS once confirmed
+pub async fn suggest_keywords(
+    yes: bool,
+    use_cache: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = ConfigDirs::read_config()?;
+    let db = DB::new()?;
+    db.create_tables()?;
+
+    let leads = db.get_lead_texts(REVIEW_ACCEPTED)?;
+    if leads.is_empty() {
+        return Err("No accepted leads yet, review some with `ruddit leads review` first".into());
+    }
+    let posts = db.get_post_texts()?;
+    let existing = &config.api_keys.lead_keywords;
+
+    println!(
+        "Looking for keywords in {} accepted leads ({} stored posts to compare with)",
+        leads.len(),
+        posts.len()
+    );
+    let proposed = ai::gemini::gemini_suggest_keywords(&leads, existing, use_cache).await?;
+
+    // (keyword, accepted leads containing it, stored posts containing it)
+    let mut suggestions: Vec<(String, usize, usize)> = Vec::new();
+    for keyword in proposed {
+        if existing
+            .iter()
+            .chain(suggestions.iter().map(|(seen, _, _)| seen))
+            .any(|seen| seen.eq_ignore_ascii_case(&keyword))
+        {
+            continue;
+        }
+        let matcher = KeywordMatcher::new(std::slice::from_ref(&keyword), "OR")
+            .with_matching(&config.matching);
+        let in_leads = leads.iter().filter(|text| matcher.matches(text)).count();
+        // Words Gemini made up rather than found are dropped
+        if in_leads == 0 {
+            continue;
+        }
+        let in_posts = posts.iter().filter(|text| matcher.matches(text)).count();
+        suggestions.push((keyword, in_leads, in_posts));
+    }
+
+    if suggestions.is_empty() {
+        println!("No new keywords found in the accepted leads");
+        return Ok(());
+    }
+    suggestions.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.cmp(&b.2)));
+
+    println!(
+        "\n{:<32} {:>14} {:>14}",
+        "KEYWORD", "ACCEPTED LEADS", "STORED POSTS"
+    );
+    for (keyword, in_leads, in_posts) in &suggestions {
+        println!(
+            "{:<32} {:>14} {:>14}",
+            keyword,
+            format!("{}/{}", in_leads, leads.len()),
+            format!("{}/{}", in_posts, posts.len())
+        );
+    }
+
+    if !yes {
+        let question = format!(
+            "\nAdd these {} keywords to LEAD_KEYWORDS? [y/N] ",
+            suggestions.len()
+        );
+        let answer = prompt(&mut io::stdin().lock(), &question)?;
+        if !matches!(answer.as_deref(), Some("y" | "yes")) {
+            println!("Config left unchanged");
+            return Ok(());
+        }
+    }
+
+    let keywords: Vec<String> = suggestions
+        .into_iter()
+        .map(|(keyword, _, _)| keyword)
+        .collect();
+    let lead_keywords = ConfigDirs::add_lead_keywords(&keywords)?;
+    println!("LEAD_KEYWORDS now has {} keywords", lead_keywords.len());
+    if !config.api_keys.lead_query.trim().is_empty() {
+        println!("Note: LEAD_QUERY is set and is used instead of LEAD_KEYWORDS");
+    }
+
+    Ok(())
+}
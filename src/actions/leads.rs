@@ -0,0 +1,418 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, Utc};
+
+use crate::database::adding::DB;
+use crate::database::leads::{
+    CommentLeadDataWrapper, LEAD_DUMP_VERSION, LeadDump, REVIEW_ACCEPTED, REVIEW_PENDING,
+    REVIEW_REJECTED,
+};
+use crate::database::queries::PostFilter;
+use crate::exports;
+use crate::exports::excel;
+use crate::filters::intents::IntentClassifier;
+use crate::filters::keywords::KeywordMatcher;
+use crate::filters::questions::is_question;
+use crate::filters::text::ellipsize;
+use crate::notify;
+use crate::settings::api_keys::{AppConfig, ConfigDirs};
+
+// Number of latest decisions used for the recent precision figure
+const PRECISION_WINDOW: usize = 50;
+
+// Characters of a title shown on one terminal line
+const TITLE_WIDTH: usize = 100;
+
+const RELEVANCE_LEVELS: [&str; 3] = ["HIGH", "MEDIUM", "LOW"];
+
+// Leads listed in a stale lead reminder
+const REMINDED_LEADS: usize = 10;
+
+// `ruddit leads review`: step through the pending leads one by one and record a
+// decision for each, only accepted leads reach the leads export and the API.
+// With an assignee only their leads are reviewed.
+pub fn review_leads(assignee: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    let mut leads = db.get_leads_with_status(REVIEW_PENDING)?;
+    if let Some(assignee) = assignee {
+        leads.retain(|lead| lead.assignee.eq_ignore_ascii_case(assignee.trim()));
+    }
+
+    if leads.is_empty() {
+        match assignee {
+            Some(assignee) => println!("No leads assigned to {} waiting for review", assignee),
+            None => println!("No leads waiting for review"),
+        }
+        return Ok(());
+    }
+
+    println!("{} leads waiting for review\n", leads.len());
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let (mut accepted, mut rejected) = (0, 0);
+
+    'leads: for (index, lead) in leads.iter().enumerate() {
+        let mut relevance = lead.relevance.clone();
+
+        loop {
+            println!(
+                "[{}/{}] {}",
+                index + 1,
+                leads.len(),
+                ellipsize(&lead.title, TITLE_WIDTH)
+            );
+            println!(
+                "  r/{} | relevance {} | sentiment {} | {}",
+                lead.subreddit, relevance, lead.sentiment, lead.formatted_date
+            );
+            if !lead.comment_sentiment.is_empty() {
+                println!("  comment sentiment {}", lead.comment_sentiment);
+            }
+            if !lead.assignee.is_empty() {
+                println!("  assigned to {}", lead.assignee);
+            }
+            println!("  {}", lead.url);
+
+            let Some(answer) = prompt(
+                &mut input,
+                "[a]ccept, [r]eject, [e]dit relevance, [s]kip, [q]uit: ",
+            )?
+            else {
+                break 'leads;
+            };
+
+            // Only store the relevance when it was changed
+            let edited = (relevance != lead.relevance).then_some(relevance.as_str());
+
+            match answer.as_str() {
+                "a" => {
+                    db.review_lead(lead.id, REVIEW_ACCEPTED, edited)?;
+                    accepted += 1;
+                }
+                "r" => {
+                    db.review_lead(lead.id, REVIEW_REJECTED, edited)?;
+                    rejected += 1;
+                }
+                "e" => {
+                    let Some(level) = prompt(&mut input, "Relevance (high, medium, low): ")? else {
+                        break 'leads;
+                    };
+                    let level = level.to_uppercase();
+                    if RELEVANCE_LEVELS.contains(&level.as_str()) {
+                        relevance = level;
+                    } else {
+                        println!("Unknown relevance '{}'", level);
+                    }
+                    println!();
+                    continue;
+                }
+                "s" => {}
+                "q" => break 'leads,
+                _ => {
+                    println!("Unknown action '{}'\n", answer);
+                    continue;
+                }
+            }
+
+            println!();
+            break;
+        }
+    }
+
+    let remaining = db.get_leads_with_status(REVIEW_PENDING)?.len();
+    println!(
+        "\nAccepted {}, rejected {}, {} leads still pending",
+        accepted, rejected, remaining
+    );
+
+    // Feedback is fed back into the lead prompt, so recent precision should climb
+    let (total_accepted, total_reviewed) = db.get_lead_precision(None)?;
+    let (recent_accepted, recent_reviewed) = db.get_lead_precision(Some(PRECISION_WINDOW))?;
+    if total_reviewed > 0 {
+        println!(
+            "Lead precision: {}% overall, {}% over the last {} decisions",
+            total_accepted * 100 / total_reviewed,
+            recent_accepted * 100 / recent_reviewed,
+            recent_reviewed
+        );
+    }
+    Ok(())
+}
+
+// `ruddit leads comments`: find single comments that are leads, even in threads
+// about something else, store them and export them with their permalinks
+pub fn find_comment_leads(filter: &PostFilter) -> Result<(), Box<dyn std::error::Error>> {
+    let config = ConfigDirs::read_config()?;
+    // --group picks the keywords, the group's posts are not narrowed further
+    let matcher = match filter.keywords.clone() {
+        Some(matcher) => matcher,
+        None => KeywordMatcher::from_config(&config)?,
+    };
+    if matcher.is_empty() {
+        return Err("No LEAD_KEYWORDS in the configuration file to match comments against".into());
+    }
+    let filter = &PostFilter {
+        keywords: None,
+        ..filter.clone()
+    };
+    let classifier = IntentClassifier::new(&config.intents);
+
+    let mut db = DB::new()?;
+    db.create_tables()?;
+    let comments = db.get_filtered_comments(filter)?;
+
+    let now = Utc::now().timestamp();
+    let leads: Vec<CommentLeadDataWrapper> = comments
+        .into_iter()
+        .filter_map(|comment| {
+            let hit = matcher.find(&comment.body)?;
+            let intent = classifier.classify("", &comment.body);
+            Some(CommentLeadDataWrapper {
+                relevance: comment_relevance(&intent, is_question("", &comment.body)).to_string(),
+                intent,
+                comment_id: comment.id,
+                post_id: comment.post_id,
+                post_title: comment.post_title,
+                subreddit: comment.subreddit,
+                author: comment.author,
+                body: comment.body,
+                score: comment.score,
+                permalink: format!("https://reddit.com{}", comment.permalink),
+                formatted_date: comment.formatted_date,
+                created_at: now,
+                keyword_group: matcher.group().to_string(),
+                matched_term: hit.term,
+                match_type: hit.match_type.as_str().to_string(),
+            })
+        })
+        .collect();
+
+    if leads.is_empty() {
+        println!("No comments matched your lead keywords");
+        return Ok(());
+    }
+
+    db.append_comment_leads(&leads)?;
+    println!("Saved {} comment leads to database", leads.len());
+
+    let min_relevance = config.leads.min_relevance;
+    let exported: Vec<CommentLeadDataWrapper> = leads
+        .into_iter()
+        .filter(|lead| min_relevance.allows(&lead.relevance))
+        .collect();
+    excel::export_comment_leads(&exported, matcher.group())?;
+    Ok(())
+}
+
+// A comment asking for a tool is worth more than one merely mentioning the topic
+fn comment_relevance(intent: &str, question: bool) -> &'static str {
+    match intent {
+        "seeking_recommendation" => "HIGH",
+        _ if question => "MEDIUM",
+        "complaint" | "comparison" => "MEDIUM",
+        _ => "LOW",
+    }
+}
+
+// Read one trimmed, lowercased answer. None on end of input.
+// `ruddit leads stale`: how long leads sit in each status, and the ones that
+// have waited longer than `days` without anyone reaching out
+pub async fn print_stale_leads(
+    days: Option<u32>,
+    notify: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = ConfigDirs::read_config()?;
+    let days = days.unwrap_or(config.leads.stale_days);
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    let now = Utc::now().timestamp();
+
+    let ages = db.get_lead_status_ages(now)?;
+    if ages.is_empty() {
+        println!("No leads stored yet");
+        return Ok(());
+    }
+    println!(
+        "{:<10} {:>6} {:>12} {:>12}",
+        "Status", "Leads", "Avg days", "Oldest days"
+    );
+    for age in &ages {
+        println!(
+            "{:<10} {:>6} {:>12.1} {:>12.1}",
+            age.status, age.leads, age.average_days, age.oldest_days
+        );
+    }
+
+    let stale = db.get_stale_leads(now - days as i64 * 86_400)?;
+    println!(
+        "\n{} leads pending or accepted for {} days or more without contact",
+        stale.len(),
+        days
+    );
+    for lead in &stale {
+        println!(
+            "  #{:<5} {:<8} {:<6} {:>4}d  {:<12} {}",
+            lead.id,
+            lead.review_status,
+            lead.relevance,
+            (now - lead.status_since) / 86_400,
+            if lead.assignee.is_empty() {
+                "unassigned"
+            } else {
+                &lead.assignee
+            },
+            ellipsize(&lead.title, TITLE_WIDTH)
+        );
+        println!("         {}", lead.url);
+    }
+    if !stale.is_empty() {
+        println!("\nMark leads as handled with `ruddit leads contacted <id>...`");
+    }
+
+    if notify {
+        let reminded = remind_stale_leads(&config, days).await?;
+        println!("Sent a reminder about {} HIGH leads", reminded);
+    }
+    Ok(())
+}
+
+// Notify about the HIGH leads waiting `days` or more for contact, returns how
+// many there were. Nothing is sent when there are none.
+pub async fn remind_stale_leads(
+    config: &AppConfig,
+    days: u32,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    let cutoff = Utc::now().timestamp() - days as i64 * 86_400;
+    let high: Vec<_> = db
+        .get_stale_leads(cutoff)?
+        .into_iter()
+        .filter(|lead| lead.relevance.eq_ignore_ascii_case("HIGH"))
+        .collect();
+    if high.is_empty() {
+        return Ok(0);
+    }
+
+    let mut message = format!(
+        "Ruddit: {} HIGH leads nobody has contacted for {} days or more",
+        high.len(),
+        days
+    );
+    for lead in high.iter().take(REMINDED_LEADS) {
+        message.push_str(&format!(
+            "\n#{} {} {}",
+            lead.id,
+            ellipsize(&lead.title, TITLE_WIDTH),
+            lead.url
+        ));
+        if !lead.assignee.is_empty() {
+            message.push_str(&format!(" (assigned to {})", lead.assignee));
+        }
+    }
+    if high.len() > REMINDED_LEADS {
+        message.push_str(&format!("\n...and {} more", high.len() - REMINDED_LEADS));
+    }
+    notify::send(&config.notifications, &message).await;
+    Ok(high.len())
+}
+
+// `ruddit leads dump`: the lead pipeline as JSON, to back it up or move it
+// to another machine without the scraped data
+pub fn dump_leads(output: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    let dump = db.dump_leads()?;
+
+    let path = match output {
+        Some(path) => path,
+        None => exports::reddit_data_folder()?.join(format!(
+            "ruddit_leads_{}.json",
+            Local::now().format("%Y-%m-%d_%H-%M-%S")
+        )),
+    };
+    if let Some(folder) = path.parent()
+        && !folder.as_os_str().is_empty()
+    {
+        fs::create_dir_all(folder)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&dump)?)?;
+
+    println!(
+        "Dumped {} leads, {} review decisions and {} comment leads to {}",
+        dump.leads.len(),
+        dump.feedback.len(),
+        dump.comment_leads.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+// `ruddit leads load`: merge a dump into this database
+pub fn load_leads(file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let dump: LeadDump = serde_json::from_str(&fs::read_to_string(file)?)
+        .map_err(|e| format!("{} is not a ruddit leads dump: {}", file.display(), e))?;
+    if dump.version > LEAD_DUMP_VERSION {
+        return Err(format!(
+            "{} was written by a newer Ruddit (dump version {}), update to load it",
+            file.display(),
+            dump.version
+        )
+        .into());
+    }
+
+    let mut db = DB::new()?;
+    db.create_leads_table()?;
+    let loaded = db.load_leads(&dump)?;
+    println!(
+        "Loaded {} new leads, updated {}, added {} review decisions and {} comment leads",
+        loaded.leads_added, loaded.leads_updated, loaded.feedback, loaded.comment_leads
+    );
+    Ok(())
+}
+
+// `ruddit leads assign`: split triage work between team members
+pub fn assign_lead(id: i64, assignee: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    let assignee = assignee.map(str::trim).unwrap_or_default();
+    if !db.assign_lead(id, assignee)? {
+        return Err(format!("No lead #{}", id).into());
+    }
+
+    if assignee.is_empty() {
+        println!("Lead #{} is unassigned", id);
+    } else {
+        println!("Lead #{} assigned to {}", id, assignee);
+    }
+    Ok(())
+}
+
+// `ruddit leads contacted`: take leads off the stale list
+pub fn mark_contacted(ids: &[i64]) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    for id in ids {
+        if db.mark_contacted(*id)? {
+            println!("Lead #{} marked as contacted", id);
+        } else {
+            eprintln!("No lead #{}", id);
+        }
+    }
+    Ok(())
+}
+
+pub fn prompt(input: &mut impl BufRead, message: &str) -> io::Result<Option<String>> {
+    print!("{}", message);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_lowercase()))
+}
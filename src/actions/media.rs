@@ -0,0 +1,100 @@
+use std::fs;
+
+use reqwest::Client;
+
+use crate::database::adding::DB;
+use crate::exports;
+
+// Download every stored attachment of a post into Reddit_data/media/<post_id>
+pub async fn download_post_media(post_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_media_table()?;
+    let media = db.get_post_media(post_id)?;
+
+    if media.is_empty() {
+        println!("No media stored for post {}", post_id);
+        return Ok(());
+    }
+
+    let folder_path = exports::reddit_data_folder()?.join("media").join(post_id);
+    fs::create_dir_all(&folder_path)?;
+
+    let client = Client::new();
+    let mut saved = 0;
+
+    for item in &media {
+        let response = match client
+            .get(&item.url)
+            .header("User-Agent", "RustRedditApp/0.1 by YourUsername")
+            .send()
+            .await
+        {
+            Ok(r) if r.status().is_success() => r,
+            Ok(r) => {
+                eprintln!("Skipping {}: HTTP {}", item.url, r.status());
+                continue;
+            }
+            Err(e) => {
+                eprintln!("Skipping {}: {}", item.url, e);
+                continue;
+            }
+        };
+
+        // Only keep actual attachments, not the HTML of link posts
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        if !content_type.starts_with("image/") && !content_type.starts_with("video/") {
+            eprintln!("Skipping {}: not a media file ({})", item.url, content_type);
+            continue;
+        }
+
+        let filename = format!(
+            "{}_{}.{}",
+            post_id,
+            item.position,
+            file_extension(&item.url, &content_type)
+        );
+
+        let bytes = response.bytes().await?;
+        fs::write(folder_path.join(&filename), &bytes)?;
+        println!("Saved {}", filename);
+        saved += 1;
+    }
+
+    println!(
+        "Downloaded {} of {} attachments to {:?}",
+        saved,
+        media.len(),
+        folder_path
+    );
+    Ok(())
+}
+
+// Pick a file extension from the URL path, falling back to the content type
+fn file_extension(url: &str, content_type: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+
+    if let Some(ext) = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        && !ext.1.is_empty()
+        && ext.1.len() <= 4
+    {
+        return ext.1.to_lowercase();
+    }
+
+    content_type
+        .split('/')
+        .nth(1)
+        .unwrap_or("bin")
+        .split(';')
+        .next()
+        .unwrap_or("bin")
+        .to_string()
+}
@@ -1 +1,22 @@
+pub mod accounts;
 pub mod add_api_keys;
+pub mod clipboard;
+pub mod db;
+pub mod display;
+pub mod interrupt;
+pub mod keywords;
+pub mod leads;
+pub mod media;
+pub mod notes;
+pub mod open;
+pub mod outcome;
+pub mod pick;
+pub mod pipeline;
+pub mod progress;
+pub mod query;
+pub mod quota;
+pub mod service;
+pub mod status;
+pub mod tags;
+pub mod thread;
+pub mod verbosity;
@@ -0,0 +1,102 @@
+use crate::actions::display::display_date;
+use crate::arguments::post_ref::PostRef;
+use crate::database::adding::DB;
+
+// What `ruddit note` was given
+pub enum NoteTarget {
+    Post(PostRef),
+    Comment(String),
+}
+
+// `ruddit note`: set, clear or show the note of a stored post or comment. A
+// bare id that is not a stored post is looked up as a comment.
+pub fn edit_note(
+    target: NoteTarget,
+    text: &str,
+    clear: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+
+    let target = match target {
+        NoteTarget::Post(post) if !db.is_post_stored(post.row_id()?)? => {
+            if post.subreddit.is_none() && db.is_comment_stored(&post.id)? {
+                NoteTarget::Comment(post.id)
+            } else {
+                return Err(format!(
+                    "{} is not a stored post or comment, fetch it first with `ruddit thread`",
+                    post.id
+                )
+                .into());
+            }
+        }
+        NoteTarget::Comment(id) if !db.is_comment_stored(&id)? => {
+            return Err(format!(
+                "Comment {} is not stored, fetch its thread first with `ruddit thread`",
+                id
+            )
+            .into());
+        }
+        target => target,
+    };
+
+    let text = text.trim();
+    let (label, current) = match &target {
+        NoteTarget::Post(post) => (
+            format!("post {}", post.id),
+            db.get_post_notes()?.remove(&post.row_id()?),
+        ),
+        NoteTarget::Comment(id) => (
+            format!("comment {}", id),
+            db.get_comment_notes()?.remove(id),
+        ),
+    };
+
+    if text.is_empty() && !clear {
+        match current {
+            Some(note) => println!("{}", note),
+            None => println!("No note on {}", label),
+        }
+        return Ok(());
+    }
+
+    match &target {
+        NoteTarget::Post(post) => db.set_post_note(post.row_id()?, text)?,
+        NoteTarget::Comment(id) => db.set_comment_note(id, text)?,
+    }
+    if clear {
+        println!("Removed the note on {}", label);
+    } else if current.is_some() {
+        println!("Replaced the note on {}", label);
+    } else {
+        println!("Added a note to {}", label);
+    }
+    Ok(())
+}
+
+// `ruddit notes`: every note with what it is attached to
+pub fn print_notes() -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let notes = db.list_notes()?;
+    if notes.is_empty() {
+        println!("No notes yet, add one with `ruddit note <post> <text>`");
+        return Ok(());
+    }
+
+    for note in &notes {
+        let edited = DB::format_timestamp(note.updated_at)?;
+        println!(
+            "{} {}  {}  {}",
+            note.kind,
+            note.id,
+            display_date(note.updated_at, &edited),
+            note.title
+        );
+        for line in note.note.lines() {
+            println!("    {}", line);
+        }
+    }
+    println!("\n{} notes", notes.len());
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::ffi::OsStr;
+use std::process::Command;
+
+use crate::arguments::post_ref::PostRef;
+use crate::database::adding::{DB, PostDataWrapper};
+
+// app_meta key holding the ids of the posts last listed by `ruddit query` or
+// a search, in order, so `ruddit open <N>` can refer to them
+const LAST_LISTING_KEY: &str = "last_listing";
+
+// Open a file, folder or URL with the desktop's default application. Returns
+// false on servers and containers without a desktop or an opener, so callers
+// can print the location instead.
+pub fn open_path(target: impl AsRef<OsStr>) -> bool {
+    let target = target.as_ref();
+
+    #[cfg(target_os = "macos")]
+    let status = Command::new("open").arg(target).status();
+
+    // explorer exits with 1 even when it opened the target
+    #[cfg(target_os = "windows")]
+    let status = Command::new("explorer")
+        .arg(target)
+        .spawn()
+        .and_then(|mut child| child.wait().map(|_| std::process::ExitStatus::default()));
+
+    #[cfg(target_os = "linux")]
+    let status = {
+        if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            return false;
+        }
+        Command::new("xdg-open").arg(target).status()
+    };
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    let status: std::io::Result<std::process::ExitStatus> =
+        Err(std::io::ErrorKind::Unsupported.into());
+
+    matches!(status, Ok(status) if status.success())
+}
+
+// Open a link in the browser, or print it when there is no desktop
+pub fn open_link(url: &str) {
+    if open_path(url) {
+        println!("Opening {}", url);
+    } else {
+        println!("No browser to open it with: {}", url);
+    }
+}
+
+// Remember the order of a listing for `ruddit open <N>`. Best effort, a
+// read-only database simply keeps the previous listing.
+pub fn remember_listing(posts: &[PostDataWrapper]) {
+    let ids: Vec<String> = posts
+        .iter()
+        .map(|post| DB::encode_reddit_id(post.id))
+        .collect();
+    let _ = DB::new().and_then(|db| {
+        db.create_meta_table()?;
+        db.set_meta(LAST_LISTING_KEY, &ids.join(","))
+    });
+}
+
+// The post at 1-based `position` of the last listing
+pub fn listed_post(position: usize) -> Result<Option<PostRef>, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_meta_table()?;
+    let listing = db.get_meta(LAST_LISTING_KEY)?.unwrap_or_default();
+    Ok(position
+        .checked_sub(1)
+        .and_then(|index| listing.split(',').filter(|id| !id.is_empty()).nth(index))
+        .map(|id| PostRef {
+            id: id.to_string(),
+            subreddit: None,
+        }))
+}
+
+// Permalink of a stored post, or its Reddit URL when it is not stored
+pub fn post_link(post: &PostRef) -> Result<String, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    if let Some(stored) = db.get_post(post.row_id()?)? {
+        return Ok(stored_link(&stored));
+    }
+    Ok(match &post.subreddit {
+        Some(subreddit) => format!(
+            "https://www.reddit.com/r/{}/comments/{}/",
+            subreddit, post.id
+        ),
+        None => format!("https://www.reddit.com/comments/{}/", post.id),
+    })
+}
+
+// Imported posts can lack a permalink, Reddit still finds them by id
+pub fn stored_link(post: &PostDataWrapper) -> String {
+    if post.permalink.is_empty() {
+        format!(
+            "https://www.reddit.com/comments/{}/",
+            DB::encode_reddit_id(post.id)
+        )
+    } else {
+        post.permalink.clone()
+    }
+}
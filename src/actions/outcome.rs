@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+
+use crate::RedditError;
+use crate::actions::{interrupt, verbosity};
+use crate::database::adding::{DB, PostDataWrapper};
+use crate::filters::keywords::KeywordMatcher;
+
+// Exit codes, so scripts and schedulers can tell why a run did not succeed.
+// 2 is used by clap for invalid arguments and 130 for Ctrl-C.
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_AUTH: i32 = 3;
+pub const EXIT_NETWORK: i32 = 4;
+pub const EXIT_NO_RESULTS: i32 = 5;
+pub const EXIT_PARTIAL: i32 = 6;
+
+// Posts and comments handled by one fetch. Skipped items were filtered out or
+// already stored, errors are comment threads that could not be fetched.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RunSummary {
+    pub fetched: usize,
+    pub inserted: usize,
+    pub skipped: usize,
+    pub errors: usize,
+}
+
+impl RunSummary {
+    // Count a batch that was received and stored
+    pub fn add(&mut self, fetched: usize, inserted: usize) {
+        self.fetched += fetched;
+        self.inserted += inserted;
+        self.skipped += fetched.saturating_sub(inserted);
+    }
+
+    // Final `key=value` line that is easy to grep or parse
+    pub fn print(&self) {
+        println!(
+            "summary fetched={} inserted={} skipped={} errors={}",
+            self.fetched, self.inserted, self.skipped, self.errors
+        );
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if interrupt::interrupted() {
+            interrupt::EXIT_INTERRUPTED
+        } else if self.errors > 0 {
+            EXIT_PARTIAL
+        } else if self.fetched == 0 {
+            EXIT_NO_RESULTS
+        } else {
+            0
+        }
+    }
+
+    // Print and record the summary, then exit with its code when the run was
+    // not clean
+    pub fn finish(&self) {
+        self.print();
+        if let Err(e) = DB::new().and_then(|db| db.record_run_summary(self)) {
+            eprintln!("Failed to record the run: {}", e);
+        }
+        exit_with(self.exit_code());
+    }
+}
+
+// Counts of one subreddit in a fetch. Keyword hits are the kept posts and the
+// fetched comments matching LEAD_KEYWORDS or the --group keywords.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SubredditCounts {
+    pub fetched: usize,
+    pub inserted: usize,
+    pub comments: usize,
+    pub keyword_hits: usize,
+}
+
+// Per-subreddit counts of a fetch, printed as one table at the end instead of
+// a line per step
+#[derive(Debug, Default)]
+pub struct FetchTable {
+    rows: BTreeMap<String, SubredditCounts>,
+}
+
+impl FetchTable {
+    pub fn row(&mut self, subreddit: &str) -> &mut SubredditCounts {
+        self.rows.entry(subreddit.to_string()).or_default()
+    }
+
+    // Count a listing as Reddit returned it, before any filtering
+    pub fn add_fetched(&mut self, posts: &[PostDataWrapper]) {
+        for post in posts {
+            self.row(&post.subreddit).fetched += 1;
+        }
+    }
+
+    // Count the kept posts that were new to the database, and those of them
+    // matching the keywords
+    pub fn add_stored(
+        &mut self,
+        posts: &[PostDataWrapper],
+        new: &HashSet<i64>,
+        keywords: Option<&KeywordMatcher>,
+    ) {
+        for post in posts {
+            let row = self.row(&post.subreddit);
+            if new.contains(&post.id) {
+                row.inserted += 1;
+            }
+            if keywords.is_some_and(|matcher| {
+                matcher.matches(&format!("{}\n{}", post.title, post.selftext))
+            }) {
+                row.keyword_hits += 1;
+            }
+        }
+    }
+
+    // Left out with --quiet, the summary line still follows
+    pub fn print(&self) {
+        if self.rows.is_empty() || verbosity::quiet() {
+            return;
+        }
+        let width = self
+            .rows
+            .keys()
+            .map(|subreddit| subreddit.chars().count() + 2)
+            .max()
+            .unwrap_or(0)
+            .max("Subreddit".len());
+        println!(
+            "\n{:<width$}  {:>7}  {:>9}  {:>8}  {:>12}",
+            "Subreddit", "Fetched", "New posts", "Comments", "Keyword hits"
+        );
+        let mut total = SubredditCounts::default();
+        for (subreddit, counts) in &self.rows {
+            print_counts(&format!("r/{}", subreddit), counts, width);
+            total.fetched += counts.fetched;
+            total.inserted += counts.inserted;
+            total.comments += counts.comments;
+            total.keyword_hits += counts.keyword_hits;
+        }
+        if self.rows.len() > 1 {
+            print_counts("Total", &total, width);
+        }
+    }
+}
+
+fn print_counts(label: &str, counts: &SubredditCounts, width: usize) {
+    println!(
+        "{:<width$}  {:>7}  {:>9}  {:>8}  {:>12}",
+        label, counts.fetched, counts.inserted, counts.comments, counts.keyword_hits
+    );
+}
+
+// Exit code for an error that ended the run
+pub fn exit_code(error: &(dyn Error + 'static)) -> i32 {
+    if let Some(error) = error.downcast_ref::<RedditError>() {
+        return match error {
+            RedditError::TokenExtraction => EXIT_AUTH,
+            RedditError::Reqwest(e) => reqwest_exit_code(e),
+            RedditError::RateLimited => EXIT_NETWORK,
+            RedditError::Database(_) => EXIT_FAILURE,
+            RedditError::Api { status, .. } => status_exit_code(*status),
+        };
+    }
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        return reqwest_exit_code(error);
+    }
+    EXIT_FAILURE
+}
+
+fn reqwest_exit_code(error: &reqwest::Error) -> i32 {
+    error
+        .status()
+        .map_or(EXIT_NETWORK, |status| status_exit_code(status.as_u16()))
+}
+
+fn status_exit_code(status: u16) -> i32 {
+    match status {
+        401 | 403 => EXIT_AUTH,
+        _ => EXIT_NETWORK,
+    }
+}
+
+pub fn exit_with(code: i32) {
+    if code != 0 {
+        std::process::exit(code);
+    }
+}
@@ -0,0 +1,224 @@
+use std::io;
+
+use crate::actions::leads::prompt;
+use crate::actions::open;
+use crate::arguments::modeling::{CommentOptions, ThreadFormat};
+use crate::database::adding::{DB, PostDataWrapper};
+use crate::database::queries::PostFilter;
+use crate::filters::text::ellipsize;
+
+// Matches listed after each search
+const SHOWN: usize = 15;
+const TITLE_WIDTH: usize = 90;
+// Comments shown by the comments action, highest scores first
+const SHOWN_COMMENTS: usize = 10;
+const COMMENT_WIDTH: usize = 160;
+
+// `ruddit pick`: fuzzy-search the stored post titles, pick one and act on it
+pub async fn pick(
+    filter: &PostFilter,
+    comment_options: &CommentOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let posts = db.get_filtered_posts(filter)?;
+    if posts.is_empty() {
+        println!("No stored posts match these filters");
+        return Ok(());
+    }
+
+    println!(
+        "{} stored posts. Type to search the titles, a number to pick a match, nothing to quit.",
+        posts.len()
+    );
+    let mut input = io::stdin().lock();
+    let mut matches: Vec<&PostDataWrapper> = Vec::new();
+
+    loop {
+        let Some(answer) = prompt(&mut input, "> ")? else {
+            return Ok(());
+        };
+        if answer.is_empty() || answer == "q" {
+            return Ok(());
+        }
+
+        let picked = answer
+            .parse::<usize>()
+            .ok()
+            .filter(|_| !matches.is_empty())
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| matches.get(index).copied());
+        if let Some(post) = picked {
+            if !act_on(&db, &mut input, post, comment_options).await? {
+                return Ok(());
+            }
+            list(&matches);
+            continue;
+        }
+
+        matches = rank(&posts, &answer);
+        if matches.is_empty() {
+            println!("No titles match '{}'", answer);
+        } else {
+            list(&matches);
+        }
+    }
+}
+
+fn list(matches: &[&PostDataWrapper]) {
+    for (index, post) in matches.iter().enumerate() {
+        println!(
+            "{:>3}. {}  r/{}  {}",
+            index + 1,
+            post.formatted_date,
+            post.subreddit,
+            ellipsize(&post.title, TITLE_WIDTH)
+        );
+    }
+}
+
+// Actions on the picked post until the user goes back (true) or quits (false)
+async fn act_on(
+    db: &DB,
+    input: &mut impl io::BufRead,
+    post: &PostDataWrapper,
+    comment_options: &CommentOptions,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let id = DB::encode_reddit_id(post.id);
+    loop {
+        println!("\n{}", post.title);
+        println!("  r/{} | {} | {}", post.subreddit, post.formatted_date, id);
+        println!("  {}", open::stored_link(post));
+
+        let Some(answer) = prompt(
+            input,
+            "[c]omments, [o]pen, [e]xport thread, mark as [l]ead, [b]ack, [q]uit: ",
+        )?
+        else {
+            return Ok(false);
+        };
+        match answer.as_str() {
+            "c" => print_comments(db, &id)?,
+            "o" => open::open_link(&open::stored_link(post)),
+            "e" => {
+                let Some(format) = prompt(input, "Format (md, html, pdf) [md]: ")? else {
+                    return Ok(false);
+                };
+                let format = match format.as_str() {
+                    "" | "md" => ThreadFormat::Md,
+                    "html" => ThreadFormat::Html,
+                    "pdf" => ThreadFormat::Pdf,
+                    other => {
+                        println!("Unknown format '{}'", other);
+                        continue;
+                    }
+                };
+                if let Err(e) =
+                    crate::actions::thread::export_thread(&id, format, None, comment_options).await
+                {
+                    eprintln!("Failed to export the thread: {}", e);
+                }
+            }
+            "l" => {
+                let Some(level) = prompt(input, "Relevance (high, medium, low) [high]: ")? else {
+                    return Ok(false);
+                };
+                let level = if level.is_empty() {
+                    "HIGH".to_string()
+                } else {
+                    level.to_uppercase()
+                };
+                if !["HIGH", "MEDIUM", "LOW"].contains(&level.as_str()) {
+                    println!("Unknown relevance '{}'", level);
+                    continue;
+                }
+                let url = if post.url.is_empty() {
+                    open::stored_link(post)
+                } else {
+                    post.url.clone()
+                };
+                let lead = db.mark_post_as_lead(post, &url, &level)?;
+                println!("Accepted as {} lead #{}", level, lead);
+            }
+            "b" => return Ok(true),
+            "q" => return Ok(false),
+            _ => println!("Unknown choice '{}'", answer),
+        }
+    }
+}
+
+fn print_comments(db: &DB, post_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut comments = db.get_post_comments(post_id)?;
+    if comments.is_empty() {
+        println!(
+            "No comments stored, fetch them with `ruddit thread {}`",
+            post_id
+        );
+        return Ok(());
+    }
+    comments.sort_by_key(|comment| std::cmp::Reverse(comment.score));
+    println!(
+        "{} comments stored, top {}:",
+        comments.len(),
+        SHOWN_COMMENTS.min(comments.len())
+    );
+    for comment in comments.iter().take(SHOWN_COMMENTS) {
+        println!(
+            "  {:>5}  u/{}: {}",
+            comment.score,
+            comment.author,
+            ellipsize(&comment.body.replace('\n', " "), COMMENT_WIDTH)
+        );
+    }
+    Ok(())
+}
+
+// Titles matching every word of the query as a subsequence, best first
+fn rank<'a>(posts: &'a [PostDataWrapper], query: &str) -> Vec<&'a PostDataWrapper> {
+    let terms: Vec<Vec<char>> = query
+        .split_whitespace()
+        .map(|term| term.chars().collect())
+        .collect();
+    let mut scored: Vec<(i64, &PostDataWrapper)> = posts
+        .iter()
+        .filter_map(|post| {
+            let title: Vec<char> = post.title.to_lowercase().chars().collect();
+            terms
+                .iter()
+                .map(|term| fuzzy_score(&title, term))
+                .sum::<Option<i64>>()
+                .map(|score| (score, post))
+        })
+        .collect();
+    // Newest first among equal scores
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.timestamp.cmp(&a.1.timestamp)));
+    scored
+        .into_iter()
+        .take(SHOWN)
+        .map(|(_, post)| post)
+        .collect()
+}
+
+// Skim-style score of `term` as a subsequence of `title`: consecutive letters
+// and letters at the start of a word count extra, gaps cost a little. None
+// when the term does not occur.
+fn fuzzy_score(title: &[char], term: &[char]) -> Option<i64> {
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for &wanted in term {
+        let found = position + title[position..].iter().position(|&c| c == wanted)?;
+        score += 16;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 12;
+        } else if let Some(previous) = previous {
+            score -= (found - previous - 1).min(8) as i64;
+        }
+        if found == 0 || !title[found - 1].is_alphanumeric() {
+            score += 10;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,290 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use clap::ValueEnum;
+
+use crate::actions::accounts::AccountPool;
+use crate::actions::interrupt;
+use crate::actions::outcome::{self, RunSummary};
+use crate::actions::verbosity;
+use crate::arguments::modeling::{CommentOptions, ExportFormat};
+use crate::database::adding::DB;
+use crate::database::queries::PostFilter;
+use crate::exports::table::TableFormat;
+use crate::filters::keywords::KeywordMatcher;
+use crate::settings::api_keys::{AppConfig, NotificationSettings, PipelineSettings};
+use crate::{ai, exports, notify};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Fetch,
+    Leads,
+    Export(ExportFormat),
+    Notify,
+}
+
+impl Stage {
+    fn name(&self) -> &'static str {
+        match self {
+            Stage::Fetch => "fetch",
+            Stage::Leads => "leads",
+            Stage::Export(_) => "export",
+            Stage::Notify => "notify",
+        }
+    }
+}
+
+// Stages of one `ruddit run` and the settings they share
+pub struct Pipeline {
+    // Name of the [[pipelines]] entry, empty when built from flags
+    pub name: String,
+    pub subreddit: String,
+    pub relevance: String,
+    pub filter: PostFilter,
+    pub comment_options: CommentOptions,
+    pub use_cache: bool,
+    pub notifications: NotificationSettings,
+    pub stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    // A [[pipelines]] entry on top of the filters given on the command line
+    pub fn from_settings(
+        settings: &PipelineSettings,
+        config: &AppConfig,
+        filter: PostFilter,
+        comment_options: CommentOptions,
+        use_cache: bool,
+    ) -> Result<Self, String> {
+        let export = if settings.export.trim().is_empty() {
+            ExportFormat::Xlsx
+        } else {
+            ExportFormat::from_str(settings.export.trim(), true)
+                .map_err(|_| format!("Unknown export format '{}'", settings.export))?
+        };
+        let stages = settings
+            .stages
+            .iter()
+            .map(|stage| match stage.trim().to_lowercase().as_str() {
+                "fetch" => Ok(Stage::Fetch),
+                "leads" => Ok(Stage::Leads),
+                "export" => Ok(Stage::Export(export)),
+                "notify" => Ok(Stage::Notify),
+                other => Err(format!(
+                    "Unknown stage '{}' in pipeline '{}', use fetch, leads, export or notify",
+                    other, settings.name
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if stages.is_empty() {
+            return Err(format!("Pipeline '{}' has no stages", settings.name));
+        }
+
+        let mut filter = filter;
+        if !settings.group.trim().is_empty() {
+            let group = config.keyword_group(Some(&settings.group))?;
+            filter.keywords = Some(KeywordMatcher::from_group(&group, &config.matching)?);
+        }
+        if let Some(days) = settings.since_days {
+            filter.since = Some(Utc::now().timestamp() - i64::from(days) * 86_400);
+        }
+        filter.questions_only |= settings.questions_only;
+        if !settings.intent.trim().is_empty() {
+            filter.intent = Some(settings.intent.trim().to_string());
+        }
+
+        let or_default = |value: &str, default: &str| {
+            if value.trim().is_empty() {
+                default.to_string()
+            } else {
+                value.trim().to_string()
+            }
+        };
+        let mut notifications = config.notifications.clone();
+        if !settings.webhook_url.trim().is_empty() {
+            notifications.webhook_url = settings.webhook_url.trim().to_string();
+        }
+
+        Ok(Pipeline {
+            name: settings.name.clone(),
+            subreddit: or_default(&settings.subreddit, &config.api_keys.subreddit),
+            relevance: or_default(&settings.relevance, &config.api_keys.relevance),
+            filter,
+            comment_options,
+            use_cache,
+            notifications,
+            stages,
+        })
+    }
+}
+
+// What the stages hand on to each other and to the final summary
+#[derive(Default)]
+pub struct PipelineState {
+    pub fetch: Option<RunSummary>,
+    pub leads: Option<usize>,
+    pub exported: Vec<PathBuf>,
+    pub failure: Option<StageFailure>,
+}
+
+pub struct StageFailure {
+    pub stage: &'static str,
+    pub error: String,
+    pub exit_code: i32,
+}
+
+// Run the stages in order. After a failure the remaining stages are skipped,
+// except the notification, which then reports the failure.
+pub async fn run(pipeline: &Pipeline, config: &AppConfig) -> PipelineState {
+    let mut state = PipelineState::default();
+
+    for stage in &pipeline.stages {
+        if interrupt::interrupted() {
+            break;
+        }
+        if state.failure.is_some() && *stage != Stage::Notify {
+            continue;
+        }
+
+        verbosity::info(format!("==> {}", stage.name()));
+        let result = match stage {
+            Stage::Fetch => fetch(pipeline, config, &mut state).await,
+            Stage::Leads => leads(pipeline, &mut state).await,
+            Stage::Export(format) => export(pipeline, *format, &mut state),
+            Stage::Notify => {
+                notify::send(&pipeline.notifications, &state.message(&pipeline.name)).await;
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            eprintln!("{} failed: {}", stage.name(), e);
+            state.failure = Some(StageFailure {
+                stage: stage.name(),
+                exit_code: outcome::exit_code(e.as_ref()),
+                error: e.to_string(),
+            });
+        }
+    }
+
+    state
+}
+
+async fn fetch(
+    pipeline: &Pipeline,
+    config: &AppConfig,
+    state: &mut PipelineState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let token = crate::get_access_token(
+        config.api_keys.reddit_api_id.clone(),
+        config.api_keys.reddit_api_secret.clone(),
+    )
+    .await?;
+    let mut pool = AccountPool::with_primary(token, config).await;
+
+    let (summary, _) = crate::fetch_subreddit_with_comments(
+        &mut pool,
+        &pipeline.subreddit,
+        &pipeline.relevance,
+        &pipeline.filter,
+        &pipeline.comment_options,
+    )
+    .await?;
+    state.fetch = Some(summary);
+    Ok(())
+}
+
+async fn leads(
+    pipeline: &Pipeline,
+    state: &mut PipelineState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    ai::gemini::gemini_generate_leads(&pipeline.filter, pipeline.use_cache).await?;
+
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    state.leads = Some(db.count_run_leads(db.current_run()?)?);
+    Ok(())
+}
+
+fn export(
+    pipeline: &Pipeline,
+    format: ExportFormat,
+    state: &mut PipelineState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let table = match format {
+        ExportFormat::Xlsx => TableFormat::Xlsx,
+        ExportFormat::Csv => TableFormat::Csv,
+        ExportFormat::Ndjson => TableFormat::Ndjson,
+        ExportFormat::Obsidian => {
+            let vault = exports::obsidian::export_obsidian_vault(&pipeline.filter)?;
+            state.exported.push(vault);
+            return Ok(());
+        }
+        ExportFormat::Duckdb => {
+            let folder = exports::duckdb::export_duckdb(&pipeline.filter)?;
+            state.exported.push(folder);
+            return Ok(());
+        }
+    };
+    let paths = exports::excel::export_posts(&pipeline.filter, table, None)?;
+    state.exported.extend(paths);
+    Ok(())
+}
+
+impl PipelineState {
+    // One line per stage that ran, shared by the terminal summary and the notification
+    fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(summary) = &self.fetch {
+            lines.push(format!(
+                "fetch: {} posts and comments, {} new, {} errors",
+                summary.fetched, summary.inserted, summary.errors
+            ));
+        }
+        if let Some(leads) = self.leads {
+            lines.push(format!("leads: {} found in this run", leads));
+        }
+        for path in &self.exported {
+            lines.push(format!("export: {}", path.display()));
+        }
+        if let Some(failure) = &self.failure {
+            lines.push(format!("failed at {}: {}", failure.stage, failure.error));
+        }
+        lines
+    }
+
+    fn message(&self, name: &str) -> String {
+        let command = if name.is_empty() {
+            "ruddit run".to_string()
+        } else {
+            format!("ruddit run {}", name)
+        };
+        let status = if self.failure.is_some() {
+            "failed"
+        } else {
+            "finished"
+        };
+        std::iter::once(format!("{} {}", command, status))
+            .chain(self.lines())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn print(&self) {
+        println!("\nPipeline summary");
+        for line in self.lines() {
+            println!("  {}", line);
+        }
+    }
+
+    // Exit code of the whole run: the failed stage decides, then the fetch
+    pub fn exit_code(&self) -> i32 {
+        if let Some(failure) = &self.failure {
+            return failure.exit_code;
+        }
+        if interrupt::interrupted() {
+            return interrupt::EXIT_INTERRUPTED;
+        }
+        self.fetch.map_or(0, |summary| summary.exit_code())
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+// Held while the spinner draws or a message is printed over it, so the two
+// never interleave on the same line
+static OUTPUT: Mutex<()> = Mutex::new(());
+
+struct Shared {
+    running: AtomicBool,
+    label: Mutex<String>,
+}
+
+// Spinner with a label for slow AI, network and export work. It only draws on a
+// terminal and without --quiet, so piped output and logs stay clean, and stops
+// when dropped.
+pub struct Progress {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Progress {
+    pub fn start(label: impl Into<String>) -> Self {
+        let shared = Arc::new(Shared {
+            running: AtomicBool::new(true),
+            label: Mutex::new(label.into()),
+        });
+
+        let draw = std::io::stdout().is_terminal() && !super::verbosity::quiet();
+        let handle = draw.then(|| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let mut frame = 0;
+                while shared.running.load(Ordering::Relaxed) {
+                    if let Ok(_output) = OUTPUT.lock() {
+                        let label = shared.label.lock().map(|l| l.clone()).unwrap_or_default();
+                        print!("\r\x1b[2K{} {}", FRAMES[frame], label);
+                        let _ = std::io::stdout().flush();
+                    }
+                    frame = (frame + 1) % FRAMES.len();
+                    thread::sleep(Duration::from_millis(100));
+                }
+            })
+        });
+
+        Progress { shared, handle }
+    }
+
+    pub fn set_label(&self, label: impl Into<String>) {
+        if let Ok(mut current) = self.shared.label.lock() {
+            *current = label.into();
+        }
+    }
+
+    // Print a line without it being drawn over by the spinner
+    pub fn println(&self, message: &str) {
+        let _output = OUTPUT.lock();
+        if self.handle.is_some() {
+            print!("\r\x1b[2K");
+        }
+        println!("{}", message);
+    }
+
+    // Stop and clear the spinner line
+    pub fn finish(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.shared.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+            let _output = OUTPUT.lock();
+            print!("\r\x1b[2K");
+            let _ = std::io::stdout().flush();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,74 @@
+use crate::actions::{clipboard, open};
+use crate::arguments::modeling::CopyFormat;
+use crate::database::adding::DB;
+use crate::database::queries::PostFilter;
+
+// `ruddit query`: stored posts matching the filters, newest first. With
+// `--noise` the quarantined posts are listed with why they were flagged.
+// Posts are numbered for `ruddit open <N>`.
+pub fn print_posts(
+    filter: &PostFilter,
+    copy: Option<CopyFormat>,
+    open_first: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let posts = db.get_filtered_posts(filter)?;
+    let tags = db.get_post_tags()?;
+    let notes = db.get_post_notes()?;
+    let reasons = if filter.noise {
+        db.get_noise_reasons()?
+    } else {
+        Default::default()
+    };
+
+    if posts.is_empty() {
+        if filter.noise {
+            println!("No quarantined posts");
+        } else {
+            println!("No posts match these filters");
+        }
+        return Ok(());
+    }
+
+    for (position, post) in posts.iter().enumerate() {
+        let title = match tags.get(&post.id) {
+            Some(tags) => format!("{}  #{}", post.title, tags.join(" #")),
+            None => post.title.clone(),
+        };
+        match reasons.get(&post.id) {
+            Some(reason) => println!(
+                "{:>3}. {}  r/{}  [{}]  {}",
+                position + 1,
+                post.formatted_date,
+                post.subreddit,
+                reason,
+                title
+            ),
+            None => println!(
+                "{:>3}. {}  r/{}  {}",
+                position + 1,
+                post.formatted_date,
+                post.subreddit,
+                title
+            ),
+        }
+        println!("     {}", post.url);
+        if let Some(note) = notes.get(&post.id) {
+            println!("     note: {}", note.replace('\n', "\n           "));
+        }
+    }
+    println!("\n{} posts, open one with `ruddit open <N>`", posts.len());
+    open::remember_listing(&posts);
+    if let Some(format) = copy {
+        clipboard::copy_posts(&posts, format);
+    }
+    if open_first {
+        open::open_link(&open::stored_link(&posts[0]));
+    }
+    if filter.noise {
+        println!("Adjust [noise] in settings.toml and run `ruddit classify` to re-check them");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+
+use crate::database::adding::DB;
+use crate::reddit::client::{RateLimit, RedditClient};
+use crate::settings;
+
+// Reddit resets its rate limit every 10 minutes
+const WINDOW_SECONDS: i64 = 600;
+
+// Ask Reddit where the rate limit of every configured app stands, next to the
+// requests ruddit itself made recently
+pub async fn print_quota() -> Result<(), Box<dyn std::error::Error>> {
+    let config = settings::api_keys::ConfigDirs::load_config()?;
+
+    let mut apps = vec![(
+        "default".to_string(),
+        config.api_keys.reddit_api_id.clone(),
+        config.api_keys.reddit_api_secret.clone(),
+    )];
+    apps.extend(config.accounts.iter().map(|account| {
+        (
+            account.name.clone(),
+            account.client_id.clone(),
+            account.client_secret.clone(),
+        )
+    }));
+
+    println!("Reddit API quota");
+    println!("{}", "-".repeat(60));
+    let mut failed = 0;
+    for (name, client_id, client_secret) in apps {
+        let limit = match crate::get_access_token(client_id, client_secret).await {
+            Ok(token) => RedditClient::new(token).rate_limit().await,
+            Err(e) => Err(e),
+        };
+        match limit {
+            Ok(limit) => println!("{:<20} {}", name, describe(&limit)),
+            Err(e) => {
+                failed += 1;
+                println!("{:<20} {}", name, e);
+            }
+        }
+    }
+
+    // The check above is counted too
+    let db = DB::new()?;
+    let now = Utc::now().timestamp();
+    println!("\nRequests made by ruddit:");
+    for (label, seconds) in [
+        ("Last 10 minutes", WINDOW_SECONDS),
+        ("Last hour", 3_600),
+        ("Last 24 hours", 86_400),
+    ] {
+        println!("  {:<18} {}", label, db.count_api_requests(now - seconds)?);
+    }
+
+    if failed > 0 {
+        return Err(format!("Could not read the quota of {} app(s)", failed).into());
+    }
+    Ok(())
+}
+
+fn describe(limit: &RateLimit) -> String {
+    let count = |value: Option<f64>| value.map_or("?".to_string(), |v| format!("{:.0}", v));
+    let reset = match limit.reset {
+        Some(reset) => format!(
+            ", resets in {} (at {})",
+            format_wait(reset),
+            (Local::now() + reset).format("%H:%M:%S")
+        ),
+        None => String::new(),
+    };
+    format!(
+        "{} used, {} remaining{}",
+        count(limit.used),
+        count(limit.remaining),
+        reset
+    )
+}
+
+fn format_wait(wait: Duration) -> String {
+    let seconds = wait.as_secs();
+    if seconds >= 60 {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
@@ -0,0 +1,224 @@
+#[cfg(not(target_os = "windows"))]
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(not(target_os = "windows"))]
+use directories::BaseDirs;
+
+#[cfg(target_os = "macos")]
+use crate::settings::paths::Paths;
+use crate::settings::paths::RUDDIT_HOME;
+
+// Register `ruddit watch` to start automatically for the current user, with
+// the --group and --db-path given to `service install`
+pub fn install_service(
+    group: Option<&str>,
+    db_path: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let exe = std::env::current_exe()?;
+    let exe = exe.to_string_lossy();
+    let args = watch_args(group, db_path)?;
+    // The service runs outside this shell, keep its files where ours are
+    let home = std::env::var(RUDDIT_HOME)
+        .ok()
+        .filter(|home| !home.is_empty());
+
+    #[cfg(target_os = "linux")]
+    {
+        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
+        let unit_dir = base_dirs.config_dir().join("systemd/user");
+        fs::create_dir_all(&unit_dir)?;
+
+        let unit = format!(
+            "[Unit]
+Description=Ruddit Reddit monitoring
+After=network-online.target
+
+[Service]
+ExecStart={}
+{}Restart=on-failure
+RestartSec=60
+
+[Install]
+WantedBy=default.target
+",
+            std::iter::once(exe.as_ref())
+                .chain(args.iter().map(String::as_str))
+                .map(|arg| format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect::<Vec<_>>()
+                .join(" "),
+            home.as_ref().map_or(String::new(), |home| format!(
+                "Environment=\"{}={}\"\n",
+                RUDDIT_HOME, home
+            ))
+        );
+
+        let unit_path = unit_dir.join("ruddit.service");
+        fs::write(&unit_path, unit)?;
+        println!("Wrote systemd user unit: {}", unit_path.display());
+
+        run("systemctl", &["--user", "daemon-reload"]);
+        run(
+            "systemctl",
+            &["--user", "enable", "--now", "ruddit.service"],
+        );
+        println!("Check it with: systemctl --user status ruddit");
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
+        let agents_dir = base_dirs.home_dir().join("Library/LaunchAgents");
+        fs::create_dir_all(&agents_dir)?;
+
+        let log_path = Paths::get()?.watch_log();
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.ruddit.watch</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+{}    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>{}</string>
+    <key>StandardErrorPath</key>
+    <string>{}</string>
+</dict>
+</plist>
+"#,
+            std::iter::once(exe.as_ref())
+                .chain(args.iter().map(String::as_str))
+                .map(|arg| format!("        <string>{}</string>\n", xml_escape(arg)))
+                .collect::<String>(),
+            home.as_ref().map_or(String::new(), |home| format!(
+                "    <key>EnvironmentVariables</key>\n    <dict>\n        <key>{}</key>\n        <string>{}</string>\n    </dict>\n",
+                RUDDIT_HOME,
+                xml_escape(home)
+            )),
+            xml_escape(&log_path.to_string_lossy()),
+            xml_escape(&log_path.to_string_lossy())
+        );
+
+        let plist_path = agents_dir.join("com.ruddit.watch.plist");
+        fs::write(&plist_path, plist)?;
+        println!("Wrote launchd agent: {}", plist_path.display());
+
+        run("launchctl", &["load", "-w", &plist_path.to_string_lossy()]);
+        println!("Logs are written to {}", log_path.display());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let command = std::iter::once(exe.as_ref())
+            .chain(args.iter().map(String::as_str))
+            .map(|arg| format!("\"{}\"", arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Scheduled tasks take no environment, set it in the shell running watch
+        let task = match &home {
+            Some(home) => format!("cmd /c set \"{}={}\" && {}", RUDDIT_HOME, home, command),
+            None => command,
+        };
+        run(
+            "schtasks",
+            &[
+                "/Create", "/TN", "Ruddit", "/TR", &task, "/SC", "ONLOGON", "/F",
+            ],
+        );
+        run("schtasks", &["/Run", "/TN", "Ruddit"]);
+    }
+
+    Ok(())
+}
+
+// Stop and remove the service created by install_service
+pub fn uninstall_service() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(target_os = "linux")]
+    {
+        run(
+            "systemctl",
+            &["--user", "disable", "--now", "ruddit.service"],
+        );
+
+        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
+        let unit_path = base_dirs.config_dir().join("systemd/user/ruddit.service");
+        if unit_path.exists() {
+            fs::remove_file(&unit_path)?;
+            println!("Removed {}", unit_path.display());
+        }
+        run("systemctl", &["--user", "daemon-reload"]);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
+        let plist_path = base_dirs
+            .home_dir()
+            .join("Library/LaunchAgents/com.ruddit.watch.plist");
+
+        run(
+            "launchctl",
+            &["unload", "-w", &plist_path.to_string_lossy()],
+        );
+        if plist_path.exists() {
+            fs::remove_file(&plist_path)?;
+            println!("Removed {}", plist_path.display());
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        run("schtasks", &["/End", "/TN", "Ruddit"]);
+        run("schtasks", &["/Delete", "/TN", "Ruddit", "/F"]);
+    }
+
+    Ok(())
+}
+
+// Arguments of the `ruddit watch` the service runs. The database path is made
+// absolute as the service starts in another folder.
+fn watch_args(
+    group: Option<&str>,
+    db_path: Option<&Path>,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut args = vec!["watch".to_string()];
+    if let Some(group) = group {
+        args.extend(["--group".to_string(), group.to_string()]);
+    }
+    if let Some(db_path) = db_path {
+        args.extend([
+            "--db-path".to_string(),
+            std::path::absolute(db_path)?.to_string_lossy().into_owned(),
+        ]);
+    }
+    Ok(args)
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Run a service manager command, reporting rather than failing when it is missing
+fn run(program: &str, args: &[&str]) {
+    match Command::new(program).args(args).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("`{} {}` exited with {}", program, args.join(" "), status),
+        Err(e) => eprintln!(
+            "Could not run `{} {}`: {}. Run it manually to finish the setup.",
+            program,
+            args.join(" "),
+            e
+        ),
+    }
+}
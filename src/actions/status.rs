@@ -0,0 +1,387 @@
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::actions::display::display_date;
+use crate::database::accounts::AccountUsage;
+use crate::database::adding::DB;
+use crate::database::runs::RunRecord;
+use crate::database::status::{AiUsage, DetectionLatency, SyncStatus};
+use crate::settings;
+
+// Result of a single health check
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn ok(detail: impl Into<String>) -> Self {
+        Check {
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn failed(detail: impl Into<String>) -> Self {
+        Check {
+            ok: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub healthy: bool,
+    pub database: Check,
+    pub config: Check,
+    pub reddit_token: Option<Check>,
+    pub last_syncs: Vec<SyncStatus>,
+    pub last_ai_call: Option<AiUsage>,
+    pub accounts: Vec<AccountUsage>,
+    // Over the posts discovered in the last LATENCY_DAYS days
+    pub detection_latency: Option<DetectionLatency>,
+}
+
+const LATENCY_DAYS: i64 = 7;
+
+// Collect the health of every moving part; the token check calls Reddit
+pub async fn build_report(check_token: bool) -> StatusReport {
+    let (database, last_syncs, last_ai_call, accounts, detection_latency) = match DB::new()
+        .and_then(|db| {
+            db.create_tables()?;
+            let stats = db.get_stats()?;
+            Ok((
+                stats,
+                db.get_sync_status()?,
+                db.get_last_ai_call()?,
+                db.get_account_usage()?,
+                db.get_detection_latency(Utc::now().timestamp() - LATENCY_DAYS * 86400)?,
+            ))
+        }) {
+        Ok((stats, syncs, ai_call, accounts, latency)) => (
+            Check::ok(format!(
+                "{} posts, {} comments, {} leads",
+                stats.posts, stats.comments, stats.leads
+            )),
+            syncs,
+            ai_call,
+            accounts,
+            latency,
+        ),
+        Err(e) => (
+            Check::failed(e.to_string()),
+            Vec::new(),
+            None,
+            Vec::new(),
+            None,
+        ),
+    };
+
+    let config = settings::api_keys::ConfigDirs::read_config().map_err(|e| e.to_string());
+    let config_check = match &config {
+        Ok(config) => {
+            let placeholder = |value: &str| {
+                value.trim().is_empty() || value == "CHANGE_ME" || value.ends_with("_here")
+            };
+            let missing: Vec<&str> = [
+                ("reddit_api_id", &config.api_keys.reddit_api_id),
+                ("reddit_api_secret", &config.api_keys.reddit_api_secret),
+                ("gemini_api_key", &config.api_keys.gemini_api_key),
+            ]
+            .iter()
+            .filter(|(_, value)| placeholder(value))
+            .map(|(name, _)| *name)
+            .collect();
+
+            if missing.is_empty() {
+                Check::ok("settings.toml parsed")
+            } else {
+                Check::failed(format!("Not configured: {}", missing.join(", ")))
+            }
+        }
+        Err(e) => Check::failed(format!("Failed to read settings.toml: {}", e)),
+    };
+
+    let reddit_token = match (check_token, &config) {
+        (false, _) => None,
+        (true, Err(_)) => Some(Check::failed("Skipped, config could not be read")),
+        (true, Ok(config)) => Some(
+            match crate::get_access_token(
+                config.api_keys.reddit_api_id.clone(),
+                config.api_keys.reddit_api_secret.clone(),
+            )
+            .await
+            {
+                Ok(token) if !token.is_empty() => Check::ok("Access token issued"),
+                Ok(_) => Check::failed("Received an empty token"),
+                Err(e) => Check::failed(e.to_string()),
+            },
+        ),
+    };
+
+    let healthy = database.ok
+        && config_check.ok
+        && reddit_token.as_ref().map(|check| check.ok).unwrap_or(true);
+
+    StatusReport {
+        healthy,
+        database,
+        config: config_check,
+        reddit_token,
+        last_syncs,
+        last_ai_call,
+        accounts,
+        detection_latency,
+    }
+}
+
+pub async fn print_status() {
+    let report = build_report(true).await;
+
+    let mark = |check: &Check| if check.ok { "OK  " } else { "FAIL" };
+
+    println!("Ruddit status");
+    println!("{}", "-".repeat(60));
+    println!(
+        "[{}] Database: {}",
+        mark(&report.database),
+        report.database.detail
+    );
+    println!(
+        "[{}] Config:   {}",
+        mark(&report.config),
+        report.config.detail
+    );
+    if let Some(token) = &report.reddit_token {
+        println!("[{}] Reddit:   {}", mark(token), token.detail);
+    }
+
+    println!("\nLast successful syncs:");
+    if report.last_syncs.is_empty() {
+        println!("  none yet");
+    }
+    for sync in &report.last_syncs {
+        println!(
+            "  {:<30} {}, {} posts",
+            sync.source,
+            display_date(sync.last_synced_at, &sync.formatted_date),
+            sync.posts
+        );
+    }
+
+    println!("\nLast AI call:");
+    match &report.last_ai_call {
+        Some(call) => println!(
+            "  {} {} at {}{}{}",
+            call.kind,
+            if call.success { "succeeded" } else { "failed" },
+            display_date(call.called_at, &call.formatted_date),
+            if call.api_key.is_empty() {
+                String::new()
+            } else {
+                format!(" (key {})", call.api_key)
+            },
+            if call.message.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", call.message)
+            }
+        ),
+        None => println!("  none yet"),
+    }
+
+    if !report.accounts.is_empty() {
+        println!("\nReddit accounts:");
+        for account in &report.accounts {
+            println!(
+                "  {:<20} {} requests, {} rate limited, last used {}",
+                account.account,
+                account.requests,
+                account.rate_limited,
+                display_date(account.last_used_at, &account.formatted_date)
+            );
+        }
+    }
+
+    if let Some(latency) = &report.detection_latency {
+        println!("\nDetection latency (last {} days):", LATENCY_DAYS);
+        println!(
+            "  median {}, 90th percentile {}, over {} posts",
+            format_duration(latency.median_secs),
+            format_duration(latency.p90_secs),
+            latency.posts
+        );
+    }
+
+    println!(
+        "\nOverall: {}",
+        if report.healthy {
+            "healthy"
+        } else {
+            "unhealthy"
+        }
+    );
+}
+
+// "45s", "12m", "3h 5m", "2d 4h"
+fn format_duration(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s => format!("{}d {}h", s / 86400, s % 86400 / 3600),
+    }
+}
+
+// `ruddit runs`: what each recent invocation stored
+pub fn print_runs(limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let runs = db.get_runs(limit)?;
+
+    if runs.is_empty() {
+        println!("No runs recorded yet");
+        return Ok(());
+    }
+
+    for run in &runs {
+        println!(
+            "#{:<5} {}  {:<8} fetched={} inserted={} skipped={} errors={}",
+            run.id,
+            display_date(run.started_at, &run.formatted_date),
+            run.command,
+            run.fetched,
+            run.inserted,
+            run.skipped,
+            run.errors
+        );
+        if !run.args.is_empty() {
+            println!("       {}", run.args);
+        }
+    }
+    println!("\nExport what a run stored with `ruddit export --run <ID>`");
+
+    Ok(())
+}
+
+// `ruddit diff --runs <FROM> <TO>`: what changed from one run to the other,
+// e.g. after editing the keywords
+pub fn print_run_diff(from: i64, to: i64, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let run = |id: i64| -> Result<RunRecord, Box<dyn std::error::Error>> {
+        Ok(db
+            .get_run(id)?
+            .ok_or_else(|| format!("No run #{}, see `ruddit runs`", id))?)
+    };
+    let (from, to) = if from <= to {
+        (run(from)?, run(to)?)
+    } else {
+        (run(to)?, run(from)?)
+    };
+    let diff = db.diff_runs(&from, &to)?;
+
+    for (label, run) in [("From", &diff.from), ("To", &diff.to)] {
+        println!(
+            "{:<5} #{} {}  {} {}",
+            label,
+            run.id,
+            display_date(run.started_at, &run.formatted_date),
+            run.command,
+            run.args
+        );
+    }
+    println!("{}", "-".repeat(60));
+
+    println!("\nPosts added: {}", diff.posts_added.len());
+    for post in diff.posts_added.iter().take(limit) {
+        println!("  r/{:<20} {}", post.subreddit, post.title);
+    }
+    more(diff.posts_added.len(), limit);
+
+    println!("\nLeads added: {}", diff.leads_added.len());
+    for lead in diff.leads_added.iter().take(limit) {
+        println!(
+            "  {:<8} {:<10} {}",
+            lead.after.relevance, lead.after.sentiment, lead.title
+        );
+    }
+    more(diff.leads_added.len(), limit);
+
+    println!("\nLeads changed: {}", diff.leads_changed.len());
+    for lead in diff.leads_changed.iter().take(limit) {
+        let Some(before) = &lead.before else {
+            continue;
+        };
+        println!("  {}", lead.title);
+        if before.relevance != lead.after.relevance {
+            println!(
+                "    relevance {} -> {}",
+                before.relevance, lead.after.relevance
+            );
+        }
+        if before.sentiment != lead.after.sentiment {
+            println!(
+                "    sentiment {} -> {}",
+                before.sentiment, lead.after.sentiment
+            );
+        }
+    }
+    more(diff.leads_changed.len(), limit);
+
+    println!("\nComment scores that moved: {}", diff.score_moves.len());
+    for moved in diff.score_moves.iter().take(limit) {
+        println!(
+            "  {:>+6} ({} -> {})  u/{} on \"{}\"",
+            moved.after - moved.before,
+            moved.before,
+            moved.after,
+            moved.author,
+            moved.post_title
+        );
+    }
+    more(diff.score_moves.len(), limit);
+
+    Ok(())
+}
+
+fn more(count: usize, limit: usize) {
+    if count > limit {
+        println!("  ... and {} more (--limit)", count - limit);
+    }
+}
+
+// `ruddit searches`: the saved searches of the config and where each left off
+pub fn print_searches() -> Result<(), Box<dyn std::error::Error>> {
+    let config = settings::api_keys::ConfigDirs::read_config()?;
+    if config.searches.is_empty() {
+        println!("No saved searches, add [[searches]] blocks to settings.toml");
+        return Ok(());
+    }
+
+    let db = DB::new()?;
+    db.create_tables()?;
+    for search in &config.searches {
+        let state = db.get_search_state(&search.name)?;
+        let scope = if search.subreddit.trim().is_empty() {
+            "all of Reddit".to_string()
+        } else {
+            format!("r/{}", search.subreddit.trim())
+        };
+        println!("{}  {}  in {}", search.name, search.query, scope);
+        match state.last_run_at {
+            Some(last_run_at) => println!(
+                "    {} runs, {} posts found, last run {}",
+                state.runs,
+                state.hits,
+                display_date(last_run_at, &DB::format_timestamp(last_run_at)?)
+            ),
+            None => println!("    not run yet"),
+        }
+    }
+    println!("\nExport what a search found with `ruddit export --saved-search <NAME>`");
+
+    Ok(())
+}
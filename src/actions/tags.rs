@@ -0,0 +1,72 @@
+use crate::arguments::modeling::normalize_tag;
+use crate::arguments::post_ref::PostRef;
+use crate::database::adding::DB;
+use crate::database::tags::STARRED;
+
+// `ruddit tag` and `ruddit star`: add or remove tags on a post that is
+// already stored
+pub fn tag_post(
+    post: &PostRef,
+    tags: &[String],
+    remove: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tags: Vec<String> = tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    if tags.is_empty() {
+        return Err("Give at least one non-empty tag".into());
+    }
+
+    let db = DB::new()?;
+    db.create_tables()?;
+    let post_id = post.row_id()?;
+    if !db.is_post_stored(post_id)? {
+        return Err(format!(
+            "Post {} is not stored, fetch it first with `ruddit thread {}`",
+            post.id, post.id
+        )
+        .into());
+    }
+
+    for tag in &tags {
+        let label = if tag == STARRED {
+            "star".to_string()
+        } else {
+            format!("tag '{}'", tag)
+        };
+        let changed = if remove {
+            db.remove_tag(post_id, tag)?
+        } else {
+            db.add_tag(post_id, tag)?
+        };
+        match (remove, changed) {
+            (false, true) => println!("Added {} to {}", label, post.id),
+            (false, false) => println!("{} already has {}", post.id, label),
+            (true, true) => println!("Removed {} from {}", label, post.id),
+            (true, false) => println!("{} has no {}", post.id, label),
+        }
+    }
+
+    Ok(())
+}
+
+// `ruddit tags`: the tags in use, most used first
+pub fn print_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let tags = db.count_tags()?;
+    if tags.is_empty() {
+        println!("No tagged posts, add tags with `ruddit tag <post> <tag>`");
+        return Ok(());
+    }
+
+    for (tag, posts) in &tags {
+        println!("{:<24} {} posts", tag, posts);
+    }
+    println!(
+        "\nList or export them with `ruddit query --tag <TAG>` or `ruddit export --tag <TAG>`"
+    );
+    Ok(())
+}
@@ -0,0 +1,457 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Local;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use crate::actions::interrupt;
+use crate::actions::verbosity;
+use crate::arguments::modeling::{CommentOptions, CommentSort, ThreadFormat};
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::database::media::MediaDataWrapper;
+use crate::exports::anonymize::Anonymizer;
+use crate::filters::keywords::KeywordMatcher;
+use crate::filters::text::ellipsize;
+use crate::reddit::client::RedditClient;
+use crate::{RedditComment, RedditPost};
+use crate::{exports, reddit, settings};
+
+// Characters of a matching comment shown in the watch alert
+const MATCH_PREVIEW: usize = 300;
+
+// Everything fetched for a single post
+#[derive(Debug)]
+pub struct Thread {
+    pub post: PostDataWrapper,
+    pub media: Vec<MediaDataWrapper>,
+    pub comments: Vec<CommentDataWrapper>,
+    pub is_self: bool,
+    pub contest_mode: bool,
+    pub locked: bool,
+    // Comments Reddit left behind "load more" links
+    pub unloaded: u64,
+    // "Continue this thread" links that were followed
+    pub continued: usize,
+}
+
+// Fetch a post and its comment tree without relying on the exact listing shape
+pub async fn fetch_thread(
+    token: &str,
+    post_id: &str,
+    options: &CommentOptions,
+) -> Result<Thread, String> {
+    let response = RedditClient::new(token)
+        .get(reddit::comments(post_id, options, true))
+        .await
+        .map_err(|e| format!("Failed to retrieve post {}: {}", post_id, e))?;
+
+    match response.status() {
+        StatusCode::NOT_FOUND => return Err(format!("Post {} was not found", post_id)),
+        StatusCode::FORBIDDEN => {
+            return Err(format!(
+                "Post {} is in a private or quarantined subreddit",
+                post_id
+            ));
+        }
+        _ => {}
+    }
+
+    let mut listings: Value = reddit::client::json(response)
+        .await
+        .map_err(|e| format!("Failed to read post {}: {}", post_id, e))?;
+
+    let post_data = &listings[0]["data"]["children"][0]["data"];
+    let post: RedditPost = serde_json::from_value(post_data.clone())
+        .map_err(|e| format!("Unexpected response for post {}: {}", post_id, e))?;
+
+    let timestamp = post.created_utc as i64;
+    let post_wrapper = PostDataWrapper {
+        id: DB::decode_reddit_id(&post.id)
+            .ok_or_else(|| format!("Unexpected id {:?} for post {}", post.id, post_id))?,
+        timestamp,
+        formatted_date: DB::format_timestamp(timestamp).map_err(|e| e.to_string())?,
+        title: post.title.clone(),
+        url: post.url.clone(),
+        relevance: "thread".to_string(),
+        subreddit: post.subreddit.clone(),
+        permalink: format!("https://reddit.com{}", post.permalink),
+        selftext: post.selftext.clone().unwrap_or_default(),
+    };
+
+    let mut thread = Thread {
+        media: crate::extract_post_media(&post),
+        post: post_wrapper,
+        comments: Vec::new(),
+        is_self: post_data["is_self"].as_bool().unwrap_or(false),
+        contest_mode: post_data["contest_mode"].as_bool().unwrap_or(false),
+        locked: post_data["locked"].as_bool().unwrap_or(false),
+        unloaded: 0,
+        continued: 0,
+    };
+
+    if let Some(listing) = listings.get_mut(1) {
+        thread.continued = follow_continuations(token, post_id, listing, options, true).await;
+    }
+
+    // The comment listing is missing entirely on some removed posts
+    if let Some(children) = listings[1]["data"]["children"].as_array() {
+        collect_comments(children, &post, options, &mut thread);
+    }
+    if options.limit > 0 {
+        thread.comments.truncate(options.limit);
+    }
+
+    Ok(thread)
+}
+
+fn collect_comments(
+    children: &[Value],
+    post: &RedditPost,
+    options: &CommentOptions,
+    thread: &mut Thread,
+) {
+    for child in children {
+        match child["kind"].as_str() {
+            Some("t1") => {
+                let Ok(comment) = serde_json::from_value::<RedditComment>(child["data"].clone())
+                else {
+                    continue;
+                };
+
+                if options.keep(comment.score) {
+                    thread.comments.push(comment_wrapper(&comment, post));
+                }
+
+                // Replies are an empty string when there are none
+                if let Some(replies) = comment.replies["data"]["children"].as_array() {
+                    collect_comments(replies, post, options, thread);
+                }
+            }
+            Some("more") => thread.unloaded += child["data"]["count"].as_u64().unwrap_or(0),
+            _ => {}
+        }
+    }
+}
+
+// Replace "continue this thread" stubs in a comment listing with the replies
+// they stand for, fetching at most `continue_limit` of them and following
+// nested ones `continue_depth` levels down. Returns how many were followed.
+pub async fn follow_continuations(
+    token: &str,
+    post_id: &str,
+    listing: &mut Value,
+    options: &CommentOptions,
+    raw_json: bool,
+) -> usize {
+    let mut pending: Vec<(String, usize)> = Vec::new();
+    find_continuations(listing, String::new(), 1, &mut pending);
+
+    let client = RedditClient::new(token);
+    let mut followed = 0;
+    while let Some((comment_pointer, depth)) = pending.pop() {
+        if followed >= options.continue_limit
+            || depth > options.continue_depth
+            || interrupt::interrupted()
+        {
+            continue;
+        }
+        let Some(comment_id) = listing
+            .pointer(&format!("{}/data/id", comment_pointer))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        else {
+            continue;
+        };
+
+        followed += 1;
+        // A link that fails is left as it was, the rest of the thread is kept
+        let Ok(response) = client
+            .get(reddit::comment_replies(
+                post_id,
+                &comment_id,
+                options,
+                raw_json,
+            ))
+            .await
+        else {
+            continue;
+        };
+        let Ok(focused) = reddit::client::json::<Value>(response).await else {
+            continue;
+        };
+
+        // The focused listing starts with the comment itself, holding all its replies
+        let replies = &focused[1]["data"]["children"][0]["data"]["replies"];
+        let replies_pointer = format!("{}/data/replies", comment_pointer);
+        if replies.is_object()
+            && let Some(slot) = listing.pointer_mut(&replies_pointer)
+        {
+            *slot = replies.clone();
+            let replies = listing
+                .pointer(&replies_pointer)
+                .cloned()
+                .unwrap_or_default();
+            find_continuations(&replies, replies_pointer, depth + 1, &mut pending);
+        }
+    }
+
+    followed
+}
+
+// Collect the JSON pointers of comments whose replies end in a "continue this
+// thread" stub. `pointer` is where `listing` sits in the whole response.
+fn find_continuations(
+    listing: &Value,
+    pointer: String,
+    depth: usize,
+    found: &mut Vec<(String, usize)>,
+) {
+    let Some(children) = listing["data"]["children"].as_array() else {
+        return;
+    };
+    for (index, child) in children.iter().enumerate() {
+        let child_pointer = format!("{}/data/children/{}", pointer, index);
+        match child["kind"].as_str() {
+            Some("t1") => find_continuations(
+                &child["data"]["replies"],
+                format!("{}/data/replies", child_pointer),
+                depth,
+                found,
+            ),
+            // Unlike "load more" stubs, these carry no comment ids
+            Some("more") if child["data"]["id"].as_str() == Some("_") => {
+                if let Some(comment_pointer) = pointer.strip_suffix("/data/replies") {
+                    found.push((comment_pointer.to_string(), depth));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn comment_wrapper(comment: &RedditComment, post: &RedditPost) -> CommentDataWrapper {
+    let timestamp = comment.created_utc as i64;
+    CommentDataWrapper {
+        id: comment.id.clone(),
+        post_id: post.id.clone(),
+        body: comment.body.clone(),
+        author: comment.author.clone(),
+        timestamp,
+        formatted_date: DB::format_timestamp(timestamp).unwrap_or_default(),
+        score: comment.score,
+        permalink: comment.permalink.clone(),
+        parent_id: comment.parent_id.clone(),
+        subreddit: post.subreddit.clone(),
+        post_title: post.title.clone(),
+    }
+}
+
+// Save the post, its media and its comments
+// Save a fetched thread, returning how many of its comments were new
+pub fn store_thread(thread: &Thread) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut db = DB::new()?;
+    db.create_tables()?;
+    db.append_results(std::slice::from_ref(&thread.post))?;
+    db.append_media(&thread.media)?;
+    Ok(db.append_comments(&thread.comments)?)
+}
+
+// `ruddit thread <id|url>`: snapshot a post and its comments into the database
+pub async fn snapshot_thread(
+    token: &str,
+    target: &str,
+    options: &CommentOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let post_ref = crate::resolve_post_ref(target).await?;
+    verbosity::info(format!("Fetching thread {}...", post_ref.id));
+
+    let thread = fetch_thread(token, &post_ref.id, options).await?;
+    let inserted = store_thread(&thread)?;
+
+    println!("\n{}", thread.post.title);
+    println!(
+        "r/{} | {} | {}",
+        thread.post.subreddit,
+        if thread.is_self {
+            "text post".to_string()
+        } else {
+            format!("link: {}", thread.post.url)
+        },
+        crate::actions::display::display_date(thread.post.timestamp, &thread.post.formatted_date)
+    );
+    println!("{}", thread.post.permalink);
+
+    if thread.contest_mode {
+        println!("Contest mode is on: comments are in random order and scores are hidden");
+    }
+    if thread.locked {
+        println!("This thread is locked");
+    }
+
+    if thread.comments.is_empty() {
+        println!("\nNo comments yet");
+    } else {
+        println!(
+            "\nSaved {}",
+            crate::actions::display::stored_counts("comments", thread.comments.len(), inserted)
+        );
+    }
+    if thread.continued > 0 {
+        println!(
+            "Followed {} \"continue this thread\" links",
+            thread.continued
+        );
+    }
+    if thread.unloaded > 0 {
+        println!(
+            "{} more comments are behind \"load more\" links and were not fetched",
+            thread.unloaded
+        );
+    }
+    if !thread.media.is_empty() {
+        println!("Saved {} media attachments", thread.media.len());
+    }
+
+    Ok(())
+}
+
+// `ruddit thread watch <id|url>`: re-fetch a thread until interrupted, storing new
+// comments and alerting on the ones that match the keywords
+pub async fn watch_thread(
+    token: String,
+    target: &str,
+    interval: u64,
+    keywords: &[String],
+    options: &CommentOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = settings::api_keys::ConfigDirs::read_config()?;
+    let matcher = if keywords.is_empty() {
+        KeywordMatcher::from_config(&config)?
+    } else {
+        KeywordMatcher::new(keywords, &config.api_keys.match_keyword)
+            .with_matching(&config.matching)
+    };
+
+    let post_ref = crate::resolve_post_ref(target).await?;
+
+    // Newest first and nothing cut off, so a new reply on a long thread is not
+    // left out of the comments that get compared
+    let options = &CommentOptions {
+        sort: CommentSort::New,
+        limit: 0,
+        ..*options
+    };
+
+    // Comments already stored are never reported
+    let thread = fetch_thread(&token, &post_ref.id, options).await?;
+    store_thread(&thread)?;
+    let db = DB::new()?;
+    let mut seen: HashSet<String> = db
+        .get_post_comments(&post_ref.id)?
+        .into_iter()
+        .map(|comment| comment.id)
+        .collect();
+
+    println!(
+        "Watching \"{}\" (r/{}) every {}s, {} comments so far",
+        thread.post.title,
+        thread.post.subreddit,
+        interval,
+        seen.len()
+    );
+
+    loop {
+        if !interrupt::sleep(Duration::from_secs(interval.max(10))).await {
+            println!("Stopped watching, {} comments seen", seen.len());
+            return Ok(());
+        }
+        let now = Local::now().format("%Y-%m-%d %H:%M:%S");
+
+        let thread = match fetch_thread(&token, &post_ref.id, options).await {
+            Ok(thread) => thread,
+            // An expired token is renewed by the client, anything else is
+            // retried on the next poll
+            Err(e) => {
+                eprintln!("[{}] {}", now, e);
+                continue;
+            }
+        };
+
+        let new_comments: Vec<CommentDataWrapper> = thread
+            .comments
+            .into_iter()
+            .filter(|comment| !seen.contains(&comment.id))
+            .collect();
+        if new_comments.is_empty() {
+            continue;
+        }
+
+        let mut db = DB::new()?;
+        db.append_comments(&new_comments)?;
+        println!("[{}] {} new comments", now, new_comments.len());
+
+        for comment in &new_comments {
+            seen.insert(comment.id.clone());
+            if matcher.matches(&comment.body) {
+                // Without keywords every comment matches and there is no term to show
+                let matched = matcher
+                    .find(&comment.body)
+                    .filter(|hit| !hit.term.is_empty())
+                    .map(|hit| format!(" on '{}' ({})", hit.term, hit.match_type.as_str()))
+                    .unwrap_or_default();
+                // Ring the terminal bell so the alert is noticed
+                println!(
+                    "\x07[{}] Match{} from u/{} ({} points): {}\n  https://reddit.com{}",
+                    now,
+                    matched,
+                    comment.author,
+                    comment.score,
+                    ellipsize(comment.body.trim(), MATCH_PREVIEW),
+                    comment.permalink
+                );
+            }
+        }
+    }
+}
+
+// `ruddit thread export <id|url>`: render a stored thread as a document. A post
+// that is not stored yet is fetched and saved first.
+pub async fn export_thread(
+    target: &str,
+    format: ThreadFormat,
+    output: Option<PathBuf>,
+    options: &CommentOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let post_ref = crate::resolve_post_ref(target).await?;
+    let db = DB::new()?;
+    db.create_tables()?;
+
+    let post = match db.get_post(post_ref.row_id()?)? {
+        Some(post) => post,
+        None => {
+            verbosity::info(format!(
+                "Thread {} is not stored, fetching it...",
+                post_ref.id
+            ));
+            let config = settings::api_keys::ConfigDirs::read_config()?;
+            let token = crate::get_access_token(
+                config.api_keys.reddit_api_id.clone(),
+                config.api_keys.reddit_api_secret.clone(),
+            )
+            .await?;
+            let thread = fetch_thread(&token, &post_ref.id, options).await?;
+            store_thread(&thread)?;
+            thread.post
+        }
+    };
+    let mut comments = db.get_post_comments(&post_ref.id)?;
+    let anonymizer = Anonymizer::from_config()?;
+    for comment in comments.iter_mut() {
+        comment.author = anonymizer.author(&comment.author);
+    }
+
+    exports::thread::export_thread(&post, &comments, format, output)?;
+    Ok(())
+}
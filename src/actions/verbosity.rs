@@ -0,0 +1,41 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// How much a run prints. Quiet keeps errors, warnings and final results such
+// as the summary line, for cron jobs that mail their output. Verbose comes
+// from -v, which also raises the log level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn get() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+pub fn quiet() -> bool {
+    get() == Verbosity::Quiet
+}
+
+pub fn verbose() -> bool {
+    get() == Verbosity::Verbose
+}
+
+// Progress line, left out with --quiet
+pub fn info(message: impl Display) {
+    if !quiet() {
+        println!("{}", message);
+    }
+}
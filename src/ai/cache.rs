@@ -0,0 +1,40 @@
+use sha2::{Digest, Sha256};
+
+use crate::ai::keys::GEMINI_MODEL;
+use crate::database;
+
+// Responses are cached under a hash of everything that shapes them:
+// the kind of call, the model, the prompts and the posts sent
+pub fn cache_key(kind: &str, system_prompt: &str, question: &str, post_ids: &[i64]) -> String {
+    let mut hasher = Sha256::new();
+    for part in [kind, GEMINI_MODEL, system_prompt, question] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    for id in post_ids {
+        hasher.update(id.to_le_bytes());
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn lookup(key: &str) -> Option<String> {
+    database::adding::DB::new()
+        .and_then(|db| db.get_ai_cache(key))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read the AI cache: {}", e);
+            None
+        })
+}
+
+pub fn store(key: &str, kind: &str, response: &str) {
+    if let Err(e) =
+        database::adding::DB::new().and_then(|db| db.store_ai_cache(key, kind, response))
+    {
+        log::warn!("Failed to write the AI cache: {}", e);
+    }
+}
@@ -1,17 +1,37 @@
 use anyhow::Result;
-use gemini_rust::Gemini;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::io::Write;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
-use std::time::Duration;
 
+use crate::actions::progress::Progress;
+use crate::actions::verbosity;
+use crate::ai::cache;
+use crate::ai::keys::{GeminiKeys, is_quota_error};
+use crate::ai::retrieval;
+use crate::database::adding::{CommentDataWrapper, PostDataWrapper};
+use crate::database::leads::{LeadDataWrapper, REVIEW_ACCEPTED, REVIEW_REJECTED};
+use crate::database::queries::PostFilter;
 use crate::exports::excel;
+use crate::filters::keywords::KeywordMatcher;
+use crate::filters::text::truncate;
+use crate::settings::api_keys::MatchMode;
 use crate::{database, settings};
 
+// Accepted and rejected leads shown to Gemini as calibration examples
+const FEEDBACK_EXAMPLES: usize = 5;
+
+// Characters of each post body and comment sent to Gemini, long self posts and
+// comment walls would otherwise crowd the rest of the data out of the prompt
+const MAX_POST_CHARS: usize = 2000;
+const MAX_COMMENT_CHARS: usize = 500;
+
+// Keywords asked for by `ruddit keywords suggest`
+const MAX_KEYWORD_SUGGESTIONS: usize = 15;
+
+// Total characters of comment text added to a --gemini question
+const MAX_QA_COMMENT_CHARS: usize = 60_000;
+
 // Define GeminiError enum
 #[derive(Debug)]
 pub enum GeminiError {
@@ -43,28 +63,98 @@ impl fmt::Display for GeminiError {
 // Implement Error trait for GeminiError
 impl std::error::Error for GeminiError {}
 
-pub async fn ask_gemini(question: &str) -> Result<Value, GeminiError> {
+// How the context of a --gemini question is picked
+#[derive(Debug, Clone)]
+pub struct AskOptions {
+    // Send every matching post instead of retrieving the most relevant ones
+    pub all_posts: bool,
+    // Posts and comments retrieved for the question
+    pub top_k: usize,
+    // Also add the top scoring stored comments of each post
+    pub comments_per_post: Option<usize>,
+    pub use_cache: bool,
+}
+
+pub async fn ask_gemini(
+    question: &str,
+    filter: &PostFilter,
+    options: &AskOptions,
+) -> Result<Value, GeminiError> {
     // Initialize database connection
     let db = database::adding::DB::new()
         .map_err(|e| GeminiError::DatabaseError(format!("Failed to connect to DB: {}", e)))?;
 
-    // Get data from database
-    let reddits = db
-        .get_db_results()
-        .map_err(|e| GeminiError::DatabaseError(format!("Failed to get DB results: {}", e)))?;
+    // Get API keys from configuration
+    let keys = GeminiKeys::from_config(
+        &settings::api_keys::ConfigDirs::read_config()
+            .map_err(|e| GeminiError::ConfigError(e.to_string()))?
+            .api_keys,
+    );
+
+    // Only the posts and comments closest to the question are sent, unless
+    // retrieval is turned off or fails
+    let retrieved = if options.all_posts {
+        None
+    } else {
+        match retrieval::retrieve(&db, &keys, question, filter, options.top_k).await {
+            Ok(found) => Some(found),
+            Err(e) => {
+                eprintln!(
+                    "Retrieval failed ({}), sending every matching post instead",
+                    e
+                );
+                None
+            }
+        }
+    };
+    let (mut reddits, mut comments) = match retrieved {
+        Some(found) => (found.posts, found.comments),
+        None => (
+            db.get_filtered_posts(filter).map_err(|e| {
+                GeminiError::DatabaseError(format!("Failed to get DB results: {}", e))
+            })?,
+            Vec::new(),
+        ),
+    };
+    if reddits.is_empty() {
+        return Err(GeminiError::DatabaseError(
+            "No stored posts match the filters, nothing to ask about".to_string(),
+        ));
+    }
+
+    if let Some(per_post) = options.comments_per_post {
+        let retrieved_ids: HashSet<String> =
+            comments.iter().map(|comment| comment.id.clone()).collect();
+        comments.extend(
+            top_comments(&db, &reddits, per_post)?
+                .into_iter()
+                .filter(|comment| !retrieved_ids.contains(&comment.id)),
+        );
+    }
+    limit_post_text(&mut reddits);
+    limit_comment_text(&mut comments);
 
     // Convert data to JSON string
-    let json_reddits = serde_json::to_string(&reddits).map_err(|e| {
+    let json_reddits = if comments.is_empty() {
+        verbosity::info(format!("Asking Gemini about {} posts", reddits.len()));
+        serde_json::to_string(&reddits)
+    } else {
+        verbosity::info(format!(
+            "Asking Gemini about {} posts and {} comments",
+            reddits.len(),
+            comments.len()
+        ));
+        serde_json::to_string(&serde_json::json!({
+            "posts": reddits,
+            "comments": comments
+        }))
+    }
+    .map_err(|e| {
         GeminiError::DatabaseError(format!("Failed to serialize DB data to JSON: {}", e))
     })?;
 
-    // Get API key from configuration
-    let api_key = settings::api_keys::ConfigDirs::read_config()
-        .map_err(|e| GeminiError::ConfigError(e.to_string()))?
-        .api_keys
-        .gemini_api_key;
-
-    let client = Gemini::new(api_key);
+    let first_key = keys.position();
+    let post_ids: Vec<i64> = reddits.iter().map(|post| post.id).collect();
 
     let mut attempts = 0;
     let max_attempts = 2;
@@ -75,61 +165,65 @@ pub async fn ask_gemini(question: &str) -> Result<Value, GeminiError> {
 
         // Create system prompt - more strict on subsequent attempts
         let system_prompt = format!(
-            "Given the following data: {}, output the information in the best way possible to answer the questions. Be as thorough as possible and provide URLs when needed.",
-            json_reddits
+            "Given the following data: {}, output the information in the best way possible to answer the questions. Be as thorough as possible and provide URLs when needed.{}",
+            json_reddits,
+            if !comments.is_empty() {
+                " Comments are linked to their post through post_title."
+            } else {
+                ""
+            }
         );
 
         log::debug!("Attempt {} - System prompt: {}", attempts, system_prompt);
 
-        // SPINNER SECTION
-        // Create a flag to uontrol the spinner thread
-        let running = Arc::new(AtomicBool::new(true));
-        let running_clone = running.clone();
-
-        // Start spinner in a separate thread
-        let spinner_handle = thread::spawn(move || {
-            let spinner_chars = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
-            let mut i = 0;
-
-            while running_clone.load(Ordering::Relaxed) {
-                print!("\r{} Thinking... ", spinner_chars[i]);
-                std::io::stdout().flush().unwrap();
+        let cache_key = cache::cache_key("ask", &system_prompt, question, &post_ids);
+        let cached = if options.use_cache {
+            cache::lookup(&cache_key)
+        } else {
+            None
+        };
+        let from_cache = cached.is_some();
 
-                i = (i + 1) % spinner_chars.len();
-                thread::sleep(Duration::from_millis(100));
+        let text_response = match cached {
+            Some(text) => {
+                verbosity::info("Using cached Gemini response (pass --no-cache to refresh)");
+                text
             }
-
-            // Clear the spinner line when done
-            print!("\r{}", " ".repeat(20));
-            print!("\r");
-            std::io::stdout().flush().unwrap();
-        });
-
-        // Make API request
-        let response = match client
-            .generate_content()
-            .with_system_prompt(&system_prompt)
-            .with_user_message(question)
-            .execute()
-            .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                running.store(false, Ordering::Relaxed);
-                spinner_handle.join().unwrap();
-                last_error = Some(GeminiError::GeminiApiError(format!(
-                    "Failed to generate content: {}",
-                    e
-                )));
-                continue;
+            None => {
+                let progress = Progress::start("Thinking...");
+
+                // Make API request
+                let response = match keys
+                    .client()
+                    .generate_content()
+                    .with_system_prompt(&system_prompt)
+                    .with_user_message(question)
+                    .execute()
+                    .await
+                {
+                    Ok(r) => r,
+                    Err(e) => {
+                        progress.finish();
+
+                        // Out of quota: retry the same attempt with the next key
+                        if is_quota_error(&e.to_string()) && keys.rotate(first_key) {
+                            attempts -= 1;
+                            continue;
+                        }
+
+                        last_error = Some(GeminiError::GeminiApiError(format!(
+                            "Failed to generate content: {}",
+                            e
+                        )));
+                        continue;
+                    }
+                };
+
+                progress.finish();
+
+                response.text()
             }
         };
-
-        // Stop the spinner
-        running.store(false, Ordering::Relaxed);
-        spinner_handle.join().unwrap();
-
-        let text_response = response.text();
         log::debug!("Raw Gemini API response: {}", text_response);
 
         let trimmed_response = text_response.trim();
@@ -154,6 +248,12 @@ pub async fn ask_gemini(question: &str) -> Result<Value, GeminiError> {
         // Try to parse the response
         match serde_json::from_str(json_str) {
             Ok(data) => {
+                if from_cache {
+                    log_ai_call("ask", true, "cached", &keys);
+                } else {
+                    cache::store(&cache_key, "ask", &text_response);
+                    log_ai_call("ask", true, "", &keys);
+                }
                 return Ok(data);
             }
             Err(e) => {
@@ -165,31 +265,51 @@ pub async fn ask_gemini(question: &str) -> Result<Value, GeminiError> {
         }
     }
 
-    Err(last_error.unwrap_or(GeminiError::GeminiApiError(
+    let error = last_error.unwrap_or(GeminiError::GeminiApiError(
         "Unknown error after multiple attempts".to_string(),
-    )))
+    ));
+    log_ai_call("ask", false, &error.to_string(), &keys);
+    Err(error)
 }
 
 // PROMPT GEMINI TO SELECTIVELY GET THE DATA BASED ON CONDITIONS
-pub async fn gemini_generate_leads() -> Result<(), GeminiError> {
+pub async fn gemini_generate_leads(
+    filter: &PostFilter,
+    use_cache: bool,
+) -> Result<(), GeminiError> {
     let settings = settings::api_keys::ConfigDirs::read_config()
         .map_err(|e| GeminiError::ConfigError(e.to_string()))?;
 
-    let question_vec = settings.api_keys.lead_keywords;
-    if question_vec.is_empty() {
+    // --group swaps LEAD_KEYWORDS for the group's keywords. Gemini also looks for
+    // them in the comments, so the posts sent are not narrowed to local matches.
+    let matcher = match filter.keywords.clone() {
+        Some(matcher) => matcher,
+        None => KeywordMatcher::from_config(&settings).map_err(GeminiError::ConfigError)?,
+    };
+    // Quarantined posts are never sent for leads
+    let filter = &PostFilter {
+        keywords: None,
+        noise: false,
+        ..filter.clone()
+    };
+    if matcher.is_empty() {
         return Err(GeminiError::ConfigError(
             "No lead keywords found in configuration file. Add default Keywords to match with reddit data and export leads".to_string(),
         ));
     }
 
-    // Get each keyword inside the vector and compose a string to pass to the API
-    let keywords = question_vec
-        .iter()
-        .map(|q| q.to_string())
-        .collect::<Vec<String>>()
-        .join(" OR ");
+    // The keywords as one boolean expression to pass to the API
+    let keywords = matcher.describe();
 
-    println!("Matching Keywords: {}", &keywords);
+    if matcher.group().is_empty() {
+        verbosity::info(format!("Matching Keywords: {}", &keywords));
+    } else {
+        verbosity::info(format!(
+            "Matching Keywords of group '{}': {}",
+            matcher.group(),
+            &keywords
+        ));
+    }
 
     // Initialize database connection for both posts and comments
     let db = database::adding::DB::new()
@@ -197,27 +317,28 @@ pub async fn gemini_generate_leads() -> Result<(), GeminiError> {
 
     // Get data from database
     let posts = db
-        .get_db_results()
+        .get_filtered_posts(filter)
         .map_err(|e| GeminiError::DatabaseError(format!("Failed to get posts: {}", e)))?;
 
     // Get all comments for these posts
     let mut all_comments = Vec::new();
     for post in &posts {
-        if let Ok(comments) = db.get_post_comments(&post.id.to_string()) {
+        if let Ok(comments) = db.get_post_comments(&database::adding::DB::encode_reddit_id(post.id))
+        {
             all_comments.extend(comments);
         }
     }
+    limit_comment_text(&mut all_comments);
 
     // Get sentiment requirements
     let sentiments = settings.api_keys.sentiment.join(" OR ");
-    let match_type = settings.api_keys.match_keyword.to_lowercase();
-    let match_operator = if match_type == "and" { "AND" } else { "OR" };
+    let match_mode = matcher.mode();
 
     let question = format!(
         "Analyze the following posts and their comments, and return ONLY those that match these criteria:
-        1. Keywords ({}) must be found in the post's title OR in the comments, using {} matching.
+        1. The keyword expression {} must hold for the post's title OR its comments. Quoted terms match case-insensitively, AND, OR and NOT are boolean operators.{}
         2. The post's sentiment OR the overall sentiment of its comments should match one of: {}.
-        3. Return ONLY posts that are likely to be leads or business opportunities for inventory management.
+        3. Return ONLY posts that are likely to be leads or business opportunities for inventory management.{}{}
 
         For each matching post, format the result as a JSON object with these fields:
         - title: the post title
@@ -229,30 +350,51 @@ pub async fn gemini_generate_leads() -> Result<(), GeminiError> {
         - top_comments: an array of up to 3 most relevant comments that match the criteria
         - comment_sentiment: the overall sentiment of the matching comments
         ",
-        keywords, match_operator, sentiments
+        keywords,
+        match match_mode {
+            MatchMode::Exact => "",
+            MatchMode::Stem => " Other forms of a word count, e.g. \"forecasts\" for \"forecast\".",
+            MatchMode::Fuzzy => " Other forms of a word and small typos count, e.g. \"forcasting\" for \"forecast\".",
+        },
+        sentiments,
+        // The local question filter is a heuristic, let Gemini confirm it
+        if filter.questions_only {
+            "\n        4. Return ONLY posts that genuinely ask a question or for recommendations."
+        } else {
+            ""
+        },
+        // The local noise filter only catches known phrases, let Gemini drop the rest
+        if settings.noise.gemini_check {
+            "\n        Never return spam, giveaways, referral links or crypto promotion, whatever the keywords."
+        } else {
+            ""
+        }
     );
+    let question = format!("{}{}", question, calibration_examples());
 
     // Initialize database connection
     let db = database::adding::DB::new()
         .map_err(|e| GeminiError::DatabaseError(format!("Failed to connect to DB: {}", e)))?;
 
     // Get data from database
-    let reddits = db
-        .get_db_results()
+    let mut reddits = db
+        .get_filtered_posts(filter)
         .map_err(|e| GeminiError::DatabaseError(format!("Failed to get DB results: {}", e)))?;
+    limit_post_text(&mut reddits);
 
     // Convert data to JSON string
     let json_reddits = serde_json::to_string(&reddits).map_err(|e| {
         GeminiError::DatabaseError(format!("Failed to serialize DB data to JSON: {}", e))
     })?;
 
-    // Get API key from configuration
-    let api_key = settings::api_keys::ConfigDirs::read_config()
-        .map_err(|e| GeminiError::ConfigError(e.to_string()))?
-        .api_keys
-        .gemini_api_key;
-
-    let client = Gemini::new(api_key);
+    // Get API keys from configuration
+    let keys = GeminiKeys::from_config(
+        &settings::api_keys::ConfigDirs::read_config()
+            .map_err(|e| GeminiError::ConfigError(e.to_string()))?
+            .api_keys,
+    );
+    let first_key = keys.position();
+    let post_ids: Vec<i64> = reddits.iter().map(|post| post.id).collect();
 
     let mut attempts = 0;
     let max_attempts = 2;
@@ -283,55 +425,54 @@ pub async fn gemini_generate_leads() -> Result<(), GeminiError> {
 
         log::debug!("Attempt {} - System prompt: {}", attempts, system_prompt);
 
-        // SPINNER SECTION
-        // Create a flag to uontrol the spinner thread
-        let running = Arc::new(AtomicBool::new(true));
-        let running_clone = running.clone();
-
-        // Start spinner in a separate thread
-        let spinner_handle = thread::spawn(move || {
-            let spinner_chars = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
-            let mut i = 0;
-
-            while running_clone.load(Ordering::Relaxed) {
-                print!("\r{} Thinking... ", spinner_chars[i]);
-                std::io::stdout().flush().unwrap();
+        let cache_key = cache::cache_key("leads", &system_prompt, &question, &post_ids);
+        let cached = if use_cache {
+            cache::lookup(&cache_key)
+        } else {
+            None
+        };
+        let from_cache = cached.is_some();
 
-                i = (i + 1) % spinner_chars.len();
-                thread::sleep(Duration::from_millis(100));
+        let text_response = match cached {
+            Some(text) => {
+                verbosity::info("Using cached Gemini response (pass --no-cache to refresh)");
+                text
             }
-
-            // Clear the spinner line when done
-            print!("\r{}", " ".repeat(20));
-            print!("\r");
-            std::io::stdout().flush().unwrap();
-        });
-
-        // Make API request
-        let response = match client
-            .generate_content()
-            .with_system_prompt(&system_prompt)
-            .with_user_message(&question)
-            .execute()
-            .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                running.store(false, Ordering::Relaxed);
-                spinner_handle.join().unwrap();
-                last_error = Some(GeminiError::GeminiApiError(format!(
-                    "Failed to generate content: {}",
-                    e
-                )));
-                continue;
+            None => {
+                let progress = Progress::start("Thinking...");
+
+                // Make API request
+                let response = match keys
+                    .client()
+                    .generate_content()
+                    .with_system_prompt(&system_prompt)
+                    .with_user_message(&question)
+                    .execute()
+                    .await
+                {
+                    Ok(r) => r,
+                    Err(e) => {
+                        progress.finish();
+
+                        // Out of quota: retry the same attempt with the next key
+                        if is_quota_error(&e.to_string()) && keys.rotate(first_key) {
+                            attempts -= 1;
+                            continue;
+                        }
+
+                        last_error = Some(GeminiError::GeminiApiError(format!(
+                            "Failed to generate content: {}",
+                            e
+                        )));
+                        continue;
+                    }
+                };
+
+                progress.finish();
+
+                response.text()
             }
         };
-
-        // Stop the spinner
-        running.store(false, Ordering::Relaxed);
-        spinner_handle.join().unwrap();
-
-        let text_response = response.text();
         log::debug!("Raw Gemini API response: {}", text_response);
 
         let trimmed_response = text_response.trim();
@@ -353,11 +494,20 @@ pub async fn gemini_generate_leads() -> Result<(), GeminiError> {
 
         log::debug!("Processed JSON string: {}", json_str);
 
-        excel::export_gemini_to_excel(json_str).expect("Failed to export gemini leads to excel");
+        excel::export_gemini_to_excel(json_str, matcher.group())
+            .expect("Failed to export gemini leads to excel");
 
         // Try to parse the response to validate it
         match serde_json::from_str::<Value>(json_str) {
-            Ok(_) => {
+            Ok(data) => {
+                let count = store_leads(&data, matcher.group())?;
+                println!("Saved {} leads to database", count);
+                if from_cache {
+                    log_ai_call("leads", true, &format!("{} leads, cached", count), &keys);
+                } else {
+                    cache::store(&cache_key, "leads", &text_response);
+                    log_ai_call("leads", true, &format!("{} leads", count), &keys);
+                }
                 return Ok(());
             }
             Err(e) => {
@@ -369,7 +519,261 @@ pub async fn gemini_generate_leads() -> Result<(), GeminiError> {
         }
     }
 
-    Err(last_error.unwrap_or(GeminiError::GeminiApiError(
+    let error = last_error.unwrap_or(GeminiError::GeminiApiError(
+        "Unknown error after multiple attempts".to_string(),
+    ));
+    log_ai_call("leads", false, &error.to_string(), &keys);
+    Err(error)
+}
+
+// Keywords proposed for LEAD_KEYWORDS from the language of accepted leads.
+// The caller checks each one against the stored data before suggesting it.
+pub async fn gemini_suggest_keywords(
+    leads: &[String],
+    existing: &[String],
+    use_cache: bool,
+) -> Result<Vec<String>, GeminiError> {
+    let keys = GeminiKeys::from_config(
+        &settings::api_keys::ConfigDirs::read_config()
+            .map_err(|e| GeminiError::ConfigError(e.to_string()))?
+            .api_keys,
+    );
+    let first_key = keys.position();
+
+    let leads: Vec<&str> = leads
+        .iter()
+        .map(|text| truncate(text, MAX_POST_CHARS))
+        .collect();
+    let system_prompt = format!(
+        "A human accepted these Reddit posts as real sales leads: {}
+
+        The keywords already used to find leads are: {}
+
+        Propose up to {} additional keywords or short phrases (one to three words) taken from the language of these posts that would find similar posts. Prefer phrases several of the posts share, leave out the existing keywords, generic words and names of people.
+
+        Return ONLY a JSON array of strings. NO text outside JSON. NO markdown blocks.",
+        serde_json::to_string(&leads).unwrap_or_default(),
+        existing.join(", "),
+        MAX_KEYWORD_SUGGESTIONS
+    );
+    let question = "Suggest keywords";
+    let cache_key = cache::cache_key("keywords", &system_prompt, question, &[]);
+
+    let mut attempts = 0;
+    let max_attempts = 2;
+    let mut last_error = None;
+
+    while attempts < max_attempts {
+        attempts += 1;
+
+        let cached = if use_cache {
+            cache::lookup(&cache_key)
+        } else {
+            None
+        };
+        let from_cache = cached.is_some();
+
+        let text_response = match cached {
+            Some(text) => {
+                verbosity::info("Using cached Gemini response (pass --no-cache to refresh)");
+                text
+            }
+            None => {
+                let progress = Progress::start("Thinking...");
+                let response = match keys
+                    .client()
+                    .generate_content()
+                    .with_system_prompt(&system_prompt)
+                    .with_user_message(question)
+                    .execute()
+                    .await
+                {
+                    Ok(r) => r,
+                    Err(e) => {
+                        progress.finish();
+
+                        // Out of quota: retry the same attempt with the next key
+                        if is_quota_error(&e.to_string()) && keys.rotate(first_key) {
+                            attempts -= 1;
+                            continue;
+                        }
+
+                        last_error = Some(GeminiError::GeminiApiError(format!(
+                            "Failed to generate content: {}",
+                            e
+                        )));
+                        continue;
+                    }
+                };
+                progress.finish();
+                response.text()
+            }
+        };
+        log::debug!("Raw Gemini API response: {}", text_response);
+
+        let json_str = text_response
+            .trim()
+            .trim_start_matches("```json")
+            .trim_start_matches("```")
+            .trim_end_matches("```")
+            .trim();
+
+        // Plain strings, or objects with a keyword field
+        match serde_json::from_str::<Vec<Value>>(json_str) {
+            Ok(items) => {
+                let keywords: Vec<String> = items
+                    .iter()
+                    .filter_map(|item| match item {
+                        Value::String(keyword) => Some(keyword.as_str()),
+                        other => other["keyword"].as_str(),
+                    })
+                    .map(|keyword| keyword.trim().to_lowercase())
+                    .filter(|keyword| !keyword.is_empty())
+                    .collect();
+                let message = format!("{} keywords", keywords.len());
+                if from_cache {
+                    log_ai_call("keywords", true, &format!("{}, cached", message), &keys);
+                } else {
+                    cache::store(&cache_key, "keywords", &text_response);
+                    log_ai_call("keywords", true, &message, &keys);
+                }
+                return Ok(keywords);
+            }
+            Err(e) => {
+                last_error = Some(GeminiError::JsonParsingError(format!(
+                    "Failed to parse JSON from API response: {}. Response was: {}",
+                    e, text_response
+                )));
+            }
+        }
+    }
+
+    let error = last_error.unwrap_or(GeminiError::GeminiApiError(
         "Unknown error after multiple attempts".to_string(),
-    )))
+    ));
+    log_ai_call("keywords", false, &error.to_string(), &keys);
+    Err(error)
+}
+
+// Past review decisions, appended to the lead prompt so Gemini learns what counts as a lead
+fn calibration_examples() -> String {
+    let sample = database::adding::DB::new()
+        .and_then(|db| {
+            db.create_leads_table()?;
+            db.get_lead_feedback_sample(FEEDBACK_EXAMPLES)
+        })
+        .unwrap_or_default();
+
+    if sample.is_empty() {
+        return String::new();
+    }
+
+    let examples = |decision: &str| {
+        sample
+            .iter()
+            .filter(|feedback| feedback.decision == decision)
+            .map(|feedback| {
+                format!(
+                    "        - [{}] r/{}: {}",
+                    feedback.relevance, feedback.subreddit, feedback.title
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    format!(
+        "
+        Calibration from a human reviewing your previous leads:
+        Accepted as real leads:
+{}
+        Rejected as not leads:
+{}
+        Favour posts like the accepted examples and leave out posts like the rejected ones.
+        ",
+        examples(REVIEW_ACCEPTED),
+        examples(REVIEW_REJECTED)
+    )
+}
+
+// The `per_post` best scoring comments of each post. When they exceed the
+// budget the lowest scoring ones are dropped first.
+fn top_comments(
+    db: &database::adding::DB,
+    posts: &[PostDataWrapper],
+    per_post: usize,
+) -> Result<Vec<CommentDataWrapper>, GeminiError> {
+    let mut selected = Vec::new();
+    for post in posts {
+        let mut comments = db
+            .get_post_comments(&database::adding::DB::encode_reddit_id(post.id))
+            .map_err(|e| GeminiError::DatabaseError(format!("Failed to get comments: {}", e)))?;
+        comments.sort_by_key(|comment| std::cmp::Reverse(comment.score));
+        comments.truncate(per_post);
+        selected.extend(comments);
+    }
+    limit_comment_text(&mut selected);
+
+    selected.sort_by_key(|comment| std::cmp::Reverse(comment.score));
+    let mut used = 0;
+    selected.retain(|comment| {
+        used += comment.body.len();
+        used <= MAX_QA_COMMENT_CHARS
+    });
+
+    // Back in post order so each post's comments sit together
+    let order: HashMap<i64, usize> = posts
+        .iter()
+        .enumerate()
+        .map(|(index, post)| (post.id, index))
+        .collect();
+    selected.sort_by_key(|comment| {
+        database::adding::DB::decode_reddit_id(&comment.post_id)
+            .and_then(|id| order.get(&id).copied())
+    });
+
+    Ok(selected)
+}
+
+fn limit_post_text(posts: &mut [PostDataWrapper]) {
+    for post in posts {
+        post.selftext = truncate(&post.selftext, MAX_POST_CHARS).to_string();
+    }
+}
+
+fn limit_comment_text(comments: &mut [CommentDataWrapper]) {
+    for comment in comments {
+        comment.body = truncate(&comment.body, MAX_COMMENT_CHARS).to_string();
+    }
+}
+
+// Persist the leads returned by Gemini so they can be queried later
+fn store_leads(data: &Value, group: &str) -> Result<usize, GeminiError> {
+    let mut leads: Vec<LeadDataWrapper> = match data {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(LeadDataWrapper::from_json)
+            .collect(),
+        other => LeadDataWrapper::from_json(other).into_iter().collect(),
+    };
+    for lead in &mut leads {
+        lead.keyword_group = group.to_string();
+    }
+
+    let mut db = database::adding::DB::new()
+        .map_err(|e| GeminiError::DatabaseError(format!("Failed to connect to DB: {}", e)))?;
+    db.create_leads_table()
+        .and_then(|_| db.append_leads(&leads))
+        .map_err(|e| GeminiError::DatabaseError(format!("Failed to save leads: {}", e)))?;
+
+    Ok(leads.len())
+}
+
+// Keep a record of every AI call for `ruddit status`
+fn log_ai_call(kind: &str, success: bool, message: &str, keys: &GeminiKeys) {
+    if let Ok(db) = database::adding::DB::new()
+        && let Err(e) = db.record_ai_call(kind, success, message, &keys.label())
+    {
+        log::warn!("Failed to record AI call: {}", e);
+    }
 }
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use gemini_rust::Gemini;
+
+use crate::settings::api_keys::ApiKeys;
+
+// Model used for every call, part of the AI cache key
+pub const GEMINI_MODEL: &str = "models/gemini-2.5-flash";
+
+// Model behind the vector index used to pick the context of --gemini questions
+pub const EMBEDDING_MODEL: &str = "models/text-embedding-004";
+
+// Key in use for the rest of the run, moved forward when one runs out of quota
+static ACTIVE_KEY: AtomicUsize = AtomicUsize::new(0);
+
+// The Gemini keys from the config: GEMINI_API_KEY first, then GEMINI_API_KEYS
+#[derive(Debug, Clone)]
+pub struct GeminiKeys {
+    keys: Vec<String>,
+}
+
+impl GeminiKeys {
+    pub fn from_config(api_keys: &ApiKeys) -> Self {
+        let mut keys: Vec<String> = Vec::new();
+        for key in std::iter::once(&api_keys.gemini_api_key).chain(&api_keys.gemini_api_keys) {
+            let key = key.trim();
+            if !key.is_empty() && !keys.iter().any(|k| k == key) {
+                keys.push(key.to_string());
+            }
+        }
+
+        GeminiKeys { keys }
+    }
+
+    fn index(&self) -> usize {
+        ACTIVE_KEY.load(Ordering::Relaxed) % self.keys.len().max(1)
+    }
+
+    pub fn client(&self) -> Gemini {
+        Gemini::with_model(
+            self.keys.get(self.index()).cloned().unwrap_or_default(),
+            GEMINI_MODEL.to_string(),
+        )
+    }
+
+    pub fn embedding_client(&self) -> Gemini {
+        Gemini::with_model(
+            self.keys.get(self.index()).cloned().unwrap_or_default(),
+            EMBEDDING_MODEL.to_string(),
+        )
+    }
+
+    // Which key served a call, without storing the key itself
+    pub fn label(&self) -> String {
+        match self.keys.get(self.index()) {
+            Some(key) => {
+                let tail: String = key
+                    .chars()
+                    .rev()
+                    .take(4)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev()
+                    .collect();
+                format!("#{} ...{}", self.index() + 1, tail)
+            }
+            None => String::new(),
+        }
+    }
+
+    // Switch to the next key after a quota error. Returns false once every key
+    // has been tried since the call started at `start`.
+    pub fn rotate(&self, start: usize) -> bool {
+        let current = self.index();
+        let next = (current + 1) % self.keys.len().max(1);
+        if next == start {
+            return false;
+        }
+
+        ACTIVE_KEY.store(next, Ordering::Relaxed);
+        eprintln!(
+            "Gemini key #{} is out of quota, switching to key #{}",
+            current + 1,
+            next + 1
+        );
+        true
+    }
+
+    // Where a call starts, to know when every key has been tried
+    pub fn position(&self) -> usize {
+        self.index()
+    }
+}
+
+// Gemini reports exhausted quota as HTTP 429 / RESOURCE_EXHAUSTED
+pub fn is_quota_error(message: &str) -> bool {
+    message.contains("429") || message.contains("RESOURCE_EXHAUSTED")
+}
@@ -1 +1,4 @@
+pub mod cache;
 pub mod gemini;
+pub mod keys;
+pub mod retrieval;
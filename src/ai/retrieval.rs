@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use gemini_rust::TaskType;
+use sha2::{Digest, Sha256};
+
+use crate::actions::interrupt;
+use crate::actions::progress::Progress;
+use crate::ai::keys::{EMBEDDING_MODEL, GeminiKeys};
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::database::embeddings::{EMBED_COMMENT, EMBED_POST, Embedding};
+use crate::database::queries::PostFilter;
+use crate::filters::text::truncate;
+
+// Documents per batchEmbedContents call, the API accepts at most 100
+const BATCH_SIZE: usize = 100;
+
+// Characters of a post or comment that are embedded
+const MAX_DOCUMENT_CHARS: usize = 2000;
+
+// Posts and comments most relevant to a question. Posts of retrieved comments
+// are included so every comment has its thread.
+pub struct Retrieved {
+    pub posts: Vec<PostDataWrapper>,
+    pub comments: Vec<CommentDataWrapper>,
+}
+
+// Embed the stored posts and comments that are new or changed since the last
+// run, returns how many were embedded
+pub async fn index_documents(
+    db: &DB,
+    keys: &GeminiKeys,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    db.create_embeddings_table()?;
+    let everything = PostFilter::default();
+
+    let posts = db
+        .get_filtered_posts(&everything)?
+        .iter()
+        .map(|post| (post.id.to_string(), post_text(post)))
+        .collect();
+    let comments = db
+        .get_filtered_comments(&everything)?
+        .iter()
+        .map(|comment| (comment.id.clone(), comment_text(comment)))
+        .collect();
+
+    Ok(embed_missing(db, keys, EMBED_POST, posts).await?
+        + embed_missing(db, keys, EMBED_COMMENT, comments).await?)
+}
+
+// The `top_k` posts and comments matching the filter that are closest to the question
+pub async fn retrieve(
+    db: &DB,
+    keys: &GeminiKeys,
+    question: &str,
+    filter: &PostFilter,
+    top_k: usize,
+) -> Result<Retrieved, Box<dyn std::error::Error>> {
+    let indexed = index_documents(db, keys).await?;
+    if indexed > 0 {
+        println!("Indexed {} new or changed posts and comments", indexed);
+    }
+
+    let query = keys
+        .embedding_client()
+        .embed_content()
+        .with_text(question)
+        .with_task_type(TaskType::RetrievalQuery)
+        .execute()
+        .await?
+        .embedding
+        .values;
+
+    // Only documents passing the filters are candidates
+    let mut posts: HashMap<String, PostDataWrapper> = db
+        .get_filtered_posts(filter)?
+        .into_iter()
+        .map(|post| (post.id.to_string(), post))
+        .collect();
+    let mut comments: HashMap<String, CommentDataWrapper> = db
+        .get_filtered_comments(filter)?
+        .into_iter()
+        .filter(|comment| parent_id(comment).is_some_and(|id| posts.contains_key(&id)))
+        .map(|comment| (comment.id.clone(), comment))
+        .collect();
+
+    let mut scored: Vec<(f32, Embedding)> = db
+        .get_embeddings(EMBED_POST)?
+        .into_iter()
+        .filter(|embedding| posts.contains_key(&embedding.item_id))
+        .chain(
+            db.get_embeddings(EMBED_COMMENT)?
+                .into_iter()
+                .filter(|embedding| comments.contains_key(&embedding.item_id)),
+        )
+        .map(|embedding| (cosine_similarity(&query, &embedding.vector), embedding))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(top_k);
+
+    let mut retrieved = Retrieved {
+        posts: Vec::new(),
+        comments: Vec::new(),
+    };
+    for (_, embedding) in scored {
+        if embedding.kind == EMBED_POST {
+            retrieved.posts.extend(posts.remove(&embedding.item_id));
+        } else if let Some(comment) = comments.remove(&embedding.item_id) {
+            retrieved
+                .posts
+                .extend(parent_id(&comment).and_then(|id| posts.remove(&id)));
+            retrieved.comments.push(comment);
+        }
+    }
+
+    Ok(retrieved)
+}
+
+async fn embed_missing(
+    db: &DB,
+    keys: &GeminiKeys,
+    kind: &str,
+    documents: Vec<(String, String)>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let stored = db.get_embedding_hashes(kind)?;
+    let pending: Vec<(String, String, String)> = documents
+        .into_iter()
+        .map(|(id, text)| {
+            let hash = content_hash(&text);
+            (id, text, hash)
+        })
+        .filter(|(id, _, hash)| stored.get(id) != Some(hash))
+        .collect();
+
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let progress = Progress::start(format!("Embedding {} {}s...", pending.len(), kind));
+    let mut embedded = 0;
+    for (index, batch) in pending.chunks(BATCH_SIZE).enumerate() {
+        // Batches already stored are kept, the rest is embedded on the next run
+        if interrupt::interrupted() {
+            break;
+        }
+        progress.set_label(format!(
+            "Embedding {}s {}/{}",
+            kind,
+            index * BATCH_SIZE + batch.len(),
+            pending.len()
+        ));
+        let response = keys
+            .embedding_client()
+            .embed_content()
+            .with_chunks(batch.iter().map(|(_, text, _)| text.clone()).collect())
+            .with_task_type(TaskType::RetrievalDocument)
+            .execute_batch()
+            .await?;
+        if response.embeddings.len() != batch.len() {
+            return Err(format!(
+                "Expected {} embeddings from Gemini, got {}",
+                batch.len(),
+                response.embeddings.len()
+            )
+            .into());
+        }
+
+        let embeddings: Vec<Embedding> = batch
+            .iter()
+            .zip(response.embeddings)
+            .map(|((id, _, hash), embedding)| Embedding {
+                kind: kind.to_string(),
+                item_id: id.clone(),
+                content_hash: hash.clone(),
+                vector: embedding.values,
+            })
+            .collect();
+        db.store_embeddings(&embeddings)?;
+        embedded += batch.len();
+    }
+    progress.finish();
+
+    Ok(embedded)
+}
+
+fn post_text(post: &PostDataWrapper) -> String {
+    format!(
+        "{}\n\n{}",
+        post.title,
+        truncate(&post.selftext, MAX_DOCUMENT_CHARS)
+    )
+}
+
+// The thread title gives short replies their context
+fn comment_text(comment: &CommentDataWrapper) -> String {
+    format!(
+        "{}\n\n{}",
+        comment.post_title,
+        truncate(&comment.body, MAX_DOCUMENT_CHARS)
+    )
+}
+
+// Post ids are stored decoded, comments point at their post in base36
+fn parent_id(comment: &CommentDataWrapper) -> Option<String> {
+    DB::decode_reddit_id(&comment.post_id).map(|id| id.to_string())
+}
+
+// Vectors are recomputed when the text or the embedding model changes
+fn content_hash(text: &str) -> String {
+    Sha256::new()
+        .chain_update(EMBEDDING_MODEL.as_bytes())
+        .chain_update([0])
+        .chain_update(text.as_bytes())
+        .finalize()
+        .iter()
+        .take(16)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
@@ -1 +1,2 @@
 pub mod modeling;
+pub mod post_ref;
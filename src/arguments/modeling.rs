@@ -1,4 +1,15 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+
+use crate::actions::verbosity::Verbosity;
+use crate::database::queries::PostFilter;
+use crate::database::tags::STARRED;
+use crate::filters::keywords::KeywordMatcher;
+use crate::settings::api_keys::{
+    AppConfig, ConfigDirs, default_comment_limit, default_continue_depth, default_continue_limit,
+};
 
 //TODO: implement token input with clap
 
@@ -17,9 +28,45 @@ pub struct Args {
     pub gemini: Option<String>,
 
     /// Subreddit name to fetch posts from (-s)
-    #[arg(short, long, help = "Subreddit name to fetch posts from")]
+    #[arg(
+        short,
+        long,
+        help = "Subreddit name to fetch posts from, or to limit the --gemini context to"
+    )]
     pub subreddit: Option<String>,
 
+    /// Add the top comments of each post to the --gemini context
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "5",
+        value_name = "PER_POST",
+        help = "Include the top scoring comments of each post in the --gemini context (5 per post by default)"
+    )]
+    pub with_comments: Option<usize>,
+
+    /// Number of posts and comments retrieved for a --gemini question
+    #[arg(
+        long,
+        default_value_t = 40,
+        help = "Number of posts and comments most relevant to a --gemini question that are sent with it"
+    )]
+    pub top_k: usize,
+
+    /// Skip retrieval and send every matching post
+    #[arg(
+        long,
+        help = "Send every matching post with a --gemini question instead of the most relevant ones"
+    )]
+    pub all_posts: bool,
+
+    /// Only send posts containing this text to Gemini
+    #[arg(
+        long,
+        help = "Only include posts whose title or body contains this text in the --gemini context"
+    )]
+    pub contains: Option<String>,
+
     /// Relevance filter for search results (-r)
     #[arg(
         short,
@@ -29,9 +76,22 @@ pub struct Args {
     pub relevance: Option<String>,
 
     /// Export the results (-e)
-    #[arg(short, long, help = "Export all the results, without filtering")]
+    #[arg(
+        short,
+        long,
+        help = "Export all the results, without filtering, or the answer when used with --gemini"
+    )]
     pub export: bool,
 
+    /// File format for an exported --gemini answer
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = AnswerFormat::Xlsx,
+        help = "File format of the --gemini answer saved by --export"
+    )]
+    pub answer_format: AnswerFormat,
+
     /// Clear cached data (-c)
     #[arg(
         short = 'C',
@@ -45,7 +105,7 @@ pub struct Args {
         short,
         long,
         requires = "relevance",
-        help = "Search for a specific keyword (requires --relevance)"
+        help = "Search Reddit, operators like author:, subreddit:, self:yes and \"quoted phrases\" are passed through (requires --relevance)"
     )]
     pub find: Option<String>,
 
@@ -66,15 +126,973 @@ pub struct Args {
     pub settings: bool,
 
     /// Fetch comments for a specific post ID (-C)
-    #[arg(short = 'c', long, help = "Fetch comments for a specific post ID")]
+    #[arg(
+        short = 'c',
+        long,
+        help = "Fetch comments for a specific post, by ID or Reddit URL"
+    )]
     pub comments: Option<String>,
 
     /// Limit the number of comments to fetch (-n)
     #[arg(
         short = 'n',
         long,
-        help = "Limit the number of comments to fetch",
-        default_value = "100"
+        help = "Limit the number of comments fetched per post (defaults to comment_limit under [fetch])"
+    )]
+    pub comment_limit: Option<usize>,
+
+    /// Copy the listed posts to the clipboard
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "links",
+        help = "Copy the posts listed by `ruddit query` or found by --find to the clipboard, as permalinks (default) or a Markdown list"
+    )]
+    pub copy: Option<CopyFormat>,
+
+    /// Open the first listed post in the browser
+    #[arg(
+        long,
+        global = true,
+        help = "Open the first post listed by `ruddit query` or found by --find in the browser"
+    )]
+    pub open_first: bool,
+
+    /// Always call Gemini instead of reusing a cached response
+    #[arg(
+        long,
+        global = true,
+        help = "Ignore cached Gemini and Reddit responses and call the APIs again"
     )]
-    pub comment_limit: usize,
+    pub no_cache: bool,
+
+    /// Order in which Reddit returns comments
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CommentSort::Best,
+        help = "Order in which Reddit returns comments"
+    )]
+    pub comment_sort: CommentSort,
+
+    /// Skip comments scoring below this
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Skip comments scoring below this when fetching"
+    )]
+    pub min_comment_score: Option<i32>,
+
+    /// Only keep posts created on or after this date
+    #[arg(
+        long,
+        global = true,
+        value_parser = parse_date,
+        help = "Only keep posts created on or after this date (YYYY-MM-DD)"
+    )]
+    pub since: Option<NaiveDate>,
+
+    /// Only keep posts created on or before this date
+    #[arg(
+        long,
+        global = true,
+        value_parser = parse_date,
+        help = "Only keep posts created on or before this date (YYYY-MM-DD)"
+    )]
+    pub until: Option<NaiveDate>,
+
+    /// Only keep posts that ask a question
+    #[arg(
+        long,
+        global = true,
+        help = "Only keep posts that ask a question or for recommendations (search, exports, leads)"
+    )]
+    pub questions_only: bool,
+
+    /// Only keep posts tagged with this intent
+    #[arg(
+        long,
+        global = true,
+        help = "Only export posts tagged with this intent (e.g. complaint, seeking_recommendation)"
+    )]
+    pub intent: Option<String>,
+
+    /// Only keep posts stored by this run
+    #[arg(
+        long,
+        global = true,
+        help = "Only export posts and comments stored by this run (ids from `ruddit runs`)"
+    )]
+    pub run: Option<i64>,
+
+    /// Only keep posts found by this saved search
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Only export posts found by this saved search (see [[searches]])"
+    )]
+    pub saved_search: Option<String>,
+
+    /// Only keep posts with this tag
+    #[arg(
+        long,
+        global = true,
+        help = "Only export posts tagged with this (see `ruddit tag`)"
+    )]
+    pub tag: Option<String>,
+
+    /// Only keep starred posts
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "tag",
+        help = "Only export posts starred with `ruddit star`"
+    )]
+    pub starred: bool,
+
+    /// Keyword group from [[keyword_groups]] to use instead of LEAD_KEYWORDS
+    #[arg(
+        long,
+        global = true,
+        help = "Use this [[keyword_groups]] entry: only its matching posts are exported, and leads are generated and labelled for it"
+    )]
+    pub group: Option<String>,
+
+    /// Open the database read-only
+    #[arg(
+        long,
+        global = true,
+        help = "Open the database read-only, for query, export and report commands next to a running `ruddit watch`"
+    )]
+    pub read_only: bool,
+
+    /// Use a throwaway in-memory database
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "read_only",
+        help = "Use an in-memory database for this run only: fetch, analyze and export without touching the stored data"
+    )]
+    pub ephemeral: bool,
+
+    /// Save raw Reddit API responses
+    #[arg(
+        long,
+        global = true,
+        help = "Save every raw Reddit API response with its URL and status under the cache folder, to report parsing problems or replay them later"
+    )]
+    pub capture: bool,
+
+    /// Database file to use
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Use this database file instead of db_path from settings.toml or ruddit.db in the data folder"
+    )]
+    pub db_path: Option<PathBuf>,
+
+    /// Answer Reddit API requests from a --capture folder
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        conflicts_with = "capture",
+        help = "Answer Reddit API requests from a folder written by --capture instead of the network, to run fetches, leads and exports offline"
+    )]
+    pub replay: Option<PathBuf>,
+
+    /// Only print errors and final results (-q)
+    #[arg(
+        short,
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Only print errors, warnings and final results such as the summary line, for cron jobs"
+    )]
+    pub quiet: bool,
+
+    /// Show log warnings (-v) and debug logs (-vv)
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Show warnings from the logs, -vv turns on debug logs (RUST_LOG takes precedence)"
+    )]
+    pub verbose: u8,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+impl Args {
+    pub fn comment_options(&self) -> CommentOptions {
+        let configured = ConfigDirs::read_config()
+            .map(|config| CommentOptions::from_config(&config))
+            .unwrap_or_default();
+
+        CommentOptions {
+            sort: self.comment_sort,
+            min_score: self.min_comment_score,
+            limit: self.comment_limit.unwrap_or(configured.limit),
+            ..configured
+        }
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        if self.quiet {
+            Verbosity::Quiet
+        } else if self.verbose > 0 {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+
+    // The --since/--until window, with --until covering the whole day, and --questions-only
+    pub fn post_filter(&self) -> PostFilter {
+        let start_of_day =
+            |date: NaiveDate| date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp());
+
+        PostFilter {
+            since: self.since.and_then(start_of_day),
+            until: self
+                .until
+                .and_then(|date| date.succ_opt())
+                .and_then(start_of_day),
+            questions_only: self.questions_only,
+            intent: self.intent.clone(),
+            run: self.run,
+            saved_search: self.saved_search.clone(),
+            tag: if self.starred {
+                Some(STARRED.to_string())
+            } else {
+                self.tag.as_deref().map(normalize_tag)
+            },
+            keywords: self.keyword_matcher(),
+            ..Default::default()
+        }
+    }
+
+    // Posts sent to Gemini for --gemini, narrowed by subreddit and text on top of
+    // the shared window and tag filters
+    pub fn ask_filter(&self) -> PostFilter {
+        PostFilter {
+            subreddit: self.subreddit.clone(),
+            contains: self.contains.clone(),
+            ..self.post_filter()
+        }
+    }
+
+    // Matcher for --group. An unknown group or bad query was already
+    // rejected by `check_group`.
+    fn keyword_matcher(&self) -> Option<KeywordMatcher> {
+        let name = self.group.as_deref()?;
+        let config = ConfigDirs::read_config().ok()?;
+        let group = config.keyword_group(Some(name)).ok()?;
+        KeywordMatcher::from_group(&group, &config.matching).ok()
+    }
+
+    // Fail early on a --group that is not in the config or has an invalid query
+    pub fn check_group(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(name) = self.group.as_deref() {
+            let config = ConfigDirs::read_config()?;
+            let group = config.keyword_group(Some(name))?;
+            KeywordMatcher::from_group(&group, &config.matching)?;
+        }
+        Ok(())
+    }
+
+    // Whether this invocation stores data: it then waits for other writing
+    // runs to finish first and cannot be combined with --read-only
+    pub fn writes(&self) -> bool {
+        match &self.command {
+            Some(Commands::Leads { action }) => !matches!(
+                action,
+                LeadsCommands::Export | LeadsCommands::Stale { .. } | LeadsCommands::Dump { .. }
+            ),
+            Some(
+                Commands::Index
+                | Commands::Classify
+                | Commands::Import { .. }
+                | Commands::Thread { .. }
+                | Commands::Run { .. }
+                | Commands::Db { .. }
+                | Commands::Tag { .. }
+                | Commands::Star { .. }
+                | Commands::Note { .. }
+                | Commands::Pick,
+            ) => true,
+            Some(_) => false,
+            None => {
+                self.clear
+                    || self.find.is_some()
+                    || self.comments.is_some()
+                    || self.leads
+                    || !(self.export || self.settings || self.open_db || self.gemini.is_some())
+            }
+        }
+    }
+
+    // Name recorded in the runs table for this invocation
+    pub fn run_command(&self) -> &'static str {
+        match &self.command {
+            Some(Commands::Media { .. }) => "media",
+            Some(Commands::Leads { .. }) => "leads",
+            Some(Commands::Export { .. }) => "export",
+            Some(Commands::Report { .. }) => "report",
+            Some(Commands::Index) => "index",
+            Some(Commands::Classify) => "classify",
+            Some(Commands::Query { .. }) => "query",
+            Some(Commands::Import { .. }) => "import",
+            Some(Commands::Thread { .. }) => "thread",
+            Some(Commands::Feed { .. }) => "feed",
+            Some(Commands::Serve { .. }) => "serve",
+            Some(Commands::Watch) => "watch",
+            Some(Commands::Run { .. }) => "run",
+            Some(Commands::Service { .. }) => "service",
+            Some(Commands::Status) => "status",
+            Some(Commands::Quota) => "quota",
+            Some(Commands::Runs { .. }) => "runs",
+            Some(Commands::Diff { .. }) => "diff",
+            Some(Commands::Tag { .. }) => "tag",
+            Some(Commands::Star { .. }) => "star",
+            Some(Commands::Tags) => "tags",
+            Some(Commands::Note { .. }) => "note",
+            Some(Commands::Notes) => "notes",
+            Some(Commands::Open { .. }) => "open",
+            Some(Commands::Pick) => "pick",
+            Some(Commands::Db { .. }) => "db",
+            Some(Commands::Searches) => "searches",
+            Some(Commands::Keywords { .. }) => "keywords",
+            Some(Commands::Mcp { .. }) => "mcp",
+            None if self.find.is_some() => "search",
+            None if self.comments.is_some() => "comments",
+            None if self.gemini.is_some() => "ask",
+            None if self.leads => "leads",
+            None => "fetch",
+        }
+    }
+}
+
+// Tags are matched case-insensitively and without surrounding spaces
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", value))
+}
+
+// Days from `start` to `end`, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Period {
+    // Unix timestamps of [start, the day after end)
+    pub fn timestamps(&self) -> (i64, i64) {
+        let start_of_day = |date: NaiveDate| {
+            date.and_hms_opt(0, 0, 0)
+                .map_or(0, |dt| dt.and_utc().timestamp())
+        };
+        (start_of_day(self.start), start_of_day(self.end) + 86_400)
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+// START..END with YYYY-MM-DD dates, or YYYY-MM for a whole month
+fn parse_period(value: &str) -> Result<Period, String> {
+    let value = value.trim();
+    let period = if let Some((start, end)) = value.split_once("..") {
+        Period {
+            start: parse_date(start)?,
+            end: parse_date(end)?,
+        }
+    } else {
+        let start = NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid period '{}', expected START..END or YYYY-MM", value))?;
+        let end = start
+            .checked_add_months(chrono::Months::new(1))
+            .and_then(|next| next.pred_opt())
+            .ok_or_else(|| format!("Invalid period '{}'", value))?;
+        Period { start, end }
+    };
+
+    if period.end < period.start {
+        return Err(format!("Period '{}' ends before it starts", value));
+    }
+    Ok(period)
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Work with media captured from image, gallery and video posts
+    Media {
+        #[command(subcommand)]
+        action: MediaCommands,
+    },
+
+    /// Review the leads found by Gemini and export the approved ones
+    Leads {
+        #[command(subcommand)]
+        action: LeadsCommands,
+    },
+
+    /// Export the stored posts in the chosen format
+    Export {
+        #[command(subcommand)]
+        target: Option<ExportTarget>,
+
+        #[arg(short, long, global = true, value_enum, default_value_t = ExportFormat::Xlsx)]
+        format: ExportFormat,
+
+        /// Export at most this many rows, newest first
+        #[arg(long, global = true)]
+        limit: Option<usize>,
+
+        /// Split the export into files of this many rows each (xlsx, csv, ndjson)
+        #[arg(long, global = true)]
+        chunk: Option<usize>,
+    },
+
+    /// Summarise recent activity and lead sentiment
+    Report {
+        #[command(subcommand)]
+        period: ReportCommands,
+    },
+
+    /// Embed new and changed posts and comments for --gemini retrieval
+    Index,
+
+    /// Re-tag stored posts as questions, with their intent and as noise, after
+    /// editing [[intents]] or [noise]
+    Classify,
+
+    /// List stored posts matching the filters, newest first
+    Query {
+        /// Only posts from this subreddit
+        #[arg(short, long)]
+        subreddit: Option<String>,
+
+        /// Only posts whose title or body contains this text
+        #[arg(long)]
+        contains: Option<String>,
+
+        /// List the posts quarantined as spam or noise instead, with the reason
+        #[arg(long)]
+        noise: bool,
+
+        /// Number of posts to show
+        #[arg(long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Import historical posts and comments from a data dump
+    Import {
+        #[arg(short, long, value_enum)]
+        format: ImportFormat,
+
+        /// File to import, or the extracted folder for a Reddit data export
+        file: PathBuf,
+    },
+
+    /// Fetch a post and all its comments into the database
+    #[command(args_conflicts_with_subcommands = true)]
+    Thread {
+        #[command(subcommand)]
+        action: Option<ThreadCommands>,
+
+        /// Post id or Reddit URL
+        post: Option<String>,
+    },
+
+    /// Generate an RSS feed of recent posts matching your lead keywords
+    Feed {
+        /// Write the feed to this file instead of Reddit_data/ruddit_feed.xml
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Serve the feed over HTTP on this address (e.g. 127.0.0.1:8080)
+        #[arg(long)]
+        serve: Option<String>,
+
+        /// Fetch posts from Reddit (--subreddit, --relevance) before building the feed
+        #[arg(long)]
+        live: bool,
+
+        /// Maximum number of items in the feed
+        #[arg(long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Serve a read-only JSON API over the database (/posts, /posts/{id}/comments, /leads, /stats)
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:3000")]
+        addr: String,
+    },
+
+    /// Run the fetch and lead schedules from [schedule] in settings.toml
+    Watch,
+
+    /// Run several stages in one go: fetch, then leads, then export, then notify
+    Run {
+        /// Pipeline from [[pipelines]] in settings.toml, instead of the stage flags
+        #[arg(conflicts_with_all = ["fetch", "leads", "export", "notify"])]
+        pipeline: Option<String>,
+
+        /// Fetch posts and comments from the subreddit
+        #[arg(long)]
+        fetch: bool,
+
+        /// Ask Gemini for leads in the stored posts
+        #[arg(long)]
+        leads: bool,
+
+        /// Export the stored posts in this format
+        #[arg(long, value_enum)]
+        export: Option<ExportFormat>,
+
+        /// Send the run summary to [notifications]
+        #[arg(long)]
+        notify: bool,
+
+        /// Subreddit to fetch, SUBREDDIT from the config by default
+        #[arg(short, long)]
+        subreddit: Option<String>,
+
+        /// Listing to fetch, RELEVANCE from the config by default
+        #[arg(short, long)]
+        relevance: Option<String>,
+    },
+
+    /// Install or remove `ruddit watch` as a background service
+    Service {
+        #[command(subcommand)]
+        action: ServiceCommands,
+    },
+
+    /// Report database, config and Reddit token health, last syncs and last AI call
+    Status,
+
+    /// Show the Reddit API requests left in the current window and when it resets
+    Quota,
+
+    /// Inspect how the configured keywords perform
+    Keywords {
+        #[command(subcommand)]
+        action: KeywordsCommands,
+    },
+
+    /// List the saved searches of [[searches]] and where each left off
+    Searches,
+
+    /// Maintain the database
+    Db {
+        #[command(subcommand)]
+        action: DbCommands,
+    },
+
+    /// List the runs that stored posts, comments or leads, newest first
+    Runs {
+        /// Number of runs to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Posts, leads and comment scores that changed between two runs
+    Diff {
+        /// Ids of the two runs to compare, from `ruddit runs`
+        #[arg(long, num_args = 2, value_names = ["FROM", "TO"], required = true)]
+        runs: Vec<i64>,
+
+        /// Posts, leads and score movements listed per section
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Tag a stored post, e.g. to group research by topic
+    Tag {
+        /// Post id or Reddit URL
+        post: String,
+
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Remove the tags instead
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// Star a stored post to find it again with --starred
+    Star {
+        /// Post id or Reddit URL
+        post: String,
+
+        /// Remove the star instead
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// List the tags in use and how many posts carry each
+    Tags,
+
+    /// Attach a note to a stored post or comment, or show the one it has
+    Note {
+        /// Post id or URL, or a comment as t1_<id> or its permalink
+        item: String,
+
+        /// Text of the note, replacing the current one. Leave out to show it.
+        text: Vec<String>,
+
+        /// Remove the note
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+    },
+
+    /// List every note, most recently edited first
+    Notes,
+
+    /// Fuzzy-search the stored post titles, pick one and show its comments,
+    /// open it, export its thread or mark it as a lead
+    Pick,
+
+    /// Open a post in the browser
+    Open {
+        /// Number of the post in the last `ruddit query` or search listing,
+        /// or a post id or URL
+        post: String,
+    },
+
+    /// Run a Model Context Protocol server (HTTP transport) for AI agents
+    Mcp {
+        /// Address to listen on, the endpoint is served at /mcp
+        #[arg(long, default_value = "127.0.0.1:3001")]
+        addr: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Excel workbook in Reddit_data
+    Xlsx,
+    /// CSV written row by row, for very large exports
+    Csv,
+    /// One JSON object per line, written row by row
+    Ndjson,
+    /// One Markdown note per post, written to the configured vault folder
+    Obsidian,
+    /// Posts and comments as typed CSV files with a script that loads them into DuckDB
+    Duckdb,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CopyFormat {
+    /// One permalink per line
+    Links,
+    /// `- [title](permalink) (r/subreddit)` per post
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AnswerFormat {
+    /// Excel workbook, one column per field of the returned objects
+    Xlsx,
+    /// CSV file with the same columns
+    Csv,
+    /// The answer as returned, alongside the question
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum CommentSort {
+    Top,
+    New,
+    #[default]
+    Best,
+}
+
+impl CommentSort {
+    // Value of the `sort` query parameter on the comments endpoint
+    pub fn as_param(&self) -> &'static str {
+        match self {
+            CommentSort::Top => "top",
+            CommentSort::New => "new",
+            CommentSort::Best => "confidence",
+        }
+    }
+}
+
+// How comments are requested from Reddit and filtered before they are stored
+#[derive(Debug, Clone, Copy)]
+pub struct CommentOptions {
+    pub sort: CommentSort,
+    pub min_score: Option<i32>,
+    // Comments kept per post, 0 keeps them all
+    pub limit: usize,
+    // "Continue this thread" links followed: nesting depth and requests per post
+    pub continue_depth: usize,
+    pub continue_limit: usize,
+}
+
+impl Default for CommentOptions {
+    fn default() -> Self {
+        CommentOptions {
+            sort: CommentSort::default(),
+            min_score: None,
+            limit: default_comment_limit(),
+            continue_depth: default_continue_depth(),
+            continue_limit: default_continue_limit(),
+        }
+    }
+}
+
+impl CommentOptions {
+    pub fn from_config(config: &AppConfig) -> Self {
+        CommentOptions {
+            limit: config.fetch.comment_limit,
+            continue_depth: config.fetch.continue_depth,
+            continue_limit: config.fetch.continue_limit,
+            ..Default::default()
+        }
+    }
+
+    pub fn keep(&self, score: i32) -> bool {
+        self.min_score.is_none_or(|min| score >= min)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// Pushshift / Arctic Shift ndjson dumps, plain or .zst compressed
+    Pushshift,
+    /// Extracted Reddit personal data export (posts, comments and saved items CSVs)
+    Gdpr,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ThreadCommands {
+    /// Re-fetch a thread periodically and report new comments matching your keywords
+    Watch {
+        /// Post id or Reddit URL
+        post: String,
+
+        /// Seconds between fetches
+        #[arg(short, long, default_value_t = 60)]
+        interval: u64,
+
+        /// Keywords to alert on, defaults to lead_keywords from the config file
+        #[arg(short, long)]
+        keyword: Vec<String>,
+    },
+
+    /// Write a post and its nested comments as one document, fetching the
+    /// thread first when it is not stored
+    Export {
+        /// Post id or Reddit URL
+        post: String,
+
+        #[arg(short, long, value_enum, default_value = "md")]
+        format: ThreadFormat,
+
+        /// File to write instead of Reddit_data/threads/thread_<id>.<format>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ThreadFormat {
+    /// Markdown, replies as nested quotes
+    Md,
+    /// Standalone HTML page
+    Html,
+    /// PDF document
+    Pdf,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MediaCommands {
+    /// Download the stored attachments of a post
+    Download {
+        /// Reddit post ID (e.g. 1abc23)
+        post_id: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LeadsCommands {
+    /// Step through new leads and accept, reject or re-rate each one
+    Review {
+        /// Only the leads assigned to this team member
+        #[arg(long)]
+        assignee: Option<String>,
+    },
+    /// Export the approved leads to Excel in Reddit_data
+    Export,
+    /// Find single comments matching your lead keywords and export them with their permalinks
+    Comments,
+    /// List the pending and accepted leads nobody has contacted for a while
+    Stale {
+        /// Days without contact, defaults to stale_days under [leads]
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Also send a reminder about the HIGH ones through [notifications]
+        #[arg(long)]
+        notify: bool,
+    },
+    /// Write the leads with their review state, feedback and comment leads to a JSON file
+    Dump {
+        /// File to write, defaults to Reddit_data/ruddit_leads_<date>.json
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Load a `leads dump` file, leads already stored take over its review state
+    Load {
+        /// JSON file written by `ruddit leads dump`
+        file: PathBuf,
+    },
+    /// Assign a lead to a team member, or unassign it when no name is given
+    Assign {
+        /// Lead id, as listed by `leads stale`
+        id: i64,
+
+        /// Team member handling the lead
+        assignee: Option<String>,
+    },
+    /// Record that the posters of these leads were contacted
+    Contacted {
+        /// Lead ids, as listed by `leads stale`
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportTarget {
+    /// Export stored comments from every post, with the title of their post
+    Comments {
+        /// Only comments from this subreddit
+        #[arg(short, long)]
+        subreddit: Option<String>,
+
+        /// Only comments matching this keyword query, e.g. '"cold chain" OR wms'
+        #[arg(short, long)]
+        keyword: Option<String>,
+
+        /// Only comments scoring at least this
+        #[arg(long, allow_negative_numbers = true)]
+        min_score: Option<i32>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeywordsCommands {
+    /// Posts and comments each keyword matched over the last runs, flagging
+    /// dead and over-broad keywords
+    Stats {
+        /// Number of runs to sum up
+        #[arg(long, default_value = "10")]
+        runs: usize,
+    },
+
+    /// Ask Gemini for new LEAD_KEYWORDS in the language of the accepted leads,
+    /// with how often each occurs, and add them once confirmed
+    Suggest {
+        /// Add the suggestions without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReportCommands {
+    /// New posts, top threads, lead sentiment and new leads of the last 7 days
+    Weekly {
+        /// Folder for the report files, defaults to Reddit_data/reports
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Week-over-week sentiment of the leads found for your keywords
+    Sentiment {
+        /// Number of weeks to show
+        #[arg(long, default_value = "12")]
+        weeks: usize,
+    },
+    /// Contrast post volume, lead sentiment, top keywords and leads of two periods
+    Compare {
+        /// First period, START..END (YYYY-MM-DD, both days included) or YYYY-MM
+        #[arg(long, value_parser = parse_period)]
+        period_a: Period,
+
+        /// Second period, compared against the first
+        #[arg(long, value_parser = parse_period)]
+        period_b: Period,
+
+        /// Number of keywords to compare
+        #[arg(long, default_value = "10")]
+        keywords: usize,
+    },
+    /// Post volume, lead sentiment and share of voice charts as image files
+    Charts {
+        /// Days of posts and mentions to chart, sentiment covers the weeks they span
+        #[arg(long, default_value = "30")]
+        days: u32,
+
+        /// Image format, both PNG and SVG when not given
+        #[arg(short, long, value_enum)]
+        format: Option<ImageFormat>,
+
+        /// Folder for the charts, defaults to Reddit_data/charts
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommands {
+    /// Import the posts, comments and leads of another ruddit database, lead
+    /// reviews that differ keep the latest decision
+    Merge {
+        /// The other ruddit.db, opened read-only
+        file: PathBuf,
+    },
+    /// Push the posts and comments stored since the last push to [elasticsearch]
+    Elasticsearch {
+        /// Push every stored post and comment again
+        #[arg(long)]
+        all: bool,
+    },
+    /// Publish the posts, comments and leads stored since the last publish to [publish]
+    Publish,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ServiceCommands {
+    /// Write a systemd user unit, launchd agent or Windows scheduled task running `ruddit watch`
+    Install,
+    /// Stop and remove the installed service
+    Uninstall,
 }
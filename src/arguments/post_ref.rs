@@ -0,0 +1,91 @@
+use crate::database::adding::DB;
+
+// A post given on the command line, either as a bare id or a Reddit URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostRef {
+    pub id: String,
+    pub subreddit: Option<String>,
+}
+
+impl PostRef {
+    // Accepts `abc123`, `t3_abc123`, `https://www.reddit.com/r/<sub>/comments/<id>/...`,
+    // `old.reddit.com` / `np.reddit.com` links, `reddit.com/comments/<id>` and `redd.it/<id>`
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        if !value.contains('/') {
+            let id = value.trim_start_matches("t3_");
+            return is_reddit_id(id).then(|| PostRef {
+                id: id.to_lowercase(),
+                subreddit: None,
+            });
+        }
+
+        // Drop the scheme, query string and fragment
+        let path = value
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .split_once("://")
+            .map_or(value, |(_, rest)| rest);
+        let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+
+        if parts.first().is_some_and(|host| host.ends_with("redd.it")) {
+            return parts
+                .get(1)
+                .filter(|id| is_reddit_id(id))
+                .map(|id| PostRef {
+                    id: id.to_lowercase(),
+                    subreddit: None,
+                });
+        }
+
+        let id = parts
+            .iter()
+            .position(|part| *part == "comments")
+            .and_then(|i| parts.get(i + 1))
+            .filter(|id| is_reddit_id(id))?;
+        let subreddit = parts
+            .iter()
+            .position(|part| *part == "r")
+            .and_then(|i| parts.get(i + 1))
+            .map(|subreddit| subreddit.to_string());
+
+        Some(PostRef {
+            id: id.to_lowercase(),
+            subreddit,
+        })
+    }
+
+    // The integer id the post is stored under
+    pub fn row_id(&self) -> Result<i64, String> {
+        DB::decode_reddit_id(&self.id).ok_or_else(|| format!("{} is not a Reddit post id", self.id))
+    }
+
+    // Mobile share links (`/r/<sub>/s/<code>`) only resolve through a redirect
+    pub fn is_share_link(value: &str) -> bool {
+        value.contains("reddit.com/") && value.contains("/s/")
+    }
+}
+
+// The comment a value names: `t1_abc123` or a comment permalink such as
+// `https://www.reddit.com/r/<sub>/comments/<post>/<slug>/<comment>/`
+pub fn comment_id(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some(id) = value.strip_prefix("t1_") {
+        return is_reddit_id(id).then(|| id.to_lowercase());
+    }
+
+    let path = value.split(['?', '#']).next().unwrap_or_default();
+    let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    parts
+        .iter()
+        .position(|part| *part == "comments")
+        .and_then(|i| parts.get(i + 3))
+        .filter(|id| is_reddit_id(id))
+        .map(|id| id.to_lowercase())
+}
+
+fn is_reddit_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 13 && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
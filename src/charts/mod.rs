@@ -0,0 +1,236 @@
+use std::path::{Path, PathBuf};
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::arguments::modeling::ImageFormat;
+use crate::database::reports::{MentionCount, PeriodCount, SentimentCount};
+
+const BLUE: RGBColor = RGBColor(68, 114, 196);
+const GREEN: RGBColor = RGBColor(112, 173, 71);
+const GREY: RGBColor = RGBColor(165, 165, 165);
+const RED: RGBColor = RGBColor(192, 0, 0);
+const GRID: RGBColor = RGBColor(221, 221, 221);
+
+const HEIGHT: u32 = 320;
+const LABEL_CHARS: usize = 12;
+// Rough width of a label character at the axis font size
+const CHAR_WIDTH: u32 = 7;
+
+pub struct Series {
+    pub name: String,
+    pub color: RGBColor,
+    pub values: Vec<i64>,
+}
+
+// Bar chart with one bar per label, the series of a bar stacked bottom up
+pub struct BarChart {
+    pub title: String,
+    pub labels: Vec<String>,
+    pub series: Vec<Series>,
+}
+
+impl BarChart {
+    // Wide enough for every label to fit under its bar
+    fn size(&self) -> (u32, u32) {
+        let longest = self
+            .labels
+            .iter()
+            .map(|label| label.chars().count().min(LABEL_CHARS) as u32)
+            .max()
+            .unwrap_or(0);
+        let step = (longest * CHAR_WIDTH + 10).max(36);
+        ((70 + self.labels.len() as u32 * step).max(480), HEIGHT)
+    }
+
+    fn total(&self, index: usize) -> i64 {
+        self.series
+            .iter()
+            .map(|series| series.values.get(index).copied().unwrap_or(0).max(0))
+            .sum()
+    }
+
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        let max = (0..self.labels.len())
+            .map(|index| self.total(index))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        // Room above the bars, more of it for the legend in the corner
+        let top = if self.series.len() > 1 {
+            max + max / 3 + 1
+        } else {
+            max + max / 10 + 1
+        };
+
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.title, ("sans-serif", 18))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(45)
+            // Integer ranges include their end, one segment per label
+            .build_cartesian_2d(
+                (0..self.labels.len().saturating_sub(1)).into_segmented(),
+                0..top,
+            )?;
+
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .light_line_style(TRANSPARENT)
+            .bold_line_style(GRID)
+            .x_labels(self.labels.len())
+            .x_label_formatter(&|value| match value {
+                SegmentValue::CenterOf(index) => self
+                    .labels
+                    .get(*index)
+                    .map(|label| label.chars().take(LABEL_CHARS).collect())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            })
+            .y_labels(5)
+            .label_style(("sans-serif", 12))
+            .draw()?;
+
+        let mut bottoms = vec![0; self.labels.len()];
+        for series in &self.series {
+            let color = series.color;
+            let bars: Vec<_> = (0..self.labels.len())
+                .map(|index| {
+                    let bottom = bottoms[index];
+                    let top = bottom + series.values.get(index).copied().unwrap_or(0).max(0);
+                    bottoms[index] = top;
+                    // The last segment ends at the edge of the axis
+                    let end = if index + 1 == self.labels.len() {
+                        SegmentValue::Last
+                    } else {
+                        SegmentValue::Exact(index + 1)
+                    };
+                    let mut bar = Rectangle::new(
+                        [(SegmentValue::Exact(index), bottom), (end, top)],
+                        color.filled(),
+                    );
+                    bar.set_margin(0, 0, 5, 5);
+                    bar
+                })
+                .collect();
+            chart
+                .draw_series(bars)?
+                .label(series.name.as_str())
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled())
+                });
+        }
+
+        if self.series.len() > 1 {
+            chart
+                .configure_series_labels()
+                .position(SeriesLabelPosition::UpperLeft)
+                .background_style(WHITE.mix(0.8))
+                .border_style(GRID)
+                .label_font(("sans-serif", 12))
+                .draw()?;
+        }
+
+        root.present()?;
+        Ok(())
+    }
+
+    // Writes `<stem>.png` and/or `<stem>.svg` in `folder`
+    pub fn write(
+        &self,
+        folder: &Path,
+        stem: &str,
+        formats: &[ImageFormat],
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let mut written = Vec::new();
+        for format in formats {
+            let path = match format {
+                ImageFormat::Png => {
+                    let path = folder.join(format!("{}.png", stem));
+                    self.draw(&BitMapBackend::new(&path, self.size()).into_drawing_area())?;
+                    path
+                }
+                ImageFormat::Svg => {
+                    let path = folder.join(format!("{}.svg", stem));
+                    self.draw(&SVGBackend::new(&path, self.size()).into_drawing_area())?;
+                    path
+                }
+            };
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+// Stored posts per day, labelled MM-DD
+pub fn post_volume(days: &[PeriodCount]) -> BarChart {
+    BarChart {
+        title: "Posts per day".to_string(),
+        labels: days
+            .iter()
+            .map(|day| day.period.get(5..).unwrap_or(&day.period).to_string())
+            .collect(),
+        series: vec![Series {
+            name: "Posts".to_string(),
+            color: BLUE,
+            values: days.iter().map(|day| day.count).collect(),
+        }],
+    }
+}
+
+// Lead sentiment per week, labelled by week number
+pub fn sentiment(weeks: &[SentimentCount]) -> BarChart {
+    let series = |name: &str, color: RGBColor, count: fn(&SentimentCount) -> i64| Series {
+        name: name.to_string(),
+        color,
+        values: weeks.iter().map(count).collect(),
+    };
+    BarChart {
+        title: "Lead sentiment per week".to_string(),
+        labels: weeks
+            .iter()
+            .map(|week| {
+                week.period
+                    .split_once('-')
+                    .map_or(week.period.as_str(), |(_, number)| number)
+                    .to_string()
+            })
+            .collect(),
+        series: vec![
+            series("Positive", GREEN, |week| week.positive),
+            series("Neutral", GREY, |week| week.neutral),
+            series("Negative", RED, |week| week.negative),
+        ],
+    }
+}
+
+// Posts and comments mentioning BRANDED_KEYWORDS and each keyword group
+pub fn share_of_voice(mentions: &[MentionCount]) -> BarChart {
+    let total: i64 = mentions.iter().map(|entry| entry.mentions).sum();
+    BarChart {
+        title: "Share of voice".to_string(),
+        labels: mentions
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{:.0}% {}",
+                    entry.mentions as f64 * 100.0 / total.max(1) as f64,
+                    entry.name
+                )
+            })
+            .collect(),
+        series: vec![Series {
+            name: "Mentions".to_string(),
+            color: BLUE,
+            values: mentions.iter().map(|entry| entry.mentions).collect(),
+        }],
+    }
+}
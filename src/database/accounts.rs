@@ -0,0 +1,110 @@
+use chrono::Utc;
+use rusqlite::{Result as RusqliteResult, params};
+use serde::{Deserialize, Serialize};
+
+use crate::database::adding::DB;
+
+// Requests made with one Reddit account
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AccountUsage {
+    pub account: String,
+    pub requests: i64,
+    pub rate_limited: i64,
+    pub last_used_at: i64,
+    pub formatted_date: String,
+}
+
+impl DB {
+    pub fn create_accounts_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS account_usage (
+                account TEXT PRIMARY KEY,
+                requests INTEGER NOT NULL DEFAULT 0,
+                rate_limited INTEGER NOT NULL DEFAULT 0,
+                last_used_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS api_requests (
+                minute INTEGER PRIMARY KEY,
+                requests INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn record_account_usage(
+        &self,
+        account: &str,
+        requests: usize,
+        rate_limited: usize,
+    ) -> RusqliteResult<()> {
+        self.create_accounts_table()?;
+        self.conn.execute(
+            "INSERT INTO account_usage (account, requests, rate_limited, last_used_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(account) DO UPDATE SET
+                requests = requests + excluded.requests,
+                rate_limited = rate_limited + excluded.rate_limited,
+                last_used_at = excluded.last_used_at",
+            params![
+                account,
+                requests as i64,
+                rate_limited as i64,
+                Utc::now().timestamp()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    // Count one Reddit API request in the current minute
+    pub fn record_api_request(&self) -> RusqliteResult<()> {
+        self.create_accounts_table()?;
+        let minute = Utc::now().timestamp() / 60 * 60;
+        self.conn.execute(
+            "INSERT INTO api_requests (minute, requests) VALUES (?1, 1)
+             ON CONFLICT(minute) DO UPDATE SET requests = requests + 1",
+            params![minute],
+        )?;
+
+        Ok(())
+    }
+
+    // Reddit API requests made since `since`, across every run
+    pub fn count_api_requests(&self, since: i64) -> RusqliteResult<i64> {
+        self.create_accounts_table()?;
+        self.conn.query_row(
+            "SELECT COALESCE(SUM(requests), 0) FROM api_requests WHERE minute >= ?1",
+            params![since / 60 * 60],
+            |row| row.get(0),
+        )
+    }
+
+    pub fn get_account_usage(&self) -> RusqliteResult<Vec<AccountUsage>> {
+        self.create_accounts_table()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT account, requests, rate_limited, last_used_at
+             FROM account_usage
+             ORDER BY account",
+        )?;
+
+        let usage = stmt
+            .query_map([], |row| {
+                let last_used_at: i64 = row.get(3)?;
+                Ok(AccountUsage {
+                    account: row.get(0)?,
+                    requests: row.get(1)?,
+                    rate_limited: row.get(2)?,
+                    last_used_at,
+                    formatted_date: DB::format_timestamp(last_used_at)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        Ok(usage)
+    }
+}
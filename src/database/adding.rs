@@ -1,8 +1,19 @@
 use chrono::{DateTime, Utc};
-use directories::BaseDirs;
-use rusqlite::{Connection, Result as RusqliteResult, params};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, OpenFlags, Result as RusqliteResult, params};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::arguments::post_ref::PostRef;
+use crate::filters::intents::IntentClassifier;
+use crate::filters::noise::NoiseClassifier;
+use crate::filters::questions::is_question;
+use crate::filters::text::clean_text;
+use crate::settings::api_keys::ConfigDirs;
+use crate::settings::paths::Paths;
 
 // Post data structure
 #[derive(Debug, Deserialize, Serialize)]
@@ -15,6 +26,7 @@ pub struct PostDataWrapper {
     pub relevance: String,
     pub subreddit: String,
     pub permalink: String,
+    pub selftext: String,
 }
 
 // Comment data structure
@@ -37,25 +49,113 @@ pub struct DB {
     pub conn: Connection,
 }
 
-impl DB {
-    pub fn new() -> RusqliteResult<Self> {
-        let base_dirs = BaseDirs::new().ok_or(rusqlite::Error::InvalidPath(PathBuf::from(
-            "Failed to get base directories",
-        )))?;
+// Set by --read-only before the first connection is opened
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+// How long a statement waits for another process's write to finish
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+// In-memory database shared by every connection of this process, see
+// --ephemeral. The memdb VFS frees it when its last connection closes, so
+// one connection is kept open for the whole run.
+const EPHEMERAL_URI: &str = "file:/ruddit-ephemeral?vfs=memdb";
+static EPHEMERAL: OnceLock<Mutex<Connection>> = OnceLock::new();
+
+pub fn set_ephemeral() -> RusqliteResult<()> {
+    let conn = Connection::open_with_flags(EPHEMERAL_URI, ephemeral_flags())?;
+    let _ = EPHEMERAL.set(Mutex::new(conn));
+    Ok(())
+}
+
+pub fn is_ephemeral() -> bool {
+    EPHEMERAL.get().is_some()
+}
+
+fn ephemeral_flags() -> OpenFlags {
+    OpenFlags::SQLITE_OPEN_READ_WRITE
+        | OpenFlags::SQLITE_OPEN_CREATE
+        | OpenFlags::SQLITE_OPEN_URI
+        | OpenFlags::SQLITE_OPEN_NO_MUTEX
+}
+
+// Platform folders, the database lives in the data folder by default
+fn paths() -> RusqliteResult<&'static Paths> {
+    Paths::get().map_err(|e| rusqlite::Error::InvalidPath(PathBuf::from(e)))
+}
+
+// The database file every connection opens, resolved once
+static DATABASE: OnceLock<PathBuf> = OnceLock::new();
 
-        let app_dir = base_dirs.data_dir().join("ruddit");
+// --db-path, set before the first connection is opened
+pub fn set_database_path(path: PathBuf) {
+    let _ = DATABASE.set(path);
+}
 
-        if !app_dir.exists() {
-            std::fs::create_dir_all(&app_dir).map_err(|e| {
+// --db-path, else db_path from settings.toml (relative to the config folder),
+// else ruddit.db in the data folder. Its folder is created when missing.
+pub fn database_path() -> RusqliteResult<PathBuf> {
+    let path = match DATABASE.get() {
+        Some(path) => path.clone(),
+        None => {
+            let configured = ConfigDirs::load_config()
+                .map(|config| config.db_path.trim().to_string())
+                .unwrap_or_default();
+            let path = if configured.is_empty() {
+                paths()?.database_file()
+            } else {
+                paths()?.config_dir.join(configured)
+            };
+            DATABASE.get_or_init(|| path).clone()
+        }
+    };
+
+    match path.parent() {
+        Some(folder) if !folder.as_os_str().is_empty() && !folder.exists() => {
+            std::fs::create_dir_all(folder).map_err(|e| {
                 rusqlite::Error::InvalidPath(PathBuf::from(format!(
-                    "Failed to create directory: {}",
+                    "Failed to create directory {}: {}",
+                    folder.display(),
                     e
                 )))
             })?;
         }
+        _ => {}
+    }
+    Ok(path)
+}
 
-        let db_path = app_dir.join("ruddit.db");
-        let conn = Connection::open(db_path)?;
+impl DB {
+    pub fn new() -> RusqliteResult<Self> {
+        let conn = if is_ephemeral() {
+            Connection::open_with_flags(EPHEMERAL_URI, ephemeral_flags())?
+        } else if READ_ONLY.load(Ordering::Relaxed) {
+            Connection::open_with_flags(
+                database_path()?,
+                OpenFlags::SQLITE_OPEN_READ_ONLY
+                    | OpenFlags::SQLITE_OPEN_URI
+                    | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?
+        } else {
+            let conn = Connection::open(database_path()?)?;
+            // Readers keep working while `ruddit watch` writes
+            conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+            conn
+        };
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        // Comments keep their post's base36 id while posts are keyed by its
+        // integer value, `reddit_id(post_id)` lets queries join the two
+        conn.create_scalar_function(
+            "reddit_id",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            // NULL for malformed ids, which then join nothing
+            |ctx| Ok(DB::decode_reddit_id(&ctx.get::<String>(0)?)),
+        )?;
 
         Ok(DB { conn })
     }
@@ -71,17 +171,247 @@ impl DB {
                 url TEXT NOT NULL,
                 relevance TEXT NOT NULL DEFAULT '',
                 subreddit TEXT NOT NULL DEFAULT '',
-                permalink TEXT NOT NULL DEFAULT ''
+                permalink TEXT NOT NULL DEFAULT '',
+                selftext TEXT NOT NULL DEFAULT '',
+                source TEXT NOT NULL DEFAULT 'reddit',
+                is_question INTEGER NOT NULL DEFAULT 0,
+                intent TEXT NOT NULL DEFAULT '',
+                selftext_clean TEXT NOT NULL DEFAULT '',
+                first_seen_at INTEGER NOT NULL DEFAULT 0,
+                last_seen_at INTEGER NOT NULL DEFAULT 0,
+                run_id INTEGER NOT NULL DEFAULT 0,
+                noise TEXT NOT NULL DEFAULT ''
             )",
             [],
         )?;
 
+        // Databases created before selftext was stored also numbered posts by
+        // rowid, move them to their Reddit ids
+        if self.add_column_if_missing("reddit_posts", "selftext", "TEXT NOT NULL DEFAULT ''")? {
+            self.rekey_posts()?;
+        }
+        self.add_column_if_missing("reddit_posts", "source", "TEXT NOT NULL DEFAULT 'reddit'")?;
+
+        // Databases created before text cleaning, clean what is already stored
+        if self.add_column_if_missing(
+            "reddit_posts",
+            "selftext_clean",
+            "TEXT NOT NULL DEFAULT ''",
+        )? {
+            self.clean_stored_text("reddit_posts", "selftext", "selftext_clean")?;
+        }
+
+        // Databases created before question and intent tagging, tag the posts already stored
+        let questions_added = self.add_column_if_missing(
+            "reddit_posts",
+            "is_question",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        let intents_added =
+            self.add_column_if_missing("reddit_posts", "intent", "TEXT NOT NULL DEFAULT ''")?;
+        // Databases created before noise quarantine, reason of a quarantined post
+        let noise_added =
+            self.add_column_if_missing("reddit_posts", "noise", "TEXT NOT NULL DEFAULT ''")?;
+        if questions_added || intents_added || noise_added {
+            self.tag_posts(&IntentClassifier::load(), &NoiseClassifier::load())?;
+        }
+
+        // Databases created before discovery tracking, 0 means not known
+        self.add_column_if_missing(
+            "reddit_posts",
+            "first_seen_at",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        self.add_column_if_missing("reddit_posts", "last_seen_at", "INTEGER NOT NULL DEFAULT 0")?;
+
+        // Databases created before run tracking, 0 means no known run
+        self.add_column_if_missing("reddit_posts", "run_id", "INTEGER NOT NULL DEFAULT 0")?;
+
         // Create comments table
         self.create_comments_table()?;
 
+        // Create media table
+        self.create_media_table()?;
+
+        // Create leads table
+        self.create_leads_table()?;
+
+        // Create sync and AI usage logs
+        self.create_status_tables()?;
+
+        // Create retry queue
+        self.create_jobs_table()?;
+
+        // Create per-account request counters
+        self.create_accounts_table()?;
+
+        // Create cache of AI responses
+        self.create_ai_cache_table()?;
+
+        // Create history of fired alerts
+        self.create_alerts_table()?;
+
+        // Create key/value store for generated settings
+        self.create_meta_table()?;
+
+        // Create vector index for --gemini retrieval
+        self.create_embeddings_table()?;
+
+        // Create log of the invocations that stored data
+        self.create_runs_table()?;
+
+        // Create saved search cursors and the posts they found
+        self.create_searches_tables()?;
+
+        // Create tags added by hand
+        self.create_tags_table()?;
+
+        // Create comments saved on Reddit
+        self.create_saved_table()?;
+
+        // Create notes on posts and comments
+        self.create_notes_tables()?;
+
         Ok(())
     }
 
+    // Recompute the question flag, intent and noise reason of every stored post,
+    // returns the number of posts tagged
+    pub fn tag_posts(
+        &self,
+        classifier: &IntentClassifier,
+        noise: &NoiseClassifier,
+    ) -> RusqliteResult<usize> {
+        let posts = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id, title, selftext_clean FROM reddit_posts")?;
+            stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE reddit_posts SET is_question = ?2, intent = ?3, noise = ?4 WHERE id = ?1",
+            )?;
+            for (id, title, selftext) in &posts {
+                stmt.execute(params![
+                    id,
+                    is_question(title, selftext),
+                    classifier.classify(title, selftext),
+                    noise.classify(title, selftext)
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(posts.len())
+    }
+
+    // Replace rowid post ids with the decoded Reddit id from the permalink,
+    // returns the number of posts moved. Duplicates of a post that the rowid
+    // numbering let in are dropped. Posts without a Reddit link keep their id.
+    pub fn rekey_posts(&self) -> RusqliteResult<usize> {
+        let posts = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id, permalink, url FROM reddit_posts")?;
+            stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut moved = 0;
+        {
+            let mut rekey =
+                tx.prepare("UPDATE OR IGNORE reddit_posts SET id = ?2 WHERE id = ?1")?;
+            let mut drop = tx.prepare("DELETE FROM reddit_posts WHERE id = ?1")?;
+            for (id, permalink, url) in &posts {
+                let Some(reddit_id) = PostRef::parse(permalink)
+                    .or_else(|| PostRef::parse(url))
+                    .and_then(|post| DB::decode_reddit_id(&post.id))
+                    .filter(|reddit_id| reddit_id != id)
+                else {
+                    continue;
+                };
+                if rekey.execute(params![id, reddit_id])? > 0 {
+                    moved += 1;
+                } else {
+                    drop.execute([id])?;
+                }
+            }
+        }
+        tx.commit()?;
+
+        Ok(moved)
+    }
+
+    // Fill a cleaned text column from its raw column, returns the number of rows
+    pub fn clean_stored_text(&self, table: &str, raw: &str, clean: &str) -> RusqliteResult<usize> {
+        let rows = {
+            let mut stmt = self
+                .conn
+                .prepare(&format!("SELECT rowid, {} FROM {}", raw, table))?;
+            stmt.query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(&format!(
+                "UPDATE {} SET {} = ?2 WHERE rowid = ?1",
+                table, clean
+            ))?;
+            for (rowid, text) in &rows {
+                stmt.execute(params![rowid, clean_text(text)])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(rows.len())
+    }
+
+    // Returns true when the column had to be added
+    pub fn add_column_if_missing(
+        &self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> RusqliteResult<bool> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<RusqliteResult<Vec<_>>>()?
+            .iter()
+            .any(|name| name == column);
+
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
+
+        Ok(!exists)
+    }
+
     pub fn create_comments_table(&self) -> RusqliteResult<()> {
         // Create comments table if it doesn't exist
         self.conn.execute(
@@ -96,54 +426,137 @@ impl DB {
                 permalink TEXT NOT NULL,
                 parent_id TEXT NOT NULL,
                 subreddit TEXT NOT NULL,
-                post_title TEXT NOT NULL
+                post_title TEXT NOT NULL,
+                source TEXT NOT NULL DEFAULT 'reddit',
+                body_clean TEXT NOT NULL DEFAULT '',
+                first_seen_at INTEGER NOT NULL DEFAULT 0,
+                last_seen_at INTEGER NOT NULL DEFAULT 0,
+                run_id INTEGER NOT NULL DEFAULT 0
             )",
             [],
         )?;
 
+        // Databases created before imports were supported
+        self.add_column_if_missing(
+            "reddit_comments",
+            "source",
+            "TEXT NOT NULL DEFAULT 'reddit'",
+        )?;
+
+        // Databases created before text cleaning
+        if self.add_column_if_missing(
+            "reddit_comments",
+            "body_clean",
+            "TEXT NOT NULL DEFAULT ''",
+        )? {
+            self.clean_stored_text("reddit_comments", "body", "body_clean")?;
+        }
+
+        // Databases created before discovery tracking
+        self.add_column_if_missing(
+            "reddit_comments",
+            "first_seen_at",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        self.add_column_if_missing(
+            "reddit_comments",
+            "last_seen_at",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        self.add_column_if_missing("reddit_comments", "run_id", "INTEGER NOT NULL DEFAULT 0")?;
+
         Ok(())
     }
 
-    pub fn append_results(&mut self, results: &[PostDataWrapper]) -> RusqliteResult<()> {
+    // Returns how many posts were new, rows already stored are left untouched
+    pub fn append_results(&mut self, results: &[PostDataWrapper]) -> RusqliteResult<usize> {
+        self.append_results_from(results, "reddit")
+    }
+
+    // Store posts tagged with where they came from (reddit, pushshift, gdpr...)
+    // and the run that stored them. New posts get first_seen_at, posts seen
+    // again only move last_seen_at.
+    pub fn append_results_from(
+        &mut self,
+        results: &[PostDataWrapper],
+        source: &str,
+    ) -> RusqliteResult<usize> {
+        let classifier = IntentClassifier::load();
+        let noise = NoiseClassifier::load();
+        let now = Utc::now().timestamp();
+        let run_id = self.current_run()?;
         let tx = self.conn.transaction()?;
+        let mut inserted = 0;
 
         {
             let mut stmt = tx.prepare(
                 "INSERT OR IGNORE INTO reddit_posts
-                (timestamp, formatted_date, title, url, relevance, subreddit, permalink)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (id, timestamp, formatted_date, title, url, relevance, subreddit, permalink, selftext, source, is_question, intent, selftext_clean, first_seen_at, last_seen_at, run_id, noise)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?14, ?15, ?16)",
             )?;
+            let mut seen_again =
+                tx.prepare("UPDATE reddit_posts SET last_seen_at = ?2 WHERE id = ?1")?;
 
             for result in results {
-                stmt.execute(params![
+                let selftext = clean_text(&result.selftext);
+                let added = stmt.execute(params![
+                    result.id,
                     result.timestamp,
                     result.formatted_date,
                     result.title,
                     result.url,
                     result.relevance,
                     result.subreddit,
-                    result.permalink
+                    result.permalink,
+                    result.selftext,
+                    source,
+                    is_question(&result.title, &selftext),
+                    classifier.classify(&result.title, &selftext),
+                    selftext,
+                    now,
+                    run_id,
+                    noise.classify(&result.title, &selftext)
                 ])?;
+                if added == 0 {
+                    seen_again.execute(params![result.id, now])?;
+                }
+                inserted += added;
             }
         }
 
         tx.commit()?;
-        println!("Added {} results", results.len());
-        Ok(())
+        Ok(inserted)
+    }
+
+    // Returns how many comments were new. Comments fetched from Reddit again
+    // get their current score, imports leave stored scores alone.
+    pub fn append_comments(&mut self, comments: &[CommentDataWrapper]) -> RusqliteResult<usize> {
+        self.append_comments_from(comments, "reddit")
     }
 
-    pub fn append_comments(&mut self, comments: &[CommentDataWrapper]) -> RusqliteResult<()> {
+    pub fn append_comments_from(
+        &mut self,
+        comments: &[CommentDataWrapper],
+        source: &str,
+    ) -> RusqliteResult<usize> {
+        let now = Utc::now().timestamp();
+        let run_id = self.current_run()?;
         let tx = self.conn.transaction()?;
+        let mut inserted = 0;
 
         {
             let mut stmt = tx.prepare(
                 "INSERT OR IGNORE INTO reddit_comments
-                (id, post_id, body, author, timestamp, formatted_date, score, permalink, parent_id, subreddit, post_title)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                (id, post_id, body, author, timestamp, formatted_date, score, permalink, parent_id, subreddit, post_title, source, body_clean, first_seen_at, last_seen_at, run_id)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?14, ?15)",
             )?;
+            let mut seen_again =
+                tx.prepare("UPDATE reddit_comments SET last_seen_at = ?2 WHERE id = ?1")?;
+            let mut rescored =
+                tx.prepare("UPDATE reddit_comments SET score = ?2 WHERE id = ?1 AND score != ?2")?;
 
             for comment in comments {
-                stmt.execute(params![
+                let added = stmt.execute(params![
                     comment.id,
                     comment.post_id,
                     comment.body,
@@ -154,19 +567,36 @@ impl DB {
                     comment.permalink,
                     comment.parent_id,
                     comment.subreddit,
-                    comment.post_title
+                    comment.post_title,
+                    source,
+                    clean_text(&comment.body),
+                    now,
+                    run_id
                 ])?;
+                let mut score_moved = added > 0;
+                if added == 0 {
+                    seen_again.execute(params![comment.id, now])?;
+                    score_moved = source == "reddit"
+                        && rescored.execute(params![comment.id, comment.score])? > 0;
+                }
+                if score_moved && run_id > 0 {
+                    tx.prepare_cached(
+                        "INSERT OR REPLACE INTO run_comment_scores (run_id, comment_id, score)
+                         VALUES (?1, ?2, ?3)",
+                    )?
+                    .execute(params![run_id, comment.id, comment.score])?;
+                }
+                inserted += added;
             }
         }
 
         tx.commit()?;
-        println!("Added {} comments", comments.len());
-        Ok(())
+        Ok(inserted)
     }
 
     pub fn get_db_results(&self) -> RusqliteResult<Vec<PostDataWrapper>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, timestamp, formatted_date, title, url, relevance, subreddit, permalink
+            "SELECT id, timestamp, formatted_date, title, url, relevance, subreddit, permalink, selftext_clean
              FROM reddit_posts
              ORDER BY timestamp DESC",
         )?;
@@ -182,6 +612,7 @@ impl DB {
                     relevance: row.get(5)?,
                     subreddit: row.get(6)?,
                     permalink: row.get(7)?,
+                    selftext: row.get(8)?,
                 })
             })?
             .collect::<RusqliteResult<Vec<_>>>()?;
@@ -189,12 +620,17 @@ impl DB {
         Ok(posts)
     }
 
+    // Comments of one post. Title and subreddit come from the stored post when
+    // there is one, older rows and imports may not carry them.
     pub fn get_post_comments(&self, post_id: &str) -> RusqliteResult<Vec<CommentDataWrapper>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, post_id, body, author, timestamp, formatted_date, score, permalink, parent_id, subreddit, post_title
-             FROM reddit_comments
-             WHERE post_id = ?1
-             ORDER BY timestamp DESC",
+            "SELECT c.id, c.post_id, c.body_clean, c.author, c.timestamp, c.formatted_date, c.score,
+                    c.permalink, c.parent_id, COALESCE(NULLIF(p.subreddit, ''), c.subreddit),
+                    COALESCE(NULLIF(p.title, ''), c.post_title)
+             FROM reddit_comments c
+             LEFT JOIN reddit_posts p ON p.id = reddit_id(c.post_id)
+             WHERE c.post_id = ?1
+             ORDER BY c.timestamp DESC",
         )?;
 
         let comments = stmt
@@ -229,9 +665,63 @@ impl DB {
         Ok(datetime.format("%Y-%m-%d %H:%M:%S").to_string())
     }
 
+    // Reddit ids are base36 strings, stored as their integer value
+    // None when the id is not base36 or does not fit in a row id
+    pub fn decode_reddit_id(id: &str) -> Option<i64> {
+        i64::from_str_radix(id, 36).ok().filter(|id| *id > 0)
+    }
+
+    pub fn encode_reddit_id(id: i64) -> String {
+        const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+        if id <= 0 {
+            return "0".to_string();
+        }
+
+        let mut n = id;
+        let mut encoded = Vec::new();
+        while n > 0 {
+            encoded.push(DIGITS[(n % 36) as usize]);
+            n /= 36;
+        }
+        encoded.reverse();
+        String::from_utf8(encoded).unwrap_or_default()
+    }
+
+    // Deletes everything scraped and what was derived from it. Kept:
+    // - app_meta: the author salt, so pseudonyms stay the same after a clear
+    // - account_usage and api_requests: Reddit's rate limit still counts the
+    //   requests made before the clear
+    // - ai_usage: the AI calls made with each key, not data about posts
     pub fn clear_database(&self) -> RusqliteResult<()> {
-        self.conn.execute("DELETE FROM reddit_posts", [])?;
-        self.conn.execute("DELETE FROM reddit_comments", [])?;
-        Ok(())
+        // Only created once keywords are counted
+        self.create_keyword_hits_table()?;
+        let tx = self.conn.unchecked_transaction()?;
+        for table in [
+            "reddit_posts",
+            "reddit_comments",
+            "post_media",
+            "leads",
+            "comment_leads",
+            "lead_feedback",
+            "post_notes",
+            "comment_notes",
+            "post_tags",
+            "saved_comments",
+            "saved_searches",
+            "search_hits",
+            "runs",
+            "run_leads",
+            "run_comment_scores",
+            "keyword_hits",
+            "embeddings",
+            "ai_cache",
+            "alert_log",
+            "jobs",
+            "sync_log",
+        ] {
+            tx.execute(&format!("DELETE FROM {}", table), [])?;
+        }
+        tx.commit()
     }
 }
@@ -0,0 +1,42 @@
+use chrono::Utc;
+use rusqlite::{OptionalExtension, Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+
+impl DB {
+    pub fn create_ai_cache_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS ai_cache (
+                key TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                response TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_ai_cache(&self, key: &str) -> RusqliteResult<Option<String>> {
+        self.create_ai_cache_table()?;
+        self.conn
+            .query_row(
+                "SELECT response FROM ai_cache WHERE key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    pub fn store_ai_cache(&self, key: &str, kind: &str, response: &str) -> RusqliteResult<()> {
+        self.create_ai_cache_table()?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO ai_cache (key, kind, response, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![key, kind, response, Utc::now().timestamp()],
+        )?;
+
+        Ok(())
+    }
+}
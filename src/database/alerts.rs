@@ -0,0 +1,80 @@
+use chrono::Utc;
+use rusqlite::{OptionalExtension, Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+use crate::filters::keywords::KeywordMatcher;
+
+impl DB {
+    pub fn create_alerts_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS alert_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                rule TEXT NOT NULL,
+                value REAL NOT NULL,
+                fired_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn record_alert(&self, rule: &str, value: f64) -> RusqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO alert_log (rule, value, fired_at) VALUES (?1, ?2, ?3)",
+            params![rule, value, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    pub fn last_alert(&self, rule: &str) -> RusqliteResult<Option<i64>> {
+        self.conn
+            .query_row(
+                "SELECT MAX(fired_at) FROM alert_log WHERE rule = ?1",
+                [rule],
+                |row| row.get(0),
+            )
+            .optional()
+            .map(Option::flatten)
+    }
+
+    // Posts and comments created since `since` that match the keywords
+    pub fn count_mentions(&self, since: i64, matcher: &KeywordMatcher) -> RusqliteResult<i64> {
+        let mut count = 0;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT title || ' ' || selftext FROM reddit_posts WHERE timestamp >= ?1")?;
+        for text in stmt.query_map([since], |row| row.get::<_, String>(0))? {
+            if matcher.matches(&text?) {
+                count += 1;
+            }
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT body FROM reddit_comments WHERE timestamp >= ?1")?;
+        for text in stmt.query_map([since], |row| row.get::<_, String>(0))? {
+            if matcher.matches(&text?) {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    // Percentage of the leads found since `since` rated negative, None without leads
+    pub fn negative_lead_share(&self, since: i64) -> RusqliteResult<Option<f64>> {
+        let (negative, total): (i64, i64) = self.conn.query_row(
+            "SELECT COALESCE(SUM(lower(sentiment) LIKE '%neg%'), 0), COUNT(*)
+             FROM leads WHERE created_at >= ?1",
+            [since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        if total == 0 {
+            return Ok(None);
+        }
+        Ok(Some(negative as f64 * 100.0 / total as f64))
+    }
+}
@@ -4,6 +4,7 @@ pub fn clear_database() -> Result<(), Box<dyn std::error::Error>> {
     println!("Clearing database...");
 
     let db = database::adding::DB::new()?;
+    db.create_tables()?;
 
     match db.clear_database() {
         Ok(_) => println!("Database cleared successfully!"),
@@ -0,0 +1,128 @@
+use rusqlite::{Result as RusqliteResult, params};
+use serde::Serialize;
+
+use crate::database::adding::DB;
+
+// Run pushed last by `ruddit db elasticsearch` or the push after a fetch
+pub const ELASTICSEARCH_RUN_KEY: &str = "elasticsearch_run";
+
+// Also the messages of crate::publish
+#[derive(Debug, Serialize)]
+pub struct PostDocument {
+    pub id: String,
+    pub subreddit: String,
+    pub title: String,
+    pub text: String,
+    pub url: String,
+    pub permalink: String,
+    pub relevance: String,
+    pub intent: String,
+    pub is_question: bool,
+    pub source: String,
+    // Unix seconds
+    pub created_at: i64,
+    pub run_id: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommentDocument {
+    pub id: String,
+    pub post_id: String,
+    pub parent_id: String,
+    pub subreddit: String,
+    pub post_title: String,
+    pub author: String,
+    pub body: String,
+    pub score: i64,
+    pub permalink: String,
+    pub source: String,
+    pub created_at: i64,
+    pub run_id: i64,
+}
+
+// Posts and comments are read a page at a time, ordered by rowid, so the
+// first call passes 0 and the next ones the rowid returned last
+impl DB {
+    pub fn max_run_id(&self) -> RusqliteResult<i64> {
+        self.conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM runs", [], |row| {
+                row.get(0)
+            })
+    }
+
+    // Posts stored by the runs after `after` up to `upto`, with their rowid
+    pub fn get_post_documents(
+        &self,
+        after: i64,
+        upto: i64,
+        from_rowid: i64,
+        limit: usize,
+    ) -> RusqliteResult<Vec<(i64, PostDocument)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, subreddit, title, selftext_clean, url, permalink, relevance, intent,
+                    is_question, source, timestamp, run_id
+             FROM reddit_posts
+             WHERE run_id > ?1 AND run_id <= ?2 AND id > ?3
+             ORDER BY id
+             LIMIT ?4",
+        )?;
+        stmt.query_map(params![after, upto, from_rowid, limit as i64], |row| {
+            let rowid: i64 = row.get(0)?;
+            Ok((
+                rowid,
+                PostDocument {
+                    id: DB::encode_reddit_id(rowid),
+                    subreddit: row.get(1)?,
+                    title: row.get(2)?,
+                    text: row.get(3)?,
+                    url: row.get(4)?,
+                    permalink: row.get(5)?,
+                    relevance: row.get(6)?,
+                    intent: row.get(7)?,
+                    is_question: row.get(8)?,
+                    source: row.get(9)?,
+                    created_at: row.get(10)?,
+                    run_id: row.get(11)?,
+                },
+            ))
+        })?
+        .collect()
+    }
+
+    pub fn get_comment_documents(
+        &self,
+        after: i64,
+        upto: i64,
+        from_rowid: i64,
+        limit: usize,
+    ) -> RusqliteResult<Vec<(i64, CommentDocument)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rowid, id, post_id, parent_id, subreddit, post_title, author, body_clean,
+                    score, permalink, source, timestamp, run_id
+             FROM reddit_comments
+             WHERE run_id > ?1 AND run_id <= ?2 AND rowid > ?3
+             ORDER BY rowid
+             LIMIT ?4",
+        )?;
+        stmt.query_map(params![after, upto, from_rowid, limit as i64], |row| {
+            Ok((
+                row.get(0)?,
+                CommentDocument {
+                    id: row.get(1)?,
+                    post_id: row.get(2)?,
+                    parent_id: row.get(3)?,
+                    subreddit: row.get(4)?,
+                    post_title: row.get(5)?,
+                    author: row.get(6)?,
+                    body: row.get(7)?,
+                    score: row.get(8)?,
+                    permalink: row.get(9)?,
+                    source: row.get(10)?,
+                    created_at: row.get(11)?,
+                    run_id: row.get(12)?,
+                },
+            ))
+        })?
+        .collect()
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+
+// Kinds of documents in the vector index
+pub const EMBED_POST: &str = "post";
+pub const EMBED_COMMENT: &str = "comment";
+
+// One embedded document: a post (decoded id) or a comment (base36 id)
+#[derive(Debug, Clone)]
+pub struct Embedding {
+    pub kind: String,
+    pub item_id: String,
+    pub content_hash: String,
+    pub vector: Vec<f32>,
+}
+
+impl DB {
+    pub fn create_embeddings_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS embeddings (
+                kind TEXT NOT NULL,
+                item_id TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                vector BLOB NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (kind, item_id)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    // Hash of the text each stored vector was computed from, to skip unchanged documents
+    pub fn get_embedding_hashes(&self, kind: &str) -> RusqliteResult<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT item_id, content_hash FROM embeddings WHERE kind = ?1")?;
+        stmt.query_map([kind], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    }
+
+    pub fn store_embeddings(&self, embeddings: &[Embedding]) -> RusqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO embeddings (kind, item_id, content_hash, vector, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let now = Utc::now().timestamp();
+            for embedding in embeddings {
+                let bytes: Vec<u8> = embedding
+                    .vector
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect();
+                stmt.execute(params![
+                    embedding.kind,
+                    embedding.item_id,
+                    embedding.content_hash,
+                    bytes,
+                    now
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    pub fn get_embeddings(&self, kind: &str) -> RusqliteResult<Vec<Embedding>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, item_id, content_hash, vector FROM embeddings WHERE kind = ?1",
+        )?;
+        stmt.query_map([kind], |row| {
+            let bytes: Vec<u8> = row.get(3)?;
+            Ok(Embedding {
+                kind: row.get(0)?,
+                item_id: row.get(1)?,
+                content_hash: row.get(2)?,
+                vector: bytes
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect(),
+            })
+        })?
+        .collect()
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::Utc;
+use rusqlite::{OptionalExtension, Result as RusqliteResult, params};
+use serde::{Deserialize, Serialize};
+
+use crate::database::adding::DB;
+use crate::settings::redact::redact;
+
+// Give up on a job after this many attempts
+pub const MAX_ATTEMPTS: i64 = 5;
+
+// Failed work waiting to be retried by `ruddit watch`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct JobDataWrapper {
+    pub id: i64,
+    pub kind: String,
+    pub payload: String,
+    pub attempts: i64,
+    pub next_run_at: i64,
+    pub last_error: String,
+    pub status: String,
+}
+
+impl DB {
+    pub fn create_jobs_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                payload TEXT NOT NULL DEFAULT '',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_run_at INTEGER NOT NULL,
+                last_error TEXT NOT NULL DEFAULT '',
+                status TEXT NOT NULL DEFAULT 'pending',
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    // Queue a failed piece of work, unless the same job is already pending
+    pub fn enqueue_job(&self, kind: &str, payload: &str, error: &str) -> RusqliteResult<()> {
+        self.create_jobs_table()?;
+
+        let pending: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM jobs WHERE kind = ?1 AND payload = ?2 AND status = 'pending'",
+                params![kind, payload],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if pending.is_some() {
+            return Ok(());
+        }
+
+        let now = Utc::now().timestamp();
+        self.conn.execute(
+            "INSERT INTO jobs (kind, payload, attempts, next_run_at, last_error, status, created_at)
+             VALUES (?1, ?2, 1, ?3, ?4, 'pending', ?5)",
+            params![kind, payload, now + backoff_seconds(1), redact(error), now],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_due_jobs(&self) -> RusqliteResult<Vec<JobDataWrapper>> {
+        self.create_jobs_table()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, kind, payload, attempts, next_run_at, last_error, status
+             FROM jobs
+             WHERE status = 'pending' AND next_run_at <= ?1
+             ORDER BY next_run_at ASC",
+        )?;
+
+        let jobs = stmt
+            .query_map([Utc::now().timestamp()], |row| {
+                Ok(JobDataWrapper {
+                    id: row.get(0)?,
+                    kind: row.get(1)?,
+                    payload: row.get(2)?,
+                    attempts: row.get(3)?,
+                    next_run_at: row.get(4)?,
+                    last_error: row.get(5)?,
+                    status: row.get(6)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        Ok(jobs)
+    }
+
+    pub fn complete_job(&self, id: i64) -> RusqliteResult<()> {
+        self.conn
+            .execute("UPDATE jobs SET status = 'done' WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    // Schedule the next attempt with exponential backoff, or give up
+    pub fn retry_job(&self, job: &JobDataWrapper, error: &str) -> RusqliteResult<()> {
+        let attempts = job.attempts + 1;
+        let status = if attempts >= MAX_ATTEMPTS {
+            "failed"
+        } else {
+            "pending"
+        };
+
+        self.conn.execute(
+            "UPDATE jobs SET attempts = ?1, next_run_at = ?2, last_error = ?3, status = ?4
+             WHERE id = ?5",
+            params![
+                attempts,
+                Utc::now().timestamp() + backoff_seconds(attempts),
+                redact(error),
+                status,
+                job.id
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+// 2, 4, 8, 16... minutes, capped at an hour
+fn backoff_seconds(attempts: i64) -> i64 {
+    (60 * 2_i64.pow(attempts.clamp(1, 6) as u32)).min(3600)
+}
+
+// Payload of a queued comment fetch
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommentJob {
+    pub post_id: String,
+    pub subreddit: String,
+    pub post_title: String,
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use rusqlite::{Result as RusqliteResult, params};
+use serde::Serialize;
+
+use crate::database::adding::DB;
+use crate::filters::keywords::KeywordMatcher;
+use crate::settings::api_keys::ConfigDirs;
+
+// How often one configured keyword matched over the last runs
+#[derive(Debug, Clone, Serialize)]
+pub struct KeywordStat {
+    // Keyword group, empty for LEAD_KEYWORDS
+    pub group: String,
+    pub keyword: String,
+    pub posts: i64,
+    pub comments: i64,
+    // Runs in which the keyword matched at least once
+    pub runs_hit: i64,
+}
+
+// Keyword hits of the last runs and the rows those runs stored
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KeywordStats {
+    pub runs: i64,
+    pub posts: i64,
+    pub comments: i64,
+    pub keywords: Vec<KeywordStat>,
+}
+
+impl DB {
+    pub fn create_keyword_hits_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS keyword_hits (
+                run_id INTEGER NOT NULL,
+                group_name TEXT NOT NULL,
+                keyword TEXT NOT NULL,
+                posts INTEGER NOT NULL DEFAULT 0,
+                comments INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (run_id, group_name, keyword)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    // Count, for every term of LEAD_KEYWORDS and the keyword groups, the posts
+    // and comments of the run it appears in. Keywords without a hit are stored
+    // too, so dead ones show up in the stats.
+    pub fn record_keyword_hits(&self, run_id: i64) -> RusqliteResult<()> {
+        let Ok(config) = ConfigDirs::read_config() else {
+            return Ok(());
+        };
+        let mut matchers: Vec<(String, KeywordMatcher)> = Vec::new();
+        if let Ok(matcher) = KeywordMatcher::from_config(&config) {
+            matchers.push((String::new(), matcher));
+        }
+        for group in &config.keyword_groups {
+            if let Ok(matcher) = KeywordMatcher::from_group(group, &config.matching) {
+                matchers.push((group.name.clone(), matcher));
+            }
+        }
+
+        // (group, keyword) -> (posts, comments)
+        let mut hits: BTreeMap<(String, String), (i64, i64)> = BTreeMap::new();
+        for (group, matcher) in &matchers {
+            for term in matcher.terms() {
+                hits.entry((group.clone(), term.to_string())).or_default();
+            }
+        }
+        if hits.is_empty() {
+            return Ok(());
+        }
+
+        let texts = |sql: &str| -> RusqliteResult<Vec<String>> {
+            let mut stmt = self.conn.prepare(sql)?;
+            stmt.query_map([run_id], |row| row.get(0))?.collect()
+        };
+        let posts = texts("SELECT title || ' ' || selftext FROM reddit_posts WHERE run_id = ?1")?;
+        let comments = texts("SELECT body FROM reddit_comments WHERE run_id = ?1")?;
+
+        for (group, matcher) in &matchers {
+            let mut count = |text: &str, is_post: bool| {
+                let mut terms = matcher.terms_in(text);
+                terms.sort_unstable();
+                terms.dedup();
+                for term in terms {
+                    if let Some((posts, comments)) =
+                        hits.get_mut(&(group.clone(), term.to_string()))
+                    {
+                        if is_post {
+                            *posts += 1;
+                        } else {
+                            *comments += 1;
+                        }
+                    }
+                }
+            };
+            posts.iter().for_each(|text| count(text, true));
+            comments.iter().for_each(|text| count(text, false));
+        }
+
+        self.create_keyword_hits_table()?;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM keyword_hits WHERE run_id = ?1", [run_id])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO keyword_hits (run_id, group_name, keyword, posts, comments)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for ((group, keyword), (posts, comments)) in &hits {
+                stmt.execute(params![run_id, group, keyword, posts, comments])?;
+            }
+        }
+        tx.commit()
+    }
+
+    // Title and stored text of the leads with this review status, the text is
+    // empty when the lead's post is not stored
+    pub fn get_lead_texts(&self, status: &str) -> RusqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.title || ' ' || COALESCE(
+                 (SELECT p.selftext_clean FROM reddit_posts p
+                  WHERE p.url = l.url OR p.permalink = l.url
+                  LIMIT 1), '')
+             FROM leads l
+             WHERE l.review_status = ?1",
+        )?;
+        stmt.query_map([status], |row| row.get(0))?.collect()
+    }
+
+    // Title and text of every stored post outside the noise quarantine
+    pub fn get_post_texts(&self) -> RusqliteResult<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT title || ' ' || selftext_clean FROM reddit_posts WHERE noise = ''")?;
+        stmt.query_map([], |row| row.get(0))?.collect()
+    }
+
+    // Hits summed over the last `runs` runs that recorded any, most hits first
+    pub fn get_keyword_stats(&self, runs: usize) -> RusqliteResult<KeywordStats> {
+        self.create_keyword_hits_table()?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT run_id FROM keyword_hits ORDER BY run_id DESC LIMIT ?1")?;
+        let run_ids: Vec<i64> = stmt
+            .query_map([runs as i64], |row| row.get(0))?
+            .collect::<RusqliteResult<_>>()?;
+        if run_ids.is_empty() {
+            return Ok(KeywordStats::default());
+        }
+        let oldest = run_ids[run_ids.len() - 1];
+
+        let count = |table: &str| -> RusqliteResult<i64> {
+            self.conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM {} WHERE run_id IN (SELECT DISTINCT run_id FROM keyword_hits WHERE run_id >= ?1)",
+                    table
+                ),
+                [oldest],
+                |row| row.get(0),
+            )
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT group_name, keyword, SUM(posts), SUM(comments),
+                    SUM(CASE WHEN posts + comments > 0 THEN 1 ELSE 0 END)
+             FROM keyword_hits
+             WHERE run_id >= ?1
+             GROUP BY group_name, keyword
+             ORDER BY SUM(posts) + SUM(comments) DESC, group_name, keyword",
+        )?;
+        let keywords = stmt
+            .query_map([oldest], |row| {
+                Ok(KeywordStat {
+                    group: row.get(0)?,
+                    keyword: row.get(1)?,
+                    posts: row.get(2)?,
+                    comments: row.get(3)?,
+                    runs_hit: row.get(4)?,
+                })
+            })?
+            .collect::<RusqliteResult<_>>()?;
+
+        Ok(KeywordStats {
+            runs: run_ids.len() as i64,
+            posts: count("reddit_posts")?,
+            comments: count("reddit_comments")?,
+            keywords,
+        })
+    }
+}
@@ -0,0 +1,703 @@
+use chrono::Utc;
+use rusqlite::{Result as RusqliteResult, params};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::adding::{DB, PostDataWrapper};
+
+// Values of the review_status column
+pub const REVIEW_PENDING: &str = "pending";
+pub const REVIEW_ACCEPTED: &str = "accepted";
+pub const REVIEW_REJECTED: &str = "rejected";
+
+// Lead produced by the Gemini lead analysis
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LeadDataWrapper {
+    pub id: i64,
+    pub title: String,
+    pub url: String,
+    pub formatted_date: String,
+    pub relevance: String,
+    pub subreddit: String,
+    pub sentiment: String,
+    pub engagement_score: String,
+    pub comment_sentiment: String,
+    pub created_at: i64,
+    pub review_status: String,
+    pub reviewed_at: Option<i64>,
+    // Keyword group the lead was generated for, empty for LEAD_KEYWORDS
+    #[serde(default)]
+    pub keyword_group: String,
+    // When the lead entered its current review status
+    #[serde(default)]
+    pub status_since: i64,
+    // When someone reached out to the poster, None until then
+    #[serde(default)]
+    pub contacted_at: Option<i64>,
+    // Team member handling the lead, empty when unassigned
+    #[serde(default)]
+    pub assignee: String,
+}
+
+// Review decision as written by `ruddit leads dump`, tied to its lead by URL
+// since ids differ between databases
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FeedbackRecord {
+    pub lead_url: Option<String>,
+    pub title: String,
+    pub subreddit: String,
+    pub relevance: String,
+    pub decision: String,
+    pub created_at: i64,
+}
+
+// The lead pipeline state, independent of the scraped posts and comments
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LeadDump {
+    pub version: u32,
+    pub dumped_at: i64,
+    pub leads: Vec<LeadDataWrapper>,
+    #[serde(default)]
+    pub feedback: Vec<FeedbackRecord>,
+    #[serde(default)]
+    pub comment_leads: Vec<CommentLeadDataWrapper>,
+}
+
+pub const LEAD_DUMP_VERSION: u32 = 1;
+
+// Rows `load_leads` added or updated
+#[derive(Debug, Default)]
+pub struct LoadedLeads {
+    pub leads_added: usize,
+    pub leads_updated: usize,
+    pub feedback: usize,
+    pub comment_leads: usize,
+}
+
+// How many leads are in a review status and for how long, on average
+#[derive(Debug, Serialize, Clone)]
+pub struct LeadStatusAge {
+    pub status: String,
+    pub leads: i64,
+    pub average_days: f64,
+    pub oldest_days: f64,
+}
+
+// A single comment flagged as a lead, wherever the thread it sits in is about
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommentLeadDataWrapper {
+    pub comment_id: String,
+    pub post_id: String,
+    pub post_title: String,
+    pub subreddit: String,
+    pub author: String,
+    pub body: String,
+    pub score: i32,
+    pub permalink: String,
+    pub formatted_date: String,
+    pub relevance: String,
+    pub intent: String,
+    pub created_at: i64,
+    pub keyword_group: String,
+    // Keyword that matched the comment and whether exactly, by stem or fuzzily
+    pub matched_term: String,
+    pub match_type: String,
+}
+
+// A review decision, kept even if the lead itself is later re-analysed or cleared
+#[derive(Debug, Serialize, Clone)]
+pub struct LeadFeedback {
+    pub lead_id: i64,
+    pub title: String,
+    pub subreddit: String,
+    pub relevance: String,
+    pub decision: String,
+    pub created_at: i64,
+}
+
+impl LeadDataWrapper {
+    // Build a lead from one object of the Gemini JSON response
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let obj = value.as_object()?;
+        let field = |name: &str| {
+            obj.get(name)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let url = field("url");
+        if url.is_empty() {
+            return None;
+        }
+
+        let created_at = Utc::now().timestamp();
+        Some(LeadDataWrapper {
+            id: 0,
+            title: field("title"),
+            url,
+            formatted_date: field("formatted_date"),
+            relevance: field("relevance").to_uppercase(),
+            subreddit: field("subreddit"),
+            sentiment: field("sentiment"),
+            engagement_score: field("engagement_score"),
+            comment_sentiment: field("comment_sentiment"),
+            created_at,
+            review_status: REVIEW_PENDING.to_string(),
+            reviewed_at: None,
+            keyword_group: String::new(),
+            status_since: created_at,
+            contacted_at: None,
+            assignee: String::new(),
+        })
+    }
+}
+
+impl DB {
+    pub fn create_leads_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS leads (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL UNIQUE,
+                formatted_date TEXT NOT NULL DEFAULT '',
+                relevance TEXT NOT NULL DEFAULT '',
+                subreddit TEXT NOT NULL DEFAULT '',
+                sentiment TEXT NOT NULL DEFAULT '',
+                engagement_score TEXT NOT NULL DEFAULT '',
+                comment_sentiment TEXT NOT NULL DEFAULT '',
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Databases created before leads could be reviewed
+        self.add_column_if_missing("leads", "review_status", "TEXT NOT NULL DEFAULT 'pending'")?;
+        self.add_column_if_missing("leads", "reviewed_at", "INTEGER")?;
+
+        // Databases created before run tracking
+        self.add_column_if_missing("leads", "run_id", "INTEGER NOT NULL DEFAULT 0")?;
+
+        // Databases created before keyword groups
+        self.add_column_if_missing("leads", "keyword_group", "TEXT NOT NULL DEFAULT ''")?;
+
+        // Databases created before lead aging, a lead has been in its status
+        // since its last review or since it was found
+        if self.add_column_if_missing("leads", "status_since", "INTEGER NOT NULL DEFAULT 0")? {
+            self.conn.execute(
+                "UPDATE leads SET status_since = COALESCE(reviewed_at, created_at)",
+                [],
+            )?;
+        }
+        self.add_column_if_missing("leads", "contacted_at", "INTEGER")?;
+
+        // Databases created before leads could be assigned
+        self.add_column_if_missing("leads", "assignee", "TEXT NOT NULL DEFAULT ''")?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS comment_leads (
+                comment_id TEXT PRIMARY KEY,
+                post_id TEXT NOT NULL,
+                post_title TEXT NOT NULL DEFAULT '',
+                subreddit TEXT NOT NULL DEFAULT '',
+                author TEXT NOT NULL DEFAULT '',
+                body TEXT NOT NULL,
+                score INTEGER NOT NULL DEFAULT 0,
+                permalink TEXT NOT NULL DEFAULT '',
+                formatted_date TEXT NOT NULL DEFAULT '',
+                relevance TEXT NOT NULL DEFAULT '',
+                intent TEXT NOT NULL DEFAULT '',
+                created_at INTEGER NOT NULL,
+                run_id INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+        self.add_column_if_missing("comment_leads", "run_id", "INTEGER NOT NULL DEFAULT 0")?;
+        self.add_column_if_missing("comment_leads", "keyword_group", "TEXT NOT NULL DEFAULT ''")?;
+
+        // Databases created before stemming and fuzzy matching
+        self.add_column_if_missing("comment_leads", "matched_term", "TEXT NOT NULL DEFAULT ''")?;
+        self.add_column_if_missing("comment_leads", "match_type", "TEXT NOT NULL DEFAULT ''")?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS lead_feedback (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                lead_id INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                subreddit TEXT NOT NULL DEFAULT '',
+                relevance TEXT NOT NULL DEFAULT '',
+                decision TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    // Leads are keyed by URL, re-analysed posts get their assessment refreshed
+    // and the run that produced it, but keep the decision made during review
+    pub fn append_leads(&mut self, leads: &[LeadDataWrapper]) -> RusqliteResult<()> {
+        let run_id = self.current_run()?;
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO leads
+                (title, url, formatted_date, relevance, subreddit, sentiment, engagement_score, comment_sentiment, created_at, run_id, keyword_group, status_since)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?9)
+                ON CONFLICT(url) DO UPDATE SET
+                    relevance = excluded.relevance,
+                    sentiment = excluded.sentiment,
+                    engagement_score = excluded.engagement_score,
+                    comment_sentiment = excluded.comment_sentiment,
+                    run_id = excluded.run_id,
+                    keyword_group = excluded.keyword_group",
+            )?;
+
+            for lead in leads {
+                stmt.execute(params![
+                    lead.title,
+                    lead.url,
+                    lead.formatted_date,
+                    lead.relevance,
+                    lead.subreddit,
+                    lead.sentiment,
+                    lead.engagement_score,
+                    lead.comment_sentiment,
+                    lead.created_at,
+                    run_id,
+                    lead.keyword_group
+                ])?;
+                if run_id > 0 {
+                    tx.prepare_cached(
+                        "INSERT OR REPLACE INTO run_leads (run_id, url, relevance, sentiment)
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )?
+                    .execute(params![
+                        run_id,
+                        lead.url,
+                        lead.relevance,
+                        lead.sentiment
+                    ])?;
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Leads stored or refreshed by a run
+    pub fn count_run_leads(&self, run_id: i64) -> RusqliteResult<usize> {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM leads WHERE run_id = ?1",
+            [run_id],
+            |row| row.get(0),
+        )
+    }
+
+    pub fn get_leads(&self) -> RusqliteResult<Vec<LeadDataWrapper>> {
+        self.query_leads("", [])
+    }
+
+    // Leads with the given review status, e.g. only the approved ones for exports
+    pub fn get_leads_with_status(&self, status: &str) -> RusqliteResult<Vec<LeadDataWrapper>> {
+        self.query_leads("WHERE review_status = ?1", [status])
+    }
+
+    // Leads generated by the runs after `after` up to `upto`
+    pub fn get_leads_in_runs(&self, after: i64, upto: i64) -> RusqliteResult<Vec<LeadDataWrapper>> {
+        self.query_leads("WHERE run_id > ?1 AND run_id <= ?2", [after, upto])
+    }
+
+    fn query_leads<P: rusqlite::Params>(
+        &self,
+        clause: &str,
+        params: P,
+    ) -> RusqliteResult<Vec<LeadDataWrapper>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, title, url, formatted_date, relevance, subreddit, sentiment, engagement_score, comment_sentiment, created_at, review_status, reviewed_at, keyword_group, status_since, contacted_at, assignee
+             FROM leads
+             {}
+             ORDER BY created_at DESC, id DESC",
+            clause
+        ))?;
+
+        let leads = stmt
+            .query_map(params, |row| {
+                Ok(LeadDataWrapper {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    url: row.get(2)?,
+                    formatted_date: row.get(3)?,
+                    relevance: row.get(4)?,
+                    subreddit: row.get(5)?,
+                    sentiment: row.get(6)?,
+                    engagement_score: row.get(7)?,
+                    comment_sentiment: row.get(8)?,
+                    created_at: row.get(9)?,
+                    review_status: row.get(10)?,
+                    reviewed_at: row.get(11)?,
+                    keyword_group: row.get(12)?,
+                    status_since: row.get(13)?,
+                    contacted_at: row.get(14)?,
+                    assignee: row.get(15)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        Ok(leads)
+    }
+
+    // Record a review decision, optionally correcting the relevance Gemini assigned
+    // A stored post marked as a lead by hand, accepted right away. A post that
+    // already was a lead keeps its assessment apart from the relevance.
+    pub fn mark_post_as_lead(
+        &self,
+        post: &PostDataWrapper,
+        url: &str,
+        relevance: &str,
+    ) -> RusqliteResult<i64> {
+        let now = Utc::now().timestamp();
+        self.conn.execute(
+            "INSERT OR IGNORE INTO leads
+             (title, url, formatted_date, relevance, subreddit, created_at, run_id, status_since)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?6)",
+            params![
+                post.title,
+                url,
+                post.formatted_date,
+                relevance,
+                post.subreddit,
+                now,
+                self.current_run()?
+            ],
+        )?;
+        let id = self
+            .conn
+            .query_row("SELECT id FROM leads WHERE url = ?1", [url], |row| {
+                row.get(0)
+            })?;
+        self.review_lead(id, REVIEW_ACCEPTED, Some(relevance))?;
+        Ok(id)
+    }
+
+    pub fn review_lead(
+        &self,
+        id: i64,
+        status: &str,
+        relevance: Option<&str>,
+    ) -> RusqliteResult<()> {
+        self.conn.execute(
+            "UPDATE leads
+             SET status_since = CASE WHEN review_status = ?2 THEN status_since ELSE ?3 END,
+                 review_status = ?2,
+                 reviewed_at = ?3,
+                 relevance = COALESCE(?4, relevance)
+             WHERE id = ?1",
+            params![id, status, Utc::now().timestamp(), relevance],
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO lead_feedback (lead_id, title, subreddit, relevance, decision, created_at)
+             SELECT id, title, subreddit, relevance, review_status, reviewed_at FROM leads WHERE id = ?1",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    // Leads nobody has contacted that have been pending or accepted since
+    // before `cutoff`, longest waiting first
+    pub fn get_stale_leads(&self, cutoff: i64) -> RusqliteResult<Vec<LeadDataWrapper>> {
+        let mut leads = self.query_leads(
+            "WHERE review_status != ?1 AND contacted_at IS NULL AND status_since <= ?2",
+            params![REVIEW_REJECTED, cutoff],
+        )?;
+        leads.sort_by_key(|lead| lead.status_since);
+        Ok(leads)
+    }
+
+    // Hand a lead to a team member, an empty name unassigns it. False for an
+    // unknown id.
+    pub fn assign_lead(&self, id: i64, assignee: &str) -> RusqliteResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE leads SET assignee = ?2 WHERE id = ?1",
+            params![id, assignee],
+        )?;
+        Ok(updated > 0)
+    }
+
+    // Record that the poster was reached out to, false for an unknown id
+    pub fn mark_contacted(&self, id: i64) -> RusqliteResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE leads SET contacted_at = ?2 WHERE id = ?1",
+            params![id, Utc::now().timestamp()],
+        )?;
+        Ok(updated > 0)
+    }
+
+    // Age of the leads in each review status as of `now`
+    pub fn get_lead_status_ages(&self, now: i64) -> RusqliteResult<Vec<LeadStatusAge>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT review_status, COUNT(*), AVG(?1 - status_since), MAX(?1 - status_since)
+             FROM leads
+             GROUP BY review_status
+             ORDER BY review_status",
+        )?;
+        stmt.query_map([now], |row| {
+            Ok(LeadStatusAge {
+                status: row.get(0)?,
+                leads: row.get(1)?,
+                average_days: row.get::<_, f64>(2)? / 86_400.0,
+                oldest_days: row.get::<_, f64>(3)? / 86_400.0,
+            })
+        })?
+        .collect()
+    }
+
+    pub fn dump_leads(&self) -> RusqliteResult<LeadDump> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.url, f.title, f.subreddit, f.relevance, f.decision, f.created_at
+             FROM lead_feedback f
+             LEFT JOIN leads l ON l.id = f.lead_id
+             ORDER BY f.id",
+        )?;
+        let feedback = stmt
+            .query_map([], |row| {
+                Ok(FeedbackRecord {
+                    lead_url: row.get(0)?,
+                    title: row.get(1)?,
+                    subreddit: row.get(2)?,
+                    relevance: row.get(3)?,
+                    decision: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        Ok(LeadDump {
+            version: LEAD_DUMP_VERSION,
+            dumped_at: Utc::now().timestamp(),
+            leads: self.get_leads()?,
+            feedback,
+            comment_leads: self.get_comment_leads()?,
+        })
+    }
+
+    // Leads are matched by URL and take over the review state of the dump.
+    // Feedback and comment leads already present are left alone.
+    pub fn load_leads(&mut self, dump: &LeadDump) -> RusqliteResult<LoadedLeads> {
+        let mut loaded = LoadedLeads::default();
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut exists = tx.prepare("SELECT 1 FROM leads WHERE url = ?1")?;
+            let mut upsert = tx.prepare(
+                "INSERT INTO leads
+                (title, url, formatted_date, relevance, subreddit, sentiment, engagement_score, comment_sentiment, created_at, review_status, reviewed_at, keyword_group, status_since, contacted_at, assignee)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                ON CONFLICT(url) DO UPDATE SET
+                    relevance = excluded.relevance,
+                    review_status = excluded.review_status,
+                    reviewed_at = excluded.reviewed_at,
+                    status_since = excluded.status_since,
+                    contacted_at = excluded.contacted_at,
+                    assignee = excluded.assignee",
+            )?;
+            for lead in &dump.leads {
+                if exists.exists([&lead.url])? {
+                    loaded.leads_updated += 1;
+                } else {
+                    loaded.leads_added += 1;
+                }
+                upsert.execute(params![
+                    lead.title,
+                    lead.url,
+                    lead.formatted_date,
+                    lead.relevance,
+                    lead.subreddit,
+                    lead.sentiment,
+                    lead.engagement_score,
+                    lead.comment_sentiment,
+                    lead.created_at,
+                    lead.review_status,
+                    lead.reviewed_at,
+                    lead.keyword_group,
+                    // Dumps of older versions carry no status age
+                    if lead.status_since > 0 {
+                        lead.status_since
+                    } else {
+                        lead.reviewed_at.unwrap_or(lead.created_at)
+                    },
+                    lead.contacted_at,
+                    lead.assignee
+                ])?;
+            }
+
+            // Decisions whose lead was cleared keep lead_id 0
+            let mut insert_feedback = tx.prepare(
+                "INSERT INTO lead_feedback (lead_id, title, subreddit, relevance, decision, created_at)
+                 SELECT COALESCE((SELECT id FROM leads WHERE url = ?1), 0), ?2, ?3, ?4, ?5, ?6
+                 WHERE NOT EXISTS (
+                     SELECT 1 FROM lead_feedback WHERE title = ?2 AND decision = ?5 AND created_at = ?6
+                 )",
+            )?;
+            for record in &dump.feedback {
+                loaded.feedback += insert_feedback.execute(params![
+                    record.lead_url,
+                    record.title,
+                    record.subreddit,
+                    record.relevance,
+                    record.decision,
+                    record.created_at
+                ])?;
+            }
+
+            let mut insert_comment_lead = tx.prepare(
+                "INSERT OR IGNORE INTO comment_leads
+                (comment_id, post_id, post_title, subreddit, author, body, score, permalink, formatted_date, relevance, intent, created_at, keyword_group, matched_term, match_type)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            )?;
+            for lead in &dump.comment_leads {
+                loaded.comment_leads += insert_comment_lead.execute(params![
+                    lead.comment_id,
+                    lead.post_id,
+                    lead.post_title,
+                    lead.subreddit,
+                    lead.author,
+                    lead.body,
+                    lead.score,
+                    lead.permalink,
+                    lead.formatted_date,
+                    lead.relevance,
+                    lead.intent,
+                    lead.created_at,
+                    lead.keyword_group,
+                    lead.matched_term,
+                    lead.match_type
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(loaded)
+    }
+
+    // The most recent decisions of each kind, used as examples in the lead prompt
+    pub fn get_lead_feedback_sample(
+        &self,
+        per_decision: usize,
+    ) -> RusqliteResult<Vec<LeadFeedback>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT lead_id, title, subreddit, relevance, decision, created_at
+             FROM lead_feedback
+             WHERE decision = ?1
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?2",
+        )?;
+
+        let mut sample = Vec::new();
+        for decision in [REVIEW_ACCEPTED, REVIEW_REJECTED] {
+            let rows = stmt.query_map(params![decision, per_decision as i64], |row| {
+                Ok(LeadFeedback {
+                    lead_id: row.get(0)?,
+                    title: row.get(1)?,
+                    subreddit: row.get(2)?,
+                    relevance: row.get(3)?,
+                    decision: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?;
+            sample.extend(rows.collect::<RusqliteResult<Vec<_>>>()?);
+        }
+
+        Ok(sample)
+    }
+
+    // Share of accepted decisions among the latest `window` ones (all when None),
+    // as (accepted, reviewed)
+    pub fn get_lead_precision(&self, window: Option<usize>) -> RusqliteResult<(i64, i64)> {
+        self.conn.query_row(
+            "SELECT COALESCE(SUM(decision = ?1), 0), COUNT(*)
+             FROM (SELECT decision FROM lead_feedback ORDER BY created_at DESC, id DESC LIMIT ?2)",
+            params![REVIEW_ACCEPTED, window.map_or(-1, |w| w as i64)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+
+    // Comment leads are keyed by comment, a re-run refreshes the assessment
+    pub fn append_comment_leads(&mut self, leads: &[CommentLeadDataWrapper]) -> RusqliteResult<()> {
+        let run_id = self.current_run()?;
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO comment_leads
+                (comment_id, post_id, post_title, subreddit, author, body, score, permalink, formatted_date, relevance, intent, created_at, run_id, keyword_group, matched_term, match_type)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+                ON CONFLICT(comment_id) DO UPDATE SET
+                    score = excluded.score,
+                    relevance = excluded.relevance,
+                    intent = excluded.intent,
+                    run_id = excluded.run_id,
+                    keyword_group = excluded.keyword_group,
+                    matched_term = excluded.matched_term,
+                    match_type = excluded.match_type",
+            )?;
+
+            for lead in leads {
+                stmt.execute(params![
+                    lead.comment_id,
+                    lead.post_id,
+                    lead.post_title,
+                    lead.subreddit,
+                    lead.author,
+                    lead.body,
+                    lead.score,
+                    lead.permalink,
+                    lead.formatted_date,
+                    lead.relevance,
+                    lead.intent,
+                    lead.created_at,
+                    run_id,
+                    lead.keyword_group,
+                    lead.matched_term,
+                    lead.match_type
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_comment_leads(&self) -> RusqliteResult<Vec<CommentLeadDataWrapper>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT comment_id, post_id, post_title, subreddit, author, body, score, permalink, formatted_date, relevance, intent, created_at, keyword_group, matched_term, match_type
+             FROM comment_leads
+             ORDER BY created_at DESC, score DESC",
+        )?;
+
+        stmt.query_map([], |row| {
+            Ok(CommentLeadDataWrapper {
+                comment_id: row.get(0)?,
+                post_id: row.get(1)?,
+                post_title: row.get(2)?,
+                subreddit: row.get(3)?,
+                author: row.get(4)?,
+                body: row.get(5)?,
+                score: row.get(6)?,
+                permalink: row.get(7)?,
+                formatted_date: row.get(8)?,
+                relevance: row.get(9)?,
+                intent: row.get(10)?,
+                created_at: row.get(11)?,
+                keyword_group: row.get(12)?,
+                matched_term: row.get(13)?,
+                match_type: row.get(14)?,
+            })
+        })?
+        .collect()
+    }
+}
@@ -0,0 +1,47 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Write};
+
+use crate::database::adding::database_path;
+use crate::settings::paths::Paths;
+
+// Advisory lock held by a run while it stores data. A second writing run
+// waits for it instead of failing halfway with "database is locked", and
+// readers are never blocked.
+pub struct WriteLock {
+    _file: File,
+}
+
+impl WriteLock {
+    // `holder` is shown to runs waiting for the lock
+    pub fn acquire(holder: &str) -> Result<WriteLock, Box<dyn std::error::Error>> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(Paths::lock_file(&database_path()?))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut current = String::new();
+                let _ = file.read_to_string(&mut current);
+                eprintln!(
+                    "Waiting for another ruddit run to finish ({})",
+                    if current.trim().is_empty() {
+                        "unknown"
+                    } else {
+                        current.trim()
+                    }
+                );
+                file.lock()?;
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        // Released when the file is closed, also when the process dies
+        file.set_len(0)?;
+        write!(file, "pid {}: {}", std::process::id(), holder)?;
+        Ok(WriteLock { _file: file })
+    }
+}
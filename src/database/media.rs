@@ -0,0 +1,80 @@
+use rusqlite::{Result as RusqliteResult, params};
+use serde::{Deserialize, Serialize};
+
+use crate::database::adding::DB;
+
+// Media attached to a post (images, gallery items, videos)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MediaDataWrapper {
+    pub post_id: String,
+    pub position: i64,
+    pub post_hint: String,
+    pub url: String,
+    pub thumbnail: String,
+}
+
+impl DB {
+    pub fn create_media_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS post_media (
+                post_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                post_hint TEXT NOT NULL DEFAULT '',
+                url TEXT NOT NULL,
+                thumbnail TEXT NOT NULL DEFAULT '',
+                PRIMARY KEY (post_id, position)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn append_media(&mut self, media: &[MediaDataWrapper]) -> RusqliteResult<()> {
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO post_media
+                (post_id, position, post_hint, url, thumbnail)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+
+            for item in media {
+                stmt.execute(params![
+                    item.post_id,
+                    item.position,
+                    item.post_hint,
+                    item.url,
+                    item.thumbnail
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_post_media(&self, post_id: &str) -> RusqliteResult<Vec<MediaDataWrapper>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT post_id, position, post_hint, url, thumbnail
+             FROM post_media
+             WHERE post_id = ?1
+             ORDER BY position ASC",
+        )?;
+
+        let media = stmt
+            .query_map([post_id], |row| {
+                Ok(MediaDataWrapper {
+                    post_id: row.get(0)?,
+                    position: row.get(1)?,
+                    post_hint: row.get(2)?,
+                    url: row.get(3)?,
+                    thumbnail: row.get(4)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        Ok(media)
+    }
+}
@@ -0,0 +1,285 @@
+use std::path::Path;
+
+use rusqlite::{OptionalExtension, Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+
+// Tables copied row by row, rows already present (by their key) are kept
+const COPIED_TABLES: [&str; 5] = [
+    "reddit_posts",
+    "reddit_comments",
+    "post_media",
+    "search_hits",
+    "comment_leads",
+];
+
+// A lead reviewed differently in both databases
+#[derive(Debug, Clone)]
+pub struct LeadConflict {
+    pub url: String,
+    pub title: String,
+    pub ours: String,
+    pub theirs: String,
+    // The status kept, the most recently reviewed one
+    pub kept: String,
+}
+
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    // Rows added per table
+    pub copied: Vec<(String, usize)>,
+    pub leads_added: usize,
+    pub leads_updated: usize,
+    pub feedback: usize,
+    pub conflicts: Vec<LeadConflict>,
+}
+
+// Review state of a lead in one of the two databases
+struct LeadState {
+    title: String,
+    status: String,
+    reviewed_at: Option<i64>,
+    status_since: i64,
+    contacted_at: Option<i64>,
+    assignee: String,
+}
+
+impl LeadState {
+    // When the state was last decided
+    fn updated_at(&self) -> i64 {
+        self.reviewed_at.unwrap_or(0).max(self.status_since)
+    }
+}
+
+impl DB {
+    // Copy the posts, comments and leads of another ruddit database into this
+    // one. The other file is opened read-only and may be from an older version:
+    // only the columns both sides have are copied.
+    pub fn merge_database(&mut self, path: &Path) -> RusqliteResult<MergeReport> {
+        let uri = format!("file:{}?mode=ro", path.display());
+        self.conn
+            .execute("ATTACH DATABASE ?1 AS other", params![uri])?;
+        let report = self.merge_attached();
+        self.conn.execute("DETACH DATABASE other", [])?;
+        report
+    }
+
+    fn merge_attached(&mut self) -> RusqliteResult<MergeReport> {
+        let mut report = MergeReport::default();
+        // Merged rows belong to the merge run, the run ids of the other
+        // database mean nothing here
+        let run_id = self.current_run()?;
+
+        for table in COPIED_TABLES {
+            let columns = self.shared_columns(table)?;
+            if columns.is_empty() {
+                continue;
+            }
+            let copied = self.conn.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO main.{0} ({1}) SELECT {2} FROM other.{0}",
+                    table,
+                    columns.join(", "),
+                    select_list(&columns, run_id)
+                ),
+                [],
+            )?;
+            report.copied.push((table.to_string(), copied));
+        }
+
+        self.merge_leads(run_id, &mut report)?;
+        Ok(report)
+    }
+
+    // Columns of `table` present in both databases, plus run_id which is
+    // filled in here. Empty when the other database does not have the table.
+    fn shared_columns(&self, table: &str) -> RusqliteResult<Vec<String>> {
+        let columns = |schema: &str| -> RusqliteResult<Vec<String>> {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT name FROM pragma_table_info('{}', '{}')",
+                table, schema
+            ))?;
+            stmt.query_map([], |row| row.get(0))?.collect()
+        };
+        let theirs = columns("other")?;
+        if theirs.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(columns("main")?
+            .into_iter()
+            .filter(|column| column == "run_id" || theirs.contains(column))
+            .collect())
+    }
+
+    // New leads are added, leads in both take the most recently reviewed state
+    fn merge_leads(&mut self, run_id: i64, report: &mut MergeReport) -> RusqliteResult<()> {
+        let mut columns = self.shared_columns("leads")?;
+        if columns.is_empty() {
+            return Ok(());
+        }
+        columns.retain(|column| column != "id");
+
+        // Older databases lack the later columns
+        let column = |name: &str, fallback: &str| {
+            if columns.iter().any(|c| c == name) {
+                name.to_string()
+            } else {
+                fallback.to_string()
+            }
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT url, title, {}, {}, {}, {}, {} FROM other.leads",
+            column("review_status", "'pending'"),
+            column("reviewed_at", "NULL"),
+            column("status_since", "created_at"),
+            column("contacted_at", "NULL"),
+            column("assignee", "''"),
+        ))?;
+        let theirs = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    LeadState {
+                        title: row.get(1)?,
+                        status: row.get(2)?,
+                        reviewed_at: row.get(3)?,
+                        status_since: row.get(4)?,
+                        contacted_at: row.get(5)?,
+                        assignee: row.get(6)?,
+                    },
+                ))
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+        drop(stmt);
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut ours = tx.prepare(
+                "SELECT title, review_status, reviewed_at, status_since, contacted_at, assignee
+                 FROM main.leads WHERE url = ?1",
+            )?;
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO main.leads ({}) SELECT {} FROM other.leads WHERE url = ?1",
+                columns.join(", "),
+                select_list(&columns, run_id)
+            ))?;
+            let mut update = tx.prepare(
+                "UPDATE main.leads
+                 SET review_status = ?2, reviewed_at = ?3, status_since = ?4,
+                     contacted_at = ?5, assignee = ?6
+                 WHERE url = ?1",
+            )?;
+
+            for (url, theirs) in &theirs {
+                let ours = ours
+                    .query_row([url], |row| {
+                        Ok(LeadState {
+                            title: row.get(0)?,
+                            status: row.get(1)?,
+                            reviewed_at: row.get(2)?,
+                            status_since: row.get(3)?,
+                            contacted_at: row.get(4)?,
+                            assignee: row.get(5)?,
+                        })
+                    })
+                    .optional()?;
+                let Some(ours) = ours else {
+                    insert.execute([url])?;
+                    report.leads_added += 1;
+                    continue;
+                };
+
+                let newest = if theirs.updated_at() > ours.updated_at() {
+                    theirs
+                } else {
+                    &ours
+                };
+                if ours.reviewed_at.is_some()
+                    && theirs.reviewed_at.is_some()
+                    && ours.status != theirs.status
+                {
+                    report.conflicts.push(LeadConflict {
+                        url: url.clone(),
+                        title: ours.title.clone(),
+                        ours: ours.status.clone(),
+                        theirs: theirs.status.clone(),
+                        kept: newest.status.clone(),
+                    });
+                }
+
+                // Having been contacted is a fact either side may know about
+                let contacted_at = ours.contacted_at.or(theirs.contacted_at);
+                let assignee = if newest.assignee.is_empty() {
+                    &ours.assignee
+                } else {
+                    &newest.assignee
+                };
+                if newest.status != ours.status
+                    || contacted_at != ours.contacted_at
+                    || *assignee != ours.assignee
+                {
+                    update.execute(params![
+                        url,
+                        newest.status,
+                        newest.reviewed_at,
+                        newest.status_since,
+                        contacted_at,
+                        assignee
+                    ])?;
+                    report.leads_updated += 1;
+                }
+            }
+
+            // Leads from databases older than lead aging
+            tx.execute(
+                "UPDATE main.leads SET status_since = COALESCE(reviewed_at, created_at) WHERE status_since = 0",
+                [],
+            )?;
+
+            // Review decisions, tied to the local lead through its URL
+            if !other_lacks_table(&tx, "lead_feedback")? {
+                report.feedback = tx.execute(
+                    "INSERT INTO main.lead_feedback (lead_id, title, subreddit, relevance, decision, created_at)
+                     SELECT COALESCE((SELECT m.id FROM main.leads m JOIN other.leads o ON o.url = m.url WHERE o.id = f.lead_id), 0),
+                            f.title, f.subreddit, f.relevance, f.decision, f.created_at
+                     FROM other.lead_feedback f
+                     WHERE NOT EXISTS (
+                         SELECT 1 FROM main.lead_feedback m
+                         WHERE m.title = f.title AND m.decision = f.decision AND m.created_at = f.created_at
+                     )",
+                    [],
+                )?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+// The columns to select from the other database, with its run ids replaced
+fn select_list(columns: &[String], run_id: i64) -> String {
+    columns
+        .iter()
+        .map(|column| {
+            if column == "run_id" {
+                run_id.to_string()
+            } else {
+                column.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Whether the other database lacks `table`
+fn other_lacks_table(conn: &rusqlite::Connection, table: &str) -> RusqliteResult<bool> {
+    let found: Option<String> = conn
+        .query_row(
+            "SELECT name FROM other.sqlite_master WHERE type = 'table' AND name = ?1",
+            [table],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(found.is_none())
+}
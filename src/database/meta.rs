@@ -0,0 +1,35 @@
+use rusqlite::{OptionalExtension, Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+
+// Small key/value store for values generated once per database
+impl DB {
+    pub fn create_meta_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS app_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_meta(&self, key: &str) -> RusqliteResult<Option<String>> {
+        self.conn
+            .query_row("SELECT value FROM app_meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+    }
+
+    pub fn set_meta(&self, key: &str, value: &str) -> RusqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO app_meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+}
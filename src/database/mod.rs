@@ -1,2 +1,22 @@
+pub mod accounts;
 pub mod adding;
+pub mod ai_cache;
+pub mod alerts;
 pub mod clear;
+pub mod elastic;
+pub mod embeddings;
+pub mod jobs;
+pub mod keywords;
+pub mod leads;
+pub mod lock;
+pub mod media;
+pub mod merge;
+pub mod meta;
+pub mod notes;
+pub mod queries;
+pub mod reports;
+pub mod runs;
+pub mod saved;
+pub mod searches;
+pub mod status;
+pub mod tags;
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+
+// A note as listed by `ruddit notes`
+#[derive(Debug)]
+pub struct Note {
+    // "post" or "comment"
+    pub kind: String,
+    pub id: String,
+    // Title of the post, or of the post the comment is on
+    pub title: String,
+    pub note: String,
+    pub updated_at: i64,
+}
+
+// Free-text triage notes on stored posts and comments, one per item
+impl DB {
+    pub fn create_notes_tables(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS post_notes (
+                post_id INTEGER PRIMARY KEY,
+                note TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS comment_notes (
+                comment_id TEXT PRIMARY KEY,
+                note TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn is_comment_stored(&self, comment_id: &str) -> RusqliteResult<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM reddit_comments WHERE id = ?1)",
+            [comment_id],
+            |row| row.get(0),
+        )
+    }
+
+    // Replaces the note the post had, an empty note removes it
+    pub fn set_post_note(&self, post_id: i64, note: &str) -> RusqliteResult<()> {
+        if note.is_empty() {
+            self.conn
+                .execute("DELETE FROM post_notes WHERE post_id = ?1", [post_id])?;
+            return Ok(());
+        }
+        self.conn.execute(
+            "INSERT INTO post_notes (post_id, note, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(post_id) DO UPDATE SET note = excluded.note, updated_at = excluded.updated_at",
+            params![post_id, note, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_comment_note(&self, comment_id: &str, note: &str) -> RusqliteResult<()> {
+        if note.is_empty() {
+            self.conn.execute(
+                "DELETE FROM comment_notes WHERE comment_id = ?1",
+                [comment_id],
+            )?;
+            return Ok(());
+        }
+        self.conn.execute(
+            "INSERT INTO comment_notes (comment_id, note, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(comment_id) DO UPDATE SET note = excluded.note, updated_at = excluded.updated_at",
+            params![comment_id, note, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_post_notes(&self) -> RusqliteResult<HashMap<i64, String>> {
+        let mut stmt = self.conn.prepare("SELECT post_id, note FROM post_notes")?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    }
+
+    pub fn get_comment_notes(&self) -> RusqliteResult<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT comment_id, note FROM comment_notes")?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    }
+
+    // Every note, most recently edited first
+    pub fn list_notes(&self) -> RusqliteResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT 'post', n.post_id, COALESCE(p.title, ''), n.note, n.updated_at
+             FROM post_notes n
+             LEFT JOIN reddit_posts p ON p.id = n.post_id
+             UNION ALL
+             SELECT 'comment', n.comment_id, COALESCE(c.post_title, ''), n.note, n.updated_at
+             FROM comment_notes n
+             LEFT JOIN reddit_comments c ON c.id = n.comment_id
+             ORDER BY 5 DESC",
+        )?;
+        stmt.query_map([], |row| {
+            let kind: String = row.get(0)?;
+            let id = if kind == "post" {
+                DB::encode_reddit_id(row.get(1)?)
+            } else {
+                row.get(1)?
+            };
+            Ok(Note {
+                kind,
+                id,
+                title: row.get(2)?,
+                note: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect()
+    }
+}
@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use rusqlite::{OptionalExtension, Result as RusqliteResult, ToSql};
+use serde::Serialize;
+
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::filters::keywords::KeywordMatcher;
+use crate::filters::questions::is_question;
+
+// Optional filters applied when reading posts from the database
+#[derive(Debug, Default, Clone)]
+pub struct PostFilter {
+    pub subreddit: Option<String>,
+    pub contains: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    // Only posts tagged as questions
+    pub questions_only: bool,
+    pub intent: Option<String>,
+    // Only rows stored by this run (see the runs table)
+    pub run: Option<i64>,
+    // Only posts whose title or text matches these keywords
+    pub keywords: Option<KeywordMatcher>,
+    // Only comments scoring at least this (comment exports)
+    pub min_score: Option<i32>,
+    // Only quarantined posts instead of leaving them out
+    pub noise: bool,
+    // Only posts found by this saved search
+    pub saved_search: Option<String>,
+    // Only posts tagged with this, see `ruddit tag`
+    pub tag: Option<String>,
+    // Only rows first stored at or after this time, whatever their Reddit date
+    pub stored_since: Option<i64>,
+}
+
+impl PostFilter {
+    // Whether a timestamp falls inside the since/until window
+    pub fn in_window(&self, timestamp: i64) -> bool {
+        self.since.is_none_or(|since| timestamp >= since)
+            && self.until.is_none_or(|until| timestamp < until)
+    }
+
+    // Whether a freshly fetched post passes the window and the question filter
+    pub fn keeps(&self, post: &PostDataWrapper) -> bool {
+        self.in_window(post.timestamp)
+            && (!self.questions_only || is_question(&post.title, &post.selftext))
+    }
+}
+
+impl DB {
+    pub fn get_filtered_posts(&self, filter: &PostFilter) -> RusqliteResult<Vec<PostDataWrapper>> {
+        let mut posts = Vec::new();
+        self.for_each_filtered_post(filter, |post| {
+            posts.push(post);
+            Ok::<_, rusqlite::Error>(())
+        })?;
+        Ok(posts)
+    }
+
+    // Hand the filtered posts to `visit` one at a time, newest first, straight
+    // from the database cursor so large exports never hold every row in memory
+    pub fn for_each_filtered_post<E: From<rusqlite::Error>>(
+        &self,
+        filter: &PostFilter,
+        mut visit: impl FnMut(PostDataWrapper) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut sql = String::from(
+            "SELECT id, timestamp, formatted_date, title, url, relevance, subreddit, permalink, selftext_clean
+             FROM reddit_posts
+             WHERE 1 = 1",
+        );
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(subreddit) = &filter.subreddit {
+            sql.push_str(" AND subreddit = ? COLLATE NOCASE");
+            values.push(Box::new(subreddit.clone()));
+        }
+        if let Some(contains) = &filter.contains {
+            sql.push_str(" AND (title LIKE ? OR selftext LIKE ?)");
+            let pattern = format!("%{}%", contains);
+            values.push(Box::new(pattern.clone()));
+            values.push(Box::new(pattern));
+        }
+        if let Some(since) = filter.since {
+            sql.push_str(" AND timestamp >= ?");
+            values.push(Box::new(since));
+        }
+        if let Some(until) = filter.until {
+            sql.push_str(" AND timestamp < ?");
+            values.push(Box::new(until));
+        }
+
+        if filter.questions_only {
+            sql.push_str(" AND is_question = 1");
+        }
+        if let Some(intent) = &filter.intent {
+            sql.push_str(" AND intent = ?");
+            values.push(Box::new(intent.clone()));
+        }
+        if let Some(run) = filter.run {
+            sql.push_str(" AND run_id = ?");
+            values.push(Box::new(run));
+        }
+        if let Some(stored_since) = filter.stored_since {
+            sql.push_str(" AND first_seen_at >= ?");
+            values.push(Box::new(stored_since));
+        }
+        if let Some(search) = &filter.saved_search {
+            sql.push_str(" AND id IN (SELECT post_id FROM search_hits WHERE search = ?)");
+            values.push(Box::new(search.clone()));
+        }
+        if let Some(tag) = &filter.tag {
+            sql.push_str(" AND id IN (SELECT post_id FROM post_tags WHERE tag = ?)");
+            values.push(Box::new(tag.clone()));
+        }
+        sql.push_str(if filter.noise {
+            " AND noise != ''"
+        } else {
+            " AND noise = ''"
+        });
+
+        sql.push_str(" ORDER BY timestamp DESC");
+        // Keywords are matched after the query, so paging has to wait until then
+        if filter.keywords.is_none() {
+            sql.push_str(&format!(
+                " LIMIT {} OFFSET {}",
+                filter.limit.map(|l| l as i64).unwrap_or(-1),
+                filter.offset.unwrap_or(0)
+            ));
+        }
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params: Vec<&dyn ToSql> = values.iter().map(|v| v.as_ref()).collect();
+
+        let rows = stmt.query_map(params.as_slice(), |row| {
+            Ok(PostDataWrapper {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                formatted_date: row.get(2)?,
+                title: row.get(3)?,
+                url: row.get(4)?,
+                relevance: row.get(5)?,
+                subreddit: row.get(6)?,
+                permalink: row.get(7)?,
+                selftext: row.get(8)?,
+            })
+        })?;
+
+        let Some(matcher) = &filter.keywords else {
+            for post in rows {
+                visit(post?)?;
+            }
+            return Ok(());
+        };
+
+        let mut skip = filter.offset.unwrap_or(0);
+        let mut take = filter.limit.unwrap_or(usize::MAX);
+        for post in rows {
+            let post = post?;
+            if take == 0 {
+                break;
+            }
+            if !matcher.matches(&format!("{}\n{}", post.title, post.selftext)) {
+                continue;
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            take -= 1;
+            visit(post)?;
+        }
+        Ok(())
+    }
+
+    // Stored comments inside the since/until window, optionally for one subreddit
+    pub fn get_filtered_comments(
+        &self,
+        filter: &PostFilter,
+    ) -> RusqliteResult<Vec<CommentDataWrapper>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, post_id, body_clean, author, timestamp, formatted_date, score, permalink, parent_id, subreddit, post_title
+             FROM reddit_comments
+             WHERE (?1 IS NULL OR subreddit = ?1 COLLATE NOCASE)
+               AND (?2 IS NULL OR timestamp >= ?2)
+               AND (?3 IS NULL OR timestamp < ?3)
+               AND (?4 IS NULL OR run_id = ?4)
+               AND (?5 IS NULL OR first_seen_at >= ?5)
+             ORDER BY timestamp DESC",
+        )?;
+
+        stmt.query_map(
+            rusqlite::params![
+                filter.subreddit,
+                filter.since,
+                filter.until,
+                filter.run,
+                filter.stored_since
+            ],
+            |row| {
+                Ok(CommentDataWrapper {
+                    id: row.get(0)?,
+                    post_id: row.get(1)?,
+                    body: row.get(2)?,
+                    author: row.get(3)?,
+                    timestamp: row.get(4)?,
+                    formatted_date: row.get(5)?,
+                    score: row.get(6)?,
+                    permalink: row.get(7)?,
+                    parent_id: row.get(8)?,
+                    subreddit: row.get(9)?,
+                    post_title: row.get(10)?,
+                })
+            },
+        )?
+        .collect()
+    }
+
+    // Stream stored comments across all posts, newest first. The post title
+    // comes from the stored post when there is one, the keyword matcher is
+    // applied to the comment text.
+    pub fn for_each_filtered_comment<E: From<rusqlite::Error>>(
+        &self,
+        filter: &PostFilter,
+        mut visit: impl FnMut(CommentDataWrapper) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.id, c.post_id, c.body_clean, c.author, c.timestamp, c.formatted_date, c.score,
+                    c.permalink, c.parent_id, c.subreddit, COALESCE(NULLIF(p.title, ''), c.post_title)
+             FROM reddit_comments c
+             LEFT JOIN reddit_posts p ON p.id = reddit_id(c.post_id)
+             WHERE (?1 IS NULL OR c.subreddit = ?1 COLLATE NOCASE)
+               AND (?2 IS NULL OR c.timestamp >= ?2)
+               AND (?3 IS NULL OR c.timestamp < ?3)
+               AND (?4 IS NULL OR c.run_id = ?4)
+               AND (?5 IS NULL OR c.score >= ?5)
+               AND (?6 IS NULL OR c.first_seen_at >= ?6)
+             ORDER BY c.timestamp DESC",
+        )?;
+
+        let rows = stmt.query_map(
+            rusqlite::params![
+                filter.subreddit,
+                filter.since,
+                filter.until,
+                filter.run,
+                filter.min_score,
+                filter.stored_since
+            ],
+            |row| {
+                Ok(CommentDataWrapper {
+                    id: row.get(0)?,
+                    post_id: row.get(1)?,
+                    body: row.get(2)?,
+                    author: row.get(3)?,
+                    timestamp: row.get(4)?,
+                    formatted_date: row.get(5)?,
+                    score: row.get(6)?,
+                    permalink: row.get(7)?,
+                    parent_id: row.get(8)?,
+                    subreddit: row.get(9)?,
+                    post_title: row.get(10)?,
+                })
+            },
+        )?;
+
+        let mut take = filter.limit.unwrap_or(usize::MAX);
+        for comment in rows {
+            let comment = comment?;
+            if take == 0 {
+                break;
+            }
+            if filter
+                .keywords
+                .as_ref()
+                .is_some_and(|matcher| !matcher.matches(&comment.body))
+            {
+                continue;
+            }
+            take -= 1;
+            visit(comment)?;
+        }
+        Ok(())
+    }
+
+    // A stored post by its id, quarantined or not
+    pub fn get_post(&self, id: i64) -> RusqliteResult<Option<PostDataWrapper>> {
+        self.conn
+            .query_row(
+                "SELECT id, timestamp, formatted_date, title, url, relevance, subreddit, permalink, selftext_clean
+                 FROM reddit_posts
+                 WHERE id = ?1",
+                [id],
+                |row| {
+                    Ok(PostDataWrapper {
+                        id: row.get(0)?,
+                        timestamp: row.get(1)?,
+                        formatted_date: row.get(2)?,
+                        title: row.get(3)?,
+                        url: row.get(4)?,
+                        relevance: row.get(5)?,
+                        subreddit: row.get(6)?,
+                        permalink: row.get(7)?,
+                        selftext: row.get(8)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    // Intent tagged on each post, keyed by post id
+    pub fn get_post_intents(&self) -> RusqliteResult<HashMap<i64, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, intent FROM reddit_posts WHERE intent != ''")?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    }
+
+    // Why each quarantined post was flagged, by post id
+    pub fn get_noise_reasons(&self) -> RusqliteResult<HashMap<i64, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, noise FROM reddit_posts WHERE noise != ''")?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    }
+
+    // Parse a YYYY-MM-DD date into a UTC epoch timestamp (start of day)
+    pub fn parse_date(date: &str) -> Option<i64> {
+        NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+            .map(|dt| dt.and_utc().timestamp())
+    }
+}
+
+// Row counts for a quick overview of the database
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct DatabaseStats {
+    pub posts: i64,
+    pub comments: i64,
+    pub leads: i64,
+    pub media: i64,
+    pub newest_post: Option<String>,
+    pub posts_per_subreddit: Vec<SubredditCount>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubredditCount {
+    pub subreddit: String,
+    pub posts: i64,
+}
+
+impl DB {
+    pub fn get_stats(&self) -> RusqliteResult<DatabaseStats> {
+        let count = |table: &str| -> RusqliteResult<i64> {
+            self.conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                    row.get(0)
+                })
+        };
+
+        let newest_post = self
+            .conn
+            .query_row(
+                "SELECT formatted_date FROM reddit_posts ORDER BY timestamp DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .ok();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT subreddit, COUNT(*) FROM reddit_posts
+             GROUP BY subreddit
+             ORDER BY COUNT(*) DESC",
+        )?;
+        let posts_per_subreddit = stmt
+            .query_map([], |row| {
+                Ok(SubredditCount {
+                    subreddit: row.get(0)?,
+                    posts: row.get(1)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        Ok(DatabaseStats {
+            posts: count("reddit_posts")?,
+            comments: count("reddit_comments")?,
+            leads: count("leads")?,
+            media: count("post_media")?,
+            newest_post,
+            posts_per_subreddit,
+        })
+    }
+}
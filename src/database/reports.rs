@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use rusqlite::{Result as RusqliteResult, params};
+use serde::Serialize;
+
+use crate::database::adding::DB;
+use crate::database::queries::SubredditCount;
+use crate::filters::keywords::KeywordMatcher;
+use crate::settings::api_keys::ConfigDirs;
+
+// Aggregates behind `ruddit report`, for posts and leads created in [since, until)
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodReport {
+    pub since: i64,
+    pub until: i64,
+    pub new_posts: i64,
+    pub new_comments: i64,
+    pub posts_per_subreddit: Vec<SubredditCount>,
+    pub top_threads: Vec<ThreadEngagement>,
+    pub sentiment_by_day: Vec<SentimentCount>,
+    pub sentiment_by_week: Vec<SentimentCount>,
+    pub leads_by_status: Vec<LeadStatusCount>,
+    pub posts_by_day: Vec<PeriodCount>,
+    pub share_of_voice: Vec<MentionCount>,
+}
+
+// Engagement is measured on the comments stored for the post
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadEngagement {
+    pub title: String,
+    pub subreddit: String,
+    pub permalink: String,
+    pub formatted_date: String,
+    pub comments: i64,
+    pub comment_score: i64,
+}
+
+// Sentiment of the leads found in a period, a day (YYYY-MM-DD) or a week (YYYY-Www)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SentimentCount {
+    pub period: String,
+    pub positive: i64,
+    pub neutral: i64,
+    pub negative: i64,
+}
+
+impl SentimentCount {
+    pub fn total(&self) -> i64 {
+        self.positive + self.neutral + self.negative
+    }
+
+    // From -1 (all negative) to 1 (all positive)
+    pub fn net(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        (self.positive - self.negative) as f64 / self.total() as f64
+    }
+}
+
+// Posts stored for a day (YYYY-MM-DD)
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodCount {
+    pub period: String,
+    pub count: i64,
+}
+
+// Posts and comments matching BRANDED_KEYWORDS ("brand") or a keyword group
+#[derive(Debug, Clone, Serialize)]
+pub struct MentionCount {
+    pub name: String,
+    pub mentions: i64,
+}
+
+// What `ruddit report compare` contrasts, for posts, comments and leads
+// created in [since, until)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PeriodStats {
+    pub posts: i64,
+    pub comments: i64,
+    pub leads: i64,
+    pub sentiment: SentimentCount,
+    // Posts and comments mentioning each keyword, "group: keyword" for groups
+    pub keywords: HashMap<String, i64>,
+}
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+const WEEK_FORMAT: &str = "%Y-W%W";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeadStatusCount {
+    pub status: String,
+    pub leads: i64,
+}
+
+impl DB {
+    pub fn get_period_report(
+        &self,
+        since: i64,
+        until: i64,
+        top: usize,
+    ) -> RusqliteResult<PeriodReport> {
+        let new_posts = self.conn.query_row(
+            "SELECT COUNT(*) FROM reddit_posts WHERE timestamp >= ?1 AND timestamp < ?2",
+            params![since, until],
+            |row| row.get(0),
+        )?;
+        let new_comments = self.conn.query_row(
+            "SELECT COUNT(*) FROM reddit_comments WHERE timestamp >= ?1 AND timestamp < ?2",
+            params![since, until],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT subreddit, COUNT(*) FROM reddit_posts
+             WHERE timestamp >= ?1 AND timestamp < ?2
+             GROUP BY subreddit
+             ORDER BY COUNT(*) DESC",
+        )?;
+        let posts_per_subreddit = stmt
+            .query_map(params![since, until], |row| {
+                Ok(SubredditCount {
+                    subreddit: row.get(0)?,
+                    posts: row.get(1)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        // Comment post ids are base36 strings while post ids are stored decoded,
+        // so comments are counted per post in Rust
+        let mut stmt = self.conn.prepare(
+            "SELECT post_id, COUNT(*), COALESCE(SUM(score), 0) FROM reddit_comments GROUP BY post_id",
+        )?;
+        let engagement = stmt
+            .query_map([], |row| {
+                Ok((
+                    DB::decode_reddit_id(&row.get::<_, String>(0)?),
+                    (row.get::<_, i64>(1)?, row.get::<_, i64>(2)?),
+                ))
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?
+            .into_iter()
+            // Comments with a malformed post id belong to no stored post
+            .filter_map(|(id, counts)| Some((id?, counts)))
+            .collect::<HashMap<_, _>>();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, subreddit, permalink, formatted_date FROM reddit_posts
+             WHERE timestamp >= ?1 AND timestamp < ?2",
+        )?;
+        let mut top_threads = stmt
+            .query_map(params![since, until], |row| {
+                let id: i64 = row.get(0)?;
+                let (comments, comment_score) = engagement.get(&id).copied().unwrap_or((0, 0));
+                Ok(ThreadEngagement {
+                    title: row.get(1)?,
+                    subreddit: row.get(2)?,
+                    permalink: row.get(3)?,
+                    formatted_date: row.get(4)?,
+                    comments,
+                    comment_score,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+        top_threads
+            .sort_by_key(|thread| std::cmp::Reverse((thread.comments, thread.comment_score)));
+        top_threads.truncate(top);
+
+        let sentiment_by_day = self.get_sentiment_trend(DAY_FORMAT, since, until)?;
+        // Eight weeks of history give the weekly report a trend to compare against
+        let sentiment_by_week =
+            self.get_sentiment_trend(WEEK_FORMAT, until - 8 * 7 * 86_400, until)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT review_status, COUNT(*) FROM leads
+             WHERE created_at >= ?1 AND created_at < ?2
+             GROUP BY review_status
+             ORDER BY review_status",
+        )?;
+        let leads_by_status = stmt
+            .query_map(params![since, until], |row| {
+                Ok(LeadStatusCount {
+                    status: row.get(0)?,
+                    leads: row.get(1)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        let posts_by_day = self.get_posts_by_day(since, until)?;
+        let share_of_voice = self.get_share_of_voice(since)?;
+
+        Ok(PeriodReport {
+            since,
+            until,
+            new_posts,
+            new_comments,
+            posts_per_subreddit,
+            top_threads,
+            sentiment_by_day,
+            sentiment_by_week,
+            leads_by_status,
+            posts_by_day,
+            share_of_voice,
+        })
+    }
+
+    pub fn get_period_stats(&self, since: i64, until: i64) -> RusqliteResult<PeriodStats> {
+        let count = |sql: &str| -> RusqliteResult<i64> {
+            self.conn
+                .query_row(sql, params![since, until], |row| row.get(0))
+        };
+        let posts =
+            count("SELECT COUNT(*) FROM reddit_posts WHERE timestamp >= ?1 AND timestamp < ?2")?;
+        let comments =
+            count("SELECT COUNT(*) FROM reddit_comments WHERE timestamp >= ?1 AND timestamp < ?2")?;
+        let leads = count("SELECT COUNT(*) FROM leads WHERE created_at >= ?1 AND created_at < ?2")?;
+
+        let mut sentiment = SentimentCount::default();
+        for day in self.get_sentiment_trend(DAY_FORMAT, since, until)? {
+            sentiment.positive += day.positive;
+            sentiment.neutral += day.neutral;
+            sentiment.negative += day.negative;
+        }
+
+        let mut keywords = HashMap::new();
+        if let Ok(config) = ConfigDirs::read_config() {
+            let mut matchers = Vec::new();
+            if let Ok(matcher) = KeywordMatcher::from_config(&config) {
+                matchers.push((String::new(), matcher));
+            }
+            for group in &config.keyword_groups {
+                if let Ok(matcher) = KeywordMatcher::from_group(group, &config.matching) {
+                    matchers.push((group.name.clone(), matcher));
+                }
+            }
+
+            let mut texts = Vec::new();
+            for sql in [
+                "SELECT title || ' ' || selftext FROM reddit_posts
+                 WHERE timestamp >= ?1 AND timestamp < ?2 AND noise = ''",
+                "SELECT body FROM reddit_comments WHERE timestamp >= ?1 AND timestamp < ?2",
+            ] {
+                let mut stmt = self.conn.prepare(sql)?;
+                for text in stmt.query_map(params![since, until], |row| row.get::<_, String>(0))? {
+                    texts.push(text?);
+                }
+            }
+
+            for (group, matcher) in &matchers {
+                for text in &texts {
+                    let mut terms = matcher.terms_in(text);
+                    terms.sort_unstable();
+                    terms.dedup();
+                    for term in terms {
+                        let label = if group.is_empty() {
+                            term.to_string()
+                        } else {
+                            format!("{}: {}", group, term)
+                        };
+                        *keywords.entry(label).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(PeriodStats {
+            posts,
+            comments,
+            leads,
+            sentiment,
+            keywords,
+        })
+    }
+
+    // Every day from since to until, days without posts included
+    pub fn get_posts_by_day(&self, since: i64, until: i64) -> RusqliteResult<Vec<PeriodCount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT strftime(?1, timestamp, 'unixepoch'), COUNT(*) FROM reddit_posts
+             WHERE timestamp >= ?2 AND timestamp < ?3
+             GROUP BY 1",
+        )?;
+        let counts = stmt
+            .query_map(params![DAY_FORMAT, since, until], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<RusqliteResult<HashMap<_, _>>>()?;
+
+        let mut days = Vec::new();
+        let mut day = since;
+        while day <= until {
+            let period = DateTime::from_timestamp(day, 0)
+                .map(|date| date.format(DAY_FORMAT).to_string())
+                .unwrap_or_default();
+            if days
+                .last()
+                .is_none_or(|last: &PeriodCount| last.period != period)
+            {
+                days.push(PeriodCount {
+                    count: counts.get(&period).copied().unwrap_or(0),
+                    period,
+                });
+            }
+            day += 86_400;
+        }
+        Ok(days)
+    }
+
+    // Mentions of BRANDED_KEYWORDS and of each keyword group since `since`,
+    // leaving out the ones without keywords
+    pub fn get_share_of_voice(&self, since: i64) -> RusqliteResult<Vec<MentionCount>> {
+        let Ok(config) = ConfigDirs::read_config() else {
+            return Ok(Vec::new());
+        };
+
+        let mut matchers = Vec::new();
+        if !config.api_keys.branded_keywords.is_empty() {
+            matchers.push((
+                "brand".to_string(),
+                KeywordMatcher::new(&config.api_keys.branded_keywords, "OR"),
+            ));
+        }
+        for group in &config.keyword_groups {
+            if let Ok(matcher) = KeywordMatcher::from_group(group, &config.matching)
+                && !matcher.is_empty()
+            {
+                matchers.push((group.name.clone(), matcher));
+            }
+        }
+
+        matchers
+            .into_iter()
+            .map(|(name, matcher)| {
+                Ok(MentionCount {
+                    name,
+                    mentions: self.count_mentions(since, &matcher)?,
+                })
+            })
+            .collect()
+    }
+
+    // Lead sentiment per week over the last `weeks` weeks
+    pub fn get_sentiment_by_week(
+        &self,
+        weeks: usize,
+        until: i64,
+    ) -> RusqliteResult<Vec<SentimentCount>> {
+        self.get_sentiment_trend(WEEK_FORMAT, until - weeks as i64 * 7 * 86_400, until)
+    }
+
+    // Gemini writes free-form sentiment, bucket it into three classes per period
+    fn get_sentiment_trend(
+        &self,
+        period_format: &str,
+        since: i64,
+        until: i64,
+    ) -> RusqliteResult<Vec<SentimentCount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT strftime(?1, created_at, 'unixepoch') AS period, lower(sentiment) FROM leads
+             WHERE created_at >= ?2 AND created_at < ?3
+             ORDER BY created_at",
+        )?;
+
+        let mut trend: Vec<SentimentCount> = Vec::new();
+        for row in stmt.query_map(params![period_format, since, until], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (period, sentiment) = row?;
+            if trend.last().is_none_or(|last| last.period != period) {
+                trend.push(SentimentCount {
+                    period,
+                    ..Default::default()
+                });
+            }
+            if let Some(entry) = trend.last_mut() {
+                if sentiment.contains("neg") {
+                    entry.negative += 1;
+                } else if sentiment.contains("pos") {
+                    entry.positive += 1;
+                } else {
+                    entry.neutral += 1;
+                }
+            }
+        }
+
+        Ok(trend)
+    }
+}
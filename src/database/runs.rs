@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use rusqlite::{OptionalExtension, Result as RusqliteResult, Row, params};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::outcome::RunSummary;
+use crate::database::adding::{DB, PostDataWrapper};
+use crate::settings::redact::redact;
+
+// One invocation that stored posts, comments or leads
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RunRecord {
+    pub id: i64,
+    pub command: String,
+    pub args: String,
+    pub started_at: i64,
+    pub formatted_date: String,
+    pub fetched: i64,
+    pub inserted: i64,
+    pub skipped: i64,
+    pub errors: i64,
+}
+
+// Relevance and sentiment a run gave a lead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeadState {
+    pub relevance: String,
+    pub sentiment: String,
+}
+
+#[derive(Debug)]
+pub struct LeadChange {
+    pub title: String,
+    pub url: String,
+    // None when the lead did not exist yet
+    pub before: Option<LeadState>,
+    pub after: LeadState,
+}
+
+#[derive(Debug)]
+pub struct ScoreMove {
+    pub comment_id: String,
+    pub author: String,
+    pub post_title: String,
+    pub before: i64,
+    pub after: i64,
+}
+
+// What happened between two runs, see `ruddit diff`
+#[derive(Debug)]
+pub struct RunDiff {
+    pub from: RunRecord,
+    pub to: RunRecord,
+    pub posts_added: Vec<PostDataWrapper>,
+    pub leads_added: Vec<LeadChange>,
+    pub leads_changed: Vec<LeadChange>,
+    // Largest movements first
+    pub score_moves: Vec<ScoreMove>,
+}
+
+struct Invocation {
+    command: String,
+    args: String,
+    // Row in the runs table, created when the first row is stored
+    run_id: Option<i64>,
+}
+
+static INVOCATION: Mutex<Option<Invocation>> = Mutex::new(None);
+
+// Rows stored from now on belong to a new run of this command. Called once
+// per process, and by `ruddit watch` before every scheduled job.
+pub fn begin(command: &str, args: &str) {
+    if let Ok(mut invocation) = INVOCATION.lock() {
+        *invocation = Some(Invocation {
+            command: command.to_string(),
+            args: redact(args),
+            run_id: None,
+        });
+    }
+}
+
+impl DB {
+    pub fn create_runs_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command TEXT NOT NULL,
+                args TEXT NOT NULL DEFAULT '',
+                started_at INTEGER NOT NULL,
+                fetched INTEGER NOT NULL DEFAULT 0,
+                inserted INTEGER NOT NULL DEFAULT 0,
+                skipped INTEGER NOT NULL DEFAULT 0,
+                errors INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        // What each run made of a lead, so `ruddit diff` can tell what changed
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS run_leads (
+                run_id INTEGER NOT NULL,
+                url TEXT NOT NULL,
+                relevance TEXT NOT NULL DEFAULT '',
+                sentiment TEXT NOT NULL DEFAULT '',
+                PRIMARY KEY (url, run_id)
+            )",
+            [],
+        )?;
+
+        // Comment scores, recorded when a comment is stored and whenever a
+        // later run sees a different score
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS run_comment_scores (
+                run_id INTEGER NOT NULL,
+                comment_id TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                PRIMARY KEY (comment_id, run_id)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    // Id stored rows are tagged with, 0 when nothing called `begin`
+    pub fn current_run(&self) -> RusqliteResult<i64> {
+        let Ok(mut guard) = INVOCATION.lock() else {
+            return Ok(0);
+        };
+        let Some(invocation) = guard.as_mut() else {
+            return Ok(0);
+        };
+        if let Some(run_id) = invocation.run_id {
+            return Ok(run_id);
+        }
+
+        self.create_runs_table()?;
+        self.conn.execute(
+            "INSERT INTO runs (command, args, started_at) VALUES (?1, ?2, ?3)",
+            params![invocation.command, invocation.args, Utc::now().timestamp()],
+        )?;
+        let run_id = self.conn.last_insert_rowid();
+        invocation.run_id = Some(run_id);
+        Ok(run_id)
+    }
+
+    // Save the counts and keyword hits of the current run, if it stored anything
+    pub fn record_run_summary(&self, summary: &RunSummary) -> RusqliteResult<()> {
+        let run_id = INVOCATION
+            .lock()
+            .ok()
+            .and_then(|invocation| invocation.as_ref().and_then(|i| i.run_id));
+        let Some(run_id) = run_id else {
+            return Ok(());
+        };
+
+        self.conn.execute(
+            "UPDATE runs SET fetched = ?2, inserted = ?3, skipped = ?4, errors = ?5 WHERE id = ?1",
+            params![
+                run_id,
+                summary.fetched as i64,
+                summary.inserted as i64,
+                summary.skipped as i64,
+                summary.errors as i64
+            ],
+        )?;
+        self.record_keyword_hits(run_id)
+    }
+
+    // Most recent runs first
+    pub fn get_runs(&self, limit: usize) -> RusqliteResult<Vec<RunRecord>> {
+        self.create_runs_table()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, command, args, started_at, fetched, inserted, skipped, errors
+             FROM runs
+             ORDER BY id DESC
+             LIMIT ?1",
+        )?;
+
+        stmt.query_map([limit as i64], run_record)?.collect()
+    }
+
+    pub fn get_run(&self, id: i64) -> RusqliteResult<Option<RunRecord>> {
+        self.create_runs_table()?;
+        self.conn
+            .query_row(
+                "SELECT id, command, args, started_at, fetched, inserted, skipped, errors
+                 FROM runs
+                 WHERE id = ?1",
+                [id],
+                run_record,
+            )
+            .optional()
+    }
+
+    // Posts stored after `from` up to and including `to`, leads that appeared
+    // or were reassessed, and comment scores that moved. Lead and score history
+    // is only kept for runs made since it was introduced.
+    pub fn diff_runs(&self, from: &RunRecord, to: &RunRecord) -> RusqliteResult<RunDiff> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, formatted_date, title, url, relevance, subreddit, permalink, selftext_clean
+             FROM reddit_posts
+             WHERE run_id > ?1 AND run_id <= ?2
+             ORDER BY timestamp DESC",
+        )?;
+        let posts_added = stmt
+            .query_map(params![from.id, to.id], |row| {
+                Ok(PostDataWrapper {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    formatted_date: row.get(2)?,
+                    title: row.get(3)?,
+                    url: row.get(4)?,
+                    relevance: row.get(5)?,
+                    subreddit: row.get(6)?,
+                    permalink: row.get(7)?,
+                    selftext: row.get(8)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        let before = self.leads_as_of(from.id)?;
+        let mut leads_added = Vec::new();
+        let mut leads_changed = Vec::new();
+        for (url, (title, after)) in self.leads_as_of(to.id)? {
+            match before.get(&url) {
+                None => leads_added.push(LeadChange {
+                    title,
+                    url,
+                    before: None,
+                    after,
+                }),
+                Some((_, old)) if *old != after => leads_changed.push(LeadChange {
+                    title,
+                    url,
+                    before: Some(old.clone()),
+                    after,
+                }),
+                Some(_) => {}
+            }
+        }
+        leads_added.sort_by(|a, b| a.title.cmp(&b.title));
+        leads_changed.sort_by(|a, b| a.title.cmp(&b.title));
+
+        let scores_before = self.comment_scores_as_of(from.id)?;
+        let mut score_moves = Vec::new();
+        for (comment_id, (author, post_title, after)) in self.comment_scores_as_of(to.id)? {
+            if let Some((_, _, before)) = scores_before.get(&comment_id)
+                && *before != after
+            {
+                score_moves.push(ScoreMove {
+                    comment_id,
+                    author,
+                    post_title,
+                    before: *before,
+                    after,
+                });
+            }
+        }
+        score_moves.sort_by_key(|m| std::cmp::Reverse((m.after - m.before).abs()));
+
+        Ok(RunDiff {
+            from: from.clone(),
+            to: to.clone(),
+            posts_added,
+            leads_added,
+            leads_changed,
+            score_moves,
+        })
+    }
+
+    // Each lead as the latest run up to `run_id` left it, keyed by URL
+    fn leads_as_of(&self, run_id: i64) -> RusqliteResult<HashMap<String, (String, LeadState)>> {
+        self.create_leads_table()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT h.url, COALESCE(l.title, h.url), h.relevance, h.sentiment
+             FROM run_leads h
+             LEFT JOIN leads l ON l.url = h.url
+             WHERE h.run_id = (SELECT MAX(run_id) FROM run_leads WHERE url = h.url AND run_id <= ?1)",
+        )?;
+        stmt.query_map([run_id], |row| {
+            Ok((
+                row.get(0)?,
+                (
+                    row.get(1)?,
+                    LeadState {
+                        relevance: row.get(2)?,
+                        sentiment: row.get(3)?,
+                    },
+                ),
+            ))
+        })?
+        .collect()
+    }
+
+    // Author, post title and score of each comment as of `run_id`
+    fn comment_scores_as_of(
+        &self,
+        run_id: i64,
+    ) -> RusqliteResult<HashMap<String, (String, String, i64)>> {
+        self.create_comments_table()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT s.comment_id, COALESCE(c.author, ''), COALESCE(c.post_title, ''), s.score
+             FROM run_comment_scores s
+             LEFT JOIN reddit_comments c ON c.id = s.comment_id
+             WHERE s.run_id = (SELECT MAX(run_id) FROM run_comment_scores
+                               WHERE comment_id = s.comment_id AND run_id <= ?1)",
+        )?;
+        stmt.query_map([run_id], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?)))
+        })?
+        .collect()
+    }
+}
+
+fn run_record(row: &Row) -> RusqliteResult<RunRecord> {
+    let started_at: i64 = row.get(3)?;
+    Ok(RunRecord {
+        id: row.get(0)?,
+        command: row.get(1)?,
+        args: row.get(2)?,
+        started_at,
+        formatted_date: DB::format_timestamp(started_at)?,
+        fetched: row.get(4)?,
+        inserted: row.get(5)?,
+        skipped: row.get(6)?,
+        errors: row.get(7)?,
+    })
+}
@@ -0,0 +1,93 @@
+use chrono::Utc;
+use rusqlite::{Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+
+// Tag of the posts saved on Reddit, from a data export
+pub const SAVED: &str = "saved";
+
+// A comment saved on Reddit. The export only has its id and permalink, the
+// comment itself is in reddit_comments once its thread is fetched.
+#[derive(Debug)]
+pub struct SavedComment {
+    pub id: String,
+    pub post_id: String,
+    pub permalink: String,
+    pub subreddit: String,
+}
+
+// Items saved on Reddit. Saved posts are tagged, so they show up under
+// --tag saved when stored now or fetched later.
+impl DB {
+    pub fn create_saved_table(&self) -> RusqliteResult<()> {
+        let existed: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'saved_comments')",
+            [],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS saved_comments (
+                id TEXT PRIMARY KEY,
+                post_id TEXT NOT NULL,
+                permalink TEXT NOT NULL,
+                subreddit TEXT NOT NULL DEFAULT '',
+                saved_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Imports before this table stored saved items as blank posts and
+        // comments, move them over
+        if !existed {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO post_tags (post_id, tag, tagged_at)
+                 SELECT id, ?1, ?2 FROM reddit_posts
+                 WHERE source = 'gdpr_saved_post' AND title = '' AND timestamp = 0",
+                params![SAVED, Utc::now().timestamp()],
+            )?;
+            self.conn.execute(
+                "DELETE FROM reddit_posts
+                 WHERE source = 'gdpr_saved_post' AND title = '' AND timestamp = 0",
+                [],
+            )?;
+            self.conn.execute(
+                "INSERT OR IGNORE INTO saved_comments (id, post_id, permalink, subreddit, saved_at)
+                 SELECT id, post_id, permalink, subreddit, ?1 FROM reddit_comments
+                 WHERE source = 'gdpr_saved_comment' AND body = '' AND timestamp = 0",
+                [Utc::now().timestamp()],
+            )?;
+            self.conn.execute(
+                "DELETE FROM reddit_comments
+                 WHERE source = 'gdpr_saved_comment' AND body = '' AND timestamp = 0",
+                [],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Returns the number of comments not saved before
+    pub fn add_saved_comments(&mut self, comments: &[SavedComment]) -> RusqliteResult<usize> {
+        let tx = self.conn.transaction()?;
+        let mut added = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO saved_comments (id, post_id, permalink, subreddit, saved_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let now = Utc::now().timestamp();
+            for comment in comments {
+                added += stmt.execute(params![
+                    comment.id,
+                    comment.post_id,
+                    comment.permalink,
+                    comment.subreddit,
+                    now
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(added)
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::Utc;
+use rusqlite::{OptionalExtension, Result as RusqliteResult, params};
+use serde::Serialize;
+
+use crate::database::adding::{DB, PostDataWrapper};
+
+// Where a saved search left off
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchState {
+    pub name: String,
+    // Creation time of the newest post found so far, 0 before the first run
+    pub cursor: i64,
+    pub last_run_at: Option<i64>,
+    pub runs: i64,
+    // Posts the search has found over all its runs
+    pub hits: i64,
+}
+
+impl DB {
+    pub fn create_searches_tables(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS saved_searches (
+                name TEXT PRIMARY KEY,
+                cursor INTEGER NOT NULL DEFAULT 0,
+                last_run_at INTEGER,
+                runs INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS search_hits (
+                search TEXT NOT NULL,
+                post_id INTEGER NOT NULL,
+                found_at INTEGER NOT NULL,
+                PRIMARY KEY (search, post_id)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_search_state(&self, name: &str) -> RusqliteResult<SearchState> {
+        let state = self
+            .conn
+            .query_row(
+                "SELECT name, cursor, last_run_at, runs,
+                        (SELECT COUNT(*) FROM search_hits WHERE search = ?1)
+                 FROM saved_searches
+                 WHERE name = ?1",
+                [name],
+                |row| {
+                    Ok(SearchState {
+                        name: row.get(0)?,
+                        cursor: row.get(1)?,
+                        last_run_at: row.get(2)?,
+                        runs: row.get(3)?,
+                        hits: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(state.unwrap_or_else(|| SearchState {
+            name: name.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    // Tag the posts a run found with the search name and move its cursor to
+    // the newest of them. Returns how many were not tagged before.
+    pub fn record_search_run(
+        &self,
+        name: &str,
+        posts: &[PostDataWrapper],
+    ) -> RusqliteResult<usize> {
+        let now = Utc::now().timestamp();
+        let newest = posts.iter().map(|post| post.timestamp).max().unwrap_or(0);
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut tagged = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO search_hits (search, post_id, found_at) VALUES (?1, ?2, ?3)",
+            )?;
+            for post in posts {
+                tagged += stmt.execute(params![name, post.id, now])?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO saved_searches (name, cursor, last_run_at, runs) VALUES (?1, ?2, ?3, 1)
+             ON CONFLICT(name) DO UPDATE SET
+                cursor = MAX(cursor, excluded.cursor),
+                last_run_at = excluded.last_run_at,
+                runs = runs + 1",
+            params![name, newest, now],
+        )?;
+        tx.commit()?;
+
+        Ok(tagged)
+    }
+}
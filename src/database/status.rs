@@ -0,0 +1,165 @@
+use chrono::Utc;
+use rusqlite::{OptionalExtension, Result as RusqliteResult, params};
+use serde::{Deserialize, Serialize};
+
+use crate::database::adding::DB;
+
+// Last successful fetch of a subreddit (or search query)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SyncStatus {
+    pub source: String,
+    pub last_synced_at: i64,
+    pub formatted_date: String,
+    pub posts: i64,
+}
+
+// One call to the AI provider
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AiUsage {
+    pub id: i64,
+    pub called_at: i64,
+    pub formatted_date: String,
+    pub kind: String,
+    pub success: bool,
+    pub message: String,
+    // Which Gemini key served the call
+    pub api_key: String,
+}
+
+// Time between a post being created on Reddit and ruddit first storing it
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DetectionLatency {
+    pub posts: usize,
+    pub median_secs: i64,
+    pub p90_secs: i64,
+}
+
+impl DB {
+    pub fn create_status_tables(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS sync_log (
+                source TEXT PRIMARY KEY,
+                last_synced_at INTEGER NOT NULL,
+                posts INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS ai_usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                called_at INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                message TEXT NOT NULL DEFAULT '',
+                api_key TEXT NOT NULL DEFAULT ''
+            )",
+            [],
+        )?;
+
+        // Databases created before key rotation
+        self.add_column_if_missing("ai_usage", "api_key", "TEXT NOT NULL DEFAULT ''")?;
+
+        Ok(())
+    }
+
+    pub fn record_sync(&self, source: &str, posts: usize) -> RusqliteResult<()> {
+        self.create_status_tables()?;
+        self.conn.execute(
+            "INSERT INTO sync_log (source, last_synced_at, posts)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(source) DO UPDATE SET
+                last_synced_at = excluded.last_synced_at,
+                posts = excluded.posts",
+            params![source, Utc::now().timestamp(), posts as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_ai_call(
+        &self,
+        kind: &str,
+        success: bool,
+        message: &str,
+        api_key: &str,
+    ) -> RusqliteResult<()> {
+        self.create_status_tables()?;
+        self.conn.execute(
+            "INSERT INTO ai_usage (called_at, kind, success, message, api_key)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![Utc::now().timestamp(), kind, success, message, api_key],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_sync_status(&self) -> RusqliteResult<Vec<SyncStatus>> {
+        self.create_status_tables()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT source, last_synced_at, posts FROM sync_log ORDER BY last_synced_at DESC",
+        )?;
+
+        let syncs = stmt
+            .query_map([], |row| {
+                let last_synced_at: i64 = row.get(1)?;
+                Ok(SyncStatus {
+                    source: row.get(0)?,
+                    last_synced_at,
+                    formatted_date: DB::format_timestamp(last_synced_at)?,
+                    posts: row.get(2)?,
+                })
+            })?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        Ok(syncs)
+    }
+
+    pub fn get_last_ai_call(&self) -> RusqliteResult<Option<AiUsage>> {
+        self.create_status_tables()?;
+        self.conn
+            .query_row(
+                "SELECT id, called_at, kind, success, message, api_key
+                 FROM ai_usage
+                 ORDER BY called_at DESC, id DESC
+                 LIMIT 1",
+                [],
+                |row| {
+                    let called_at: i64 = row.get(1)?;
+                    Ok(AiUsage {
+                        id: row.get(0)?,
+                        called_at,
+                        formatted_date: DB::format_timestamp(called_at)?,
+                        kind: row.get(2)?,
+                        success: row.get(3)?,
+                        message: row.get(4)?,
+                        api_key: row.get(5)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    // Latency of the Reddit posts discovered since the given time. Imported
+    // posts and posts stored before discovery tracking are left out.
+    pub fn get_detection_latency(&self, since: i64) -> RusqliteResult<Option<DetectionLatency>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT MAX(first_seen_at - timestamp, 0)
+             FROM reddit_posts
+             WHERE source = 'reddit' AND first_seen_at > 0 AND first_seen_at >= ?1",
+        )?;
+        let mut latencies = stmt
+            .query_map([since], |row| row.get::<_, i64>(0))?
+            .collect::<RusqliteResult<Vec<_>>>()?;
+
+        if latencies.is_empty() {
+            return Ok(None);
+        }
+        latencies.sort_unstable();
+        let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+
+        Ok(Some(DetectionLatency {
+            posts: latencies.len(),
+            median_secs: percentile(50),
+            p90_secs: percentile(90),
+        }))
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{Result as RusqliteResult, params};
+
+use crate::database::adding::DB;
+
+// Tag given by `ruddit star`
+pub const STARRED: &str = "starred";
+
+// Labels added to stored posts by hand with `ruddit tag` and `ruddit star`
+impl DB {
+    pub fn create_tags_table(&self) -> RusqliteResult<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS post_tags (
+                post_id INTEGER NOT NULL,
+                tag TEXT NOT NULL,
+                tagged_at INTEGER NOT NULL,
+                PRIMARY KEY (post_id, tag)
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_post_tags_tag ON post_tags (tag)",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn is_post_stored(&self, post_id: i64) -> RusqliteResult<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM reddit_posts WHERE id = ?1)",
+            [post_id],
+            |row| row.get(0),
+        )
+    }
+
+    // Returns false when the post already had the tag
+    pub fn add_tag(&self, post_id: i64, tag: &str) -> RusqliteResult<bool> {
+        let added = self.conn.execute(
+            "INSERT OR IGNORE INTO post_tags (post_id, tag, tagged_at) VALUES (?1, ?2, ?3)",
+            params![post_id, tag, Utc::now().timestamp()],
+        )?;
+        Ok(added > 0)
+    }
+
+    // Returns false when the post did not have the tag
+    pub fn remove_tag(&self, post_id: i64, tag: &str) -> RusqliteResult<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM post_tags WHERE post_id = ?1 AND tag = ?2",
+            params![post_id, tag],
+        )?;
+        Ok(removed > 0)
+    }
+
+    // Tags of every tagged post, in alphabetical order
+    pub fn get_post_tags(&self) -> RusqliteResult<HashMap<i64, Vec<String>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT post_id, tag FROM post_tags ORDER BY tag")?;
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+        for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (post_id, tag) = row?;
+            tags.entry(post_id).or_default().push(tag);
+        }
+        Ok(tags)
+    }
+
+    // Every tag in use with how many posts carry it, most used first
+    pub fn count_tags(&self) -> RusqliteResult<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tag, COUNT(*) FROM post_tags GROUP BY tag ORDER BY COUNT(*) DESC, tag",
+        )?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect()
+    }
+}
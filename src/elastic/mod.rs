@@ -0,0 +1,225 @@
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::database::adding::DB;
+use crate::database::elastic::ELASTICSEARCH_RUN_KEY;
+use crate::exports::anonymize::Anonymizer;
+use crate::settings::api_keys::{AppConfig, ElasticsearchSettings};
+use crate::settings::redact::redact;
+
+// Documents sent per _bulk request
+const BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Default)]
+pub struct PushSummary {
+    pub posts: usize,
+    pub comments: usize,
+}
+
+struct Cluster {
+    client: Client,
+    settings: ElasticsearchSettings,
+}
+
+impl Cluster {
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!(
+            "{}/{}",
+            self.settings.url.trim().trim_end_matches('/'),
+            path
+        );
+        let request = self.client.request(method, url);
+        if !self.settings.api_key.trim().is_empty() {
+            request.header(
+                "Authorization",
+                format!("ApiKey {}", self.settings.api_key.trim()),
+            )
+        } else if !self.settings.username.is_empty() {
+            request.basic_auth(&self.settings.username, Some(&self.settings.password))
+        } else {
+            request
+        }
+    }
+
+    fn index(&self, kind: &str) -> String {
+        format!("{}-{}", self.settings.index.trim(), kind)
+    }
+
+    // Mappings for both indices, kept up to date by every push
+    async fn put_template(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let keyword = json!({ "type": "keyword" });
+        let text = json!({ "type": "text", "fields": { "keyword": { "type": "keyword", "ignore_above": 256 } } });
+        let body = json!({
+            "index_patterns": [format!("{}-*", self.settings.index.trim())],
+            "template": {
+                "mappings": {
+                    "properties": {
+                        "id": keyword,
+                        "post_id": keyword,
+                        "parent_id": keyword,
+                        "subreddit": keyword,
+                        "title": text,
+                        "post_title": text,
+                        "text": { "type": "text" },
+                        "body": { "type": "text" },
+                        "url": keyword,
+                        "permalink": keyword,
+                        "relevance": keyword,
+                        "intent": keyword,
+                        "is_question": { "type": "boolean" },
+                        "source": keyword,
+                        "author": keyword,
+                        "score": { "type": "integer" },
+                        "created_at": { "type": "date", "format": "epoch_second" },
+                        "run_id": { "type": "long" }
+                    }
+                }
+            }
+        });
+        self.request(
+            Method::PUT,
+            &format!("_index_template/{}", self.settings.index.trim()),
+        )
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+        Ok(())
+    }
+
+    // Index the documents by id, so pushing a row again replaces it
+    async fn bulk<T: Serialize>(
+        &self,
+        index: &str,
+        documents: &[(String, T)],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut body = String::new();
+        for (id, document) in documents {
+            body.push_str(&json!({ "index": { "_index": index, "_id": id } }).to_string());
+            body.push('\n');
+            body.push_str(&serde_json::to_string(document)?);
+            body.push('\n');
+        }
+        let response: Value = self
+            .request(Method::POST, "_bulk")
+            .header("Content-Type", "application/x-ndjson")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if response["errors"].as_bool() == Some(true) {
+            let failed: Vec<&Value> = response["items"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|item| item["index"]["error"].as_object().map(|_| &item["index"]))
+                .collect();
+            let reason = failed
+                .first()
+                .and_then(|item| item["error"]["reason"].as_str())
+                .unwrap_or("unknown error");
+            return Err(format!(
+                "{} of {} documents were rejected by {}: {}",
+                failed.len(),
+                documents.len(),
+                index,
+                reason
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+// Push the posts and comments stored since the last push, or all of them.
+// The last pushed run is only moved forward once everything went through.
+pub async fn push(
+    settings: &ElasticsearchSettings,
+    all: bool,
+) -> Result<PushSummary, Box<dyn std::error::Error>> {
+    if settings.url.trim().is_empty() {
+        return Err("Set url under [elasticsearch] in settings.toml first".into());
+    }
+    let cluster = Cluster {
+        client: Client::new(),
+        settings: settings.clone(),
+    };
+    cluster.put_template().await?;
+
+    let db = DB::new()?;
+    db.create_tables()?;
+    db.create_runs_table()?;
+    db.create_meta_table()?;
+    let anonymizer = Anonymizer::from_config()?;
+
+    // Rows stored before runs were recorded have run 0
+    let after = if all {
+        -1
+    } else {
+        db.get_meta(ELASTICSEARCH_RUN_KEY)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(-1)
+    };
+    let upto = db.max_run_id()?;
+    let mut summary = PushSummary::default();
+
+    let index = cluster.index("posts");
+    let mut from = 0;
+    loop {
+        let page = db.get_post_documents(after, upto, from, BATCH_SIZE)?;
+        let Some((last, _)) = page.last() else {
+            break;
+        };
+        from = *last;
+        let documents: Vec<_> = page
+            .into_iter()
+            .map(|(_, post)| (post.id.clone(), post))
+            .collect();
+        cluster.bulk(&index, &documents).await?;
+        summary.posts += documents.len();
+    }
+
+    let index = cluster.index("comments");
+    let mut from = 0;
+    loop {
+        let page = db.get_comment_documents(after, upto, from, BATCH_SIZE)?;
+        let Some((last, _)) = page.last() else {
+            break;
+        };
+        from = *last;
+        let documents: Vec<_> = page
+            .into_iter()
+            .map(|(_, mut comment)| {
+                comment.author = anonymizer.author(&comment.author);
+                (comment.id.clone(), comment)
+            })
+            .collect();
+        cluster.bulk(&index, &documents).await?;
+        summary.comments += documents.len();
+    }
+
+    db.set_meta(ELASTICSEARCH_RUN_KEY, &upto.to_string())?;
+    Ok(summary)
+}
+
+// Called after each fetch, does nothing unless [elasticsearch] is set up. A
+// failed push is reported and retried with the next one.
+pub async fn push_after_fetch(config: &AppConfig) {
+    if config.elasticsearch.url.trim().is_empty() {
+        return;
+    }
+    match push(&config.elasticsearch, false).await {
+        Ok(summary) => println!(
+            "Pushed {} posts and {} comments to Elasticsearch",
+            summary.posts, summary.comments
+        ),
+        Err(e) => eprintln!(
+            "Failed to push to Elasticsearch: {}",
+            redact(&e.to_string())
+        ),
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+use crate::database::adding::DB;
+use crate::settings::api_keys::ConfigDirs;
+
+const SALT_KEY: &str = "author_salt";
+
+// Replaces Reddit usernames with stable pseudonyms when anonymize_authors is set
+pub struct Anonymizer {
+    salt: Option<String>,
+}
+
+impl Anonymizer {
+    // Without a readable config the option can't be on, names are kept
+    pub fn from_config() -> Result<Self, Box<dyn std::error::Error>> {
+        let Ok(config) = ConfigDirs::read_config() else {
+            return Ok(Anonymizer { salt: None });
+        };
+        if !config.exports.anonymize_authors {
+            return Ok(Anonymizer { salt: None });
+        }
+
+        let salt = if config.exports.author_salt.trim().is_empty() {
+            stored_salt()?
+        } else {
+            config.exports.author_salt.trim().to_string()
+        };
+        Ok(Anonymizer { salt: Some(salt) })
+    }
+
+    // The same author always maps to the same pseudonym for a given salt
+    pub fn author(&self, name: &str) -> String {
+        let Some(salt) = &self.salt else {
+            return name.to_string();
+        };
+        // Deleted and empty authors carry no identity
+        if name.is_empty() || name == "[deleted]" {
+            return name.to_string();
+        }
+
+        let digest = Sha256::new()
+            .chain_update(salt.as_bytes())
+            .chain_update(name.as_bytes())
+            .finalize();
+        let hex: String = digest[..5].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("user_{}", hex)
+    }
+}
+
+// Salt generated on first use and kept in the database, so pseudonyms stay stable
+fn stored_salt() -> Result<String, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_meta_table()?;
+    if let Some(salt) = db.get_meta(SALT_KEY)? {
+        return Ok(salt);
+    }
+
+    let seed = format!(
+        "{:?}-{}-{:p}",
+        SystemTime::now().duration_since(UNIX_EPOCH)?,
+        std::process::id(),
+        &db
+    );
+    let salt: String = Sha256::digest(seed.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    db.set_meta(SALT_KEY, &salt)?;
+    Ok(salt)
+}
@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatAlign, Workbook};
+use serde_json::{Map, Value};
+
+use crate::arguments::modeling::AnswerFormat;
+use crate::exports;
+use crate::exports::locale::ExportLocale;
+
+// Save the structured answer of a `--gemini` question to Reddit_data
+pub fn export_answer(
+    question: &str,
+    answer: &Value,
+    format: AnswerFormat,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let folder_path = exports::reddit_data_folder()?;
+    fs::create_dir_all(&folder_path)?;
+
+    let extension = match format {
+        AnswerFormat::Xlsx => "xlsx",
+        AnswerFormat::Csv => "csv",
+        AnswerFormat::Json => "json",
+    };
+    let path = folder_path.join(format!(
+        "Gemini_answer_{}.{}",
+        Local::now().format("%d-%m-%Y_%H-%M-%S"),
+        extension
+    ));
+
+    let rows = answer_rows(answer);
+    let columns = infer_columns(&rows);
+
+    match format {
+        AnswerFormat::Xlsx => write_excel(question, &rows, &columns, &path)?,
+        AnswerFormat::Csv => {
+            let locale = ExportLocale::from_config()?;
+            let mut writer = locale.csv_writer(&path)?;
+            writer.write_record(&columns)?;
+            for row in &rows {
+                writer.write_record(
+                    columns
+                        .iter()
+                        .map(|column| csv_text(field(row, column), &locale)),
+                )?;
+            }
+            writer.flush()?;
+        }
+        AnswerFormat::Json => {
+            let document = serde_json::json!({ "question": question, "answer": answer });
+            fs::write(&path, serde_json::to_string_pretty(&document)?)?;
+        }
+    }
+
+    Ok(path)
+}
+
+// One row per object. Gemini often wraps its list in an object such as
+// {"posts": [...]}, in which case the largest list of objects is used.
+fn answer_rows(answer: &Value) -> Vec<Map<String, Value>> {
+    match answer {
+        Value::Array(items) => items.iter().map(as_row).collect(),
+        Value::Object(object) => {
+            let nested = object
+                .values()
+                .filter_map(Value::as_array)
+                .filter(|items| items.iter().any(Value::is_object))
+                .max_by_key(|items| items.len());
+            match nested {
+                Some(items) => items.iter().map(as_row).collect(),
+                None => vec![object.clone()],
+            }
+        }
+        other => vec![as_row(other)],
+    }
+}
+
+fn as_row(value: &Value) -> Map<String, Value> {
+    match value {
+        Value::Object(object) => object.clone(),
+        other => Map::from_iter([("value".to_string(), other.clone())]),
+    }
+}
+
+// Every key seen in any row, in the order it first appears
+fn infer_columns(rows: &[Map<String, Value>]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for key in rows.iter().flat_map(|row| row.keys()) {
+        if !columns.contains(key) {
+            columns.push(key.clone());
+        }
+    }
+    columns
+}
+
+// Rows missing a column leave the cell empty
+fn field<'a>(row: &'a Map<String, Value>, column: &str) -> &'a Value {
+    row.get(column).unwrap_or(&Value::Null)
+}
+
+// Lists of plain values are joined, nested objects are kept as compact JSON
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items)
+            if items
+                .iter()
+                .all(|item| !item.is_object() && !item.is_array()) =>
+        {
+            items.iter().map(cell_text).collect::<Vec<_>>().join(", ")
+        }
+        other => other.to_string(),
+    }
+}
+
+// Numbers follow the decimal separator of the export locale
+fn csv_text(value: &Value, locale: &ExportLocale) -> String {
+    match value {
+        Value::Number(number) => number
+            .as_f64()
+            .map_or_else(|| number.to_string(), |number| locale.number(number)),
+        other => cell_text(other),
+    }
+}
+
+fn write_excel(
+    question: &str,
+    rows: &[Map<String, Value>],
+    columns: &[String],
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_align(FormatAlign::Center).set_bold();
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Answer")?;
+    for (col, column) in columns.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, column, &header_format)?;
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let row_num = (index + 1) as u32;
+        for (col, column) in columns.iter().enumerate() {
+            let col = col as u16;
+            match field(row, column) {
+                Value::Number(number) => match number.as_f64() {
+                    Some(number) => worksheet.write_number(row_num, col, number)?,
+                    None => worksheet.write_string(row_num, col, number.to_string())?,
+                },
+                Value::Bool(flag) => worksheet.write_boolean(row_num, col, *flag)?,
+                other => worksheet.write_string(row_num, col, cell_text(other))?,
+            };
+        }
+    }
+    worksheet.autofit();
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Question")?;
+    worksheet.write_string_with_format(0, 0, "Question", &header_format)?;
+    worksheet.write_string(0, 1, question)?;
+    worksheet.write_string_with_format(1, 0, "Asked", &header_format)?;
+    // Local time, Excel datetimes carry no zone
+    let asked = ExcelDateTime::from_timestamp(Local::now().naive_local().and_utc().timestamp())?;
+    let asked_format = Format::new().set_num_format("yyyy-mm-dd hh:mm");
+    worksheet.write_datetime_with_format(1, 1, asked, &asked_format)?;
+    worksheet.autofit();
+
+    workbook.save(path)?;
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use chrono::DateTime;
+
+use crate::actions::progress::Progress;
+use crate::database::adding::DB;
+use crate::database::queries::PostFilter;
+use crate::exports;
+use crate::exports::anonymize::Anonymizer;
+use crate::exports::excel::dated_stem;
+
+// Column names and DuckDB types of the two tables, in CSV order
+const POST_COLUMNS: [(&str, &str); 9] = [
+    ("id", "VARCHAR"),
+    ("created_at", "TIMESTAMP"),
+    ("subreddit", "VARCHAR"),
+    ("title", "VARCHAR"),
+    ("text", "VARCHAR"),
+    ("url", "VARCHAR"),
+    ("permalink", "VARCHAR"),
+    ("relevance", "VARCHAR"),
+    ("intent", "VARCHAR"),
+];
+const COMMENT_COLUMNS: [(&str, &str); 9] = [
+    ("id", "VARCHAR"),
+    ("post_id", "VARCHAR"),
+    ("parent_id", "VARCHAR"),
+    ("subreddit", "VARCHAR"),
+    ("author", "VARCHAR"),
+    ("created_at", "TIMESTAMP"),
+    ("score", "INTEGER"),
+    ("body", "VARCHAR"),
+    ("permalink", "VARCHAR"),
+];
+
+// Write the filtered posts and their comments as CSV files plus a load.sql
+// script that builds `ruddit.duckdb` from them, and run it when the DuckDB
+// CLI is installed. Returns the export folder.
+pub fn export_duckdb(filter: &PostFilter) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let intents = db.get_post_intents()?;
+    let anonymizer = Anonymizer::from_config()?;
+
+    let folder = exports::reddit_data_folder()?.join(dated_stem(
+        "Reddit_duckdb",
+        filter
+            .keywords
+            .as_ref()
+            .map_or("", |matcher| matcher.group()),
+    ));
+    fs::create_dir_all(&folder)?;
+
+    let progress = Progress::start("Exporting posts...");
+    let mut posts = csv_writer(&folder.join("posts.csv"), &POST_COLUMNS)?;
+    let mut post_count = 0;
+    db.for_each_filtered_post(filter, |post| {
+        posts.write_record([
+            DB::encode_reddit_id(post.id),
+            timestamp(post.timestamp),
+            post.subreddit,
+            post.title,
+            post.selftext,
+            post.url,
+            post.permalink,
+            post.relevance,
+            intents.get(&post.id).cloned().unwrap_or_default(),
+        ])?;
+        post_count += 1;
+        Ok::<_, Box<dyn std::error::Error>>(())
+    })?;
+    posts.flush()?;
+
+    progress.set_label("Exporting comments...");
+    let mut comments = csv_writer(&folder.join("comments.csv"), &COMMENT_COLUMNS)?;
+    let mut comment_count = 0;
+    // Comments follow the date, subreddit and run filters, not --limit or the
+    // keywords, which select posts
+    let comment_filter = PostFilter {
+        limit: None,
+        keywords: None,
+        ..filter.clone()
+    };
+    db.for_each_filtered_comment(&comment_filter, |comment| {
+        comments.write_record([
+            comment.id,
+            comment.post_id,
+            comment.parent_id,
+            comment.subreddit,
+            anonymizer.author(&comment.author),
+            timestamp(comment.timestamp),
+            comment.score.to_string(),
+            comment.body,
+            comment.permalink,
+        ])?;
+        comment_count += 1;
+        Ok::<_, Box<dyn std::error::Error>>(())
+    })?;
+    comments.flush()?;
+    progress.finish();
+
+    let script = folder.join("load.sql");
+    fs::write(&script, load_script(&folder))?;
+    println!(
+        "Exported {} posts and {} comments to {}",
+        post_count,
+        comment_count,
+        folder.display()
+    );
+
+    let database = folder.join("ruddit.duckdb");
+    match Command::new("duckdb")
+        .arg(&database)
+        .stdin(File::open(&script)?)
+        .stdout(Stdio::null())
+        .status()
+    {
+        Ok(status) if status.success() => println!("Saved {}", database.display()),
+        Ok(status) => eprintln!("duckdb failed to run load.sql ({})", status),
+        Err(_) => println!(
+            "Install the DuckDB CLI and run `duckdb {} < {}` to build the database",
+            database.display(),
+            script.display()
+        ),
+    }
+    Ok(folder)
+}
+
+fn csv_writer(
+    path: &Path,
+    columns: &[(&str, &str)],
+) -> Result<csv::Writer<File>, Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(columns.iter().map(|(name, _)| name))?;
+    Ok(writer)
+}
+
+// UTC, in the format DuckDB reads as a TIMESTAMP
+fn timestamp(seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+// Typed tables read from the CSV files, with absolute paths so the script
+// runs from any folder
+fn load_script(folder: &Path) -> String {
+    let table = |name: &str, columns: &[(&str, &str)]| {
+        let types = columns
+            .iter()
+            .map(|(column, kind)| format!("'{}': '{}'", column, kind))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let path = folder.join(format!("{}.csv", name));
+        format!(
+            "CREATE OR REPLACE TABLE {} AS\nSELECT * FROM read_csv('{}', header = true, columns = {{{}}});\n",
+            name,
+            path.display().to_string().replace('\'', "''"),
+            types
+        )
+    };
+    format!(
+        "-- Build with: duckdb ruddit.duckdb < load.sql\n{}{}",
+        table("posts", &POST_COLUMNS),
+        table("comments", &COMMENT_COLUMNS)
+    )
+}
@@ -1,100 +1,374 @@
 use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
+use crate::actions::progress::Progress;
+use crate::actions::verbosity;
 use crate::database::adding::DB;
+use crate::database::leads::{CommentLeadDataWrapper, REVIEW_ACCEPTED};
+use crate::database::queries::PostFilter;
+use crate::exports;
+use crate::exports::anonymize::Anonymizer;
+use crate::exports::fields::{self, CommentRow, PostRow};
+use crate::exports::locale::ExportLocale;
+use crate::exports::table::{self, TableFormat, TableWriter};
+use crate::filters::text::truncate;
+use crate::settings::api_keys::ConfigDirs;
 use chrono::Local;
-use directories::UserDirs;
 use rust_xlsxwriter::{Format, FormatAlign, Workbook, XlsxError};
 use serde_json::Value;
+use zip::unstable::write::FileOptionsExt;
+use zip::write::SimpleFileOptions;
+
+// "forecast (fuzzy)", empty for leads stored before match types were recorded
+fn matched_label(lead: &CommentLeadDataWrapper) -> String {
+    match (lead.matched_term.as_str(), lead.match_type.as_str()) {
+        ("", _) => String::new(),
+        (term, "") => term.to_string(),
+        (term, match_type) => format!("{} ({})", term, match_type),
+    }
+}
+
+// "<prefix>_<group>_<date>.xlsx", the group is left out when empty
+fn dated_filename(prefix: &str, group: &str) -> String {
+    format!("{}.xlsx", dated_stem(prefix, group))
+}
+
+// "<prefix>_<group>_<date>" without an extension
+pub fn dated_stem(prefix: &str, group: &str) -> String {
+    let group: String = group
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let date = Local::now().format("%d-%m-%Y_%H-%M-%S");
+
+    if group.is_empty() {
+        format!("{}_{}", prefix, date)
+    } else {
+        format!("{}_{}_{}", prefix, group, date)
+    }
+}
+
+pub fn create_excel(filter: &PostFilter) -> Result<(), Box<dyn std::error::Error>> {
+    export_posts(filter, TableFormat::Xlsx, None).map(|_| ())
+}
+
+// Stream the filtered posts into Reddit_data, `chunk` rows per file. Rows go
+// from the database cursor to the writer without collecting them first.
+pub fn export_posts(
+    filter: &PostFilter,
+    format: TableFormat,
+    chunk: Option<usize>,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    let intents = db.get_post_intents()?;
+    db.create_tags_table()?;
+    db.create_notes_tables()?;
+    let tags = db.get_post_tags()?;
+    let notes = db.get_post_notes()?;
+
+    let folder_path = exports::reddit_data_folder()?;
+    fs::create_dir_all(&folder_path)?;
+
+    let stem = dated_stem(
+        "Reddit_data",
+        filter
+            .keywords
+            .as_ref()
+            .map_or("", |matcher| matcher.group()),
+    );
+    let exports = ConfigDirs::read_config()?.exports;
+    let locale = ExportLocale::from_settings(&exports)?;
+    let fields = fields::select(
+        fields::POST_FIELDS,
+        &exports.columns.posts,
+        fields::DEFAULT_POST_FIELDS,
+    )?;
+    let headers = fields::headers(&fields);
+    let mut writer = TableWriter::new(
+        &folder_path,
+        &stem,
+        "Reddit Posts",
+        &headers,
+        format,
+        locale,
+        chunk,
+    );
 
-pub fn create_excel() -> Result<(), Box<dyn std::error::Error>> {
-    // Get data from database with proper error handling
+    let progress = Progress::start("Exporting posts...");
+    let written = db.for_each_filtered_post(filter, |post| {
+        let intent = intents.get(&post.id).cloned().unwrap_or_default();
+        let tags = tags.get(&post.id).cloned().unwrap_or_default();
+        let note = notes.get(&post.id).cloned().unwrap_or_default();
+        let row = PostRow {
+            post,
+            intent,
+            tags,
+            note,
+        };
+        writer.write_row(&fields::values(&fields, &row))?;
+        if writer.rows_written().is_multiple_of(10_000) {
+            progress.set_label(format!("Exported {} posts...", writer.rows_written()));
+        }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    });
+    let rows = writer.rows_written();
+    let paths = written.and_then(|()| writer.finish());
+    progress.finish();
+
+    let paths = paths?;
+    println!("Exported {} records", rows);
+    for path in &paths {
+        println!("Saved {}", path.display());
+    }
+    Ok(paths)
+}
+
+// Stream stored comments from every post into Reddit_data, with the title of
+// the post each one belongs to
+pub fn export_comments(
+    filter: &PostFilter,
+    format: TableFormat,
+    chunk: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let db = DB::new()?;
-    let data = db.get_db_results()?;
+    db.create_comments_table()?;
+    db.create_notes_tables()?;
+    let notes = db.get_comment_notes()?;
+    let anonymizer = Anonymizer::from_config()?;
+    let exports = ConfigDirs::read_config()?.exports;
+    let locale = ExportLocale::from_settings(&exports)?;
+    let fields = fields::select(
+        fields::COMMENT_FIELDS,
+        &exports.columns.comments,
+        fields::DEFAULT_COMMENT_FIELDS,
+    )?;
+
+    let folder_path = exports::reddit_data_folder()?;
+    fs::create_dir_all(&folder_path)?;
+
+    let stem = dated_stem(
+        "Reddit_comments",
+        filter.subreddit.as_deref().unwrap_or_default(),
+    );
+    let headers = fields::headers(&fields);
+    let mut writer = TableWriter::new(
+        &folder_path,
+        &stem,
+        "Reddit Comments",
+        &headers,
+        format,
+        locale,
+        chunk,
+    );
+
+    let progress = Progress::start("Exporting comments...");
+    let written = db.for_each_filtered_comment(filter, |mut comment| {
+        comment.author = anonymizer.author(&comment.author);
+        let note = notes.get(&comment.id).cloned().unwrap_or_default();
+        writer.write_row(&fields::values(&fields, &CommentRow { comment, note }))?;
+        if writer.rows_written().is_multiple_of(10_000) {
+            progress.set_label(format!("Exported {} comments...", writer.rows_written()));
+        }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    });
+    let rows = writer.rows_written();
+    let paths = written.and_then(|()| writer.finish());
+    progress.finish();
+
+    let paths = paths?;
+    println!("Exported {} comments", rows);
+    for path in &paths {
+        println!("Saved {}", path.display());
+    }
+    Ok(())
+}
 
-    let user_dirs = UserDirs::new().ok_or("Failed to get user directories")?;
-    let desktop = user_dirs
-        .desktop_dir()
-        .ok_or("Failed to get desktop directory")?;
+// Export the leads accepted during `ruddit leads review`
+pub fn export_approved_leads() -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_leads_table()?;
+    let min_relevance = ConfigDirs::read_config()?.leads.min_relevance;
+    let leads: Vec<_> = db
+        .get_leads_with_status(REVIEW_ACCEPTED)?
+        .into_iter()
+        .filter(|lead| min_relevance.allows(&lead.relevance))
+        .collect();
+
+    if leads.is_empty() {
+        println!(
+            "No approved leads to export, run `ruddit leads review` first or lower min_relevance"
+        );
+        return Ok(());
+    }
 
-    println!("Exporting {} records to Excel", data.len());
+    verbosity::info(format!("Exporting {} approved leads to Excel", leads.len()));
+    let locale = ExportLocale::from_config()?;
 
-    // Create new workbook
     let mut workbook = Workbook::new();
     let worksheet = workbook.add_worksheet();
-    worksheet.set_name("Reddit Posts")?;
+    worksheet.set_name("Leads")?;
 
-    // Create header format
     let header_format = Format::new().set_align(FormatAlign::Center).set_bold();
-
-    // Write headers
-    let headers = ["Date", "Title", "URL", "Relevance", "Subreddit"];
+    let headers = [
+        "Date",
+        "Title",
+        "URL",
+        "Relevance",
+        "Subreddit",
+        "Sentiment",
+        "Comment Sentiment",
+        "Group",
+        "Assignee",
+    ];
 
     for (col, header) in headers.iter().enumerate() {
         worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
     }
 
-    // Write data rows
-    for (row, result) in data.iter().enumerate() {
+    for (row, lead) in leads.iter().enumerate() {
         let row_num = (row + 1) as u32;
+        table::write_date_text(worksheet, row_num, 0, &lead.formatted_date, &locale)?;
         let cells = [
-            result.formatted_date.clone(),
-            result.title.clone(),
-            result.url.clone(),
-            result.relevance.clone(),
-            result.subreddit.clone(),
+            &lead.title,
+            &lead.url,
+            &lead.relevance,
+            &lead.subreddit,
+            &lead.sentiment,
+            &lead.comment_sentiment,
+            &lead.keyword_group,
+            &lead.assignee,
         ];
 
         for (col, cell) in cells.iter().enumerate() {
-            worksheet.write_string(row_num, col as u16, cell)?;
+            worksheet.write_string(row_num, (col + 1) as u16, *cell)?;
         }
     }
 
-    // Auto-fit columns for better readability
     worksheet.autofit();
 
-    // Save to file with timestamp
     let filename = format!(
-        "Reddit_data_{}.xlsx",
+        "Reddit_leads_{}.xlsx",
         Local::now().format("%d-%m-%Y_%H-%M-%S")
     );
+    let folder_path = exports::reddit_data_folder()?;
+    fs::create_dir_all(&folder_path)?;
+
+    let saved = save_leads_workbook(&mut workbook, &folder_path.join(filename))?;
+    println!("Successfully exported to {:?}", saved);
+    Ok(())
+}
 
-    let folder_name = "Reddit_data";
-    let folder_path = desktop.join(folder_name);
+// Export comment leads, one row per comment with its permalink
+pub fn export_comment_leads(
+    leads: &[CommentLeadDataWrapper],
+    group: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let anonymizer = Anonymizer::from_config()?;
+    let locale = ExportLocale::from_config()?;
+    verbosity::info(format!("Exporting {} comment leads to Excel", leads.len()));
 
-    // Create directory with better error handling
-    if let Err(e) = fs::create_dir_all(&folder_path) {
-        eprintln!("Failed to create directory {:?}: {}", folder_path, e);
-        return Err(Box::new(e));
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("Comment Leads")?;
+
+    let header_format = Format::new().set_align(FormatAlign::Center).set_bold();
+    let headers = [
+        "Date",
+        "Subreddit",
+        "Post Title",
+        "Author",
+        "Comment",
+        "Score",
+        "Relevance",
+        "Intent",
+        "Matched",
+        "Link",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
     }
 
-    // Try to save with explicit error handling
-    workbook
-        .save(folder_path.join(filename.as_str()))
-        .map_err(|e| {
-            eprintln!("Failed to save workbook to {:?}: {}", folder_path, e);
-            Box::new(e)
-        })?;
-    println!("Successfully exported to {:?}", folder_path);
+    for (index, lead) in leads.iter().enumerate() {
+        let row = (index + 1) as u32;
+        table::write_date_text(worksheet, row, 0, &lead.formatted_date, &locale)?;
+        worksheet.write_string(row, 1, &lead.subreddit)?;
+        worksheet.write_string(row, 2, &lead.post_title)?;
+        worksheet.write_string(row, 3, anonymizer.author(&lead.author))?;
+        worksheet.write_string(row, 4, &lead.body)?;
+        worksheet.write_number(row, 5, lead.score as f64)?;
+        worksheet.write_string(row, 6, &lead.relevance)?;
+        worksheet.write_string(row, 7, &lead.intent)?;
+        worksheet.write_string(row, 8, matched_label(lead))?;
+        worksheet.write_string(row, 9, &lead.permalink)?;
+    }
+
+    worksheet.set_column_width(0, 20)?; // Date
+    worksheet.set_column_width(1, 20)?; // Subreddit
+    worksheet.set_column_width(2, 50)?; // Post Title
+    worksheet.set_column_width(3, 20)?; // Author
+    worksheet.set_column_width(4, 100)?; // Comment
+    worksheet.set_column_width(5, 10)?; // Score
+    worksheet.set_column_width(6, 12)?; // Relevance
+    worksheet.set_column_width(7, 24)?; // Intent
+    worksheet.set_column_width(8, 24)?; // Matched
+    worksheet.set_column_width(9, 50)?; // Link
+
+    let filename = dated_filename("Reddit_comment_leads", group);
+    let folder_path = exports::reddit_data_folder()?;
+    fs::create_dir_all(&folder_path)?;
+
+    let saved = save_leads_workbook(&mut workbook, &folder_path.join(filename))?;
+    println!("Successfully exported to {:?}", saved);
     Ok(())
 }
 
-// Export the filtered data by the LLM into a .xlsx
-pub fn export_gemini_to_excel(json_str: &str) -> Result<(), XlsxError> {
+// Export the filtered data by the LLM into a .xlsx, labelled with the keyword
+// group the leads were generated for
+pub fn export_gemini_to_excel(json_str: &str, group: &str) -> Result<(), XlsxError> {
     let gemini_values: Vec<Value> = match serde_json::from_str(json_str) {
         Ok(arr) => arr,
         Err(_) => {
             match serde_json::from_str::<Value>(json_str) {
                 Ok(obj) => vec![obj],
                 Err(e) => {
-                    eprintln!("Warning: Failed to parse JSON, using empty data. Error: {}", e);
-                    eprintln!("JSON content (first 1000 chars): {}", &json_str[..json_str.len().min(1000)]);
+                    eprintln!(
+                        "Warning: Failed to parse JSON, using empty data. Error: {}",
+                        e
+                    );
+                    eprintln!(
+                        "JSON content (first 1000 chars): {}",
+                        truncate(json_str, 1000)
+                    );
                     Vec::new() // Return empty vector instead of failing
                 }
             }
         }
     };
 
-    println!("Processing {} items from JSON", gemini_values.len());
+    // Everything is still saved to the database, only the export honours min_relevance
+    let config = ConfigDirs::read_config().unwrap_or_default();
+    let min_relevance = config.leads.min_relevance;
+    let locale = ExportLocale::from_settings(&config.exports)
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
+    let gemini_values: Vec<Value> = gemini_values
+        .into_iter()
+        .filter(|lead| min_relevance.allows(lead["relevance"].as_str().unwrap_or_default()))
+        .collect();
+
+    let anonymizer = Anonymizer::from_config()
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e.to_string())))?;
+
+    verbosity::info(format!(
+        "Processing {} items from JSON",
+        gemini_values.len()
+    ));
 
     // Create workbook
     let mut workbook = Workbook::new();
@@ -133,7 +407,7 @@ pub fn export_gemini_to_excel(json_str: &str) -> Result<(), XlsxError> {
             worksheet.write_string(row, 1, url)?;
 
             if let Some(date) = obj.get("formatted_date").and_then(|v| v.as_str()) {
-                worksheet.write_string(row, 2, date)?;
+                table::write_date_text(worksheet, row, 2, date, &locale)?;
             }
             if let Some(relevance) = obj.get("relevance").and_then(|v| v.as_str()) {
                 worksheet.write_string(row, 3, relevance)?;
@@ -184,7 +458,7 @@ pub fn export_gemini_to_excel(json_str: &str) -> Result<(), XlsxError> {
                     if let Some(comment_obj) = comment.as_object() {
                         worksheet.write_string(row_num, 0, title)?;
                         if let Some(author) = comment_obj.get("author").and_then(|v| v.as_str()) {
-                            worksheet.write_string(row_num, 1, author)?;
+                            worksheet.write_string(row_num, 1, anonymizer.author(author))?;
                         }
                         if let Some(text) = comment_obj.get("text").and_then(|v| v.as_str()) {
                             worksheet.write_string(row_num, 2, text)?;
@@ -211,29 +485,11 @@ pub fn export_gemini_to_excel(json_str: &str) -> Result<(), XlsxError> {
         worksheet.set_column_width(4, 30)?; // URL
     }
 
-    // Get user's desktop directory
-    let user_dirs = UserDirs::new().ok_or_else(|| {
-        XlsxError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Failed to get user directories",
-        ))
-    })?;
-
-    let desktop = user_dirs.desktop_dir().ok_or_else(|| {
-        XlsxError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Failed to get desktop directory",
-        ))
-    })?;
-
     // Create output directory and save file
-    let folder_name = "Reddit_data";
-    let filename = format!(
-        "Ruddit_leads_{}.xlsx",
-        Local::now().format("%d-%m-%Y_%H-%M-%S")
-    );
+    let filename = dated_filename("Ruddit_leads", group);
 
-    let folder_path = desktop.join(folder_name);
+    let folder_path = exports::reddit_data_folder()
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e.to_string())))?;
     // Create directory with better error handling
     if let Err(e) = fs::create_dir_all(&folder_path) {
         eprintln!("Failed to create directory {:?}: {}", folder_path, e);
@@ -241,25 +497,102 @@ pub fn export_gemini_to_excel(json_str: &str) -> Result<(), XlsxError> {
     }
 
     let save_path = folder_path.join(&filename);
-    workbook.save(&save_path).map_err(|e| {
+    let saved = save_leads_workbook(&mut workbook, &save_path).map_err(|e| {
         eprintln!("Failed to save workbook to {:?}: {}", save_path, e);
         e
     })?;
-    println!("Successfully exported to {:?}", save_path);
+    println!("Successfully exported to {:?}", saved);
     Ok(())
 }
 
+// Lead files hold prospect details, with leads_password set the workbook only
+// ever lands on disk inside a password-protected zip. Nothing is written when
+// the settings cannot be read, as the password could not be checked.
+fn save_leads_workbook(workbook: &mut Workbook, path: &Path) -> Result<PathBuf, XlsxError> {
+    let password = ConfigDirs::read_config()
+        .map_err(|e| {
+            XlsxError::IoError(std::io::Error::other(format!(
+                "Not writing the lead file, leads_password could not be checked: {}",
+                e
+            )))
+        })?
+        .exports
+        .leads_password;
+    if password.is_empty() {
+        workbook.save(path)?;
+        return Ok(path.to_path_buf());
+    }
+
+    let buffer = workbook.save_to_buffer()?;
+    let entry_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("leads.xlsx");
+    let zip_path = path.with_extension("zip");
+
+    // ZipCrypto opens in every unzip tool, including the one built into Windows
+    let options = SimpleFileOptions::default().with_deprecated_encryption(password.as_bytes());
+    let mut archive = zip::ZipWriter::new(fs::File::create(&zip_path)?);
+    archive
+        .start_file(entry_name, options)
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
+    archive.write_all(&buffer)?;
+    archive
+        .finish()
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
+
+    Ok(zip_path)
+}
+
 // Function to export comments for a specific post
-pub fn export_comments_from_db(post_id: &str) -> Result<(), XlsxError> {
+pub async fn export_comments_from_db(token: &str, post_id: &str) -> Result<(), XlsxError> {
     // Get comments from database
-    let db = DB::new()
-        .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
+    let db = DB::new().map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
 
-    let comments = db
+    let mut comments = db
         .get_post_comments(post_id)
         .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
 
-    println!("Exporting {} comments to Excel", comments.len());
+    // The post is neither stored nor named on its comments, ask Reddit for it
+    if comments.iter().any(|comment| comment.post_title.is_empty()) {
+        match crate::get_post_info(token, post_id).await {
+            Ok(Some(post)) => {
+                for comment in comments.iter_mut() {
+                    if comment.post_title.is_empty() {
+                        comment.post_title = post.title.clone();
+                    }
+                    if comment.subreddit.is_empty() {
+                        comment.subreddit = post.subreddit.clone();
+                    }
+                }
+            }
+            Ok(None) => eprintln!(
+                "Post {} is no longer on Reddit, its title is left empty",
+                post_id
+            ),
+            Err(e) => eprintln!("Failed to fetch the title of post {}: {}", post_id, e),
+        }
+    }
+
+    let notes = db
+        .create_notes_tables()
+        .and_then(|_| db.get_comment_notes())
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
+    let anonymizer = Anonymizer::from_config()
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e.to_string())))?;
+    let exports = ConfigDirs::read_config()
+        .map(|config| config.exports)
+        .unwrap_or_default();
+    let locale = ExportLocale::from_settings(&exports)
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
+    let fields = fields::select(
+        fields::COMMENT_FIELDS,
+        &exports.columns.comments,
+        fields::DEFAULT_COMMENT_FIELDS,
+    )
+    .map_err(|e| XlsxError::IoError(std::io::Error::other(e)))?;
+
+    verbosity::info(format!("Exporting {} comments to Excel", comments.len()));
 
     // Create workbook and worksheet
     let mut workbook = Workbook::new();
@@ -268,63 +601,38 @@ pub fn export_comments_from_db(post_id: &str) -> Result<(), XlsxError> {
     // Set up headers with formatting
     let header_format = Format::new().set_align(FormatAlign::Center).set_bold();
 
-    let headers = [
-        "Subreddit",
-        "Post Title",
-        "Author",
-        "Comment",
-        "Score",
-        "Date",
-        "Link",
-    ];
-    for (col, header) in headers.iter().enumerate() {
-        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
+    for (col, field) in fields.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, field.header, &header_format)?;
+        let width = match field.name {
+            "comment" => 100,
+            "post_title" | "link" | "note" => 50,
+            "score" => 10,
+            _ => 20,
+        };
+        worksheet.set_column_width(col as u16, width)?;
     }
 
     // Write comment data
-    for (idx, comment) in comments.iter().enumerate() {
+    for (idx, mut comment) in comments.into_iter().enumerate() {
         let row = (idx + 1) as u32;
-        worksheet.write_string(row, 0, &comment.subreddit)?;
-        worksheet.write_string(row, 1, &comment.post_id)?;
-        worksheet.write_string(row, 2, &comment.author)?;
-        worksheet.write_string(row, 3, &comment.body)?;
-        worksheet.write_number(row, 4, comment.score as f64)?;
-        worksheet.write_string(row, 5, &comment.formatted_date)?;
-        worksheet.write_string(row, 6, format!("https://reddit.com{}", comment.permalink))?;
+        comment.author = anonymizer.author(&comment.author);
+        let note = notes.get(&comment.id).cloned().unwrap_or_default();
+        let values = fields::values(&fields, &CommentRow { comment, note });
+        for (col, cell) in values.iter().enumerate() {
+            table::write_cell(worksheet, row, col as u16, cell, &locale)?;
+        }
     }
 
-    // Set column widths
-    worksheet.set_column_width(0, 20)?; // Subreddit
-    worksheet.set_column_width(1, 50)?; // Post Title
-    worksheet.set_column_width(2, 20)?; // Author
-    worksheet.set_column_width(3, 100)?; // Comment
-    worksheet.set_column_width(4, 10)?; // Score
-    worksheet.set_column_width(5, 20)?; // Date
-    worksheet.set_column_width(6, 50)?; // Link
-
     // Save the workbook
-    let user_dirs = UserDirs::new().ok_or_else(|| {
-        XlsxError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Failed to get user directories",
-        ))
-    })?;
 
-    let desktop = user_dirs.desktop_dir().ok_or_else(|| {
-        XlsxError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Failed to get desktop directory",
-        ))
-    })?;
-
-    let folder_name = "Reddit_data";
     let filename = format!(
         "Reddit_comments_{}_{}",
         post_id,
         Local::now().format("%d-%m-%Y_%H-%M-%S")
     );
 
-    let folder_path = desktop.join(folder_name);
+    let folder_path = exports::reddit_data_folder()
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e.to_string())))?;
     // Create directory with better error handling
     if let Err(e) = fs::create_dir_all(&folder_path) {
         eprintln!("Failed to create directory {:?}: {}", folder_path, e);
@@ -342,7 +650,7 @@ pub fn export_comments_from_db(post_id: &str) -> Result<(), XlsxError> {
 
 // Function to export the leads that are generated from the LLM
 pub async fn export_leads_with_gemini(data: &str) -> Result<(), XlsxError> {
-    export_gemini_to_excel(data)
+    export_gemini_to_excel(data, "")
 }
 
 // Function to export the comments that are generated from the LLM
@@ -350,12 +658,18 @@ pub async fn export_comments_with_gemini(data: &str) -> Result<(), XlsxError> {
     let json_data: Value = match serde_json::from_str(data) {
         Ok(value) => value,
         Err(e) => {
-            eprintln!("Warning: Failed to parse JSON in export_comments_with_gemini, using empty object. Error: {}", e);
-            eprintln!("JSON content (first 1000 chars): {}", &data[..data.len().min(1000)]);
+            eprintln!(
+                "Warning: Failed to parse JSON in export_comments_with_gemini, using empty object. Error: {}",
+                e
+            );
+            eprintln!("JSON content (first 1000 chars): {}", truncate(data, 1000));
             Value::Null
         }
     };
 
+    let anonymizer = Anonymizer::from_config()
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e.to_string())))?;
+
     let mut workbook = Workbook::new();
     let worksheet = workbook.add_worksheet();
     worksheet.set_name("Comments")?;
@@ -391,10 +705,12 @@ pub async fn export_comments_with_gemini(data: &str) -> Result<(), XlsxError> {
                             worksheet.write_string(
                                 row,
                                 1,
-                                comment_obj
-                                    .get("author")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or_default(),
+                                anonymizer.author(
+                                    comment_obj
+                                        .get("author")
+                                        .and_then(|v| v.as_str())
+                                        .unwrap_or_default(),
+                                ),
                             )?;
                             worksheet.write_string(
                                 row,
@@ -427,27 +743,13 @@ pub async fn export_comments_with_gemini(data: &str) -> Result<(), XlsxError> {
     worksheet.set_column_width(3, 15)?;
     worksheet.set_column_width(4, 30)?;
 
-    let user_dirs = UserDirs::new().ok_or_else(|| {
-        XlsxError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Failed to get user directories",
-        ))
-    })?;
-
-    let desktop = user_dirs.desktop_dir().ok_or_else(|| {
-        XlsxError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Failed to get desktop directory",
-        ))
-    })?;
-
-    let folder_name = "Reddit_data";
     let filename = format!(
         "Ruddit_comments_{}.xlsx",
         Local::now().format("%d-%m-%Y_%H-%M-%S")
     );
 
-    let folder_path = desktop.join(folder_name);
+    let folder_path = exports::reddit_data_folder()
+        .map_err(|e| XlsxError::IoError(std::io::Error::other(e.to_string())))?;
     if let Err(e) = fs::create_dir_all(&folder_path) {
         eprintln!("Failed to create directory {:?}: {}", folder_path, e);
         return Err(XlsxError::IoError(e));
@@ -0,0 +1,166 @@
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::exports::locale::ExportLocale;
+
+// A value of an export row. Dates keep their timestamp and numbers their value
+// so each format can write them its own way.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Date(i64),
+}
+
+impl Cell {
+    // The cell as text, for CSV and NDJSON files
+    pub fn render(&self, locale: &ExportLocale) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(value) => locale.number(*value),
+            Cell::Date(timestamp) => locale.date(*timestamp),
+        }
+    }
+}
+
+// A column of a tabular export: the name used in `[exports.columns]`, the
+// header written to the file and how to read it from a row
+pub struct Field<T> {
+    pub name: &'static str,
+    pub header: &'static str,
+    value: fn(&T) -> Cell,
+}
+
+impl<T> Field<T> {
+    const fn new(name: &'static str, header: &'static str, value: fn(&T) -> Cell) -> Self {
+        Field {
+            name,
+            header,
+            value,
+        }
+    }
+}
+
+// A stored post with the intent it was tagged with, its `ruddit tag` tags
+// and its `ruddit note`
+pub struct PostRow {
+    pub post: PostDataWrapper,
+    pub intent: String,
+    pub tags: Vec<String>,
+    pub note: String,
+}
+
+// A stored comment with its `ruddit note`
+pub struct CommentRow {
+    pub comment: CommentDataWrapper,
+    pub note: String,
+}
+
+pub const POST_FIELDS: &[Field<PostRow>] = &[
+    Field::new("date", "Date", |row| Cell::Date(row.post.timestamp)),
+    Field::new("title", "Title", |row| Cell::Text(row.post.title.clone())),
+    Field::new("url", "URL", |row| Cell::Text(row.post.url.clone())),
+    Field::new("relevance", "Relevance", |row| {
+        Cell::Text(row.post.relevance.clone())
+    }),
+    Field::new("subreddit", "Subreddit", |row| {
+        Cell::Text(row.post.subreddit.clone())
+    }),
+    Field::new("intent", "Intent", |row| Cell::Text(row.intent.clone())),
+    Field::new("id", "ID", |row| {
+        Cell::Text(DB::encode_reddit_id(row.post.id))
+    }),
+    Field::new("link", "Link", |row| Cell::Text(row.post.permalink.clone())),
+    Field::new("text", "Text", |row| Cell::Text(row.post.selftext.clone())),
+    Field::new("tags", "Tags", |row| Cell::Text(row.tags.join(", "))),
+    Field::new("note", "Note", |row| Cell::Text(row.note.clone())),
+];
+
+// Columns exported when none are configured
+pub const DEFAULT_POST_FIELDS: &[&str] = &[
+    "date",
+    "title",
+    "url",
+    "relevance",
+    "subreddit",
+    "intent",
+    "note",
+];
+
+pub const COMMENT_FIELDS: &[Field<CommentRow>] = &[
+    Field::new("date", "Date", |row| Cell::Date(row.comment.timestamp)),
+    Field::new("subreddit", "Subreddit", |row| {
+        Cell::Text(row.comment.subreddit.clone())
+    }),
+    Field::new("post_title", "Post Title", |row| {
+        Cell::Text(row.comment.post_title.clone())
+    }),
+    Field::new("author", "Author", |row| {
+        Cell::Text(row.comment.author.clone())
+    }),
+    Field::new("comment", "Comment", |row| {
+        Cell::Text(row.comment.body.clone())
+    }),
+    Field::new("score", "Score", |row| {
+        Cell::Number(row.comment.score as f64)
+    }),
+    Field::new("link", "Link", |row| {
+        Cell::Text(format!("https://reddit.com{}", row.comment.permalink))
+    }),
+    Field::new("id", "ID", |row| Cell::Text(row.comment.id.clone())),
+    Field::new("post_id", "Post ID", |row| {
+        Cell::Text(row.comment.post_id.clone())
+    }),
+    Field::new("parent_id", "Parent ID", |row| {
+        Cell::Text(row.comment.parent_id.clone())
+    }),
+    Field::new("note", "Note", |row| Cell::Text(row.note.clone())),
+];
+
+pub const DEFAULT_COMMENT_FIELDS: &[&str] = &[
+    "date",
+    "subreddit",
+    "post_title",
+    "author",
+    "comment",
+    "score",
+    "link",
+    "note",
+];
+
+// The fields named in the config, in their order. An empty list keeps the
+// defaults, an unknown name is an error listing the ones available.
+pub fn select<T>(
+    fields: &'static [Field<T>],
+    names: &[String],
+    defaults: &[&str],
+) -> Result<Vec<&'static Field<T>>, String> {
+    let names: Vec<&str> = if names.is_empty() {
+        defaults.to_vec()
+    } else {
+        names.iter().map(|name| name.trim()).collect()
+    };
+
+    names
+        .into_iter()
+        .map(|name| {
+            fields
+                .iter()
+                .find(|field| field.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    let available: Vec<&str> = fields.iter().map(|field| field.name).collect();
+                    format!(
+                        "Unknown export column '{}', use one of: {}",
+                        name,
+                        available.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+pub fn headers<T>(fields: &[&Field<T>]) -> Vec<&'static str> {
+    fields.iter().map(|field| field.header).collect()
+}
+
+pub fn values<T>(fields: &[&Field<T>], row: &T) -> Vec<Cell> {
+    fields.iter().map(|field| (field.value)(row)).collect()
+}
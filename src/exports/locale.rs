@@ -0,0 +1,175 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+use crate::settings::api_keys::{ConfigDirs, ExportSettings};
+
+// Layout of stored dates, in UTC
+const STORED_DATE: &str = "%Y-%m-%d %H:%M:%S";
+// Layout of dates stored without a time, such as the post date of Gemini leads
+const STORED_DAY: &str = "%Y-%m-%d";
+
+// Order of day, month and year in exported dates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateOrder {
+    // 2025-01-31 14:05:00, as stored
+    #[default]
+    Ymd,
+    // 31/01/2025 14:05:00
+    Dmy,
+    // 01/31/2025 14:05:00
+    Mdy,
+}
+
+impl DateOrder {
+    fn pattern(self) -> &'static str {
+        match self {
+            DateOrder::Ymd => STORED_DATE,
+            DateOrder::Dmy => "%d/%m/%Y %H:%M:%S",
+            DateOrder::Mdy => "%m/%d/%Y %H:%M:%S",
+        }
+    }
+
+    // Number format of datetime cells in workbooks
+    pub fn excel_format(self) -> &'static str {
+        match self {
+            DateOrder::Ymd => "yyyy-mm-dd hh:mm:ss",
+            DateOrder::Dmy => "dd/mm/yyyy hh:mm:ss",
+            DateOrder::Mdy => "mm/dd/yyyy hh:mm:ss",
+        }
+    }
+
+    // Number format of whole days, such as report periods
+    pub fn excel_day_format(self) -> &'static str {
+        match self {
+            DateOrder::Ymd => "yyyy-mm-dd",
+            DateOrder::Dmy => "dd/mm/yyyy",
+            DateOrder::Mdy => "mm/dd/yyyy",
+        }
+    }
+}
+
+// A date kept as stored text, with or without its time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoredDate {
+    Timestamp(i64),
+    Day(NaiveDate),
+}
+
+// None for text in another layout
+pub fn parse_stored(stored: &str) -> Option<StoredDate> {
+    let stored = stored.trim();
+    NaiveDateTime::parse_from_str(stored, STORED_DATE)
+        .map(|date| StoredDate::Timestamp(date.and_utc().timestamp()))
+        .or_else(|_| NaiveDate::parse_from_str(stored, STORED_DAY).map(StoredDate::Day))
+        .ok()
+}
+
+// How dates and numbers are written to CSV files, and the order of the dates
+// in workbooks, from `date_order` and `decimal_separator` under [exports]. With a decimal
+// comma CSV columns are separated by ";", which is what Excel expects where
+// the comma is the decimal separator.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportLocale {
+    pub date_order: DateOrder,
+    pub decimal_comma: bool,
+}
+
+impl ExportLocale {
+    pub fn from_settings(settings: &ExportSettings) -> Result<Self, String> {
+        let date_order = match settings.date_order.trim().to_lowercase().as_str() {
+            "" | "ymd" => DateOrder::Ymd,
+            "dmy" => DateOrder::Dmy,
+            "mdy" => DateOrder::Mdy,
+            other => {
+                return Err(format!(
+                    "Unknown date_order '{}' under [exports], use ymd, dmy or mdy",
+                    other
+                ));
+            }
+        };
+        let decimal_comma = match settings.decimal_separator.trim() {
+            "" | "." => false,
+            "," => true,
+            other => {
+                return Err(format!(
+                    "Unknown decimal_separator '{}' under [exports], use \".\" or \",\"",
+                    other
+                ));
+            }
+        };
+        Ok(ExportLocale {
+            date_order,
+            decimal_comma,
+        })
+    }
+
+    pub fn from_config() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::from_settings(&ConfigDirs::read_config()?.exports)?)
+    }
+
+    // A stored UTC timestamp in the configured order
+    pub fn date(&self, timestamp: i64) -> String {
+        DateTime::from_timestamp(timestamp, 0)
+            .map(|date| date.format(self.date_order.pattern()).to_string())
+            .unwrap_or_default()
+    }
+
+    // Whole numbers without decimals, others with the configured separator
+    pub fn number(&self, value: f64) -> String {
+        let text = if value.fract() == 0.0 && value.abs() < 1e15 {
+            format!("{}", value as i64)
+        } else {
+            value.to_string()
+        };
+        if self.decimal_comma {
+            text.replace('.', ",")
+        } else {
+            text
+        }
+    }
+
+    pub fn csv_delimiter(&self) -> u8 {
+        if self.decimal_comma { b';' } else { b',' }
+    }
+
+    // A CSV writer using the configured delimiter
+    pub fn csv_writer(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<csv::Writer<std::fs::File>, csv::Error> {
+        csv::WriterBuilder::new()
+            .delimiter(self.csv_delimiter())
+            .from_path(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stored_timestamps() {
+        assert_eq!(
+            parse_stored("2025-01-31 14:05:00"),
+            Some(StoredDate::Timestamp(1738332300))
+        );
+    }
+
+    #[test]
+    fn parses_days_stored_without_a_time() {
+        assert_eq!(
+            parse_stored("2025-01-31"),
+            NaiveDate::from_ymd_opt(2025, 1, 31).map(StoredDate::Day)
+        );
+        assert_eq!(
+            parse_stored(" 2025-01-31 "),
+            NaiveDate::from_ymd_opt(2025, 1, 31).map(StoredDate::Day)
+        );
+    }
+
+    #[test]
+    fn other_layouts_stay_text() {
+        assert_eq!(parse_stored("31/01/2025"), None);
+        assert_eq!(parse_stored("2025-01-31T14:05:00Z"), None);
+        assert_eq!(parse_stored(""), None);
+    }
+}
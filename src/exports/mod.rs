@@ -1 +1,27 @@
+pub mod anonymize;
+pub mod answer;
+pub mod duckdb;
 pub mod excel;
+pub mod fields;
+pub mod locale;
+pub mod obsidian;
+pub mod pdf;
+pub mod report;
+pub mod rss;
+pub mod table;
+pub mod thread;
+
+use std::path::PathBuf;
+
+use crate::settings::api_keys::ConfigDirs;
+use crate::settings::paths::Paths;
+
+// Folder exports are written to: `folder` under [exports] when set, else
+// Reddit_data on the desktop. Servers and containers have no desktop folder,
+// there it is the exports folder next to the database.
+pub fn reddit_data_folder() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let configured = ConfigDirs::load_config()
+        .map(|config| config.exports.folder)
+        .unwrap_or_default();
+    Ok(Paths::get()?.exports_dir(&configured))
+}
@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::actions::interrupt;
+use crate::actions::progress::Progress;
+use crate::actions::verbosity;
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::database::queries::PostFilter;
+use crate::exports;
+use crate::exports::anonymize::Anonymizer;
+use crate::settings;
+
+// Number of comments rendered under each note
+const TOP_COMMENTS: usize = 5;
+
+// Write one Markdown note per stored post into the Obsidian vault folder
+// Write the notes and return the vault folder they went to
+pub fn export_obsidian_vault(filter: &PostFilter) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let posts = db.get_filtered_posts(filter)?;
+    let intents = db.get_post_intents()?;
+    let anonymizer = Anonymizer::from_config()?;
+
+    let vault_path = vault_folder()?;
+    fs::create_dir_all(&vault_path)?;
+
+    verbosity::info(format!(
+        "Exporting {} posts to {:?}",
+        posts.len(),
+        vault_path
+    ));
+
+    let progress = Progress::start("Writing notes...");
+    let mut written = 0;
+    for (index, post) in posts.iter().enumerate() {
+        if interrupt::interrupted() {
+            break;
+        }
+        progress.set_label(format!("Writing note {}/{}", index + 1, posts.len()));
+        let reddit_id = DB::encode_reddit_id(post.id);
+
+        let mut comments = db.get_post_comments(&reddit_id)?;
+        comments.sort_by_key(|c| std::cmp::Reverse(c.score));
+        comments.truncate(TOP_COMMENTS);
+
+        let filename = format!("{} {}.md", reddit_id, sanitize_filename(&post.title));
+        fs::write(
+            vault_path.join(filename),
+            render_note(
+                post,
+                &reddit_id,
+                intents.get(&post.id).map_or("", |intent| intent),
+                &comments,
+                &anonymizer,
+            ),
+        )?;
+        written += 1;
+    }
+
+    progress.finish();
+
+    if interrupt::interrupted() {
+        println!(
+            "Interrupted: wrote {} of {} notes to {:?}",
+            written,
+            posts.len(),
+            vault_path
+        );
+        return Ok(vault_path);
+    }
+    println!("Successfully exported to {:?}", vault_path);
+    Ok(vault_path)
+}
+
+// Configured vault folder, or Reddit_data/obsidian
+fn vault_folder() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let configured = settings::api_keys::ConfigDirs::read_config()
+        .map(|config| config.exports.obsidian_vault)
+        .unwrap_or_default();
+
+    if !configured.trim().is_empty() {
+        return Ok(PathBuf::from(configured.trim()));
+    }
+
+    Ok(exports::reddit_data_folder()?.join("obsidian"))
+}
+
+fn render_note(
+    post: &PostDataWrapper,
+    reddit_id: &str,
+    intent: &str,
+    comments: &[CommentDataWrapper],
+    anonymizer: &Anonymizer,
+) -> String {
+    let mut note = String::new();
+
+    // Front-matter
+    note.push_str("---\n");
+    note.push_str(&format!("title: {}\n", yaml_string(&post.title)));
+    note.push_str(&format!("id: {}\n", yaml_string(reddit_id)));
+    note.push_str(&format!("subreddit: {}\n", yaml_string(&post.subreddit)));
+    note.push_str(&format!("date: {}\n", yaml_string(&post.formatted_date)));
+    note.push_str(&format!("url: {}\n", yaml_string(&post.url)));
+    note.push_str(&format!("permalink: {}\n", yaml_string(&post.permalink)));
+    if !intent.is_empty() {
+        note.push_str(&format!("intent: {}\n", yaml_string(intent)));
+    }
+    note.push_str("tags:\n");
+    note.push_str("  - reddit\n");
+    note.push_str(&format!("  - {}\n", tag(&post.subreddit)));
+    if !post.relevance.is_empty() {
+        note.push_str(&format!("  - {}\n", tag(&post.relevance)));
+    }
+    note.push_str("---\n\n");
+
+    // Body
+    note.push_str(&format!("# {}\n\n", post.title));
+    if !post.selftext.is_empty() {
+        note.push_str(&post.selftext);
+        note.push_str("\n\n");
+    }
+    note.push_str(&format!("[View on Reddit]({})\n", post.permalink));
+
+    if !comments.is_empty() {
+        note.push_str("\n## Top comments\n");
+        for comment in comments {
+            note.push('\n');
+            for line in comment.body.lines() {
+                note.push_str(&format!("> {}\n", line));
+            }
+            note.push_str(&format!(
+                "> — u/{} · {} points · {}\n",
+                anonymizer.author(&comment.author),
+                comment.score,
+                comment.formatted_date
+            ));
+        }
+    }
+
+    note
+}
+
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn tag(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+fn sanitize_filename(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+
+    cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(80)
+        .collect()
+}
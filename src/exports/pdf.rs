@@ -0,0 +1,256 @@
+// Minimal PDF writer for text documents: A4 pages, the built-in Helvetica
+// fonts and lines wrapped to the page width. Characters outside Latin-1 are
+// written as '?', the standard fonts have no glyphs for them.
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const LINE_SPACING: f32 = 1.35;
+
+// Average Helvetica glyph width as a share of the font size, used to wrap
+const CHAR_WIDTH: f32 = 0.5;
+const BOLD_CHAR_WIDTH: f32 = 0.55;
+
+struct Line {
+    x: f32,
+    y: f32,
+    size: f32,
+    bold: bool,
+    text: String,
+}
+
+pub struct PdfText {
+    pages: Vec<Vec<Line>>,
+    // Baseline of the next line on the current page
+    y: f32,
+}
+
+impl PdfText {
+    pub fn new() -> Self {
+        PdfText {
+            pages: vec![Vec::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    // Wrapped text starting `indent` points from the left margin. Line breaks
+    // in the text are kept.
+    pub fn paragraph(&mut self, text: &str, indent: f32, size: f32, bold: bool) {
+        let indent = indent.min(PAGE_WIDTH / 2.0);
+        let width = if bold { BOLD_CHAR_WIDTH } else { CHAR_WIDTH };
+        let max_chars = ((PAGE_WIDTH - 2.0 * MARGIN - indent) / (size * width)).max(10.0) as usize;
+
+        for line in text.lines() {
+            for wrapped in wrap(line, max_chars) {
+                self.line(wrapped, indent, size, bold);
+            }
+        }
+    }
+
+    // Vertical gap of `height` points
+    pub fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    // One table row, each cell starting at its column's offset from the left
+    // margin and cut to the space before the next column
+    pub fn row(&mut self, cells: &[String], columns: &[f32], size: f32, bold: bool) {
+        let width = if bold { BOLD_CHAR_WIDTH } else { CHAR_WIDTH };
+        self.advance(size);
+        for (index, (cell, x)) in cells.iter().zip(columns).enumerate() {
+            let end = columns
+                .get(index + 1)
+                .copied()
+                .unwrap_or(PAGE_WIDTH - 2.0 * MARGIN);
+            let max_chars = ((end - x) / (size * width)).max(2.0) as usize - 1;
+            let mut text: String = cell.chars().take(max_chars).collect();
+            if cell.chars().count() > max_chars {
+                text.pop();
+                text.push('\u{2026}');
+            }
+            self.push(text, *x, size, bold);
+        }
+    }
+
+    fn line(&mut self, text: String, indent: f32, size: f32, bold: bool) {
+        self.advance(size);
+        self.push(text, indent, size, bold);
+    }
+
+    // Move down one line of `size`, onto a new page when this one is full
+    fn advance(&mut self, size: f32) {
+        let height = size * LINE_SPACING;
+        if self.y - height < MARGIN {
+            self.pages.push(Vec::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+    }
+
+    fn push(&mut self, text: String, indent: f32, size: f32, bold: bool) {
+        if let Some(page) = self.pages.last_mut() {
+            page.push(Line {
+                x: MARGIN + indent,
+                y: self.y,
+                size,
+                bold,
+                text,
+            });
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // 1 catalog, 2 page tree, 3 and 4 fonts, then a page and its content per page
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|index| 5 + index * 2).collect();
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{} 0 R", id))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                page_ids.len()
+            )
+            .into_bytes(),
+            font("Helvetica"),
+            font("Helvetica-Bold"),
+        ];
+
+        for (page, id) in self.pages.iter().zip(&page_ids) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    id + 1
+                )
+                .into_bytes(),
+            );
+
+            let mut content = Vec::new();
+            for line in page {
+                content.extend_from_slice(
+                    format!(
+                        "BT /{} {} Tf {:.1} {:.1} Td (",
+                        if line.bold { "F2" } else { "F1" },
+                        line.size,
+                        line.x,
+                        line.y
+                    )
+                    .as_bytes(),
+                );
+                content.extend(encode(&line.text));
+                content.extend_from_slice(b") Tj ET\n");
+            }
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend(content);
+            stream.extend_from_slice(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            pdf.extend_from_slice(object);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = pdf.len();
+        pdf.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        pdf.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .as_bytes(),
+        );
+        pdf
+    }
+}
+
+impl Default for PdfText {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn font(name: &str) -> Vec<u8> {
+    format!(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+        name
+    )
+    .into_bytes()
+}
+
+// Words packed into lines of at most `max_chars`, longer words are cut
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > max_chars {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..max_chars).collect());
+        }
+        if word.is_empty() {
+            continue;
+        }
+        let word: String = word.into_iter().collect();
+
+        let length = current.chars().count();
+        if length > 0 && length + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+
+    // Blank lines keep their place as paragraph breaks
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+// WinAnsi bytes of a PDF string literal, with its delimiters escaped
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                bytes.push(c as u8);
+            }
+            c if (' '..='\u{ff}').contains(&c) && !('\u{7f}'..'\u{a0}').contains(&c) => {
+                bytes.push(c as u32 as u8)
+            }
+            '\t' => bytes.push(b' '),
+            // Typographic punctuation WinAnsi has outside Latin-1
+            '\u{2018}' => bytes.push(0x91),
+            '\u{2019}' => bytes.push(0x92),
+            '\u{201c}' => bytes.push(0x93),
+            '\u{201d}' => bytes.push(0x94),
+            '\u{2022}' => bytes.push(0x95),
+            '\u{2013}' => bytes.push(0x96),
+            '\u{2014}' => bytes.push(0x97),
+            '\u{2026}' => bytes.push(0x85),
+            _ => bytes.push(b'?'),
+        }
+    }
+    bytes
+}
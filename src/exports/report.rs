@@ -0,0 +1,684 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use rust_xlsxwriter::{
+    Chart, ChartFormat, ChartSolidFill, ChartType, Format, FormatAlign, Workbook,
+};
+
+use crate::arguments::modeling::{ImageFormat, Period};
+use crate::charts;
+use crate::database::adding::DB;
+use crate::database::reports::{PeriodReport, PeriodStats, SentimentCount};
+use crate::exports;
+use crate::exports::locale::ExportLocale;
+use crate::exports::pdf::PdfText;
+use crate::exports::table;
+
+// Threads listed in the "Top threads" section
+const TOP_THREADS: usize = 10;
+
+// `ruddit report weekly`: summarise the last 7 days as Markdown, HTML, Excel and PDF
+pub fn write_weekly_report(output: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let until = Utc::now();
+    let since = until - Duration::days(7);
+
+    let db = DB::new()?;
+    db.create_tables()?;
+    let report = db.get_period_report(since.timestamp(), until.timestamp(), TOP_THREADS)?;
+
+    let folder_path = match output {
+        Some(path) => path,
+        None => exports::reddit_data_folder()?.join("reports"),
+    };
+    fs::create_dir_all(&folder_path)?;
+
+    let name = format!("weekly_report_{}", until.format("%Y-%m-%d"));
+    // PNG for the Markdown, which not every viewer renders SVG in, SVG for the HTML
+    let formats = [ImageFormat::Png, ImageFormat::Svg];
+    charts::post_volume(&report.posts_by_day).write(
+        &folder_path,
+        &format!("{}_posts", name),
+        &formats,
+    )?;
+    charts::sentiment(&report.sentiment_by_week).write(
+        &folder_path,
+        &format!("{}_sentiment", name),
+        &formats,
+    )?;
+    if !report.share_of_voice.is_empty() {
+        charts::share_of_voice(&report.share_of_voice).write(
+            &folder_path,
+            &format!("{}_share_of_voice", name),
+            &formats,
+        )?;
+    }
+
+    fs::write(
+        folder_path.join(format!("{}.md", name)),
+        render_markdown(&report, &name),
+    )?;
+    fs::write(
+        folder_path.join(format!("{}.html", name)),
+        render_html(&report, &name),
+    )?;
+    write_excel(&report, &folder_path.join(format!("{}.xlsx", name)))?;
+    fs::write(
+        folder_path.join(format!("{}.pdf", name)),
+        render_pdf(&report),
+    )?;
+
+    println!(
+        "{} new posts, {} new comments, {} new leads",
+        report.new_posts,
+        report.new_comments,
+        report
+            .leads_by_status
+            .iter()
+            .map(|status| status.leads)
+            .sum::<i64>()
+    );
+    println!("Weekly report written to {:?}", folder_path.join(name));
+    Ok(())
+}
+
+fn day(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn title(report: &PeriodReport) -> String {
+    format!(
+        "Ruddit weekly report, {} to {}",
+        day(report.since),
+        day(report.until)
+    )
+}
+
+// `name` is the file name the charts written next to the report start with
+fn render_markdown(report: &PeriodReport, name: &str) -> String {
+    let mut md = format!("# {}\n\n", title(report));
+
+    md.push_str("## Overview\n\n");
+    md.push_str(&format!("![Posts per day]({}_posts.png)\n\n", name));
+    md.push_str(&format!("- New posts: {}\n", report.new_posts));
+    md.push_str(&format!("- New comments: {}\n", report.new_comments));
+    for subreddit in &report.posts_per_subreddit {
+        md.push_str(&format!(
+            "  - r/{}: {} posts\n",
+            subreddit.subreddit, subreddit.posts
+        ));
+    }
+
+    md.push_str("\n## Top threads\n\n");
+    if report.top_threads.is_empty() {
+        md.push_str("No new threads this week.\n");
+    } else {
+        md.push_str("| Thread | Subreddit | Date | Comments | Comment score |\n");
+        md.push_str("| --- | --- | --- | --- | --- |\n");
+        for thread in &report.top_threads {
+            md.push_str(&format!(
+                "| [{}]({}) | r/{} | {} | {} | {} |\n",
+                thread.title.replace('|', "\\|"),
+                thread.permalink,
+                thread.subreddit,
+                thread.formatted_date,
+                thread.comments,
+                thread.comment_score
+            ));
+        }
+    }
+
+    md.push_str("\n## Sentiment of new leads\n\n");
+    if report.sentiment_by_day.is_empty() {
+        md.push_str("No leads were generated this week.\n");
+    } else {
+        md.push_str("| Day | Positive | Neutral | Negative |\n");
+        md.push_str("| --- | --- | --- | --- |\n");
+        for day in &report.sentiment_by_day {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                day.period, day.positive, day.neutral, day.negative
+            ));
+        }
+    }
+
+    md.push_str("\n## Sentiment trend, last 8 weeks\n\n");
+    if report.sentiment_by_week.is_empty() {
+        md.push_str("No leads in the last 8 weeks.\n");
+    } else {
+        md.push_str(&format!(
+            "Net sentiment: `{}`\n\n",
+            sparkline(&report.sentiment_by_week)
+        ));
+        md.push_str(&format!(
+            "![Lead sentiment per week]({}_sentiment.png)\n\n",
+            name
+        ));
+        md.push_str("| Week | Positive | Neutral | Negative | Net |\n");
+        md.push_str("| --- | --- | --- | --- | --- |\n");
+        for week in &report.sentiment_by_week {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {:+.2} |\n",
+                week.period,
+                week.positive,
+                week.neutral,
+                week.negative,
+                week.net()
+            ));
+        }
+    }
+
+    if !report.share_of_voice.is_empty() {
+        md.push_str("\n## Share of voice\n\n");
+        md.push_str(&format!(
+            "![Share of voice]({}_share_of_voice.png)\n\n",
+            name
+        ));
+        md.push_str("| Keywords | Mentions |\n| --- | --- |\n");
+        for entry in &report.share_of_voice {
+            md.push_str(&format!("| {} | {} |\n", entry.name, entry.mentions));
+        }
+    }
+
+    md.push_str("\n## New leads by status\n\n");
+    if report.leads_by_status.is_empty() {
+        md.push_str("No new leads.\n");
+    }
+    for status in &report.leads_by_status {
+        md.push_str(&format!("- {}: {}\n", status.status, status.leads));
+    }
+
+    md
+}
+
+fn render_html(report: &PeriodReport, name: &str) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(&title(report))));
+    html.push_str(
+        "<style>body{font-family:sans-serif;max-width:960px;margin:2em auto}\
+         table{border-collapse:collapse}td,th{border:1px solid #ccc;padding:4px 8px}</style>\n",
+    );
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&title(report))));
+
+    html.push_str("<h2>Overview</h2>\n");
+    html.push_str(&format!(
+        "<img src=\"{}_posts.svg\" alt=\"Posts per day\">\n<ul>\n",
+        escape_html(name)
+    ));
+    html.push_str(&format!("<li>New posts: {}</li>\n", report.new_posts));
+    html.push_str(&format!("<li>New comments: {}</li>\n", report.new_comments));
+    for subreddit in &report.posts_per_subreddit {
+        html.push_str(&format!(
+            "<li>r/{}: {} posts</li>\n",
+            escape_html(&subreddit.subreddit),
+            subreddit.posts
+        ));
+    }
+    html.push_str("</ul>\n");
+
+    html.push_str("<h2>Top threads</h2>\n<table>\n");
+    html.push_str(
+        "<tr><th>Thread</th><th>Subreddit</th><th>Date</th><th>Comments</th><th>Comment score</th></tr>\n",
+    );
+    for thread in &report.top_threads {
+        html.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>r/{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&thread.permalink),
+            escape_html(&thread.title),
+            escape_html(&thread.subreddit),
+            escape_html(&thread.formatted_date),
+            thread.comments,
+            thread.comment_score
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Sentiment of new leads</h2>\n<table>\n");
+    html.push_str("<tr><th>Day</th><th>Positive</th><th>Neutral</th><th>Negative</th></tr>\n");
+    for day in &report.sentiment_by_day {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            day.period, day.positive, day.neutral, day.negative
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Sentiment trend, last 8 weeks</h2>\n");
+    if report.sentiment_by_week.is_empty() {
+        html.push_str("<p>No leads in the last 8 weeks.</p>\n");
+    } else {
+        html.push_str(&format!(
+            "<img src=\"{}_sentiment.svg\" alt=\"Lead sentiment per week\">\n",
+            escape_html(name)
+        ));
+    }
+
+    if !report.share_of_voice.is_empty() {
+        html.push_str("<h2>Share of voice</h2>\n");
+        html.push_str(&format!(
+            "<img src=\"{}_share_of_voice.svg\" alt=\"Share of voice\">\n<table>\n",
+            escape_html(name)
+        ));
+        html.push_str("<tr><th>Keywords</th><th>Mentions</th></tr>\n");
+        for entry in &report.share_of_voice {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&entry.name),
+                entry.mentions
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("<h2>New leads by status</h2>\n<ul>\n");
+    for status in &report.leads_by_status {
+        html.push_str(&format!(
+            "<li>{}: {}</li>\n",
+            escape_html(&status.status),
+            status.leads
+        ));
+    }
+    html.push_str("</ul>\n</body>\n</html>\n");
+
+    html
+}
+
+// The Markdown sections as a document to attach to an email
+fn render_pdf(report: &PeriodReport) -> Vec<u8> {
+    let mut pdf = PdfText::new();
+    pdf.paragraph(&title(report), 0.0, 16.0, true);
+
+    let heading = |pdf: &mut PdfText, text: &str| {
+        pdf.space(12.0);
+        pdf.paragraph(text, 0.0, 13.0, true);
+        pdf.space(2.0);
+    };
+
+    heading(&mut pdf, "Overview");
+    pdf.paragraph(
+        &format!("New posts: {}", report.new_posts),
+        0.0,
+        10.0,
+        false,
+    );
+    pdf.paragraph(
+        &format!("New comments: {}", report.new_comments),
+        0.0,
+        10.0,
+        false,
+    );
+    for subreddit in &report.posts_per_subreddit {
+        pdf.paragraph(
+            &format!("r/{}: {} posts", subreddit.subreddit, subreddit.posts),
+            14.0,
+            10.0,
+            false,
+        );
+    }
+
+    heading(&mut pdf, "Top threads");
+    if report.top_threads.is_empty() {
+        pdf.paragraph("No new threads this week.", 0.0, 10.0, false);
+    } else {
+        let columns = [0.0, 245.0, 330.0, 405.0, 455.0];
+        let headers = ["Thread", "Subreddit", "Date", "Comments", "Score"];
+        pdf.row(&headers.map(String::from), &columns, 9.0, true);
+        for thread in &report.top_threads {
+            let cells = [
+                thread.title.clone(),
+                format!("r/{}", thread.subreddit),
+                // The day is enough in a column this narrow
+                thread
+                    .formatted_date
+                    .split(' ')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                thread.comments.to_string(),
+                thread.comment_score.to_string(),
+            ];
+            pdf.row(&cells, &columns, 9.0, false);
+        }
+    }
+
+    let sentiment_columns = [0.0, 90.0, 160.0, 230.0, 300.0];
+    heading(&mut pdf, "Sentiment of new leads");
+    if report.sentiment_by_day.is_empty() {
+        pdf.paragraph("No leads were generated this week.", 0.0, 10.0, false);
+    } else {
+        let headers = ["Day", "Positive", "Neutral", "Negative"];
+        pdf.row(&headers.map(String::from), &sentiment_columns, 9.0, true);
+        for day in &report.sentiment_by_day {
+            let cells = [
+                day.period.clone(),
+                day.positive.to_string(),
+                day.neutral.to_string(),
+                day.negative.to_string(),
+            ];
+            pdf.row(&cells, &sentiment_columns, 9.0, false);
+        }
+    }
+
+    heading(&mut pdf, "Sentiment trend, last 8 weeks");
+    if report.sentiment_by_week.is_empty() {
+        pdf.paragraph("No leads in the last 8 weeks.", 0.0, 10.0, false);
+    } else {
+        let headers = ["Week", "Positive", "Neutral", "Negative", "Net"];
+        pdf.row(&headers.map(String::from), &sentiment_columns, 9.0, true);
+        for week in &report.sentiment_by_week {
+            let cells = [
+                week.period.clone(),
+                week.positive.to_string(),
+                week.neutral.to_string(),
+                week.negative.to_string(),
+                format!("{:+.2}", week.net()),
+            ];
+            pdf.row(&cells, &sentiment_columns, 9.0, false);
+        }
+    }
+
+    if !report.share_of_voice.is_empty() {
+        heading(&mut pdf, "Share of voice");
+        let headers = ["Keywords", "Mentions"];
+        pdf.row(&headers.map(String::from), &sentiment_columns, 9.0, true);
+        for entry in &report.share_of_voice {
+            let cells = [entry.name.clone(), entry.mentions.to_string()];
+            pdf.row(&cells, &sentiment_columns, 9.0, false);
+        }
+    }
+
+    heading(&mut pdf, "New leads by status");
+    if report.leads_by_status.is_empty() {
+        pdf.paragraph("No new leads.", 0.0, 10.0, false);
+    }
+    for status in &report.leads_by_status {
+        pdf.paragraph(
+            &format!("{}: {}", status.status, status.leads),
+            0.0,
+            10.0,
+            false,
+        );
+    }
+
+    pdf.to_bytes()
+}
+
+// One worksheet per section
+fn write_excel(report: &PeriodReport, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let locale = ExportLocale::from_config()?;
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_align(FormatAlign::Center).set_bold();
+
+    let overview = workbook.add_worksheet();
+    overview.set_name("Overview")?;
+    overview.write_string_with_format(0, 0, title(report), &header_format)?;
+    overview.write_string(1, 0, "New posts")?;
+    overview.write_number(1, 1, report.new_posts as f64)?;
+    overview.write_string(2, 0, "New comments")?;
+    overview.write_number(2, 1, report.new_comments as f64)?;
+    for (index, status) in report.leads_by_status.iter().enumerate() {
+        let row = (index + 4) as u32;
+        overview.write_string(row, 0, format!("Leads {}", status.status))?;
+        overview.write_number(row, 1, status.leads as f64)?;
+    }
+    overview.autofit();
+
+    let threads = workbook.add_worksheet();
+    threads.set_name("Top Threads")?;
+    let headers = [
+        "Title",
+        "Subreddit",
+        "Date",
+        "Comments",
+        "Comment Score",
+        "URL",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        threads.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+    for (index, thread) in report.top_threads.iter().enumerate() {
+        let row = (index + 1) as u32;
+        threads.write_string(row, 0, &thread.title)?;
+        threads.write_string(row, 1, &thread.subreddit)?;
+        table::write_date_text(threads, row, 2, &thread.formatted_date, &locale)?;
+        threads.write_number(row, 3, thread.comments as f64)?;
+        threads.write_number(row, 4, thread.comment_score as f64)?;
+        threads.write_string(row, 5, &thread.permalink)?;
+    }
+    threads.autofit();
+
+    let sentiment = workbook.add_worksheet();
+    sentiment.set_name("Sentiment")?;
+    let headers = ["Day", "Positive", "Neutral", "Negative"];
+    for (col, header) in headers.iter().enumerate() {
+        sentiment.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+    for (index, day) in report.sentiment_by_day.iter().enumerate() {
+        let row = (index + 1) as u32;
+        table::write_day(sentiment, row, 0, &day.period, &locale)?;
+        sentiment.write_number(row, 1, day.positive as f64)?;
+        sentiment.write_number(row, 2, day.neutral as f64)?;
+        sentiment.write_number(row, 3, day.negative as f64)?;
+    }
+    sentiment.autofit();
+
+    // Weekly counts with a stacked column chart next to them
+    let trend = workbook.add_worksheet();
+    trend.set_name("Sentiment Trend")?;
+    let headers = ["Week", "Positive", "Neutral", "Negative", "Net"];
+    for (col, header) in headers.iter().enumerate() {
+        trend.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+    for (index, week) in report.sentiment_by_week.iter().enumerate() {
+        let row = (index + 1) as u32;
+        trend.write_string(row, 0, &week.period)?;
+        trend.write_number(row, 1, week.positive as f64)?;
+        trend.write_number(row, 2, week.neutral as f64)?;
+        trend.write_number(row, 3, week.negative as f64)?;
+        trend.write_number(row, 4, week.net())?;
+    }
+    trend.autofit();
+
+    if !report.sentiment_by_week.is_empty() {
+        let last_row = report.sentiment_by_week.len() as u32;
+        let mut chart = Chart::new(ChartType::ColumnStacked);
+        chart.title().set_name("Lead sentiment per week");
+        for (col, color) in [(1, "70AD47"), (2, "A5A5A5"), (3, "C00000")] {
+            chart
+                .add_series()
+                .set_name(("Sentiment Trend", 0, col))
+                .set_categories(("Sentiment Trend", 1, 0, last_row, 0))
+                .set_values(("Sentiment Trend", 1, col, last_row, col))
+                .set_format(
+                    ChartFormat::new().set_solid_fill(ChartSolidFill::new().set_color(color)),
+                );
+        }
+        trend.insert_chart(1, 6, &chart)?;
+    }
+
+    workbook.save(path)?;
+    Ok(())
+}
+
+// `ruddit report charts`: the report charts of the last `days` days as standalone images
+pub fn write_charts(
+    days: u32,
+    format: Option<ImageFormat>,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let until = Utc::now();
+    let since = until - Duration::days(days as i64);
+
+    let db = DB::new()?;
+    db.create_tables()?;
+    let posts = db.get_posts_by_day(since.timestamp(), until.timestamp())?;
+    let weeks = (days as usize).div_ceil(7);
+    let sentiment = db.get_sentiment_by_week(weeks, until.timestamp())?;
+    let share_of_voice = db.get_share_of_voice(since.timestamp())?;
+
+    let folder_path = match output {
+        Some(path) => path,
+        None => exports::reddit_data_folder()?.join("charts"),
+    };
+    fs::create_dir_all(&folder_path)?;
+
+    let formats = match format {
+        Some(format) => vec![format],
+        None => vec![ImageFormat::Png, ImageFormat::Svg],
+    };
+    let date = until.format("%Y-%m-%d");
+    let mut written =
+        charts::post_volume(&posts).write(&folder_path, &format!("posts_{}", date), &formats)?;
+    written.extend(charts::sentiment(&sentiment).write(
+        &folder_path,
+        &format!("sentiment_{}", date),
+        &formats,
+    )?);
+    if share_of_voice.is_empty() {
+        println!("No share of voice chart, set BRANDED_KEYWORDS or [[keyword_groups]]");
+    } else {
+        written.extend(charts::share_of_voice(&share_of_voice).write(
+            &folder_path,
+            &format!("share_of_voice_{}", date),
+            &formats,
+        )?);
+    }
+
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+// `ruddit report compare`: period B against period A, with percentage changes
+pub fn print_comparison(
+    period_a: Period,
+    period_b: Period,
+    keywords: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let (since, until) = period_a.timestamps();
+    let a = db.get_period_stats(since, until)?;
+    let (since, until) = period_b.timestamps();
+    let b = db.get_period_stats(since, until)?;
+
+    println!(
+        "{:<28} {:>24} {:>24} {:>8}",
+        "",
+        format!("A {}", period_a),
+        format!("B {}", period_b),
+        "Change"
+    );
+    let row = |label: &str, a: i64, b: i64| {
+        println!("{:<28} {:>24} {:>24} {:>8}", label, a, b, change(a, b));
+    };
+    row("Posts", a.posts, b.posts);
+    row("Comments", a.comments, b.comments);
+    row("Leads", a.leads, b.leads);
+    row("  positive", a.sentiment.positive, b.sentiment.positive);
+    row("  neutral", a.sentiment.neutral, b.sentiment.neutral);
+    row("  negative", a.sentiment.negative, b.sentiment.negative);
+    println!(
+        "{:<28} {:>24} {:>24} {:>+8.2}",
+        "Net lead sentiment",
+        format!("{:+.2}", a.sentiment.net()),
+        format!("{:+.2}", b.sentiment.net()),
+        b.sentiment.net() - a.sentiment.net()
+    );
+
+    // The top keywords of either period, busiest in B first
+    let mut top: Vec<&String> = Vec::new();
+    for stats in [&b, &a] {
+        let mut ranked: Vec<(&String, &i64)> = stats.keywords.iter().collect();
+        ranked.sort_by(|x, y| y.1.cmp(x.1).then(x.0.cmp(y.0)));
+        for (keyword, mentions) in ranked.into_iter().take(keywords) {
+            if *mentions > 0 && !top.contains(&keyword) {
+                top.push(keyword);
+            }
+        }
+    }
+
+    println!("\nTop keywords, posts and comments mentioning them");
+    if top.is_empty() {
+        println!("  No keyword mentions in either period");
+    }
+    for keyword in top {
+        let mentions = |stats: &PeriodStats| stats.keywords.get(keyword).copied().unwrap_or(0);
+        row(
+            &format!("  {}", keyword.chars().take(26).collect::<String>()),
+            mentions(&a),
+            mentions(&b),
+        );
+    }
+    Ok(())
+}
+
+// Percentage change from `a` to `b`, "new" when `a` is zero
+fn change(a: i64, b: i64) -> String {
+    if a == 0 {
+        return if b == 0 {
+            "0%".to_string()
+        } else {
+            "new".to_string()
+        };
+    }
+    format!("{:+.0}%", (b - a) as f64 * 100.0 / a as f64)
+}
+
+// `ruddit report sentiment`: weekly lead sentiment with a sparkline of the net score
+pub fn print_sentiment_trend(weeks: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let trend = db.get_sentiment_by_week(weeks, Utc::now().timestamp())?;
+
+    if trend.is_empty() {
+        println!("No leads in the last {} weeks", weeks);
+        return Ok(());
+    }
+
+    println!(
+        "Net lead sentiment, last {} weeks: {}\n",
+        weeks,
+        sparkline(&trend)
+    );
+    println!(
+        "{:<10} {:>8} {:>8} {:>8} {:>6}",
+        "Week", "Positive", "Neutral", "Negative", "Net"
+    );
+    for week in &trend {
+        println!(
+            "{:<10} {:>8} {:>8} {:>8} {:>+6.2}",
+            week.period,
+            week.positive,
+            week.neutral,
+            week.negative,
+            week.net()
+        );
+    }
+    Ok(())
+}
+
+// One block per period, from all negative (lowest) to all positive (highest)
+fn sparkline(trend: &[SentimentCount]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    trend
+        .iter()
+        .map(|period| {
+            let level = ((period.net() + 1.0) / 2.0 * (BLOCKS.len() - 1) as f64).round();
+            BLOCKS[level as usize]
+        })
+        .collect()
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,128 @@
+use std::fs;
+use std::path::PathBuf;
+
+use axum::{Router, http::header, response::IntoResponse, routing::get};
+use chrono::{DateTime, Utc};
+
+use crate::database::adding::{DB, PostDataWrapper};
+use crate::exports;
+use crate::filters::keywords::KeywordMatcher;
+use crate::settings;
+
+// Build an RSS 2.0 feed of the most recent keyword-matching posts
+pub fn build_feed(limit: usize) -> Result<String, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    let posts = db.get_db_results()?;
+
+    let matcher = match settings::api_keys::ConfigDirs::read_config() {
+        Ok(config) => KeywordMatcher::from_config(&config)?,
+        Err(_) => KeywordMatcher::new(&[], ""),
+    };
+
+    // get_db_results is already sorted newest first
+    let items: Vec<&PostDataWrapper> = posts
+        .iter()
+        .filter(|post| matcher.matches(&format!("{} {}", post.title, post.selftext)))
+        .take(limit)
+        .collect();
+
+    Ok(render_feed(&items))
+}
+
+// Write the feed to a file, defaulting to Reddit_data/ruddit_feed.xml
+pub fn write_feed(output: Option<PathBuf>, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let feed = build_feed(limit)?;
+
+    let path = match output {
+        Some(path) => path,
+        None => {
+            let folder_path = exports::reddit_data_folder()?;
+            fs::create_dir_all(&folder_path)?;
+            folder_path.join("ruddit_feed.xml")
+        }
+    };
+
+    fs::write(&path, feed)?;
+    println!("Feed written to {:?}", path);
+    Ok(())
+}
+
+// Serve the feed over HTTP, rebuilt from the database on every request
+pub async fn serve_feed(addr: &str, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let app = Router::new()
+        .route("/", get(move || feed_handler(limit)))
+        .route("/feed.xml", get(move || feed_handler(limit)));
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Serving RSS feed on http://{}/feed.xml", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn feed_handler(limit: usize) -> impl IntoResponse {
+    match build_feed(limit) {
+        Ok(feed) => (
+            axum::http::StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+            feed,
+        ),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            format!("Failed to build feed: {}", e),
+        ),
+    }
+}
+
+fn render_feed(posts: &[&PostDataWrapper]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\">\n<channel>\n");
+    xml.push_str("  <title>Ruddit leads</title>\n");
+    xml.push_str("  <link>https://www.reddit.com</link>\n");
+    xml.push_str("  <description>Reddit posts matching your Ruddit keywords</description>\n");
+    xml.push_str(&format!(
+        "  <lastBuildDate>{}</lastBuildDate>\n",
+        Utc::now().to_rfc2822()
+    ));
+
+    for post in posts {
+        let pub_date = DateTime::from_timestamp(post.timestamp, 0)
+            .map(|date| date.to_rfc2822())
+            .unwrap_or_default();
+
+        xml.push_str("  <item>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&post.title)));
+        xml.push_str(&format!(
+            "    <link>{}</link>\n",
+            escape_xml(&post.permalink)
+        ));
+        xml.push_str(&format!(
+            "    <guid isPermaLink=\"true\">{}</guid>\n",
+            escape_xml(&post.permalink)
+        ));
+        xml.push_str(&format!("    <pubDate>{}</pubDate>\n", pub_date));
+        xml.push_str(&format!(
+            "    <category>{}</category>\n",
+            escape_xml(&post.subreddit)
+        ));
+        xml.push_str(&format!(
+            "    <description>{}</description>\n",
+            escape_xml(&post.selftext)
+        ));
+        xml.push_str("  </item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,263 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatAlign, Workbook, Worksheet};
+
+use crate::exports::fields::Cell;
+use crate::exports::locale::{self, ExportLocale, StoredDate};
+
+// Rows left in a worksheet once the header is written
+const XLSX_MAX_ROWS: usize = 1_048_575;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Xlsx,
+    Csv,
+    Ndjson,
+}
+
+impl TableFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            TableFormat::Xlsx => "xlsx",
+            TableFormat::Csv => "csv",
+            TableFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+enum Sink {
+    Xlsx(Box<Workbook>),
+    Csv(Box<csv::Writer<File>>),
+    Ndjson(BufWriter<File>),
+}
+
+// Writes rows to one or more files, starting a new `_partN` file every
+// `chunk` rows. CSV and NDJSON go straight to disk, an xlsx part is held in
+// memory until it is full, so a chunk size keeps big workbooks bounded. CSV
+// files follow the export locale, NDJSON always uses the stored layout.
+pub struct TableWriter<'a> {
+    folder: PathBuf,
+    stem: String,
+    sheet: &'a str,
+    columns: &'a [&'a str],
+    format: TableFormat,
+    locale: ExportLocale,
+    chunk: usize,
+    sink: Option<Sink>,
+    rows: usize,
+    paths: Vec<PathBuf>,
+}
+
+impl<'a> TableWriter<'a> {
+    pub fn new(
+        folder: &Path,
+        stem: &str,
+        sheet: &'a str,
+        columns: &'a [&'a str],
+        format: TableFormat,
+        locale: ExportLocale,
+        chunk: Option<usize>,
+    ) -> Self {
+        let max = match format {
+            TableFormat::Xlsx => XLSX_MAX_ROWS,
+            TableFormat::Csv | TableFormat::Ndjson => usize::MAX,
+        };
+        TableWriter {
+            folder: folder.to_path_buf(),
+            stem: stem.to_string(),
+            sheet,
+            columns,
+            format,
+            locale,
+            chunk: chunk.filter(|&size| size > 0).unwrap_or(max).min(max),
+            sink: None,
+            rows: 0,
+            paths: Vec::new(),
+        }
+    }
+
+    pub fn write_row(&mut self, cells: &[Cell]) -> Result<(), Box<dyn std::error::Error>> {
+        if self.sink.is_none() || self.rows == self.chunk {
+            self.start_part()?;
+        }
+        let row = self.rows;
+        match self.sink.as_mut() {
+            Some(Sink::Xlsx(workbook)) => {
+                let worksheet = workbook.worksheet_from_index(0)?;
+                for (col, cell) in cells.iter().enumerate() {
+                    write_cell(worksheet, (row + 1) as u32, col as u16, cell, &self.locale)?;
+                }
+            }
+            Some(Sink::Csv(writer)) => {
+                writer.write_record(cells.iter().map(|cell| cell.render(&self.locale)))?
+            }
+            Some(Sink::Ndjson(writer)) => {
+                // Written by hand so the keys keep the column order
+                writer.write_all(b"{")?;
+                for (index, (column, cell)) in self.columns.iter().zip(cells).enumerate() {
+                    if index > 0 {
+                        writer.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut *writer, column)?;
+                    writer.write_all(b":")?;
+                    serde_json::to_writer(&mut *writer, &cell.render(&ExportLocale::default()))?;
+                }
+                writer.write_all(b"}\n")?;
+            }
+            None => unreachable!("a part was just started"),
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    // Close the last part and return every file written, in order. A single
+    // part drops its `_part1` suffix, an export without rows still gets a header.
+    pub fn finish(mut self) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        if self.sink.is_none() && self.paths.is_empty() {
+            self.start_part()?;
+        }
+        self.close_part()?;
+        if let [only] = self.paths.as_mut_slice() {
+            let path = self
+                .folder
+                .join(format!("{}.{}", self.stem, self.format.extension()));
+            std::fs::rename(&*only, &path)?;
+            *only = path;
+        }
+        Ok(self.paths)
+    }
+
+    pub fn rows_written(&self) -> usize {
+        self.paths.len().saturating_sub(1) * self.chunk + self.rows
+    }
+
+    fn start_part(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.close_part()?;
+        let path = self.folder.join(format!(
+            "{}_part{}.{}",
+            self.stem,
+            self.paths.len() + 1,
+            self.format.extension()
+        ));
+
+        self.sink = Some(match self.format {
+            TableFormat::Xlsx => {
+                let mut workbook = Workbook::new();
+                let worksheet = workbook.add_worksheet();
+                worksheet.set_name(self.sheet)?;
+                write_header(worksheet, self.columns)?;
+                Sink::Xlsx(Box::new(workbook))
+            }
+            TableFormat::Csv => {
+                let mut writer = self.locale.csv_writer(&path)?;
+                writer.write_record(self.columns)?;
+                Sink::Csv(Box::new(writer))
+            }
+            TableFormat::Ndjson => Sink::Ndjson(BufWriter::new(File::create(&path)?)),
+        });
+        self.paths.push(path);
+        self.rows = 0;
+        Ok(())
+    }
+
+    fn close_part(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(sink) = self.sink.take() else {
+            return Ok(());
+        };
+        match sink {
+            Sink::Xlsx(mut workbook) => {
+                workbook.worksheet_from_index(0)?.autofit();
+                workbook.save(self.paths.last().expect("part has a path"))?;
+            }
+            Sink::Csv(mut writer) => writer.flush()?,
+            Sink::Ndjson(mut writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+// Numbers go in as numbers and dates as Excel datetimes shown in the locale's
+// order, so they sort, sum and work in pivot tables
+pub fn write_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    cell: &Cell,
+    locale: &ExportLocale,
+) -> Result<(), rust_xlsxwriter::XlsxError> {
+    match cell {
+        Cell::Text(text) => worksheet.write_string(row, col, text)?,
+        Cell::Number(value) => worksheet.write_number(row, col, *value)?,
+        Cell::Date(timestamp) => return write_datetime(worksheet, row, col, *timestamp, locale),
+    };
+    Ok(())
+}
+
+// Dates Excel cannot hold, before 1900, are written as text
+pub fn write_datetime(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    timestamp: i64,
+    locale: &ExportLocale,
+) -> Result<(), rust_xlsxwriter::XlsxError> {
+    match ExcelDateTime::from_timestamp(timestamp) {
+        Ok(datetime) => {
+            let format = Format::new().set_num_format(locale.date_order.excel_format());
+            worksheet.write_datetime_with_format(row, col, datetime, &format)?
+        }
+        Err(_) => worksheet.write_string(row, col, locale.date(timestamp))?,
+    };
+    Ok(())
+}
+
+// A date kept as stored text, such as the post date of a lead
+pub fn write_date_text(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    stored: &str,
+    locale: &ExportLocale,
+) -> Result<(), rust_xlsxwriter::XlsxError> {
+    match locale::parse_stored(stored) {
+        Some(StoredDate::Timestamp(timestamp)) => {
+            write_datetime(worksheet, row, col, timestamp, locale)
+        }
+        Some(StoredDate::Day(_)) => write_day(worksheet, row, col, stored.trim(), locale),
+        None => {
+            worksheet.write_string(row, col, stored)?;
+            Ok(())
+        }
+    }
+}
+
+// A day such as a report period, stored as YYYY-MM-DD
+pub fn write_day(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    day: &str,
+    locale: &ExportLocale,
+) -> Result<(), rust_xlsxwriter::XlsxError> {
+    match ExcelDateTime::parse_from_str(day) {
+        Ok(date) => {
+            let format = Format::new().set_num_format(locale.date_order.excel_day_format());
+            worksheet.write_datetime_with_format(row, col, date, &format)?
+        }
+        Err(_) => worksheet.write_string(row, col, day)?,
+    };
+    Ok(())
+}
+
+fn write_header(
+    worksheet: &mut Worksheet,
+    columns: &[&str],
+) -> Result<(), rust_xlsxwriter::XlsxError> {
+    let header_format = Format::new().set_align(FormatAlign::Center).set_bold();
+    for (col, column) in columns.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *column, &header_format)?;
+    }
+    Ok(())
+}
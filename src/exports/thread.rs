@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::arguments::modeling::ThreadFormat;
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::exports;
+use crate::exports::pdf::PdfText;
+use crate::exports::report::escape_html;
+
+// Points each reply level is indented by in the PDF
+const PDF_INDENT: f32 = 14.0;
+
+// `ruddit thread export`: a post and its comment tree as one readable document,
+// replies nested under their parent, best scoring first
+pub fn export_thread(
+    post: &PostDataWrapper,
+    comments: &[CommentDataWrapper],
+    format: ThreadFormat,
+    output: Option<PathBuf>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = match output {
+        Some(path) => path,
+        None => exports::reddit_data_folder()?.join("threads").join(format!(
+            "thread_{}.{}",
+            DB::encode_reddit_id(post.id),
+            extension(format)
+        )),
+    };
+    if let Some(folder) = path.parent()
+        && !folder.as_os_str().is_empty()
+    {
+        fs::create_dir_all(folder)?;
+    }
+
+    let tree = nest(comments);
+    match format {
+        ThreadFormat::Md => fs::write(&path, render_markdown(post, &tree))?,
+        ThreadFormat::Html => fs::write(&path, render_html(post, &tree))?,
+        ThreadFormat::Pdf => fs::write(&path, render_pdf(post, &tree))?,
+    }
+
+    println!(
+        "Exported the thread with {} comments to {}",
+        comments.len(),
+        path.display()
+    );
+    Ok(path)
+}
+
+fn extension(format: ThreadFormat) -> &'static str {
+    match format {
+        ThreadFormat::Md => "md",
+        ThreadFormat::Html => "html",
+        ThreadFormat::Pdf => "pdf",
+    }
+}
+
+// Comments in reading order with their reply depth. Comments whose parent
+// was not stored start a branch of their own.
+fn nest(comments: &[CommentDataWrapper]) -> Vec<(usize, &CommentDataWrapper)> {
+    let ids: HashSet<String> = comments
+        .iter()
+        .map(|comment| format!("t1_{}", comment.id))
+        .collect();
+    let mut roots = Vec::new();
+    let mut replies: HashMap<&str, Vec<&CommentDataWrapper>> = HashMap::new();
+    for comment in comments {
+        if ids.contains(&comment.parent_id) {
+            replies
+                .entry(comment.parent_id.as_str())
+                .or_default()
+                .push(comment);
+        } else {
+            roots.push(comment);
+        }
+    }
+
+    let order = |siblings: &mut Vec<&CommentDataWrapper>| {
+        siblings.sort_by(|a, b| b.score.cmp(&a.score).then(a.timestamp.cmp(&b.timestamp)));
+    };
+    order(&mut roots);
+    replies.values_mut().for_each(order);
+
+    let mut tree = Vec::with_capacity(comments.len());
+    let mut stack: Vec<(usize, &CommentDataWrapper)> = roots
+        .into_iter()
+        .rev()
+        .map(|comment| (0, comment))
+        .collect();
+    while let Some((depth, comment)) = stack.pop() {
+        tree.push((depth, comment));
+        if let Some(children) = replies.get(format!("t1_{}", comment.id).as_str()) {
+            stack.extend(children.iter().rev().map(|child| (depth + 1, *child)));
+        }
+    }
+    tree
+}
+
+fn byline(comment: &CommentDataWrapper) -> String {
+    format!(
+        "{} · {} points · {}",
+        comment.author, comment.score, comment.formatted_date
+    )
+}
+
+// The link of a link post, None for text posts
+fn link(post: &PostDataWrapper) -> Option<&str> {
+    (!post.url.is_empty() && !post.url.contains("/comments/")).then_some(post.url.as_str())
+}
+
+fn render_markdown(post: &PostDataWrapper, tree: &[(usize, &CommentDataWrapper)]) -> String {
+    let mut md = format!("# {}\n\n", post.title);
+    md.push_str(&format!(
+        "r/{} · {} · [Reddit]({})\n\n",
+        post.subreddit, post.formatted_date, post.permalink
+    ));
+    if let Some(url) = link(post) {
+        md.push_str(&format!("Link: <{}>\n\n", url));
+    }
+    if !post.selftext.trim().is_empty() {
+        md.push_str(post.selftext.trim());
+        md.push_str("\n\n");
+    }
+
+    md.push_str(&format!("## {} comments\n", tree.len()));
+    for (depth, comment) in tree {
+        // Each reply level is one more level of quoting
+        let quote = ">".repeat(depth + 1);
+        md.push_str(&format!("\n{} **{}**\n{}\n", quote, byline(comment), quote));
+        for line in comment.body.trim().lines() {
+            if line.trim().is_empty() {
+                md.push_str(&format!("{}\n", quote));
+            } else {
+                md.push_str(&format!("{} {}\n", quote, line));
+            }
+        }
+    }
+    md
+}
+
+fn render_html(post: &PostDataWrapper, tree: &[(usize, &CommentDataWrapper)]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(&post.title)));
+    html.push_str(
+        "<style>body{font-family:sans-serif;max-width:860px;margin:2em auto;line-height:1.45}\
+         .meta{color:#666;font-size:.9em}.comment{border-left:2px solid #ddd;padding:2px 0 2px 10px;margin:10px 0}\
+         .body{white-space:pre-wrap}</style>\n",
+    );
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&post.title)));
+    html.push_str(&format!(
+        "<p class=\"meta\">r/{} · {} · <a href=\"{}\">Reddit</a></p>\n",
+        escape_html(&post.subreddit),
+        escape_html(&post.formatted_date),
+        escape_html(&post.permalink)
+    ));
+    if let Some(url) = link(post) {
+        html.push_str(&format!(
+            "<p>Link: <a href=\"{0}\">{0}</a></p>\n",
+            escape_html(url)
+        ));
+    }
+    if !post.selftext.trim().is_empty() {
+        html.push_str(&format!(
+            "<p class=\"body\">{}</p>\n",
+            escape_html(post.selftext.trim())
+        ));
+    }
+
+    html.push_str(&format!("<h2>{} comments</h2>\n", tree.len()));
+    for (depth, comment) in tree {
+        html.push_str(&format!(
+            "<div class=\"comment\" style=\"margin-left:{}px\">\n<div class=\"meta\"><b>{}</b></div>\n<div class=\"body\">{}</div>\n</div>\n",
+            depth * 24,
+            escape_html(&byline(comment)),
+            escape_html(comment.body.trim())
+        ));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_pdf(post: &PostDataWrapper, tree: &[(usize, &CommentDataWrapper)]) -> Vec<u8> {
+    let mut pdf = PdfText::new();
+    pdf.paragraph(&post.title, 0.0, 16.0, true);
+    pdf.space(4.0);
+    pdf.paragraph(
+        &format!("r/{} · {}", post.subreddit, post.formatted_date),
+        0.0,
+        9.0,
+        false,
+    );
+    pdf.paragraph(&post.permalink, 0.0, 9.0, false);
+    if let Some(url) = link(post) {
+        pdf.paragraph(&format!("Link: {}", url), 0.0, 9.0, false);
+    }
+    if !post.selftext.trim().is_empty() {
+        pdf.space(8.0);
+        pdf.paragraph(post.selftext.trim(), 0.0, 10.0, false);
+    }
+
+    pdf.space(12.0);
+    pdf.paragraph(&format!("{} comments", tree.len()), 0.0, 13.0, true);
+    for (depth, comment) in tree {
+        let indent = *depth as f32 * PDF_INDENT;
+        pdf.space(6.0);
+        pdf.paragraph(&byline(comment), indent, 9.0, true);
+        pdf.paragraph(comment.body.trim(), indent, 10.0, false);
+    }
+    pdf.to_bytes()
+}
@@ -0,0 +1,71 @@
+// Subreddits and link domains never stored by fetches and searches
+
+use reqwest::Url;
+
+use crate::database::adding::PostDataWrapper;
+use crate::settings::api_keys::FetchSettings;
+
+#[derive(Debug, Default, Clone)]
+pub struct Exclusions {
+    subreddits: Vec<String>,
+    domains: Vec<String>,
+}
+
+impl Exclusions {
+    // Names are accepted as `memes`, `r/memes` or `/r/memes`, domains with or
+    // without `www.`
+    pub fn from_settings(settings: &FetchSettings) -> Self {
+        let subreddits = settings
+            .excluded_subreddits
+            .iter()
+            .map(|name| {
+                let name = name.trim().trim_start_matches('/');
+                name.strip_prefix("r/").unwrap_or(name).to_lowercase()
+            })
+            .filter(|name| !name.is_empty())
+            .collect();
+        let domains = settings
+            .excluded_domains
+            .iter()
+            .map(|domain| {
+                let domain = domain.trim().to_lowercase();
+                domain.strip_prefix("www.").unwrap_or(&domain).to_string()
+            })
+            .filter(|domain| !domain.is_empty())
+            .collect();
+
+        Exclusions {
+            subreddits,
+            domains,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subreddits.is_empty() && self.domains.is_empty()
+    }
+
+    // Whether the post is in an excluded subreddit or links to an excluded
+    // domain. A domain also covers its subdomains, `imgur.com` drops `i.imgur.com`.
+    pub fn excludes(&self, post: &PostDataWrapper) -> bool {
+        if self
+            .subreddits
+            .iter()
+            .any(|subreddit| subreddit.eq_ignore_ascii_case(&post.subreddit))
+        {
+            return true;
+        }
+
+        let Some(host) = Url::parse(&post.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+        else {
+            return false;
+        };
+        self.domains.iter().any(|domain| {
+            host == *domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+        })
+    }
+}
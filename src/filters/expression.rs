@@ -0,0 +1,397 @@
+use std::fmt;
+
+use crate::filters::fuzzy::{self, KeywordHit, MatchType, Text};
+use crate::settings::api_keys::MatchingSettings;
+
+// Boolean keyword expression such as `("freight" OR "3PL") AND NOT "job"`.
+// Terms match case-insensitively anywhere in the text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Term(String),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    // All keywords joined with AND or OR, the shape of LEAD_KEYWORDS and MATCH.
+    // None when there are no keywords.
+    pub fn from_keywords(keywords: &[String], match_all: bool) -> Option<Expr> {
+        let mut terms: Vec<Expr> = keywords
+            .iter()
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .map(Expr::Term)
+            .collect();
+
+        match terms.len() {
+            0 => None,
+            1 => terms.pop(),
+            _ if match_all => Some(Expr::And(terms)),
+            _ => Some(Expr::Or(terms)),
+        }
+    }
+
+    // Parse AND, OR, NOT (upper case), parentheses, "quoted phrases" and bare
+    // words. NOT binds tightest, then AND, then OR.
+    pub fn parse(input: &str) -> Result<Expr, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("Empty keyword expression".to_string());
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some(token) => Err(format!(
+                "Unexpected {} in keyword expression '{}'",
+                token, input
+            )),
+        }
+    }
+
+    // The first term that made the text match, None when it does not match
+    pub fn find(&self, text: &Text, settings: &MatchingSettings) -> Option<KeywordHit> {
+        let mut hit = None;
+        if !self.eval(text, settings, &mut hit) {
+            return None;
+        }
+        Some(hit.unwrap_or(KeywordHit {
+            term: String::new(),
+            match_type: MatchType::Exact,
+        }))
+    }
+
+    // The terms a text can match on, the ones under NOT left out
+    pub fn terms(&self) -> Vec<&str> {
+        match self {
+            Expr::Term(term) => vec![term.as_str()],
+            Expr::Not(_) => Vec::new(),
+            Expr::And(items) | Expr::Or(items) => items.iter().flat_map(Expr::terms).collect(),
+        }
+    }
+
+    // Only branches that hold may record their hit
+    fn eval(&self, text: &Text, settings: &MatchingSettings, hit: &mut Option<KeywordHit>) -> bool {
+        match self {
+            Expr::Term(term) => match fuzzy::find_term(term, text, settings) {
+                Some(match_type) => {
+                    hit.get_or_insert(KeywordHit {
+                        term: term.clone(),
+                        match_type,
+                    });
+                    true
+                }
+                None => false,
+            },
+            Expr::Not(inner) => !inner.eval(text, settings, &mut None),
+            Expr::And(items) => {
+                let mut first = None;
+                if items
+                    .iter()
+                    .all(|item| item.eval(text, settings, &mut first))
+                {
+                    if hit.is_none() {
+                        *hit = first;
+                    }
+                    return true;
+                }
+                false
+            }
+            Expr::Or(items) => items.iter().any(|item| {
+                let mut first = None;
+                let holds = item.eval(text, settings, &mut first);
+                if holds && hit.is_none() {
+                    *hit = first;
+                }
+                holds
+            }),
+        }
+    }
+}
+
+// Rendered back into the expression syntax, also used in the lead prompt
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, items: &[Expr], op: &str| {
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                match item {
+                    Expr::And(_) | Expr::Or(_) => write!(f, "({})", item)?,
+                    _ => write!(f, "{}", item)?,
+                }
+            }
+            Ok(())
+        };
+
+        match self {
+            Expr::Term(term) => write!(f, "\"{}\"", term),
+            Expr::Not(inner) => match inner.as_ref() {
+                Expr::And(_) | Expr::Or(_) => write!(f, "NOT ({})", inner),
+                _ => write!(f, "NOT {}", inner),
+            },
+            Expr::And(items) => join(f, items, "AND"),
+            Expr::Or(items) => join(f, items, "OR"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::Not => write!(f, "NOT"),
+            Token::Term(term) => write!(f, "\"{}\"", term),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut phrase = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => phrase.push(c),
+                        None => {
+                            return Err(format!(
+                                "Unclosed quote in keyword expression '{}'",
+                                input
+                            ));
+                        }
+                    }
+                }
+                let phrase = phrase.trim().to_lowercase();
+                if phrase.is_empty() {
+                    return Err(format!("Empty phrase in keyword expression '{}'", input));
+                }
+                tokens.push(Token::Term(phrase));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Term(word.to_lowercase()),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next_is(&self, token: &Token) -> bool {
+        self.tokens.get(self.pos) == Some(token)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut items = vec![self.and()?];
+        while self.next_is(&Token::Or) {
+            self.pos += 1;
+            items.push(self.and()?);
+        }
+        Ok(flatten(items, Expr::Or))
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut items = vec![self.unary()?];
+        while self.next_is(&Token::And) {
+            self.pos += 1;
+            items.push(self.unary()?);
+        }
+        Ok(flatten(items, Expr::And))
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.next_is(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+
+        match token {
+            Some(Token::Term(term)) => Ok(Expr::Term(term)),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                if !self.next_is(&Token::Close) {
+                    return Err("Missing ')' in keyword expression".to_string());
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(token) => Err(format!("Expected a keyword, found {}", token)),
+            None => Err("Keyword expression ends with an operator".to_string()),
+        }
+    }
+}
+
+fn flatten(mut items: Vec<Expr>, combine: fn(Vec<Expr>) -> Expr) -> Expr {
+    if items.len() == 1 {
+        items.remove(0)
+    } else {
+        combine(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(text: &str) -> Expr {
+        Expr::Term(text.to_string())
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_and_and_than_or() {
+        let expr = Expr::parse("a OR b AND NOT c").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Or(vec![
+                term("a"),
+                Expr::And(vec![term("b"), Expr::Not(Box::new(term("c")))]),
+            ])
+        );
+    }
+
+    #[test]
+    fn parentheses_group_before_operators() {
+        let expr = Expr::parse("(a OR b) AND NOT (c OR d)").unwrap();
+        assert_eq!(
+            expr,
+            Expr::And(vec![
+                Expr::Or(vec![term("a"), term("b")]),
+                Expr::Not(Box::new(Expr::Or(vec![term("c"), term("d")]))),
+            ])
+        );
+    }
+
+    #[test]
+    fn quoted_phrases_are_one_lowercase_term() {
+        let expr = Expr::parse("\"Supply Chain\" OR 3PL").unwrap();
+        assert_eq!(expr, Expr::Or(vec![term("supply chain"), term("3pl")]));
+        // Operators inside quotes are words of the phrase
+        assert_eq!(
+            Expr::parse("\"this AND that\"").unwrap(),
+            term("this and that")
+        );
+    }
+
+    #[test]
+    fn lowercase_operators_are_words() {
+        assert_eq!(
+            Expr::parse("a and b").unwrap_err(),
+            "Unexpected \"and\" in keyword expression 'a and b'"
+        );
+    }
+
+    #[test]
+    fn unclosed_quote_is_an_error() {
+        assert_eq!(
+            Expr::parse("\"freight AND job").unwrap_err(),
+            "Unclosed quote in keyword expression '\"freight AND job'"
+        );
+    }
+
+    #[test]
+    fn trailing_operator_is_an_error() {
+        assert_eq!(
+            Expr::parse("freight AND").unwrap_err(),
+            "Keyword expression ends with an operator"
+        );
+        assert_eq!(
+            Expr::parse("freight OR NOT").unwrap_err(),
+            "Keyword expression ends with an operator"
+        );
+    }
+
+    #[test]
+    fn adjacent_words_need_an_operator() {
+        assert_eq!(
+            Expr::parse("freight broker").unwrap_err(),
+            "Unexpected \"broker\" in keyword expression 'freight broker'"
+        );
+    }
+
+    #[test]
+    fn unbalanced_parentheses_and_empty_input_are_errors() {
+        assert_eq!(
+            Expr::parse("(a OR b").unwrap_err(),
+            "Missing ')' in keyword expression"
+        );
+        assert!(Expr::parse("a OR b)").is_err());
+        assert!(Expr::parse("\"\"").is_err());
+        assert_eq!(Expr::parse("  ").unwrap_err(), "Empty keyword expression");
+    }
+
+    #[test]
+    fn display_round_trips_through_parse() {
+        for input in [
+            "freight",
+            "a OR b AND NOT c",
+            "(\"freight\" OR \"3PL\") AND NOT \"job\"",
+            "NOT (a AND b) OR (c OR d) AND e",
+            "\"supply chain\" AND (wms OR tms)",
+        ] {
+            let expr = Expr::parse(input).unwrap();
+            let rendered = expr.to_string();
+            assert_eq!(Expr::parse(&rendered).unwrap(), expr, "{}", rendered);
+        }
+        assert_eq!(
+            Expr::parse("(a OR b) AND NOT c").unwrap().to_string(),
+            "(\"a\" OR \"b\") AND NOT \"c\""
+        );
+    }
+}
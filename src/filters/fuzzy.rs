@@ -0,0 +1,150 @@
+use crate::settings::api_keys::{MatchMode, MatchingSettings};
+
+// Endings removed to reduce a word to its stem, longest first
+const SUFFIXES: [&str; 12] = [
+    "ations", "ation", "ings", "edly", "ing", "ies", "ers", "ed", "er", "es", "ly", "s",
+];
+
+// Shortest stem a suffix may leave behind
+const MIN_STEM: usize = 3;
+
+// How a keyword was found in the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchType {
+    Exact,
+    Stem,
+    Fuzzy,
+}
+
+impl MatchType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchType::Exact => "exact",
+            MatchType::Stem => "stem",
+            MatchType::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+// The keyword that made a text match and how it was found. The term is empty
+// when the text only matched through NOT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordHit {
+    pub term: String,
+    pub match_type: MatchType,
+}
+
+// Text prepared once for all the terms of an expression
+pub struct Text {
+    lower: String,
+    // Stems of the words, only filled outside exact mode
+    stems: Vec<String>,
+}
+
+impl Text {
+    pub fn new(text: &str, mode: MatchMode) -> Self {
+        let lower = text.to_lowercase();
+        let stems = match mode {
+            MatchMode::Exact => Vec::new(),
+            MatchMode::Stem | MatchMode::Fuzzy => stems(&lower),
+        };
+        Text { lower, stems }
+    }
+}
+
+// How `term` (lower case) occurs in the text, None when it does not
+pub fn find_term(term: &str, text: &Text, settings: &MatchingSettings) -> Option<MatchType> {
+    if text.lower.contains(term) {
+        return Some(MatchType::Exact);
+    }
+    if settings.mode == MatchMode::Exact {
+        return None;
+    }
+
+    let term_stems = stems(term);
+    if term_stems.is_empty() {
+        return None;
+    }
+    if contains_run(&text.stems, &term_stems, |a, b| a == b) {
+        return Some(MatchType::Stem);
+    }
+
+    let max_distance = settings.max_distance.clamp(1, 2);
+    if settings.mode == MatchMode::Fuzzy
+        && contains_run(&text.stems, &term_stems, |word, keyword| {
+            distance(word, keyword) <= allowed_edits(keyword, max_distance)
+        })
+    {
+        return Some(MatchType::Fuzzy);
+    }
+    None
+}
+
+// "forecasting" and "forecasts" both give "forecast"
+pub fn stem(word: &str) -> String {
+    for suffix in SUFFIXES {
+        // "business" is not a plural
+        if suffix == "s" && word.ends_with("ss") {
+            continue;
+        }
+        if let Some(base) = word.strip_suffix(suffix)
+            && base.chars().count() >= MIN_STEM
+        {
+            return if suffix == "ies" {
+                format!("{}y", base)
+            } else {
+                drop_final_e(base)
+            };
+        }
+    }
+    drop_final_e(word)
+}
+
+// "price", "prices" and "pricing" all end up as "pric"
+fn drop_final_e(word: &str) -> String {
+    match word.strip_suffix('e') {
+        Some(base) if base.chars().count() >= MIN_STEM => base.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+fn stems(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(stem)
+        .collect()
+}
+
+// Whether `needle` appears as consecutive words of `haystack`
+fn contains_run(haystack: &[String], needle: &[String], same: impl Fn(&str, &str) -> bool) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window.iter().zip(needle).all(|(a, b)| same(a, b)))
+}
+
+// Short words are too easily confused with others to allow any edits
+fn allowed_edits(keyword: &str, max_distance: usize) -> usize {
+    match keyword.chars().count() {
+        0..4 => 0,
+        4..8 => 1,
+        _ => max_distance,
+    }
+}
+
+// Levenshtein distance in characters
+pub fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,126 @@
+use crate::settings::api_keys::{ConfigDirs, IntentRule};
+
+// Taxonomy used when no [[intents]] are configured, checked in this order
+const DEFAULT_INTENTS: [(&str, &[&str]); 5] = [
+    (
+        "hiring",
+        &[
+            "hiring",
+            "job opening",
+            "we're looking for a",
+            "freelancer wanted",
+            "contract role",
+        ],
+    ),
+    (
+        "comparison",
+        &[
+            " vs ",
+            " vs. ",
+            "versus",
+            "compared to",
+            "comparison",
+            "better than",
+        ],
+    ),
+    (
+        "seeking_recommendation",
+        &[
+            "recommend",
+            "suggestion",
+            "looking for",
+            "alternative to",
+            "alternatives to",
+            "what do you use",
+            "which tool",
+            "best tool",
+        ],
+    ),
+    (
+        "complaint",
+        &[
+            "frustrated",
+            "frustrating",
+            "terrible",
+            "worst",
+            "disappointed",
+            "doesn't work",
+            "does not work",
+            "awful",
+            "fed up",
+            "rant",
+        ],
+    ),
+    (
+        "news",
+        &[
+            "announces",
+            "announced",
+            "launches",
+            "launched",
+            "acquires",
+            "acquisition",
+            "released",
+        ],
+    ),
+];
+
+// Keyword-based intent tagging, the first intent with a matching keyword wins
+#[derive(Debug, Clone)]
+pub struct IntentClassifier {
+    intents: Vec<(String, Vec<String>)>,
+}
+
+impl IntentClassifier {
+    pub fn new(rules: &[IntentRule]) -> Self {
+        if rules.is_empty() {
+            return Self::default();
+        }
+
+        IntentClassifier {
+            intents: rules
+                .iter()
+                .map(|rule| {
+                    (
+                        rule.name.clone(),
+                        rule.keywords.iter().map(|k| k.to_lowercase()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    // The configured taxonomy, or the default one when the config can't be read
+    pub fn load() -> Self {
+        ConfigDirs::read_config()
+            .map(|config| Self::new(&config.intents))
+            .unwrap_or_default()
+    }
+
+    // Intent of a post, empty when nothing matches
+    pub fn classify(&self, title: &str, body: &str) -> String {
+        let text = format!(" {} {} ", title, body).to_lowercase();
+
+        self.intents
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|k| text.contains(k.as_str())))
+            .map(|(name, _)| name.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for IntentClassifier {
+    fn default() -> Self {
+        IntentClassifier {
+            intents: DEFAULT_INTENTS
+                .iter()
+                .map(|(name, keywords)| {
+                    (
+                        name.to_string(),
+                        keywords.iter().map(|k| k.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::filters::expression::Expr;
+use crate::filters::fuzzy::{self, KeywordHit, Text};
+use crate::settings::api_keys::{AppConfig, KeywordGroup, MatchMode, MatchingSettings};
+
+// Local keyword matching against the configured lead keywords or a keyword group
+#[derive(Debug, Clone)]
+pub struct KeywordMatcher {
+    // None when there are no keywords
+    expression: Option<Expr>,
+    // Keyword group the matcher was built from, empty for LEAD_KEYWORDS
+    group: String,
+    matching: MatchingSettings,
+}
+
+impl KeywordMatcher {
+    pub fn new(keywords: &[String], match_keyword: &str) -> Self {
+        KeywordMatcher {
+            group: String::new(),
+            matching: MatchingSettings::default(),
+            expression: Expr::from_keywords(keywords, match_keyword.eq_ignore_ascii_case("and")),
+        }
+    }
+
+    // A boolean expression such as `("freight" OR "3PL") AND NOT "job"`, an empty
+    // query gives an empty matcher
+    pub fn from_query(query: &str) -> Result<Self, String> {
+        let expression = if query.trim().is_empty() {
+            None
+        } else {
+            Some(Expr::parse(query)?)
+        };
+        Ok(KeywordMatcher {
+            group: String::new(),
+            matching: MatchingSettings::default(),
+            expression,
+        })
+    }
+
+    // LEAD_QUERY when set, otherwise LEAD_KEYWORDS combined with MATCH
+    pub fn from_config(config: &AppConfig) -> Result<Self, String> {
+        let api_keys = &config.api_keys;
+        let matcher = if api_keys.lead_query.trim().is_empty() {
+            Self::new(&api_keys.lead_keywords, &api_keys.match_keyword)
+        } else {
+            Self::from_query(&api_keys.lead_query)
+                .map_err(|e| format!("Invalid LEAD_QUERY: {}", e))?
+        };
+        Ok(matcher.with_matching(&config.matching))
+    }
+
+    pub fn from_group(group: &KeywordGroup, matching: &MatchingSettings) -> Result<Self, String> {
+        let matcher = if group.query.trim().is_empty() {
+            Self::new(&group.keywords, &group.match_keyword)
+        } else {
+            Self::from_query(&group.query)
+                .map_err(|e| format!("Invalid query of keyword group '{}': {}", group.name, e))?
+        };
+        Ok(KeywordMatcher {
+            group: group.name.clone(),
+            ..matcher.with_matching(matching)
+        })
+    }
+
+    // Stemming and fuzzy matching from the [matching] settings
+    pub fn with_matching(self, matching: &MatchingSettings) -> Self {
+        KeywordMatcher {
+            matching: *matching,
+            ..self
+        }
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    // Whether word forms or typos also count, for the lead prompt
+    pub fn mode(&self) -> MatchMode {
+        self.matching.mode
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expression.is_none()
+    }
+
+    // The expression in the query syntax, for messages and the lead prompt
+    pub fn describe(&self) -> String {
+        self.expression
+            .as_ref()
+            .map(|expression| expression.to_string())
+            .unwrap_or_default()
+    }
+
+    // An empty matcher lets everything through
+    pub fn matches(&self, text: &str) -> bool {
+        self.expression.is_none() || self.find(text).is_some()
+    }
+
+    // Every term of the expression found in the text, whether or not the whole
+    // expression holds. Used for the per-run keyword stats.
+    pub fn terms_in(&self, text: &str) -> Vec<&str> {
+        let Some(expression) = self.expression.as_ref() else {
+            return Vec::new();
+        };
+        let text = Text::new(text, self.matching.mode);
+        expression
+            .terms()
+            .into_iter()
+            .filter(|term| fuzzy::find_term(term, &text, &self.matching).is_some())
+            .collect()
+    }
+
+    pub fn terms(&self) -> Vec<&str> {
+        self.expression
+            .as_ref()
+            .map(Expr::terms)
+            .unwrap_or_default()
+    }
+
+    // The keyword that made the text match and how, None for an empty matcher
+    pub fn find(&self, text: &str) -> Option<KeywordHit> {
+        let expression = self.expression.as_ref()?;
+        expression.find(&Text::new(text, self.matching.mode), &self.matching)
+    }
+}
@@ -0,0 +1,8 @@
+pub mod exclusions;
+pub mod expression;
+pub mod fuzzy;
+pub mod intents;
+pub mod keywords;
+pub mod noise;
+pub mod questions;
+pub mod text;
@@ -0,0 +1,130 @@
+use crate::settings::api_keys::{ConfigDirs, NoiseSettings};
+
+// Phrases of posts that are almost never leads, grouped by the reason stored
+// with a quarantined post. Checked in this order, the first hit wins.
+const NOISE_PATTERNS: [(&str, &[&str]); 4] = [
+    (
+        "giveaway",
+        &[
+            "giveaway",
+            "give away",
+            "free gift card",
+            "win a free",
+            "to enter, ",
+            "upvote to win",
+        ],
+    ),
+    (
+        "referral",
+        &[
+            "referral code",
+            "referral link",
+            "ref link",
+            "use my code",
+            "promo code",
+            "sign up with my link",
+            "invite code",
+        ],
+    ),
+    (
+        "crypto",
+        &[
+            "airdrop",
+            "presale",
+            "pre-sale",
+            "100x",
+            "1000x",
+            "to the moon",
+            "pump and dump",
+            "memecoin",
+            "meme coin",
+            "connect your wallet",
+            "free crypto",
+        ],
+    ),
+    (
+        "promotion",
+        &[
+            "dm me for",
+            "t.me/",
+            "telegram group",
+            "whatsapp me",
+            "limited time offer",
+            "click the link in",
+            "make money fast",
+        ],
+    ),
+];
+
+// A post linking out more than this many times is treated as link spam
+const MAX_LINKS: usize = 5;
+
+// Heuristic spam and noise tagging. Quarantined posts keep their reason and are
+// left out of lead generation and the default queries.
+#[derive(Debug, Clone)]
+pub struct NoiseClassifier {
+    enabled: bool,
+    patterns: Vec<(String, Vec<String>)>,
+}
+
+impl NoiseClassifier {
+    pub fn new(settings: &NoiseSettings) -> Self {
+        let mut patterns: Vec<(String, Vec<String>)> = NOISE_PATTERNS
+            .iter()
+            .map(|(reason, phrases)| {
+                (
+                    reason.to_string(),
+                    phrases.iter().map(|p| p.to_string()).collect(),
+                )
+            })
+            .collect();
+        let custom: Vec<String> = settings
+            .patterns
+            .iter()
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if !custom.is_empty() {
+            patterns.push(("custom".to_string(), custom));
+        }
+
+        NoiseClassifier {
+            enabled: settings.enabled,
+            patterns,
+        }
+    }
+
+    // The configured classifier, or the built-in one when the config can't be read
+    pub fn load() -> Self {
+        ConfigDirs::read_config()
+            .map(|config| Self::new(&config.noise))
+            .unwrap_or_default()
+    }
+
+    // Why a post looks like noise, empty when it looks genuine
+    pub fn classify(&self, title: &str, body: &str) -> String {
+        if !self.enabled {
+            return String::new();
+        }
+
+        let text = format!(" {} {} ", title, body).to_lowercase();
+        if let Some((reason, _)) = self
+            .patterns
+            .iter()
+            .find(|(_, phrases)| phrases.iter().any(|p| text.contains(p.as_str())))
+        {
+            return reason.clone();
+        }
+
+        if text.matches("http://").count() + text.matches("https://").count() > MAX_LINKS {
+            return "links".to_string();
+        }
+        String::new()
+    }
+}
+
+impl Default for NoiseClassifier {
+    fn default() -> Self {
+        Self::new(&NoiseSettings::default())
+    }
+}
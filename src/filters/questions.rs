@@ -0,0 +1,69 @@
+// Local heuristics spotting posts that ask for help or recommendations
+
+// Openings of a question title
+const QUESTION_STARTS: [&str; 24] = [
+    "how ",
+    "what ",
+    "which ",
+    "who ",
+    "where ",
+    "when ",
+    "why ",
+    "is there ",
+    "are there ",
+    "is it ",
+    "can ",
+    "could ",
+    "should ",
+    "would ",
+    "does ",
+    "do ",
+    "did ",
+    "has anyone ",
+    "have you ",
+    "anyone ",
+    "any ",
+    "recommend",
+    "looking for ",
+    "help ",
+];
+
+// Phrases that ask for input even without a question mark
+const REQUEST_PHRASES: [&str; 12] = [
+    "any recommendations",
+    "any suggestions",
+    "any advice",
+    "any tips",
+    "recommendations for",
+    "suggestions for",
+    "looking for a ",
+    "looking for an ",
+    "need advice",
+    "need help",
+    "what do you use",
+    "what are you using",
+];
+
+// Whether a post reads as a question. The title decides most cases, the first
+// paragraph of the body catches titles that only name the topic.
+pub fn is_question(title: &str, body: &str) -> bool {
+    let title = title.trim().to_lowercase();
+    if title.ends_with('?') || title.contains("? ") {
+        return true;
+    }
+    if QUESTION_STARTS.iter().any(|start| title.starts_with(start)) {
+        return true;
+    }
+
+    let opening = body
+        .trim()
+        .split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    REQUEST_PHRASES
+        .iter()
+        .any(|phrase| title.contains(phrase) || opening.contains(phrase))
+        || opening.trim_end().ends_with('?')
+}
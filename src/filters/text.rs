@@ -0,0 +1,198 @@
+// Normalises Reddit post and comment text before it reaches exports and prompts
+
+use unicode_segmentation::UnicodeSegmentation;
+
+// Characters that render as nothing but break matching and column widths
+const INVISIBLE: [char; 6] = [
+    '\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}', '\u{00AD}',
+];
+
+// Plain text from Reddit markdown: escaped newlines and HTML entities decoded,
+// markdown syntax and invisible characters dropped, whitespace collapsed.
+// Paragraph breaks are kept as a single blank line.
+pub fn clean_text(raw: &str) -> String {
+    let text = raw.replace("\\n", "\n").replace("\r\n", "\n");
+    // Reddit often double-encodes, as in &amp;#x200B;
+    let text = decode_entities(&decode_entities(&text));
+    let text: String = text.chars().filter(|c| !INVISIBLE.contains(c)).collect();
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || is_rule(trimmed) {
+            continue;
+        }
+
+        let line = strip_inline(&strip_block(trimmed));
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        // Collapse runs of blank lines into one
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+// Decode named and numeric HTML entities, unknown ones are left as written
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| entity(&rest[1..=end]).map(|c| (c, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+// Horizontal rules such as ---, *** or ___
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|mark| compact.chars().all(|c| c == *mark))
+}
+
+// Headings and quote markers are dropped, bullets become dashes
+fn strip_block(line: &str) -> String {
+    let mut line = line;
+    while let Some(rest) = line.strip_prefix('>') {
+        line = rest.trim_start();
+    }
+
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if hashes > 0 && line[hashes..].starts_with(' ') {
+        line = line[hashes..].trim_start();
+    }
+
+    match line.strip_prefix("* ").or_else(|| line.strip_prefix("+ ")) {
+        Some(item) => format!("- {}", item),
+        None => line.to_string(),
+    }
+}
+
+// Links keep their text and address, emphasis, code and spoiler marks go.
+// Single underscores are kept since they are common in names and URLs.
+fn strip_inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        match chars[i] {
+            '\\' if next.is_some_and(|c| c.is_ascii_punctuation()) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            '!' if next == Some('[') => i += 1,
+            '[' => match link_at(&chars, i) {
+                Some((text, url, end)) => {
+                    let text = strip_inline(&text);
+                    if text.is_empty() || text == url {
+                        out.push_str(&url);
+                    } else {
+                        out.push_str(&format!("{} ({})", text, url));
+                    }
+                    i = end;
+                }
+                None => {
+                    out.push('[');
+                    i += 1;
+                }
+            },
+            '>' if next == Some('!') => i += 2,
+            '!' if next == Some('<') => i += 2,
+            '~' if next == Some('~') => i += 2,
+            '_' if next == Some('_') => i += 2,
+            '*' | '`' => i += 1,
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+// A [text](url) link starting at `start`, with the index just past it
+fn link_at(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let close = start + chars[start..].iter().position(|c| *c == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = close + 1 + chars[close + 1..].iter().position(|c| *c == ')')?;
+
+    let text: String = chars[start + 1..close].iter().collect();
+    let url: String = chars[close + 2..end].iter().collect();
+    Some((text.trim().to_string(), url.trim().to_string(), end + 1))
+}
+
+// At most `max_chars` characters of `text`. The cut moves back to the start of
+// the grapheme cluster rather than split an accented letter, an emoji sequence
+// or a flag, so it never panics on multi-byte text and never leaves half a
+// glyph behind.
+pub fn truncate(text: &str, max_chars: usize) -> &str {
+    let Some((limit, _)) = text.char_indices().nth(max_chars) else {
+        return text;
+    };
+    let end = text
+        .grapheme_indices(true)
+        .map(|(start, _)| start)
+        .take_while(|start| *start <= limit)
+        .last()
+        .unwrap_or(0);
+    &text[..end]
+}
+
+// Truncated text marked with an ellipsis, for terminal output
+pub fn ellipsize(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    format!(
+        "{}…",
+        truncate(text, max_chars.saturating_sub(1)).trim_end()
+    )
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+
+use crate::actions::display::stored_counts;
+use crate::actions::verbosity;
+use crate::arguments::post_ref::PostRef;
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::database::saved::{SAVED, SavedComment};
+
+// Values of the source column for each file of the export
+const POSTS_SOURCE: &str = "gdpr_post";
+const COMMENTS_SOURCE: &str = "gdpr_comment";
+
+type Row = HashMap<String, String>;
+
+// Import the CSVs of an extracted Reddit data request (reddit.com/settings/data-request)
+pub fn import_gdpr(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if !dir.is_dir() {
+        return Err(format!(
+            "{:?} is not a directory, extract the Reddit export first",
+            dir
+        )
+        .into());
+    }
+
+    let mut db = DB::new()?;
+    db.create_tables()?;
+
+    let mut imported = false;
+
+    if let Some(rows) = read_csv(&dir.join("posts.csv"))? {
+        let posts: Vec<PostDataWrapper> = rows.iter().filter_map(map_post).collect();
+        report_skipped("posts.csv", rows.len() - posts.len());
+        let inserted = db.append_results_from(&posts, POSTS_SOURCE)?;
+        println!("{}", stored_counts("posts", posts.len(), inserted));
+        imported = true;
+    }
+
+    if let Some(rows) = read_csv(&dir.join("comments.csv"))? {
+        let comments: Vec<CommentDataWrapper> = rows
+            .iter()
+            .filter_map(|row| map_comment(&db, row))
+            .collect();
+        let inserted = db.append_comments_from(&comments, COMMENTS_SOURCE)?;
+        println!("{}", stored_counts("comments", comments.len(), inserted));
+        imported = true;
+    }
+
+    // Saved items only carry an id and permalink, so they are recorded as
+    // saved rather than stored as blank posts and comments
+    if let Some(rows) = read_csv(&dir.join("saved_posts.csv"))? {
+        let ids: Vec<i64> = rows
+            .iter()
+            .filter_map(|row| DB::decode_reddit_id(field(row, "id")))
+            .collect();
+        report_skipped("saved_posts.csv", rows.len() - ids.len());
+        let mut added = 0;
+        for id in &ids {
+            if db.add_tag(*id, SAVED)? {
+                added += 1;
+            }
+        }
+        println!("{}", stored_counts("saved posts", ids.len(), added));
+        imported = true;
+    }
+
+    if let Some(rows) = read_csv(&dir.join("saved_comments.csv"))? {
+        let comments: Vec<SavedComment> = rows.iter().filter_map(map_saved_comment).collect();
+        let added = db.add_saved_comments(&comments)?;
+        println!("{}", stored_counts("saved comments", comments.len(), added));
+        imported = true;
+    }
+
+    if !imported {
+        return Err(format!(
+            "No posts.csv, comments.csv, saved_posts.csv or saved_comments.csv found in {:?}",
+            dir
+        )
+        .into());
+    }
+
+    println!("Imported Reddit data export from {:?}", dir);
+    Ok(())
+}
+
+// Rows without a usable id or date are left out rather than stored under a
+// made-up id
+fn report_skipped(file: &str, skipped: usize) {
+    if skipped > 0 {
+        eprintln!(
+            "Skipped {} rows of {} without a valid id or date",
+            skipped, file
+        );
+    }
+}
+
+fn read_csv(path: &Path) -> Result<Option<Vec<Row>>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let mut reader = csv::Reader::from_path(path)?;
+    let rows = reader.deserialize::<Row>().collect::<Result<Vec<_>, _>>()?;
+
+    verbosity::info(format!("Read {} rows from {:?}", rows.len(), path));
+    Ok(Some(rows))
+}
+
+fn field<'a>(row: &'a Row, name: &str) -> &'a str {
+    row.get(name).map(|value| value.trim()).unwrap_or_default()
+}
+
+// Export dates look like "2023-01-31 18:04:12 UTC"
+fn parse_date(date: &str) -> i64 {
+    NaiveDateTime::parse_from_str(date.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S")
+        .map(|date| date.and_utc().timestamp())
+        .unwrap_or(0)
+}
+
+fn post_id_from_permalink(permalink: &str) -> Option<String> {
+    PostRef::parse(permalink).map(|post| post.id)
+}
+
+fn subreddit_from_permalink(permalink: &str) -> String {
+    PostRef::parse(permalink)
+        .and_then(|post| post.subreddit)
+        .unwrap_or_default()
+}
+
+fn post_title(db: &DB, post_id: &str) -> String {
+    let Some(post_id) = DB::decode_reddit_id(post_id) else {
+        return String::new();
+    };
+    db.conn
+        .query_row(
+            "SELECT title FROM reddit_posts WHERE id = ?1",
+            [post_id],
+            |row| row.get(0),
+        )
+        .unwrap_or_default()
+}
+
+fn map_post(row: &Row) -> Option<PostDataWrapper> {
+    let id = field(row, "id");
+    if id.is_empty() {
+        return None;
+    }
+
+    let timestamp = parse_date(field(row, "date"));
+    Some(PostDataWrapper {
+        id: DB::decode_reddit_id(id)?,
+        timestamp,
+        formatted_date: DB::format_timestamp(timestamp).ok()?,
+        title: field(row, "title").to_string(),
+        url: field(row, "url").to_string(),
+        relevance: "own".to_string(),
+        subreddit: field(row, "subreddit").to_string(),
+        permalink: field(row, "permalink").to_string(),
+        selftext: field(row, "body").to_string(),
+    })
+}
+
+fn map_comment(db: &DB, row: &Row) -> Option<CommentDataWrapper> {
+    let id = field(row, "id");
+    let permalink = field(row, "permalink");
+    if id.is_empty() {
+        return None;
+    }
+
+    let post_id = post_id_from_permalink(permalink)
+        .or_else(|| post_id_from_permalink(field(row, "link")))
+        .unwrap_or_default();
+    let timestamp = parse_date(field(row, "date"));
+
+    Some(CommentDataWrapper {
+        id: id.to_string(),
+        post_title: post_title(db, &post_id),
+        post_id,
+        body: field(row, "body").to_string(),
+        author: String::new(),
+        timestamp,
+        formatted_date: DB::format_timestamp(timestamp).ok()?,
+        score: 0,
+        permalink: permalink.to_string(),
+        parent_id: field(row, "parent").to_string(),
+        subreddit: field(row, "subreddit").to_string(),
+    })
+}
+
+fn map_saved_comment(row: &Row) -> Option<SavedComment> {
+    let id = field(row, "id");
+    if id.is_empty() {
+        return None;
+    }
+
+    let permalink = field(row, "permalink");
+    Some(SavedComment {
+        id: id.to_string(),
+        post_id: post_id_from_permalink(permalink).unwrap_or_default(),
+        permalink: permalink.to_string(),
+        subreddit: subreddit_from_permalink(permalink),
+    })
+}
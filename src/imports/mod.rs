@@ -0,0 +1,2 @@
+pub mod gdpr;
+pub mod pushshift;
@@ -0,0 +1,183 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::actions::display::stored_counts;
+use crate::actions::interrupt;
+use crate::actions::outcome::RunSummary;
+use crate::actions::verbosity;
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+
+// Rows are written to the database in batches of this size
+const BATCH_SIZE: usize = 5000;
+
+// Value of the source column for imported rows
+const SOURCE: &str = "pushshift";
+
+// Stream a Pushshift / Arctic Shift ndjson dump (optionally .zst) into the database
+pub fn import_pushshift(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "zst") {
+        let mut decoder = zstd::stream::read::Decoder::new(file)?;
+        // Pushshift dumps are compressed with --long=31
+        decoder.window_log_max(31)?;
+        Box::new(decoder)
+    } else {
+        Box::new(file)
+    };
+
+    let mut db = DB::new()?;
+    db.create_tables()?;
+
+    let mut posts = Vec::new();
+    let mut comments = Vec::new();
+    let (mut post_count, mut comment_count, mut skipped) = (0, 0, 0);
+    let (mut new_posts, mut new_comments) = (0, 0);
+
+    verbosity::info(format!("Importing {:?}...", path));
+
+    for line in BufReader::new(reader).lines() {
+        // The pending batch is still written below
+        if interrupt::interrupted() {
+            println!("Interrupted, saving the lines read so far");
+            break;
+        }
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Ok(item) = serde_json::from_str::<Value>(&line) else {
+            skipped += 1;
+            continue;
+        };
+
+        // Submissions have a title, comments a body
+        if item.get("title").is_some() {
+            match map_post(&item) {
+                Some(post) => posts.push(post),
+                None => skipped += 1,
+            }
+        } else if item.get("body").is_some() {
+            match map_comment(&db, &item) {
+                Some(comment) => comments.push(comment),
+                None => skipped += 1,
+            }
+        } else {
+            skipped += 1;
+        }
+
+        if posts.len() >= BATCH_SIZE {
+            post_count += posts.len();
+            new_posts += db.append_results_from(&posts, SOURCE)?;
+            posts.clear();
+        }
+        if comments.len() >= BATCH_SIZE {
+            comment_count += comments.len();
+            new_comments += db.append_comments_from(&comments, SOURCE)?;
+            comments.clear();
+        }
+    }
+
+    post_count += posts.len();
+    comment_count += comments.len();
+    new_posts += db.append_results_from(&posts, SOURCE)?;
+    new_comments += db.append_comments_from(&comments, SOURCE)?;
+
+    println!(
+        "Read {} posts and {} comments ({} lines skipped)",
+        post_count, comment_count, skipped
+    );
+    let mut summary = RunSummary::default();
+    summary.add(post_count, new_posts);
+    summary.add(comment_count, new_comments);
+    db.record_run_summary(&summary)?;
+
+    println!("Imported {}", stored_counts("posts", post_count, new_posts));
+    println!(
+        "Imported {}",
+        stored_counts("comments", comment_count, new_comments)
+    );
+    Ok(())
+}
+
+// Dumps store numbers as either JSON numbers or strings depending on the year
+fn as_i64(value: &Value) -> Option<i64> {
+    value
+        .as_i64()
+        .or_else(|| value.as_f64().map(|f| f as i64))
+        .or_else(|| {
+            value
+                .as_str()
+                .and_then(|s| s.parse::<f64>().ok())
+                .map(|f| f as i64)
+        })
+}
+
+fn as_string(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+fn map_post(item: &Value) -> Option<PostDataWrapper> {
+    let id = item["id"].as_str()?;
+    let timestamp = as_i64(&item["created_utc"])?;
+    let subreddit = as_string(&item["subreddit"]);
+
+    let permalink = match item["permalink"].as_str() {
+        Some(permalink) => format!("https://reddit.com{}", permalink),
+        None => format!("https://reddit.com/r/{}/comments/{}/", subreddit, id),
+    };
+
+    Some(PostDataWrapper {
+        id: DB::decode_reddit_id(id)?,
+        timestamp,
+        formatted_date: DB::format_timestamp(timestamp).ok()?,
+        title: as_string(&item["title"]),
+        url: as_string(&item["url"]),
+        relevance: "archive".to_string(),
+        subreddit,
+        permalink,
+        selftext: as_string(&item["selftext"]),
+    })
+}
+
+fn map_comment(db: &DB, item: &Value) -> Option<CommentDataWrapper> {
+    let id = item["id"].as_str()?;
+    let timestamp = as_i64(&item["created_utc"])?;
+    let subreddit = as_string(&item["subreddit"]);
+    let post_id = as_string(&item["link_id"])
+        .trim_start_matches("t3_")
+        .to_string();
+
+    let permalink = match item["permalink"].as_str() {
+        Some(permalink) => permalink.to_string(),
+        None => format!("/r/{}/comments/{}/_/{}/", subreddit, post_id, id),
+    };
+
+    // Dumps do not carry the post title, use the stored post when we have it
+    let post_title = db
+        .conn
+        .query_row(
+            "SELECT title FROM reddit_posts WHERE id = ?1",
+            [DB::decode_reddit_id(&post_id)?],
+            |row| row.get(0),
+        )
+        .unwrap_or_default();
+
+    Some(CommentDataWrapper {
+        id: id.to_string(),
+        post_id,
+        body: as_string(&item["body"]),
+        author: as_string(&item["author"]),
+        timestamp,
+        formatted_date: DB::format_timestamp(timestamp).ok()?,
+        score: as_i64(&item["score"]).unwrap_or(0) as i32,
+        permalink,
+        parent_id: as_string(&item["parent_id"]),
+        subreddit,
+        post_title,
+    })
+}
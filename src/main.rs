@@ -1,32 +1,71 @@
+use std::collections::HashSet;
+
 use base64::{Engine as _, engine::general_purpose};
 
 use clap::Parser;
+use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    arguments::modeling::Args,
+    actions::accounts::AccountPool,
+    actions::notes::NoteTarget,
+    actions::outcome::{FetchTable, RunSummary},
+    actions::pipeline::{Pipeline, Stage},
+    arguments::modeling::{
+        Args, Commands, CommentOptions, DbCommands, ExportFormat, ExportTarget, ImportFormat,
+        KeywordsCommands, LeadsCommands, MediaCommands, ReportCommands, ServiceCommands,
+        ThreadCommands,
+    },
+    arguments::post_ref::PostRef,
     database::adding::{CommentDataWrapper, PostDataWrapper},
+    database::jobs::CommentJob,
+    database::media::MediaDataWrapper,
+    database::queries::PostFilter,
+    database::tags::STARRED,
+    filters::keywords::KeywordMatcher,
     settings::api_keys::AppConfig,
 };
-use std::process::Command;
 
 pub mod actions;
 pub mod ai;
 pub mod arguments;
+pub mod charts;
 pub mod database;
+pub mod elastic;
 pub mod exports;
+pub mod filters;
+pub mod imports;
+pub mod mcp;
+pub mod notify;
+pub mod publish;
+pub mod reddit;
+pub mod server;
 pub mod settings;
+pub mod watch;
 
+// A post as the Reddit API returns it
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct RedditPost {
-    id: String,
-    title: String,
-    url: String,
-    created_utc: f64,
-    subreddit: String,
-    permalink: String,
-    selftext: Option<String>,
+pub struct RedditPost {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub created_utc: f64,
+    pub subreddit: String,
+    pub permalink: String,
+    pub selftext: Option<String>,
+    #[serde(default)]
+    pub post_hint: Option<String>,
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    #[serde(default)]
+    pub is_gallery: Option<bool>,
+    #[serde(default)]
+    pub gallery_data: Option<serde_json::Value>,
+    #[serde(default)]
+    pub media_metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub media: Option<serde_json::Value>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -52,6 +91,9 @@ struct RedditComment {
 #[derive(Deserialize, Debug, Clone)]
 struct RedditListingData {
     children: Vec<RedditListingChild>,
+    // Cursor of the next page, None on the last one
+    #[serde(default)]
+    after: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -67,11 +109,50 @@ struct RedditListing {
 // Define a custom error type for better error handling
 #[derive(Debug)]
 #[allow(dead_code)]
-enum RedditError {
+pub enum RedditError {
     Reqwest(reqwest::Error),
     TokenExtraction,
+    RateLimited,
+    Database(String),
+    // An error response from the API, with what Reddit said went wrong
+    Api {
+        status: u16,
+        message: String,
+        endpoint: String,
+    },
 }
 
+impl std::fmt::Display for RedditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RedditError::Reqwest(e) => write!(
+                f,
+                "Reddit request failed: {}",
+                settings::redact::redact(&e.to_string())
+            ),
+            RedditError::TokenExtraction => write!(
+                f,
+                "Reddit did not return an access token, check your API credentials"
+            ),
+            RedditError::RateLimited => write!(f, "Rate limited by Reddit"),
+            RedditError::Database(e) => write!(f, "Database error: {}", e),
+            RedditError::Api {
+                status,
+                message,
+                endpoint,
+            } => write!(
+                f,
+                "Reddit API error {} on {}: {}",
+                status,
+                endpoint,
+                settings::redact::redact(message)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RedditError {}
+
 impl From<reqwest::Error> for RedditError {
     fn from(e: reqwest::Error) -> Self {
         RedditError::Reqwest(e)
@@ -107,8 +188,81 @@ impl AppState {
     }
 }
 
+// Collect image, gallery and video attachments of a post
+fn extract_post_media(post: &RedditPost) -> Vec<MediaDataWrapper> {
+    let post_hint = post.post_hint.clone().unwrap_or_default();
+    let thumbnail = post
+        .thumbnail
+        .clone()
+        .filter(|t| t.starts_with("http"))
+        .unwrap_or_default();
+
+    // Reddit HTML-escapes URLs inside media_metadata
+    let unescape = |url: &str| url.replace("&amp;", "&");
+
+    let mut urls = Vec::new();
+
+    if post.is_gallery.unwrap_or(false) {
+        let items = post
+            .gallery_data
+            .as_ref()
+            .and_then(|g| g["items"].as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        for item in items {
+            let Some(media_id) = item["media_id"].as_str() else {
+                continue;
+            };
+            let source = post
+                .media_metadata
+                .as_ref()
+                .map(|m| &m[media_id]["s"])
+                .cloned()
+                .unwrap_or_default();
+
+            if let Some(url) = source["u"]
+                .as_str()
+                .or_else(|| source["gif"].as_str())
+                .or_else(|| source["mp4"].as_str())
+            {
+                urls.push(unescape(url));
+            }
+        }
+    } else if let Some(video) = post
+        .media
+        .as_ref()
+        .and_then(|m| m["reddit_video"]["fallback_url"].as_str())
+    {
+        urls.push(unescape(video));
+    } else if post_hint == "image" || post_hint.ends_with(":video") {
+        urls.push(post.url.clone());
+    }
+
+    let post_hint = if post.is_gallery.unwrap_or(false) {
+        "gallery".to_string()
+    } else {
+        post_hint
+    };
+
+    urls.into_iter()
+        .enumerate()
+        .map(|(position, url)| MediaDataWrapper {
+            post_id: post.id.clone(),
+            position: position as i64,
+            post_hint: post_hint.clone(),
+            url,
+            thumbnail: thumbnail.clone(),
+        })
+        .collect()
+}
+
 // Function to get access token from Reddit API
 async fn get_access_token(client_id: String, client_secret: String) -> Result<String, RedditError> {
+    // Replayed responses need no login
+    if reddit::replay::is_enabled() {
+        return Ok("replay".to_string());
+    }
     let credentials = format!("{}:{}", client_id, client_secret);
     let encoded = general_purpose::STANDARD.encode(credentials);
 
@@ -121,107 +275,214 @@ async fn get_access_token(client_id: String, client_secret: String) -> Result<St
         .send()
         .await?;
 
-    let json: serde_json::Value = response.json().await?;
-    json["access_token"]
+    let json: serde_json::Value = reddit::client::json(response).await?;
+    let token = json["access_token"]
         .as_str()
-        .map(|s| s.to_string())
-        .ok_or(RedditError::TokenExtraction)
+        .ok_or(RedditError::TokenExtraction)?;
+    reddit::client::remember_token(token, &client_id, &client_secret);
+    Ok(token.to_string())
 }
 
-// Function to fetch and print posts from a subreddit
+// Fetch the posts of a subreddit listing. Nothing is printed, callers show
+// them with `actions::display::print_fetched`.
 async fn get_subreddit_posts(
     access_token: &str,
     subreddit: &str,
     relevance: &str,
-) -> Result<Vec<PostDataWrapper>, RedditError> {
-    let client = Client::new();
-    let url = format!(
-        "https://oauth.reddit.com/r/{}/{}?limit=100",
-        subreddit, relevance
-    );
+) -> Result<(Vec<PostDataWrapper>, Vec<MediaDataWrapper>), RedditError> {
+    let client = reddit::client::RedditClient::new(access_token);
+    let options = reddit::client::ListingOptions {
+        sort: relevance.to_string(),
+        limit: reddit::client::MAX_PAGE_SIZE,
+    };
+    let mut stream = std::pin::pin!(client.posts(subreddit, &options));
+
+    let mut posts = Vec::new();
+    let mut media = Vec::new();
+    while let Some(post) = stream.next().await {
+        let post = post?;
+        let Some(id) = database::adding::DB::decode_reddit_id(&post.id) else {
+            eprintln!("Skipping post with malformed id {:?}", post.id);
+            continue;
+        };
+        media.extend(extract_post_media(&post));
+        posts.push(PostDataWrapper {
+            id,
+            title: post.title.clone(),
+            url: post.url.clone(),
+            timestamp: post.created_utc as i64,
+            formatted_date: database::adding::DB::format_timestamp(post.created_utc as i64)
+                .expect("Failed to format timestamp"),
+            relevance: relevance.to_string(),
+            subreddit: post.subreddit.clone(),
+            permalink: format!("https://reddit.com{}", post.permalink),
+            selftext: post.selftext.clone().unwrap_or_default(),
+        });
+    }
 
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", access_token))
-        .header("User-Agent", "RustRedditApp/0.1 by YourUsername")
-        .send()
-        .await?;
+    Ok((posts, media))
+}
 
-    let listing: RedditListing = response.json().await?;
+// Turn a post id or URL from the command line into a post id, following share links
+async fn resolve_post_ref(value: &str) -> Result<PostRef, Box<dyn std::error::Error>> {
+    let url = if PostRef::is_share_link(value) {
+        let response = Client::new()
+            .get(value.trim())
+            .header("User-Agent", "RustRedditApp/0.1 by YourUsername")
+            .send()
+            .await?;
+        response.url().to_string()
+    } else {
+        value.to_string()
+    };
 
-    let posts = listing
-        .data
-        .children
-        .into_iter()
-        .filter_map(|child| {
-            if let RedditData::Post(post) = &child.data {
-                Some(PostDataWrapper {
-                    id: post.id.parse().unwrap_or(0),
-                    title: post.title.clone(),
-                    url: post.url.clone(),
-                    timestamp: post.created_utc as i64,
-                    formatted_date: database::adding::DB::format_timestamp(post.created_utc as i64)
-                        .expect("Failed to format timestamp"),
-                    relevance: relevance.to_string(),
-                    subreddit: post.subreddit.clone(),
-                    permalink: format!("https://reddit.com{}", post.permalink.clone()),
-                })
-            } else {
-                None
-            }
-        })
-        .collect();
+    PostRef::parse(&url).ok_or_else(|| format!("'{}' is not a Reddit post id or URL", value).into())
+}
+
+// Flatten the children of a comment listing and their nested replies, applying
+// the score threshold and the per-post limit. "Load more" stubs and anything
+// else that is not a comment is skipped.
+fn collect_comments(
+    children: &[serde_json::Value],
+    options: &CommentOptions,
+) -> Vec<RedditComment> {
+    let mut comments = Vec::new();
+    for child in children {
+        if let Ok(comment) = serde_json::from_value::<RedditComment>(child["data"].clone()) {
+            push_comment_tree(&comment, options, &mut comments);
+        }
+    }
+
+    if options.limit > 0 {
+        comments.truncate(options.limit);
+    }
+    comments
+}
+
+fn push_comment_tree(
+    comment: &RedditComment,
+    options: &CommentOptions,
+    comments: &mut Vec<RedditComment>,
+) {
+    if options.keep(comment.score) {
+        comments.push(comment.clone());
+    }
 
-    for posts in &posts {
-        println!("{:#?}", &posts);
+    // Replies are an empty string when there are none
+    let Some(children) = comment.replies["data"]["children"].as_array() else {
+        return;
+    };
+    for child in children {
+        if let Ok(reply) = serde_json::from_value::<RedditComment>(child["data"].clone()) {
+            push_comment_tree(&reply, options, comments);
+        }
     }
+}
 
-    Ok(posts)
+// What the comments endpoint returned for one post
+enum PostComments {
+    // The post and its comments, empty when nobody has replied yet
+    Found {
+        post: Box<RedditPost>,
+        comments: Vec<RedditComment>,
+    },
+    // Deleted, private or promoted posts and responses without a post in them
+    Unavailable(&'static str),
 }
 
+// Without `wait_on_rate_limit` a 429 comes back as RateLimited at once
 async fn get_post_comments(
     access_token: &str,
     post_id: &str,
-) -> Result<Vec<RedditListing>, RedditError> {
-    let client = Client::new();
-    let url = format!("https://oauth.reddit.com/comments/{}", post_id);
+    options: &CommentOptions,
+    wait_on_rate_limit: bool,
+) -> Result<PostComments, RedditError> {
+    let client = reddit::client::RedditClient::new(access_token);
+    let url = reddit::comments(post_id, options, false);
+    let response = if wait_on_rate_limit {
+        client.get(url).await?
+    } else {
+        client.get_or_rate_limited(url).await?
+    };
 
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", access_token))
-        .header("User-Agent", "RustRedditApp/0.1 by YourUsername")
-        .send()
-        .await?;
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND => {
+            return Ok(PostComments::Unavailable(
+                "the post was deleted or no longer exists",
+            ));
+        }
+        reqwest::StatusCode::FORBIDDEN => {
+            return Ok(PostComments::Unavailable(
+                "the post is in a private or quarantined subreddit",
+            ));
+        }
+        _ => {}
+    }
 
-    let listings: Vec<RedditListing> = response.json().await?;
+    // The first listing holds the post, the second its comments. Read them
+    // field by field so an odd shape is reported instead of failing the run.
+    let mut listings: serde_json::Value = reddit::client::json(response).await?;
+    let Some(post) = listings[0]["data"]["children"]
+        .as_array()
+        .and_then(|children| children.first())
+        .and_then(|child| serde_json::from_value::<RedditPost>(child["data"].clone()).ok())
+    else {
+        return Ok(PostComments::Unavailable(
+            "Reddit returned no post, it may be promoted or removed",
+        ));
+    };
 
-    if listings.len() < 2 {
-        println!("Warning: Unexpected response format");
-        return Ok(Vec::new());
+    if let Some(listing) = listings.get_mut(1) {
+        actions::thread::follow_continuations(access_token, post_id, listing, options, false).await;
     }
+    let children = listings[1]["data"]["children"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(PostComments::Found {
+        post: Box::new(post),
+        comments: collect_comments(children, options),
+    })
+}
 
-    Ok(listings)
+// Look up a single post by its id, None when Reddit no longer has it
+async fn get_post_info(
+    access_token: &str,
+    post_id: &str,
+) -> Result<Option<RedditPost>, RedditError> {
+    let response = reddit::client::RedditClient::new(access_token)
+        .get(reddit::info(&format!("t3_{}", post_id)))
+        .await?;
+
+    let listing: serde_json::Value = reddit::client::json(response).await?;
+    Ok(listing["data"]["children"]
+        .as_array()
+        .and_then(|children| children.first())
+        .and_then(|child| serde_json::from_value(child["data"].clone()).ok()))
 }
 
+// Run a Reddit search, without printing like `get_subreddit_posts`
 async fn search_subreddit_posts(
     access_token: &str,
-    query: &str,
+    url: reqwest::Url,
     relevance: &str,
-) -> Result<Vec<PostDataWrapper>, RedditError> {
-    let client = Client::new();
-    let url = format!(
-        "https://oauth.reddit.com/search?q={}&limit=1000&t=all",
-        query
-    );
-
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", access_token))
-        .header("User-Agent", "RustRedditApp/0.1 by YourUsername")
-        .send()
+) -> Result<(Vec<PostDataWrapper>, Vec<MediaDataWrapper>), RedditError> {
+    let response = reddit::client::RedditClient::new(access_token)
+        .get(url)
         .await?;
 
-    let listing: RedditListing = response.json().await?;
+    let listing: RedditListing = reddit::client::json(response).await?;
+
+    let media = listing
+        .data
+        .children
+        .iter()
+        .filter_map(|child| match &child.data {
+            RedditData::Post(post) => Some(extract_post_media(post)),
+            _ => None,
+        })
+        .flatten()
+        .collect();
 
     let posts = listing
         .data
@@ -229,8 +490,12 @@ async fn search_subreddit_posts(
         .into_iter()
         .filter_map(|child| {
             if let RedditData::Post(post) = &child.data {
+                let Some(id) = database::adding::DB::decode_reddit_id(&post.id) else {
+                    eprintln!("Skipping post with malformed id {:?}", post.id);
+                    return None;
+                };
                 Some(PostDataWrapper {
-                    id: post.id.parse().unwrap_or(0),
+                    id,
                     title: post.title.clone(),
                     url: post.url.clone(),
                     timestamp: post.created_utc as i64,
@@ -239,6 +504,7 @@ async fn search_subreddit_posts(
                     relevance: relevance.to_string(),
                     subreddit: post.subreddit.clone(),
                     permalink: format!("https://reddit.com{}", post.permalink.clone()),
+                    selftext: post.selftext.clone().unwrap_or_default(),
                 })
             } else {
                 None
@@ -246,27 +512,791 @@ async fn search_subreddit_posts(
         })
         .collect();
 
+    Ok((posts, media))
+}
+
+// Outcome of fetching the comments of one post
+enum CommentsFetch {
+    // How many comments were found, how many of those were new and how many
+    // matched the keywords
+    Saved {
+        found: usize,
+        inserted: usize,
+        keyword_hits: usize,
+    },
+    // Why the post had no comments to fetch
+    Unavailable(&'static str),
+}
+
+// Fetch the comments of one post and save them, counting those matching
+// `keywords` for the fetch table
+async fn fetch_post_comments_into_db(
+    token: &str,
+    db: &mut database::adding::DB,
+    job: &CommentJob,
+    options: &CommentOptions,
+    keywords: Option<&KeywordMatcher>,
+    wait_on_rate_limit: bool,
+) -> Result<CommentsFetch, RedditError> {
+    let (post, comments) =
+        match get_post_comments(token, &job.post_id, options, wait_on_rate_limit).await? {
+            PostComments::Found { post, comments } => (post, comments),
+            PostComments::Unavailable(reason) => return Ok(CommentsFetch::Unavailable(reason)),
+        };
+
+    let comments = comments
+        .iter()
+        .map(|comment| CommentDataWrapper {
+            id: comment.id.clone(),
+            post_id: job.post_id.clone(),
+            body: comment.body.clone(),
+            author: comment.author.clone(),
+            timestamp: comment.created_utc as i64,
+            formatted_date: database::adding::DB::format_timestamp(comment.created_utc as i64)
+                .expect("Failed to format timestamp"),
+            score: comment.score,
+            permalink: comment.permalink.clone(),
+            parent_id: comment.parent_id.clone(),
+            // The post as Reddit returns it now, queued retries may predate edits
+            subreddit: post.subreddit.clone(),
+            post_title: post.title.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut inserted = 0;
+    if !comments.is_empty() {
+        db.create_comments_table()
+            .map_err(|e| RedditError::Database(e.to_string()))?;
+        inserted = db
+            .append_comments(&comments)
+            .map_err(|e| RedditError::Database(e.to_string()))?;
+    }
+
+    let keyword_hits = keywords.map_or(0, |matcher| {
+        comments
+            .iter()
+            .filter(|comment| matcher.matches(&comment.body))
+            .count()
+    });
+
+    Ok(CommentsFetch::Saved {
+        found: comments.len(),
+        inserted,
+        keyword_hits,
+    })
+}
+
+// Keywords counted in the fetch table: the --group ones, else LEAD_KEYWORDS
+fn fetch_keywords(filter: &PostFilter) -> Option<KeywordMatcher> {
+    filter
+        .keywords
+        .clone()
+        .or_else(|| {
+            let config = settings::api_keys::ConfigDirs::read_config().ok()?;
+            KeywordMatcher::from_config(&config).ok()
+        })
+        .filter(|matcher| !matcher.is_empty())
+}
+
+// Ids of the posts not stored yet, checked before they are saved
+fn new_post_ids(db: &database::adding::DB, posts: &[PostDataWrapper]) -> HashSet<i64> {
+    posts
+        .iter()
+        .map(|post| post.id)
+        .filter(|id| !db.is_post_stored(*id).unwrap_or(true))
+        .collect()
+}
+
+// Drop fetched posts (and their media) outside the --since/--until window,
+// with --questions-only that are not questions, and from the subreddits and
+// domains excluded under [fetch]
+fn retain_matching(
+    filter: &PostFilter,
+    posts: &mut Vec<PostDataWrapper>,
+    media: &mut Vec<MediaDataWrapper>,
+) {
+    let fetched = posts.len();
+    let exclusions = settings::api_keys::ConfigDirs::read_config()
+        .map(|config| filters::exclusions::Exclusions::from_settings(&config.fetch))
+        .unwrap_or_default();
+    if !exclusions.is_empty() {
+        posts.retain(|post| !exclusions.excludes(post));
+        if posts.len() < fetched {
+            actions::verbosity::info(format!(
+                "Skipped {} posts from excluded subreddits or domains",
+                fetched - posts.len()
+            ));
+        }
+    }
+    let unexcluded = posts.len();
+    posts.retain(|post| filter.keeps(post));
+    if posts.len() < unexcluded {
+        actions::verbosity::info(format!(
+            "Skipped {} posts outside the requested filters",
+            unexcluded - posts.len()
+        ));
+    }
+
+    if posts.len() < fetched {
+        let kept: Vec<String> = posts
+            .iter()
+            .map(|post| database::adding::DB::encode_reddit_id(post.id))
+            .collect();
+        media.retain(|item| kept.contains(&item.post_id));
+    }
+}
+
+// Fetch a subreddit listing and the comments of every post, saving everything,
+// and print the counts per subreddit. Returns the counts of the run and the
+// comment fetches that failed so the caller can retry them.
+async fn fetch_subreddit_with_comments(
+    pool: &mut AccountPool,
+    subreddit: &str,
+    relevance: &str,
+    filter: &PostFilter,
+    comment_options: &CommentOptions,
+) -> Result<(RunSummary, Vec<(CommentJob, String)>), Box<dyn std::error::Error>> {
+    actions::verbosity::info(format!(
+        "Fetching posts from r/{} ({} posts)...",
+        subreddit, relevance
+    ));
+
+    let progress = actions::progress::Progress::start("Waiting for Reddit...");
+    let listing = get_subreddit_posts(&pool.token(), subreddit, relevance).await;
+    progress.finish();
+    let (mut posts, mut media) = listing?;
+    actions::display::print_fetched(&posts);
+    let mut summary = RunSummary {
+        fetched: posts.len(),
+        skipped: posts.len(),
+        ..RunSummary::default()
+    };
+    let mut table = FetchTable::default();
+    table.add_fetched(&posts);
+    retain_matching(filter, &mut posts, &mut media);
+
+    let keywords = fetch_keywords(filter);
+    let mut db = database::adding::DB::new()?;
+    db.create_tables()?;
+    let new = new_post_ids(&db, &posts);
+    let inserted = db.append_results(&posts)?;
+    summary.inserted += inserted;
+    summary.skipped -= inserted;
+    db.append_media(&media)?;
+    db.record_sync(subreddit, posts.len())?;
+    table.add_stored(&posts, &new, keywords.as_ref());
+
+    // Also fetch and save comments for each post
+    let progress = actions::progress::Progress::start("Fetching comments...");
+    let mut failures = Vec::new();
+    let mut done = 0;
+    let (mut comments_found, mut comments_inserted) = (0, 0);
     for post in &posts {
-        println!("{:#?}", &post);
+        // Posts are already saved, stop before the next thread
+        if actions::interrupt::interrupted() {
+            break;
+        }
+        progress.set_label(format!("Fetching comments {}/{}", done + 1, posts.len()));
+
+        let job = CommentJob {
+            post_id: database::adding::DB::encode_reddit_id(post.id),
+            subreddit: post.subreddit.clone(),
+            post_title: post.title.clone(),
+        };
+
+        // On a rate limit, retry the same post with the next account, or wait
+        // it out when there is no other account
+        loop {
+            let token = pool.token();
+            let wait = !pool.can_rotate();
+            let fetch = fetch_post_comments_into_db(
+                &token,
+                &mut db,
+                &job,
+                comment_options,
+                keywords.as_ref(),
+                wait,
+            )
+            .await;
+            match fetch {
+                Err(RedditError::RateLimited) if pool.rate_limited() => continue,
+                Err(e) => failures.push((job, format!("Failed to retrieve comments: {}", e))),
+                Ok(CommentsFetch::Saved {
+                    found,
+                    inserted,
+                    keyword_hits,
+                }) => {
+                    comments_found += found;
+                    comments_inserted += inserted;
+                    let row = table.row(&job.subreddit);
+                    row.comments += inserted;
+                    row.keyword_hits += keyword_hits;
+                }
+                Ok(CommentsFetch::Unavailable(reason)) => {
+                    progress.println(&format!("No comments for '{}': {}", job.post_title, reason))
+                }
+            }
+            break;
+        }
+        done += 1;
+    }
+    progress.finish();
+    pool.save_usage();
+    summary.add(comments_found, comments_inserted);
+    table.print();
+
+    if actions::interrupt::interrupted() {
+        println!(
+            "Interrupted: saved {} posts and the comments of {} of them",
+            posts.len(),
+            done
+        );
     }
 
-    Ok(posts)
+    if !failures.is_empty() {
+        eprintln!(
+            "Warning: failed to fetch comments for {} posts",
+            failures.len()
+        );
+    }
+    summary.errors = failures.len();
+
+    Ok((summary, failures))
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    // initiate clap / args
+    let args = Args::parse();
+    settings::redact::init_logger(args.verbose);
+    actions::verbosity::set(args.verbosity());
+    actions::interrupt::install();
+    database::runs::begin(
+        args.run_command(),
+        &std::env::args().skip(1).collect::<Vec<_>>().join(" "),
+    );
+
+    if let Err(e) = args.check_group() {
+        eprintln!("Error: {}", e);
+        std::process::exit(actions::outcome::EXIT_FAILURE);
+    }
+
+    if let Some(path) = &args.db_path {
+        database::adding::set_database_path(path.clone());
+    }
+    database::adding::set_read_only(args.read_only);
+    if args.read_only && args.writes() {
+        eprintln!(
+            "Error: `{}` stores data and cannot run with --read-only",
+            args.run_command()
+        );
+        std::process::exit(actions::outcome::EXIT_FAILURE);
+    }
+    if args.ephemeral
+        && let Err(e) = database::adding::set_ephemeral()
+    {
+        eprintln!("Error: Failed to create the in-memory database: {}", e);
+        std::process::exit(actions::outcome::EXIT_FAILURE);
+    }
+    // Captures should hold what Reddit sends now, not a cached copy
+    if args.no_cache || args.capture {
+        reddit::cache::disable();
+    }
+    if args.capture {
+        match reddit::capture::enable() {
+            Ok(folder) => eprintln!("Capturing Reddit responses to {}", folder.display()),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(actions::outcome::EXIT_FAILURE);
+            }
+        }
+    }
+    if let Some(folder) = &args.replay {
+        match reddit::replay::enable(folder) {
+            Ok(count) => eprintln!(
+                "Replaying {} captured responses from {}",
+                count,
+                folder.display()
+            ),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(actions::outcome::EXIT_FAILURE);
+            }
+        }
+    }
+    // Dropped when main returns, std::process::exit releases it with the process
+    let _lock = if args.writes() && !args.ephemeral {
+        match database::lock::WriteLock::acquire(args.run_command()) {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                eprintln!("Error: Failed to lock the database: {}", e);
+                std::process::exit(actions::outcome::EXIT_FAILURE);
+            }
+        }
+    } else {
+        None
+    };
+
+    if let Err(e) = run(args).await {
+        eprintln!("Error: {}", settings::redact::redact(&e.to_string()));
+        std::process::exit(actions::outcome::exit_code(e.as_ref()));
+    }
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    // Subcommands that work on stored data only and need no Reddit token
+    match &args.command {
+        Some(Commands::Media {
+            action: MediaCommands::Download { post_id },
+        }) => {
+            actions::media::download_post_media(post_id).await?;
+            return Ok(());
+        }
+        Some(Commands::Leads { action }) => {
+            match action {
+                LeadsCommands::Review { assignee } => {
+                    actions::leads::review_leads(assignee.as_deref())?
+                }
+                LeadsCommands::Export => exports::excel::export_approved_leads()?,
+                LeadsCommands::Comments => actions::leads::find_comment_leads(&args.post_filter())?,
+                LeadsCommands::Stale { days, notify } => {
+                    actions::leads::print_stale_leads(*days, *notify).await?
+                }
+                LeadsCommands::Contacted { ids } => actions::leads::mark_contacted(ids)?,
+                LeadsCommands::Dump { output } => actions::leads::dump_leads(output.clone())?,
+                LeadsCommands::Load { file } => actions::leads::load_leads(file)?,
+                LeadsCommands::Assign { id, assignee } => {
+                    actions::leads::assign_lead(*id, assignee.as_deref())?
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Report {
+            period: ReportCommands::Weekly { output },
+        }) => {
+            exports::report::write_weekly_report(output.clone())?;
+            return Ok(());
+        }
+        Some(Commands::Report {
+            period: ReportCommands::Sentiment { weeks },
+        }) => {
+            exports::report::print_sentiment_trend(*weeks)?;
+            return Ok(());
+        }
+        Some(Commands::Report {
+            period:
+                ReportCommands::Compare {
+                    period_a,
+                    period_b,
+                    keywords,
+                },
+        }) => {
+            exports::report::print_comparison(*period_a, *period_b, *keywords)?;
+            return Ok(());
+        }
+        Some(Commands::Report {
+            period:
+                ReportCommands::Charts {
+                    days,
+                    format,
+                    output,
+                },
+        }) => {
+            exports::report::write_charts(*days, *format, output.clone())?;
+            return Ok(());
+        }
+        Some(Commands::Export {
+            target:
+                Some(ExportTarget::Comments {
+                    subreddit,
+                    keyword,
+                    min_score,
+                }),
+            format,
+            limit,
+            chunk,
+        }) => {
+            let table = match format {
+                ExportFormat::Xlsx => exports::table::TableFormat::Xlsx,
+                ExportFormat::Csv => exports::table::TableFormat::Csv,
+                ExportFormat::Ndjson => exports::table::TableFormat::Ndjson,
+                ExportFormat::Obsidian | ExportFormat::Duckdb => {
+                    eprintln!("Comments can be exported as xlsx, csv or ndjson");
+                    std::process::exit(actions::outcome::EXIT_FAILURE);
+                }
+            };
+            let keywords =
+                match keyword {
+                    Some(query) => match filters::keywords::KeywordMatcher::from_query(query) {
+                        Ok(matcher) => Some(matcher.with_matching(
+                            &settings::api_keys::ConfigDirs::read_config()?.matching,
+                        )),
+                        Err(e) => {
+                            eprintln!("Invalid --keyword query: {}", e);
+                            std::process::exit(actions::outcome::EXIT_FAILURE);
+                        }
+                    },
+                    None => args.post_filter().keywords,
+                };
+            let filter = database::queries::PostFilter {
+                subreddit: subreddit.clone(),
+                limit: *limit,
+                min_score: *min_score,
+                keywords,
+                ..args.post_filter()
+            };
+            if let Err(e) = exports::excel::export_comments(&filter, table, *chunk) {
+                eprintln!("Failed to export comments: {}", e);
+                actions::outcome::exit_with(actions::outcome::EXIT_FAILURE);
+            }
+            actions::interrupt::exit_if_interrupted();
+            return Ok(());
+        }
+        Some(Commands::Export {
+            target: None,
+            format,
+            limit,
+            chunk,
+        }) => {
+            let filter = database::queries::PostFilter {
+                limit: *limit,
+                ..args.post_filter()
+            };
+            let table = match format {
+                ExportFormat::Xlsx => Some(exports::table::TableFormat::Xlsx),
+                ExportFormat::Csv => Some(exports::table::TableFormat::Csv),
+                ExportFormat::Ndjson => Some(exports::table::TableFormat::Ndjson),
+                ExportFormat::Obsidian | ExportFormat::Duckdb => None,
+            };
+            let result = match table {
+                Some(table) => exports::excel::export_posts(&filter, table, *chunk).map(|_| ()),
+                None if chunk.is_some() => {
+                    Err("--chunk applies to xlsx, csv and ndjson exports".into())
+                }
+                None if *format == ExportFormat::Duckdb => {
+                    exports::duckdb::export_duckdb(&filter).map(|_| ())
+                }
+                None => exports::obsidian::export_obsidian_vault(&filter).map(|_| ()),
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to export data: {}", e);
+                actions::outcome::exit_with(actions::outcome::EXIT_FAILURE);
+            }
+            actions::interrupt::exit_if_interrupted();
+            return Ok(());
+        }
+        Some(Commands::Index) => {
+            let db = database::adding::DB::new()?;
+            db.create_tables()?;
+            let keys = ai::keys::GeminiKeys::from_config(
+                &settings::api_keys::ConfigDirs::read_config()?.api_keys,
+            );
+            let indexed = ai::retrieval::index_documents(&db, &keys).await?;
+            println!("Indexed {} new or changed posts and comments", indexed);
+            actions::interrupt::exit_if_interrupted();
+            return Ok(());
+        }
+        Some(Commands::Classify) => {
+            let db = database::adding::DB::new()?;
+            db.create_tables()?;
+            let tagged = db.tag_posts(
+                &filters::intents::IntentClassifier::load(),
+                &filters::noise::NoiseClassifier::load(),
+            )?;
+            println!("Tagged {} posts", tagged);
+            return Ok(());
+        }
+        Some(Commands::Query {
+            subreddit,
+            contains,
+            noise,
+            limit,
+        }) => {
+            let filter = database::queries::PostFilter {
+                subreddit: subreddit.clone(),
+                contains: contains.clone(),
+                noise: *noise,
+                limit: Some(*limit),
+                ..args.post_filter()
+            };
+            actions::query::print_posts(&filter, args.copy, args.open_first)?;
+            return Ok(());
+        }
+        Some(Commands::Import { format, file }) => {
+            match format {
+                ImportFormat::Pushshift => imports::pushshift::import_pushshift(file)?,
+                ImportFormat::Gdpr => imports::gdpr::import_gdpr(file)?,
+            }
+            actions::interrupt::exit_if_interrupted();
+            return Ok(());
+        }
+        Some(Commands::Feed {
+            output,
+            serve,
+            live: false,
+            limit,
+        }) => {
+            match serve {
+                Some(addr) => exports::rss::serve_feed(addr, *limit).await?,
+                None => exports::rss::write_feed(output.clone(), *limit)?,
+            }
+            return Ok(());
+        }
+        Some(Commands::Serve { addr }) => {
+            server::serve(addr).await?;
+            return Ok(());
+        }
+        Some(Commands::Watch) => {
+            watch::run(args.group.as_deref()).await?;
+            actions::interrupt::exit_if_interrupted();
+            return Ok(());
+        }
+        Some(Commands::Run {
+            pipeline,
+            fetch,
+            leads,
+            export,
+            notify,
+            subreddit,
+            relevance,
+        }) => {
+            let config = settings::api_keys::ConfigDirs::read_config()?;
+            let mut pipeline = match pipeline {
+                Some(name) => match config.pipeline(name).and_then(|settings| {
+                    Pipeline::from_settings(
+                        settings,
+                        &config,
+                        args.post_filter(),
+                        args.comment_options(),
+                        !args.no_cache,
+                    )
+                }) {
+                    Ok(pipeline) => pipeline,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(actions::outcome::EXIT_FAILURE);
+                    }
+                },
+                None => {
+                    let stages: Vec<Stage> = [
+                        fetch.then_some(Stage::Fetch),
+                        leads.then_some(Stage::Leads),
+                        export.map(Stage::Export),
+                        notify.then_some(Stage::Notify),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                    if stages.is_empty() {
+                        eprintln!(
+                            "Name a pipeline from [[pipelines]] or pick stages: --fetch, --leads, --export <FORMAT> or --notify"
+                        );
+                        std::process::exit(actions::outcome::EXIT_FAILURE);
+                    }
+                    Pipeline {
+                        name: String::new(),
+                        subreddit: config.api_keys.subreddit.clone(),
+                        relevance: config.api_keys.relevance.clone(),
+                        filter: args.post_filter(),
+                        comment_options: args.comment_options(),
+                        use_cache: !args.no_cache,
+                        notifications: config.notifications.clone(),
+                        stages,
+                    }
+                }
+            };
+            // The flags win over the pipeline's own subreddit and listing
+            if let Some(subreddit) = subreddit {
+                pipeline.subreddit = subreddit.clone();
+            }
+            if let Some(relevance) = relevance {
+                pipeline.relevance = relevance.clone();
+            }
+            let state = actions::pipeline::run(&pipeline, &config).await;
+            state.print();
+            if let Some(summary) = &state.fetch {
+                database::adding::DB::new()?.record_run_summary(summary)?;
+            }
+            actions::outcome::exit_with(state.exit_code());
+            return Ok(());
+        }
+        Some(Commands::Service { action }) => {
+            match action {
+                ServiceCommands::Install => actions::service::install_service(
+                    args.group.as_deref(),
+                    args.db_path.as_deref(),
+                )?,
+                ServiceCommands::Uninstall => actions::service::uninstall_service()?,
+            }
+            return Ok(());
+        }
+        Some(Commands::Status) => {
+            actions::status::print_status().await;
+            return Ok(());
+        }
+        Some(Commands::Quota) => {
+            actions::quota::print_quota().await?;
+            return Ok(());
+        }
+        Some(Commands::Runs { limit }) => {
+            actions::status::print_runs(*limit)?;
+            return Ok(());
+        }
+        Some(Commands::Diff { runs, limit }) => {
+            actions::status::print_run_diff(runs[0], runs[1], *limit)?;
+            return Ok(());
+        }
+        Some(Commands::Db {
+            action: DbCommands::Merge { file },
+        }) => {
+            actions::db::merge_database(file)?;
+            return Ok(());
+        }
+        Some(Commands::Db {
+            action: DbCommands::Publish,
+        }) => {
+            let config = settings::api_keys::ConfigDirs::read_config()?;
+            if config.publish.broker == settings::api_keys::Broker::None {
+                return Err("Set broker and url under [publish] in settings.toml first".into());
+            }
+            let published = publish::publish_new(&config.publish).await?;
+            println!(
+                "Published {} new items to {}",
+                published,
+                config.publish.url.trim()
+            );
+            return Ok(());
+        }
+        Some(Commands::Db {
+            action: DbCommands::Elasticsearch { all },
+        }) => {
+            let config = settings::api_keys::ConfigDirs::read_config()?;
+            let summary = elastic::push(&config.elasticsearch, *all).await?;
+            println!(
+                "Pushed {} posts and {} comments to {}",
+                summary.posts,
+                summary.comments,
+                config.elasticsearch.url.trim()
+            );
+            return Ok(());
+        }
+        Some(Commands::Thread {
+            action:
+                Some(ThreadCommands::Export {
+                    post,
+                    format,
+                    output,
+                }),
+            ..
+        }) => {
+            actions::thread::export_thread(post, *format, output.clone(), &args.comment_options())
+                .await?;
+            return Ok(());
+        }
+        Some(Commands::Searches) => {
+            actions::status::print_searches()?;
+            return Ok(());
+        }
+        Some(Commands::Tag { post, tags, remove }) => {
+            let post = resolve_post_ref(post).await?;
+            actions::tags::tag_post(&post, tags, *remove)?;
+            return Ok(());
+        }
+        Some(Commands::Star { post, remove }) => {
+            let post = resolve_post_ref(post).await?;
+            actions::tags::tag_post(&post, &[STARRED.to_string()], *remove)?;
+            return Ok(());
+        }
+        Some(Commands::Tags) => {
+            actions::tags::print_tags()?;
+            return Ok(());
+        }
+        Some(Commands::Note { item, text, clear }) => {
+            let target = match arguments::post_ref::comment_id(item) {
+                Some(comment_id) => NoteTarget::Comment(comment_id),
+                None => NoteTarget::Post(resolve_post_ref(item).await?),
+            };
+            actions::notes::edit_note(target, &text.join(" "), *clear)?;
+            return Ok(());
+        }
+        Some(Commands::Notes) => {
+            actions::notes::print_notes()?;
+            return Ok(());
+        }
+        Some(Commands::Pick) => {
+            actions::pick::pick(&args.ask_filter(), &args.comment_options()).await?;
+            return Ok(());
+        }
+        Some(Commands::Open { post }) => {
+            let listed = match post.trim().parse::<usize>() {
+                Ok(position) => actions::open::listed_post(position)?,
+                Err(_) => None,
+            };
+            let post = match listed {
+                Some(post) => post,
+                None => resolve_post_ref(post).await?,
+            };
+            actions::open::open_link(&actions::open::post_link(&post)?);
+            return Ok(());
+        }
+        Some(Commands::Keywords { action }) => {
+            match action {
+                KeywordsCommands::Stats { runs } => actions::keywords::print_keyword_stats(*runs)?,
+                KeywordsCommands::Suggest { yes } => {
+                    actions::keywords::suggest_keywords(*yes, !args.no_cache).await?
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Mcp { addr }) => {
+            mcp::serve(addr).await?;
+            return Ok(());
+        }
+        _ => {}
+    }
+
     // Config stuff from the settings file
     settings::api_keys::ConfigDirs::create_default_config().unwrap();
 
+    // Neither needs a Reddit token, so they also work offline and on servers
+    if args.settings {
+        settings::api_keys::ConfigDirs::edit_config_file()?;
+        return Ok(());
+    }
+
+    // Open database folder if requested
+    if args.open_db {
+        let db_path = database::adding::database_path()?
+            .parent()
+            .map(std::path::Path::to_path_buf)
+            .unwrap_or_default();
+        if actions::open::open_path(&db_path) {
+            println!("Opening database folder: {:?}", db_path);
+        } else {
+            println!("The database is in {}", db_path.display());
+        }
+        return Ok(());
+    }
+
     // Read the config
     let config = settings::api_keys::ConfigDirs::read_config().unwrap_or_else(|err| {
         eprintln!("Warning: using default config because: {err}");
         AppConfig::default()
     });
 
-    let api_keys = config.api_keys;
-    let client_id = api_keys.reddit_api_id;
-    let client_secret = api_keys.reddit_api_secret;
+    // Reusing recent responses would hide the new comments watching is for
+    if !matches!(
+        &args.command,
+        Some(Commands::Thread {
+            action: Some(ThreadCommands::Watch { .. }),
+            ..
+        })
+    ) {
+        reddit::cache::set_fresh_for(config.fetch.cache_fresh_seconds);
+    }
+
+    let client_id = config.api_keys.reddit_api_id.clone();
+    let client_secret = config.api_keys.reddit_api_secret.clone();
 
     // If the user has not set the API keys and app config, prompt them to do so
     let token = match get_access_token(client_id, client_secret).await {
@@ -275,258 +1305,274 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             eprintln!("Received an empty token. Check your API credentials.");
             settings::api_keys::ConfigDirs::edit_config_file()
                 .unwrap_or_else(|e| eprintln!("Failed to open config file for editing: {e}"));
-            return Ok(());
+            std::process::exit(actions::outcome::EXIT_AUTH);
+        }
+        // Unreachable Reddit is not a credentials problem, keep the editor closed
+        Err(RedditError::Reqwest(e)) => {
+            eprintln!("Failed to reach Reddit for an access token: {}", e);
+            std::process::exit(actions::outcome::EXIT_NETWORK);
         }
-
         Err(e) => {
-            eprintln!("Failed to retrieve access token: {:?}", e);
+            eprintln!("Failed to retrieve access token: {}", e);
             settings::api_keys::ConfigDirs::edit_config_file()
                 .unwrap_or_else(|e| eprintln!("Failed to open config file for editing: {e}"));
-            return Ok(());
+            std::process::exit(actions::outcome::EXIT_AUTH);
         }
     };
 
-    // initiate clap / args
-    let args = Args::parse();
-
-    // Handle comment fetching
-    if let Some(post_id) = args.comments {
-        println!("Fetching comments for post {}...", post_id);
+    // Live feed: fetch fresh posts first, then build the feed from the database
+    if let Some(Commands::Feed {
+        output,
+        serve,
+        limit,
+        ..
+    }) = &args.command
+    {
+        let subreddit = args.subreddit.as_deref().unwrap_or("supplychain");
+        let relevance = args.relevance.as_deref().unwrap_or("new");
+
+        actions::verbosity::info(format!(
+            "Fetching posts from r/{} ({} posts)...",
+            subreddit, relevance
+        ));
+        let progress = actions::progress::Progress::start("Waiting for Reddit...");
+        let listing = get_subreddit_posts(&token, subreddit, relevance).await;
+        progress.finish();
+        let (mut posts, mut media) = listing?;
+        actions::display::print_fetched(&posts);
+        let mut table = FetchTable::default();
+        table.add_fetched(&posts);
+        let filter = args.post_filter();
+        retain_matching(&filter, &mut posts, &mut media);
 
-        let post_details = get_post_comments(&token, &post_id)
-            .await
-            .expect("Failed to retrieve comments");
+        let mut db = database::adding::DB::new()?;
+        db.create_tables()?;
+        let new = new_post_ids(&db, &posts);
+        db.append_results(&posts)?;
+        db.append_media(&media)?;
+        db.record_sync(subreddit, posts.len())?;
+        table.add_stored(&posts, &new, fetch_keywords(&filter).as_ref());
+        table.print();
+
+        match serve {
+            Some(addr) => exports::rss::serve_feed(addr, *limit).await?,
+            None => exports::rss::write_feed(output.clone(), *limit)?,
+        }
+        return Ok(());
+    }
 
-        // Extract post title and subreddit from the first listing
-        let post_data = match &post_details[0].data.children[0].data {
-            RedditData::Post(post) => post,
-            _ => panic!("Expected post data"),
-        };
-        let post_title = post_data.title.clone();
-        let subreddit = post_data.subreddit.clone();
+    if let Some(Commands::Thread { action, post }) = &args.command {
+        match (action, post) {
+            (
+                Some(ThreadCommands::Watch {
+                    post,
+                    interval,
+                    keyword,
+                }),
+                _,
+            ) => {
+                actions::thread::watch_thread(
+                    token,
+                    post,
+                    *interval,
+                    keyword,
+                    &args.comment_options(),
+                )
+                .await?
+            }
+            (None, Some(post)) => {
+                actions::thread::snapshot_thread(&token, post, &args.comment_options()).await?
+            }
+            // Handled before the token is needed
+            (Some(ThreadCommands::Export { .. }), _) => {}
+            (None, None) => eprintln!(
+                "Pass a post id or URL, or use `ruddit thread watch <id>` or `ruddit thread export <id>`"
+            ),
+        }
+        actions::interrupt::exit_if_interrupted();
+        return Ok(());
+    }
 
-        // Get comments from second listing
-        let comments = post_details[1]
-            .data
-            .children
-            .iter()
-            .filter_map(|child| {
-                if let RedditData::Comment(comment) = &child.data {
-                    Some(comment.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+    // Handle comment fetching
+    if let Some(post) = &args.comments {
+        let post = resolve_post_ref(post).await?;
+        let post_id = post.id;
+        match &post.subreddit {
+            Some(subreddit) => actions::verbosity::info(format!(
+                "Fetching comments for post {} in r/{}...",
+                post_id, subreddit
+            )),
+            None => actions::verbosity::info(format!("Fetching comments for post {}...", post_id)),
+        }
 
-        // Convert to CommentDataWrapper
-        let comment_wrappers: Vec<CommentDataWrapper> = comments
-            .iter()
-            .map(|comment| CommentDataWrapper {
-                id: comment.id.clone(),
-                post_id: post_id.clone(),
-                body: comment.body.clone(),
-                author: comment.author.clone(),
-                timestamp: comment.created_utc as i64,
-                formatted_date: database::adding::DB::format_timestamp(comment.created_utc as i64)
-                    .expect("Failed to format timestamp"),
-                score: comment.score,
-                permalink: comment.permalink.clone(),
-                parent_id: comment.parent_id.clone(),
-                subreddit: subreddit.clone(),
-                post_title: post_title.clone(),
-            })
-            .collect();
+        let thread =
+            actions::thread::fetch_thread(&token, &post_id, &args.comment_options()).await?;
+        let comment_wrappers = &thread.comments;
 
-        println!("\nFound {} comments", comment_wrappers.len());
+        actions::verbosity::info(format!("\nFound {} comments", comment_wrappers.len()));
 
         // Print comments in a readable format
-        for (i, comment) in comment_wrappers.iter().enumerate() {
-            println!("\nComment #{}", i + 1);
-            println!("Subreddit: r/{}", comment.subreddit);
-            println!("Post: {}", comment.post_title);
-            println!("Author: u/{}", comment.author);
-            println!("Score: {} points", comment.score);
-            println!("Posted: {}", comment.formatted_date);
-            println!("Link: https://reddit.com{}", comment.permalink);
-            println!("\nContent:");
-            println!("{}\n", comment.body.replace("\\n", "\n").trim());
-            println!("{}", "-".repeat(80));
+        if !actions::verbosity::quiet() {
+            for (i, comment) in comment_wrappers.iter().enumerate() {
+                println!("\nComment #{}", i + 1);
+                println!("Subreddit: r/{}", comment.subreddit);
+                println!("Post: {}", comment.post_title);
+                println!("Author: u/{}", comment.author);
+                println!("Score: {} points", comment.score);
+                println!(
+                    "Posted: {}",
+                    actions::display::display_date(comment.timestamp, &comment.formatted_date)
+                );
+                println!("Link: https://reddit.com{}", comment.permalink);
+                println!("\nContent:");
+                println!("{}\n", filters::text::clean_text(&comment.body));
+                println!("{}", "-".repeat(80));
+            }
         }
 
         // Save to database
-        let mut db = database::adding::DB::new()?;
-        db.create_comments_table()?;
-        db.append_comments(&comment_wrappers)?;
+        let inserted = actions::thread::store_thread(&thread)?;
+
+        actions::verbosity::info(format!(
+            "\nSaved {}",
+            actions::display::stored_counts("comments", comment_wrappers.len(), inserted)
+        ));
 
-        println!("\nComments saved to database!");
+        let mut summary = RunSummary::default();
+        summary.add(comment_wrappers.len(), inserted);
 
         // Export comments to Excel if export flag is set
         if args.export {
-            if let Err(e) = exports::excel::export_comments_from_db(&post_id) {
+            if let Err(e) = exports::excel::export_comments_from_db(&token, &post_id).await {
                 eprintln!("Failed to export comments to Excel: {}", e);
+                summary.errors += 1;
             } else {
                 println!("Comments successfully exported to Excel!");
             }
         }
+        summary.finish();
         return Ok(());
     }
 
     // Find-Search option
-    if let (Some(keyword), Some(relevance)) = (args.find, &args.relevance) {
-        let posts = search_subreddit_posts(&token, &keyword, relevance)
-            .await
-            .expect("Failed to retrieve the posts data");
+    if let (Some(keyword), Some(relevance)) = (&args.find, &args.relevance) {
+        let progress =
+            actions::progress::Progress::start(format!("Searching Reddit for '{}'...", keyword));
+        let results = search_subreddit_posts(&token, reddit::search(keyword), relevance).await;
+        progress.finish();
+        let (mut posts, mut media) = results?;
+        actions::display::print_fetched(&posts);
+        let fetched = posts.len();
+        let mut table = FetchTable::default();
+        table.add_fetched(&posts);
+        let filter = args.post_filter();
+        retain_matching(&filter, &mut posts, &mut media);
         let mut db = database::adding::DB::new()?;
         db.create_tables()?;
-        db.append_results(&posts)?;
-        println!(
-            "Successfully appended {} new posts to database",
-            posts.len()
-        );
-        return Ok(());
-    }
-
-    // If the user needs to open the settings
-    // Run it before all the other logic
-    if args.settings {
-        settings::api_keys::ConfigDirs::edit_config_file().unwrap();
-    }
+        let new = new_post_ids(&db, &posts);
+        let inserted = db.append_results(&posts)?;
+        db.append_media(&media)?;
+        db.record_sync(&format!("search: {}", keyword), posts.len())?;
+        table.add_stored(&posts, &new, fetch_keywords(&filter).as_ref());
+        table.print();
+        actions::open::remember_listing(&posts);
+        if let Some(format) = args.copy {
+            actions::clipboard::copy_posts(&posts, format);
+        }
+        if let Some(first) = posts.first().filter(|_| args.open_first) {
+            actions::open::open_link(&actions::open::stored_link(first));
+        }
 
-    // Open database folder if requested
-    if args.open_db {
-        let base_dirs = directories::BaseDirs::new().expect("Failed to get base directories");
-        let db_path = base_dirs.config_dir().join("ruddit");
-
-        #[cfg(target_os = "macos")]
-        Command::new("open")
-            .arg(&db_path)
-            .spawn()
-            .expect("Failed to open database folder")
-            .wait()?;
-
-        #[cfg(target_os = "windows")]
-        Command::new("explorer")
-            .arg(&db_path)
-            .spawn()
-            .expect("Failed to open database folder")
-            .wait()?;
-
-        #[cfg(target_os = "linux")]
-        Command::new("xdg-open")
-            .arg(&db_path)
-            .spawn()
-            .expect("Failed to open database folder")
-            .wait()?;
-
-        println!("Opening database folder: {:?}", db_path);
+        elastic::push_after_fetch(&config).await;
+        publish::publish_after_run(&config).await;
+        let mut summary = RunSummary::default();
+        summary.add(fetched, inserted);
+        summary.finish();
         return Ok(());
     }
 
     // Query GEMINI
-    if let Some(q) = args.gemini {
-        match ai::gemini::ask_gemini(&q).await {
+    if let Some(q) = &args.gemini {
+        match ai::gemini::ask_gemini(
+            q,
+            &args.ask_filter(),
+            &ai::gemini::AskOptions {
+                all_posts: args.all_posts,
+                top_k: args.top_k,
+                comments_per_post: args.with_comments,
+                use_cache: !args.no_cache,
+            },
+        )
+        .await
+        {
             Ok(structured_data) => {
                 // Use serde_json to pretty-print the result
                 match serde_json::to_string_pretty(&structured_data) {
-                    Ok(pretty) => {
-                        println!("{}", pretty);
-                        return Ok(());
-                    }
+                    Ok(pretty) => println!("{}", pretty),
                     Err(e) => eprintln!("Error pretty-printing JSON: {}", e),
                 }
+
+                if args.export {
+                    match exports::answer::export_answer(q, &structured_data, args.answer_format) {
+                        Ok(path) => println!("Successfully exported answer to {:?}", path),
+                        Err(e) => {
+                            eprintln!("Failed to export answer: {}", e);
+                            actions::outcome::exit_with(actions::outcome::EXIT_PARTIAL);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Error from Gemini API call: {}", e);
+                actions::outcome::exit_with(actions::outcome::EXIT_FAILURE);
             }
-            Err(e) => eprintln!("Error from Gemini API call: {}", e),
         }
         return Ok(());
     }
 
     if args.export {
-        match exports::excel::create_excel() {
+        match exports::excel::create_excel(&args.post_filter()) {
             Ok(_) => println!("Successfully exported data to Excel"),
-            Err(e) => eprintln!("Failed to export data: {}", e),
+            Err(e) => {
+                eprintln!("Failed to export data: {}", e);
+                actions::outcome::exit_with(actions::outcome::EXIT_FAILURE);
+            }
         }
     } else if !args.export && !args.clear && !args.leads && !args.settings {
         // Only proceed if at least one argument is provided else use default values
         if args.subreddit.is_none() || args.subreddit.is_some() {
+            let filter = args.post_filter();
+            let comment_options = args.comment_options();
             let subreddit = args.subreddit.unwrap_or_else(|| "supplychain".to_string());
             let relevance = args.relevance.unwrap_or_else(|| "hot".to_string());
 
-            println!(
-                "Fetching posts from r/{} ({} posts)...",
-                subreddit, relevance
-            );
-
-            let posts = get_subreddit_posts(&token, &subreddit, &relevance)
-                .await
-                .expect("Failed to retrieve the posts data");
-
-            println!("Saving {} posts to database...", posts.len());
-            let mut db = database::adding::DB::new()?;
-            db.create_tables()?;
-            db.append_results(&posts)?;
-            println!(
-                "Successfully appended {} new posts to database",
-                posts.len()
-            );
-            // Also fetch and save comments for each post
-            println!("Fetching comments for posts...");
-            for post in &posts {
-                if let Ok(post_comments) = get_post_comments(&token, &post.id.to_string()).await {
-                    if let Some(post_data) = post_comments.first() {
-                        if let RedditData::Post(_post_info) = &post_data.data.children[0].data {
-                            let comments = post_comments[1]
-                                .data
-                                .children
-                                .iter()
-                                .filter_map(|child| {
-                                    if let RedditData::Comment(comment) = &child.data {
-                                        Some(CommentDataWrapper {
-                                            id: comment.id.clone(),
-                                            post_id: post.id.to_string(),
-                                            body: comment.body.clone(),
-                                            author: comment.author.clone(),
-                                            timestamp: comment.created_utc as i64,
-                                            formatted_date: database::adding::DB::format_timestamp(
-                                                comment.created_utc as i64,
-                                            )
-                                            .expect("Failed to format timestamp"),
-                                            score: comment.score,
-                                            permalink: comment.permalink.clone(),
-                                            parent_id: comment.parent_id.clone(),
-                                            subreddit: post.subreddit.clone(),
-                                            post_title: post.title.clone(),
-                                        })
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect::<Vec<_>>();
-
-                            if !comments.is_empty() {
-                                db.create_comments_table()?;
-                                db.append_comments(&comments)?;
-                            }
-                        }
-                    }
-                }
-            }
-
-            println!("Done! Posts and comments saved to database.");
+            let mut pool = AccountPool::with_primary(token, &config).await;
+            let (summary, _) = fetch_subreddit_with_comments(
+                &mut pool,
+                &subreddit,
+                &relevance,
+                &filter,
+                &comment_options,
+            )
+            .await?;
+
+            elastic::push_after_fetch(&config).await;
+            publish::publish_after_run(&config).await;
+            summary.finish();
         } else {
             println!("No subreddit or relevance specified. Use --help for usage info.");
         }
     } else if args.leads {
-        println!("Analyzing posts and comments for leads...");
-        match ai::gemini::gemini_generate_leads().await {
+        actions::verbosity::info("Analyzing posts and comments for leads...");
+        match ai::gemini::gemini_generate_leads(&args.post_filter(), !args.no_cache).await {
             Ok(_) => {
                 println!("Lead analysis completed successfully!");
                 println!("Results have been exported to Excel in the Reddit_data folder.");
+                publish::publish_after_run(&config).await;
                 return Ok(());
             }
             Err(e) => {
-                eprintln!("Failed to generate leads: {}", e);
-                return Ok(());
+                return Err(format!("Failed to generate leads: {}", e).into());
             }
         }
     }
@@ -0,0 +1,281 @@
+use axum::{Json, Router, http::StatusCode, response::IntoResponse, routing::post};
+use serde_json::{Value, json};
+
+use crate::database::adding::{DB, PostDataWrapper};
+use crate::database::queries::PostFilter;
+use crate::filters::questions::is_question;
+use crate::{ai, settings};
+
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+// Model Context Protocol server (Streamable HTTP transport, JSON responses)
+pub async fn serve(addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+    DB::new()?.create_tables()?;
+
+    let app = Router::new().route("/mcp", post(mcp_handler));
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Serving the Ruddit MCP server on http://{}/mcp", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn mcp_handler(Json(request): Json<Value>) -> impl IntoResponse {
+    let id = request.get("id").cloned();
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    // Notifications carry no id and expect no response body
+    let Some(id) = id else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let result = match method.as_str() {
+        "initialize" => Ok(json!({
+            "protocolVersion": params["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "ruddit", "version": env!("CARGO_PKG_VERSION") }
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => Ok(call_tool(&params).await),
+        _ => Err(json!({ "code": -32601, "message": format!("Method not found: {}", method) })),
+    };
+
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    };
+
+    Json(response).into_response()
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "fetch_subreddit",
+            "description": "Fetch posts from a subreddit and store them in the Ruddit database",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "subreddit": { "type": "string", "description": "Subreddit name without r/" },
+                    "relevance": { "type": "string", "description": "hot | new | top | rising", "default": "hot" }
+                },
+                "required": ["subreddit"]
+            }
+        },
+        {
+            "name": "search_reddit",
+            "description": "Search Reddit for posts matching a query and store them in the Ruddit database",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "relevance": { "type": "string", "default": "relevance" },
+                    "questions_only": { "type": "boolean", "description": "Only posts asking a question or for recommendations", "default": false }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "query_posts",
+            "description": "Query posts stored in the Ruddit database",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "subreddit": { "type": "string" },
+                    "contains": { "type": "string", "description": "Text to look for in title or body" },
+                    "since": { "type": "string", "description": "YYYY-MM-DD" },
+                    "until": { "type": "string", "description": "YYYY-MM-DD" },
+                    "limit": { "type": "integer", "default": 50 },
+                    "intent": { "type": "string", "description": "Only posts tagged with this intent, e.g. complaint" },
+                    "questions_only": { "type": "boolean", "description": "Only posts asking a question or for recommendations", "default": false }
+                }
+            }
+        },
+        {
+            "name": "get_comments",
+            "description": "Get the stored comments of a post",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "post_id": { "type": "string", "description": "Reddit post id, e.g. 1abc23" }
+                },
+                "required": ["post_id"]
+            }
+        },
+        {
+            "name": "generate_leads",
+            "description": "Run the Gemini lead analysis over the stored data and return the saved leads",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "questions_only": { "type": "boolean", "description": "Only posts asking a question or for recommendations", "default": false }
+                }
+            }
+        }
+    ])
+}
+
+async fn call_tool(params: &Value) -> Value {
+    let name = params["name"].as_str().unwrap_or_default();
+    let args = &params["arguments"];
+
+    let result = match name {
+        "fetch_subreddit" => fetch_subreddit(args).await,
+        "search_reddit" => search_reddit(args).await,
+        "query_posts" => query_posts(args),
+        "get_comments" => get_comments(args),
+        "generate_leads" => generate_leads(args).await,
+        _ => Err(format!("Unknown tool: {}", name)),
+    };
+
+    match result {
+        Ok(value) => json!({
+            "content": [{ "type": "text", "text": value.to_string() }],
+            "isError": false
+        }),
+        Err(e) => json!({
+            "content": [{ "type": "text", "text": e }],
+            "isError": true
+        }),
+    }
+}
+
+async fn reddit_token() -> Result<String, String> {
+    let config = settings::api_keys::ConfigDirs::read_config().map_err(|e| e.to_string())?;
+
+    crate::get_access_token(
+        config.api_keys.reddit_api_id,
+        config.api_keys.reddit_api_secret,
+    )
+    .await
+    .map_err(|e| format!("Failed to retrieve access token: {:?}", e))
+}
+
+fn required_str<'a>(args: &'a Value, name: &str) -> Result<&'a str, String> {
+    args[name]
+        .as_str()
+        .filter(|s| !s.trim().is_empty())
+        .ok_or(format!("Missing required argument '{}'", name))
+}
+
+fn posts_json(posts: &[PostDataWrapper]) -> Value {
+    posts
+        .iter()
+        .map(|post| {
+            json!({
+                "id": DB::encode_reddit_id(post.id),
+                "title": post.title,
+                "subreddit": post.subreddit,
+                "date": post.formatted_date,
+                "url": post.url,
+                "permalink": post.permalink,
+                "selftext": post.selftext,
+            })
+        })
+        .collect()
+}
+
+fn store_posts(
+    source: &str,
+    posts: &[PostDataWrapper],
+    media: &[crate::database::media::MediaDataWrapper],
+) -> Result<(), String> {
+    let mut db = DB::new().map_err(|e| e.to_string())?;
+    db.create_tables().map_err(|e| e.to_string())?;
+    db.append_results(posts).map_err(|e| e.to_string())?;
+    db.append_media(media).map_err(|e| e.to_string())?;
+    db.record_sync(source, posts.len())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn fetch_subreddit(args: &Value) -> Result<Value, String> {
+    let subreddit = required_str(args, "subreddit")?;
+    let relevance = args["relevance"].as_str().unwrap_or("hot");
+    let token = reddit_token().await?;
+
+    let (posts, media) = crate::get_subreddit_posts(&token, subreddit, relevance)
+        .await
+        .map_err(|e| format!("Failed to retrieve posts: {:?}", e))?;
+    store_posts(subreddit, &posts, &media)?;
+
+    Ok(posts_json(&posts))
+}
+
+async fn search_reddit(args: &Value) -> Result<Value, String> {
+    let query = required_str(args, "query")?;
+    let relevance = args["relevance"].as_str().unwrap_or("relevance");
+    let token = reddit_token().await?;
+
+    let (mut posts, media) =
+        crate::search_subreddit_posts(&token, crate::reddit::search(query), relevance)
+            .await
+            .map_err(|e| format!("Failed to search posts: {:?}", e))?;
+    if args["questions_only"].as_bool().unwrap_or(false) {
+        posts.retain(|post| is_question(&post.title, &post.selftext));
+    }
+    store_posts(&format!("search: {}", query), &posts, &media)?;
+
+    Ok(posts_json(&posts))
+}
+
+fn query_posts(args: &Value) -> Result<Value, String> {
+    let date = |name: &str| match args[name].as_str() {
+        Some(value) => DB::parse_date(value)
+            .map(Some)
+            .ok_or(format!("Invalid date '{}', expected YYYY-MM-DD", value)),
+        None => Ok(None),
+    };
+
+    let filter = PostFilter {
+        subreddit: args["subreddit"].as_str().map(String::from),
+        contains: args["contains"].as_str().map(String::from),
+        since: date("since")?,
+        until: date("until")?,
+        limit: Some(args["limit"].as_u64().unwrap_or(50) as usize),
+        offset: None,
+        questions_only: args["questions_only"].as_bool().unwrap_or(false),
+        intent: args["intent"].as_str().map(String::from),
+        run: None,
+        keywords: None,
+        min_score: None,
+        noise: false,
+        saved_search: None,
+        tag: None,
+        stored_since: None,
+    };
+
+    let posts = DB::new()
+        .and_then(|db| db.get_filtered_posts(&filter))
+        .map_err(|e| e.to_string())?;
+
+    Ok(posts_json(&posts))
+}
+
+fn get_comments(args: &Value) -> Result<Value, String> {
+    let post_id = required_str(args, "post_id")?;
+
+    let comments = DB::new()
+        .and_then(|db| db.get_post_comments(post_id))
+        .map_err(|e| e.to_string())?;
+
+    serde_json::to_value(comments).map_err(|e| e.to_string())
+}
+
+async fn generate_leads(args: &Value) -> Result<Value, String> {
+    let filter = PostFilter {
+        questions_only: args["questions_only"].as_bool().unwrap_or(false),
+        ..Default::default()
+    };
+    ai::gemini::gemini_generate_leads(&filter, true)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let leads = DB::new()
+        .and_then(|db| db.get_leads())
+        .map_err(|e| e.to_string())?;
+
+    serde_json::to_value(leads).map_err(|e| e.to_string())
+}
@@ -0,0 +1,29 @@
+use reqwest::Client;
+
+use crate::settings::api_keys::NotificationSettings;
+use crate::settings::redact::redact;
+
+// Print a notification and deliver it to every configured channel
+pub async fn send(settings: &NotificationSettings, message: &str) {
+    // Ring the terminal bell so the alert is noticed
+    println!("\x07{}", message);
+
+    if !settings.webhook_url.trim().is_empty()
+        && let Err(e) = post_webhook(settings.webhook_url.trim(), message).await
+    {
+        eprintln!(
+            "Failed to send webhook notification: {}",
+            redact(&e.to_string())
+        );
+    }
+}
+
+async fn post_webhook(url: &str, message: &str) -> Result<(), reqwest::Error> {
+    Client::new()
+        .post(url)
+        .json(&serde_json::json!({ "text": message }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+pub mod nats;
+
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::database::adding::DB;
+use crate::exports::anonymize::Anonymizer;
+use crate::settings::api_keys::{AppConfig, Broker, PublishSettings};
+use crate::settings::redact::redact;
+
+// Run published last, newer runs are published next time
+const PUBLISH_RUN_KEY: &str = "publish_run";
+
+// Records sent per Kafka REST Proxy request
+const KAFKA_BATCH_SIZE: usize = 500;
+
+// One JSON message, keyed by the Reddit id or the lead URL
+pub struct Message {
+    pub subject: String,
+    pub key: String,
+    pub payload: Value,
+}
+
+// Publish the posts, comments and leads stored since the last publish. When
+// nothing was published yet, only the latest run is, not the whole history.
+pub async fn publish_new(settings: &PublishSettings) -> Result<usize, Box<dyn std::error::Error>> {
+    let db = DB::new()?;
+    db.create_tables()?;
+    db.create_leads_table()?;
+    db.create_runs_table()?;
+    db.create_meta_table()?;
+    let anonymizer = Anonymizer::from_config()?;
+
+    let upto = db.max_run_id()?;
+    let after = db
+        .get_meta(PUBLISH_RUN_KEY)?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(upto - 1);
+    if after >= upto {
+        return Ok(0);
+    }
+
+    let topic = settings.topic.trim();
+    let mut messages = Vec::new();
+    for (_, post) in db.get_post_documents(after, upto, 0, usize::MAX)? {
+        messages.push(message(topic, "posts", post.id.clone(), &post)?);
+    }
+    for (_, mut comment) in db.get_comment_documents(after, upto, 0, usize::MAX)? {
+        comment.author = anonymizer.author(&comment.author);
+        messages.push(message(topic, "comments", comment.id.clone(), &comment)?);
+    }
+    for lead in db.get_leads_in_runs(after, upto)? {
+        messages.push(message(topic, "leads", lead.url.clone(), &lead)?);
+    }
+
+    match settings.broker {
+        Broker::None => return Ok(0),
+        Broker::Kafka => publish_kafka(settings.url.trim(), &messages).await?,
+        Broker::Nats => {
+            nats::publish(settings.url.trim(), settings.token.trim(), &messages).await?
+        }
+    }
+    db.set_meta(PUBLISH_RUN_KEY, &upto.to_string())?;
+    Ok(messages.len())
+}
+
+fn message<T: Serialize>(
+    topic: &str,
+    kind: &str,
+    key: String,
+    item: &T,
+) -> Result<Message, serde_json::Error> {
+    Ok(Message {
+        subject: format!("{}.{}", topic, kind),
+        key,
+        payload: serde_json::to_value(item)?,
+    })
+}
+
+// Kafka REST Proxy v2, one request per topic and batch
+async fn publish_kafka(url: &str, messages: &[Message]) -> Result<(), Box<dyn std::error::Error>> {
+    let client = Client::new();
+    let mut start = 0;
+    while start < messages.len() {
+        let subject = &messages[start].subject;
+        let batch: Vec<&Message> = messages[start..]
+            .iter()
+            .take_while(|message| &message.subject == subject)
+            .take(KAFKA_BATCH_SIZE)
+            .collect();
+        let records: Vec<Value> = batch
+            .iter()
+            .map(|message| json!({ "key": message.key, "value": message.payload }))
+            .collect();
+        client
+            .post(format!("{}/topics/{}", url.trim_end_matches('/'), subject))
+            .header("Content-Type", "application/vnd.kafka.json.v2+json")
+            .json(&json!({ "records": records }))
+            .send()
+            .await?
+            .error_for_status()?;
+        start += batch.len();
+    }
+    Ok(())
+}
+
+// Called after each fetch and lead run, does nothing unless [publish] is set
+// up. A failed publish is reported and retried with the next one.
+pub async fn publish_after_run(config: &AppConfig) {
+    if config.publish.broker == Broker::None || config.publish.url.trim().is_empty() {
+        return;
+    }
+    match publish_new(&config.publish).await {
+        Ok(0) => {}
+        Ok(published) => println!("Published {} new items", published),
+        Err(e) => eprintln!("Failed to publish new items: {}", redact(&e.to_string())),
+    }
+}
@@ -0,0 +1,63 @@
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::publish::Message;
+
+const DEFAULT_PORT: u16 = 4222;
+
+// Publish over the plain-text NATS protocol: read the server's INFO, send
+// CONNECT and one PUB per message, then PING and wait for the PONG that
+// confirms the server processed them all
+pub async fn publish(
+    url: &str,
+    token: &str,
+    messages: &[Message],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let address = url
+        .strip_prefix("nats://")
+        .unwrap_or(url)
+        .trim_end_matches('/');
+    let address = if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    };
+
+    let (reader, mut writer) = TcpStream::connect(&address).await?.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    match lines.next_line().await? {
+        Some(line) if line.starts_with("INFO") => {}
+        other => return Err(format!("{} is not a NATS server: {:?}", address, other).into()),
+    }
+
+    let mut connect = json!({ "verbose": false, "pedantic": false, "name": "ruddit" });
+    if !token.is_empty() {
+        connect["auth_token"] = json!(token);
+    }
+    let mut buffer = format!("CONNECT {}\r\n", connect).into_bytes();
+    for message in messages {
+        let payload = message.payload.to_string();
+        buffer.extend_from_slice(
+            format!(
+                "PUB {} {}\r\n{}\r\n",
+                message.subject,
+                payload.len(),
+                payload
+            )
+            .as_bytes(),
+        );
+    }
+    buffer.extend_from_slice(b"PING\r\n");
+    writer.write_all(&buffer).await?;
+
+    while let Some(line) = lines.next_line().await? {
+        if line.starts_with("PONG") {
+            return Ok(());
+        }
+        if let Some(error) = line.strip_prefix("-ERR") {
+            return Err(format!("NATS server error:{}", error).into());
+        }
+    }
+    Err("NATS server closed the connection".into())
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+use reqwest::header::{ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{RequestBuilder, Response, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::settings::paths::Paths;
+
+// Older entries are deleted, revalidating them would rarely pay off
+const KEEP_FOR: Duration = Duration::from_secs(86_400);
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+// How long a cached response is used without asking Reddit, from
+// cache_fresh_seconds under [fetch]. 0 revalidates every request.
+static FRESH_SECONDS: AtomicI64 = AtomicI64::new(0);
+static PRUNE: Once = Once::new();
+
+// An API response kept in the cache folder
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedResponse {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: i64,
+    body: String,
+}
+
+// For --no-cache: always ask Reddit and keep nothing
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn set_fresh_for(seconds: u64) {
+    FRESH_SECONDS.store(seconds.min(i64::MAX as u64) as i64, Ordering::Relaxed);
+}
+
+fn path(url: &Url) -> Option<PathBuf> {
+    if DISABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let key: String = Sha256::digest(url.as_str().as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let folder = Paths::get().ok()?.cache_dir.join("responses");
+    Some(folder.join(format!("{}.json", key)))
+}
+
+fn load(url: &Url) -> Option<CachedResponse> {
+    let cached: CachedResponse = serde_json::from_slice(&fs::read(path(url)?).ok()?).ok()?;
+    // Guards against a hash collision or a hand-edited file
+    (cached.url == url.as_str()).then_some(cached)
+}
+
+fn save(url: &Url, cached: &CachedResponse) {
+    let Some(path) = path(url) else {
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, serde_json::to_vec(cached)?));
+    if let Err(e) = result {
+        log::warn!("Failed to cache the response of {}: {}", url, e);
+    }
+    PRUNE.call_once(|| {
+        if let Some(folder) = path.parent() {
+            prune(folder);
+        }
+    });
+}
+
+// The cached response when it was fetched less than FRESH_SECONDS ago
+pub fn fresh(url: &Url) -> Option<Response> {
+    let fresh_seconds = FRESH_SECONDS.load(Ordering::Relaxed);
+    if fresh_seconds == 0 {
+        return None;
+    }
+    let cached = load(url)?;
+    if Utc::now().timestamp() - cached.fetched_at >= fresh_seconds {
+        return None;
+    }
+    super::client::stored_response(url, 200, cached.body.into_bytes(), "cache")
+}
+
+// Ask Reddit to answer 304 Not Modified when the cached copy is still current
+pub fn conditional(url: &Url, request: RequestBuilder) -> RequestBuilder {
+    let Some(cached) = load(url) else {
+        return request;
+    };
+    let mut request = request;
+    if let Some(etag) = &cached.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &cached.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    request
+}
+
+// The cached copy after a 304, fresh again from now
+pub fn revalidated(url: &Url) -> Option<Response> {
+    let mut cached = load(url)?;
+    cached.fetched_at = Utc::now().timestamp();
+    save(url, &cached);
+    super::client::stored_response(url, 200, cached.body.into_bytes(), "cache")
+}
+
+// Keep a successful response and its validators
+pub fn store(url: &Url, headers: &HeaderMap, body: &str) {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    save(
+        url,
+        &CachedResponse {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            fetched_at: Utc::now().timestamp(),
+            body: body.to_string(),
+        },
+    );
+}
+
+fn prune(folder: &std::path::Path) {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return;
+    };
+    let cutoff = SystemTime::now() - KEEP_FOR;
+    for entry in entries.flatten() {
+        let old = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified < cutoff);
+        if old {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
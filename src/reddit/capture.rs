@@ -0,0 +1,72 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::{Local, Utc};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::paths::Paths;
+
+// Index of a capture folder, one line per response
+pub const INDEX_FILE: &str = "requests.jsonl";
+
+// Folder of this run's captures, set by --capture
+static FOLDER: OnceLock<PathBuf> = OnceLock::new();
+static CAPTURED: AtomicUsize = AtomicUsize::new(0);
+
+// A captured response as listed in the index. The body is kept as Reddit
+// sent it in `file`, next to the index.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CapturedResponse {
+    pub file: String,
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub captured_at: i64,
+}
+
+// Start writing every Reddit API response of this run to a new folder under
+// the cache dir, returned so it can be shown
+pub fn enable() -> Result<PathBuf, String> {
+    let folder = Paths::get()?.cache_dir.join("captures").join(format!(
+        "{}-{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    ));
+    fs::create_dir_all(&folder)
+        .map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    Ok(FOLDER.get_or_init(|| folder).clone())
+}
+
+// Save one response body with its request, when capturing. The OAuth token
+// response is left out, it holds the access token.
+pub fn record(method: &str, url: &Url, status: StatusCode, body: &str) {
+    let Some(folder) = FOLDER.get() else {
+        return;
+    };
+    if url.path() == "/api/v1/access_token" {
+        return;
+    }
+    let number = CAPTURED.fetch_add(1, Ordering::Relaxed) + 1;
+    let entry = CapturedResponse {
+        file: format!("{:04}.json", number),
+        method: method.to_string(),
+        url: url.to_string(),
+        status: status.as_u16(),
+        captured_at: Utc::now().timestamp(),
+    };
+
+    let result = fs::write(folder.join(&entry.file), body).and_then(|_| {
+        let mut index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(folder.join(INDEX_FILE))?;
+        writeln!(index, "{}", serde_json::to_string(&entry)?)
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to capture the response of {}: {}", entry.url, e);
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use futures::Stream;
+use futures::stream;
+use reqwest::{Client, Response, ResponseBuilderExt, StatusCode, Url};
+use serde::de::DeserializeOwned;
+
+use super::{cache, capture, replay};
+use crate::actions::interrupt;
+use crate::database::adding::DB;
+use crate::{RedditData, RedditError, RedditListing, RedditPost};
+
+// Largest page the listing endpoints return
+pub const MAX_PAGE_SIZE: usize = 100;
+
+// Times a page answered with 429 is retried before the stream gives up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+// Wait after a 429 without a Retry-After header, in seconds
+const DEFAULT_RETRY_AFTER: u64 = 60;
+
+// Longest wait a Retry-After header can ask for, one rate limit window
+const MAX_RETRY_AFTER: u64 = 600;
+
+// Which posts of a subreddit listing to read
+#[derive(Debug, Clone)]
+pub struct ListingOptions {
+    // hot, new, top, rising or controversial
+    pub sort: String,
+    // Posts read across all pages, 0 reads until the listing ends
+    pub limit: usize,
+}
+
+impl Default for ListingOptions {
+    fn default() -> Self {
+        ListingOptions {
+            sort: "hot".to_string(),
+            limit: MAX_PAGE_SIZE,
+        }
+    }
+}
+
+// Access tokens are valid for an hour. The app credentials behind every token
+// are kept so a token that expires during a long crawl can be renewed, and
+// the replacement is shared by every client still holding the old one.
+#[derive(Default)]
+struct TokenRegistry {
+    credentials: HashMap<String, (String, String)>,
+    renewed: HashMap<String, String>,
+}
+
+static TOKENS: LazyLock<Mutex<TokenRegistry>> = LazyLock::new(Mutex::default);
+
+// Remember which app a token was issued to
+pub fn remember_token(token: &str, client_id: &str, client_secret: &str) {
+    if let Ok(mut tokens) = TOKENS.lock() {
+        tokens.credentials.insert(
+            token.to_string(),
+            (client_id.to_string(), client_secret.to_string()),
+        );
+    }
+}
+
+// The token that replaced `token`, or `token` itself
+fn current_token(token: &str) -> String {
+    let Ok(tokens) = TOKENS.lock() else {
+        return token.to_string();
+    };
+    let mut current = token;
+    while let Some(next) = tokens.renewed.get(current) {
+        current = next;
+    }
+    current.to_string()
+}
+
+// Log in again with the app behind an expired token. Returns false when the
+// app is unknown or the login fails.
+async fn renew_token(expired: &str) -> bool {
+    let credentials = match TOKENS.lock() {
+        Ok(tokens) => tokens.credentials.get(expired).cloned(),
+        Err(_) => None,
+    };
+    let Some((client_id, client_secret)) = credentials else {
+        return false;
+    };
+
+    match crate::get_access_token(client_id, client_secret).await {
+        Ok(token) if !token.is_empty() => {
+            if let Ok(mut tokens) = TOKENS.lock() {
+                tokens.renewed.insert(expired.to_string(), token);
+            }
+            // stderr, so JSON and CSV written to stdout stay parseable
+            eprintln!("Reddit access token expired, logged in again");
+            true
+        }
+        _ => false,
+    }
+}
+
+// Reddit API client for an access token
+#[derive(Debug, Clone)]
+pub struct RedditClient {
+    http: Client,
+    token: String,
+}
+
+// Rate limit headers Reddit sends with every API response
+#[derive(Debug, Clone, Default)]
+pub struct RateLimit {
+    // Requests used and left in the current window
+    pub used: Option<f64>,
+    pub remaining: Option<f64>,
+    // Until the window resets
+    pub reset: Option<Duration>,
+}
+
+impl RateLimit {
+    fn from_response(response: &Response) -> Self {
+        RateLimit {
+            used: header_number(response, "x-ratelimit-used"),
+            remaining: header_number(response, "x-ratelimit-remaining"),
+            reset: header_number(response, "x-ratelimit-reset").map(Duration::from_secs_f64),
+        }
+    }
+}
+
+// A listing page and what to do before asking for the next one
+struct Page {
+    posts: Vec<RedditPost>,
+    after: Option<String>,
+    // Set when the rate limit window is used up
+    wait: Option<Duration>,
+}
+
+// Where a post stream is between pages
+struct Cursor {
+    client: RedditClient,
+    subreddit: String,
+    options: ListingOptions,
+    after: Option<String>,
+    buffer: VecDeque<RedditPost>,
+    yielded: usize,
+    wait: Option<Duration>,
+    done: bool,
+}
+
+impl RedditClient {
+    pub fn new(token: impl Into<String>) -> Self {
+        RedditClient {
+            http: Client::new(),
+            token: token.into(),
+        }
+    }
+
+    // Authorized GET of an API URL. Every request is counted, for `ruddit quota`.
+    // A 401 renews the token once and repeats the request, a 429 waits for
+    // Retry-After and tries again before giving up with RateLimited.
+    pub async fn get(&self, url: Url) -> Result<Response, RedditError> {
+        self.send(url, true, true).await
+    }
+
+    // Like get, but a 429 is returned as RateLimited straight away, for callers
+    // that can move on to another account
+    pub async fn get_or_rate_limited(&self, url: Url) -> Result<Response, RedditError> {
+        self.send(url, false, true).await
+    }
+
+    // `use_cache` answers from a response cached within cache_fresh_seconds
+    // and revalidates the others with their ETag
+    async fn send(
+        &self,
+        url: Url,
+        wait_on_rate_limit: bool,
+        use_cache: bool,
+    ) -> Result<Response, RedditError> {
+        let use_cache = use_cache && !replay::is_enabled();
+        if use_cache && let Some(response) = cache::fresh(&url) {
+            return Ok(response);
+        }
+
+        let mut renewed = false;
+        let mut retries = 0;
+        loop {
+            let token = current_token(&self.token);
+            let response = match replay::response(&url) {
+                Some(response) => response,
+                None => {
+                    let mut request = self
+                        .http
+                        .get(url.clone())
+                        .header("Authorization", format!("Bearer {}", token))
+                        .header("User-Agent", "RustRedditApp/0.1 by YourUsername");
+                    if use_cache {
+                        request = cache::conditional(&url, request);
+                    }
+                    let response = request.send().await?;
+                    // Counting must never fail a request, e.g. with --read-only
+                    let _ = DB::new().and_then(|db| db.record_api_request());
+                    response
+                }
+            };
+            if response.status() == StatusCode::NOT_MODIFIED
+                && let Some(cached) = cache::revalidated(&url)
+            {
+                return Ok(cached);
+            }
+
+            if response.status() == StatusCode::UNAUTHORIZED && !renewed {
+                renewed = true;
+                if renew_token(&token).await {
+                    continue;
+                }
+            }
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                if !wait_on_rate_limit || retries == MAX_RATE_LIMIT_RETRIES {
+                    return Err(RedditError::RateLimited);
+                }
+                retries += 1;
+                let wait = header_number(&response, "retry-after")
+                    .unwrap_or(DEFAULT_RETRY_AFTER as f64)
+                    .min(MAX_RETRY_AFTER as f64)
+                    .ceil() as u64;
+                eprintln!("Rate limited by Reddit, resuming in {}s", wait);
+                // A replayed 429 is followed by the response that came after it
+                if !replay::is_enabled() && !interrupt::sleep(Duration::from_secs(wait)).await {
+                    return Err(RedditError::RateLimited);
+                }
+                continue;
+            }
+            return Ok(response);
+        }
+    }
+
+    // Where the rate limit window stands, read from a request that lists nothing
+    pub async fn rate_limit(&self) -> Result<RateLimit, RedditError> {
+        // Never cached, the headers have to be current
+        let response = check(self.send(super::rate_limit_probe(), true, false).await?).await?;
+        Ok(RateLimit::from_response(&response))
+    }
+
+    // Posts of a subreddit in listing order. Pages are fetched as the stream is
+    // read, following the `after` cursor and waiting out rate limits, so posts
+    // can be processed as they arrive. The stream ends at the limit, at the end
+    // of the listing, after an error or on Ctrl-C.
+    pub fn posts(
+        &self,
+        subreddit: &str,
+        options: &ListingOptions,
+    ) -> impl Stream<Item = Result<RedditPost, RedditError>> + use<> {
+        let cursor = Cursor {
+            client: self.clone(),
+            subreddit: subreddit.to_string(),
+            options: options.clone(),
+            after: None,
+            buffer: VecDeque::new(),
+            yielded: 0,
+            wait: None,
+            done: false,
+        };
+
+        stream::unfold(cursor, |mut cursor| async move {
+            loop {
+                let limit = cursor.options.limit;
+                if limit > 0 && cursor.yielded >= limit {
+                    return None;
+                }
+                if let Some(post) = cursor.buffer.pop_front() {
+                    cursor.yielded += 1;
+                    return Some((Ok(post), cursor));
+                }
+                if cursor.done || interrupt::interrupted() {
+                    return None;
+                }
+                if let Some(wait) = cursor.wait.take()
+                    && !interrupt::sleep(wait).await
+                {
+                    return None;
+                }
+
+                let page_size = match limit {
+                    0 => MAX_PAGE_SIZE,
+                    _ => (limit - cursor.yielded).min(MAX_PAGE_SIZE),
+                };
+                let page = cursor
+                    .client
+                    .listing_page(
+                        &cursor.subreddit,
+                        &cursor.options.sort,
+                        page_size,
+                        cursor.after.as_deref(),
+                    )
+                    .await;
+                match page {
+                    Ok(page) => {
+                        cursor.done = page.after.is_none() || page.posts.is_empty();
+                        cursor.after = page.after;
+                        cursor.wait = page.wait;
+                        cursor.buffer.extend(page.posts);
+                    }
+                    Err(e) => {
+                        cursor.done = true;
+                        return Some((Err(e), cursor));
+                    }
+                }
+            }
+        })
+    }
+
+    async fn listing_page(
+        &self,
+        subreddit: &str,
+        sort: &str,
+        limit: usize,
+        after: Option<&str>,
+    ) -> Result<Page, RedditError> {
+        let response = self
+            .get(super::subreddit_listing(subreddit, sort, limit, after))
+            .await?;
+
+        let rate_limit = RateLimit::from_response(&response);
+        let wait = match rate_limit.remaining {
+            Some(remaining) if remaining < 1.0 => rate_limit.reset,
+            _ => None,
+        };
+
+        let listing: RedditListing = json(response).await?;
+        let posts = listing
+            .data
+            .children
+            .into_iter()
+            .filter_map(|child| match child.data {
+                RedditData::Post(post) => Some(post),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Page {
+            posts,
+            after: listing.data.after,
+            wait,
+        })
+    }
+}
+
+// The response when it succeeded, otherwise an Api error with the message
+// from its body
+pub async fn check(response: Response) -> Result<Response, RedditError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let url = response.url().clone();
+    let body = response.text().await.unwrap_or_default();
+    capture::record("GET", &url, status, &body);
+    Err(api_error(status, url.path(), &body, None))
+}
+
+// Decode a JSON response. A body that is not the expected shape is reported
+// with Reddit's own error message when it has one.
+pub async fn json<T: DeserializeOwned>(response: Response) -> Result<T, RedditError> {
+    let response = check(response).await?;
+    let status = response.status();
+    let url = response.url().clone();
+    let headers = response.headers().clone();
+    let body = response.text().await?;
+    if headers.get(SOURCE_HEADER).is_none() {
+        capture::record("GET", &url, status, &body);
+        if url.host_str() == Some(super::API_HOST) {
+            cache::store(&url, &headers, &body);
+        }
+    }
+    serde_json::from_str(&body).map_err(|e| {
+        api_error(
+            status,
+            url.path(),
+            &body,
+            Some(format!("unexpected response ({})", e)),
+        )
+    })
+}
+
+fn api_error(
+    status: StatusCode,
+    endpoint: &str,
+    body: &str,
+    fallback: Option<String>,
+) -> RedditError {
+    let message = super::error_message(body).or(fallback).unwrap_or_else(|| {
+        status
+            .canonical_reason()
+            .unwrap_or("request failed")
+            .to_string()
+    });
+    RedditError::Api {
+        status: status.as_u16(),
+        message,
+        endpoint: endpoint.to_string(),
+    }
+}
+
+// Marks responses that were not received from Reddit just now
+const SOURCE_HEADER: &str = "x-ruddit-source";
+
+// A response built from a stored body, `source` says where it came from
+pub fn stored_response(url: &Url, status: u16, body: Vec<u8>, source: &str) -> Option<Response> {
+    http::Response::builder()
+        .status(status)
+        .url(url.clone())
+        .header("content-type", "application/json")
+        .header(SOURCE_HEADER, source)
+        .body(body)
+        .ok()
+        .map(Response::from)
+}
+
+fn header_number(response: &Response, name: &str) -> Option<f64> {
+    response
+        .headers()
+        .get(name)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|value: &f64| value.is_finite() && *value >= 0.0)
+}
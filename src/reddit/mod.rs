@@ -0,0 +1,301 @@
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::arguments::modeling::CommentOptions;
+
+pub mod cache;
+pub mod capture;
+pub mod client;
+pub mod replay;
+
+const API_BASE: &str = "https://oauth.reddit.com";
+
+// Host of API_BASE, only its responses are cached
+const API_HOST: &str = "oauth.reddit.com";
+
+// Build an API URL from raw path segments and query pairs. Every part is
+// percent-encoded, so subreddit names, post ids and search queries can never
+// break out of their place in the URL.
+fn endpoint(segments: &[&str], query: &[(&str, &str)]) -> Url {
+    let mut url = Url::parse(API_BASE).expect("API base URL is valid");
+    url.path_segments_mut()
+        .expect("API base URL has a path")
+        .extend(segments);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    url
+}
+
+// One page of `/r/<subreddit>/<sort>`, continuing after the `after` cursor of
+// the previous page. Multireddits like `rust+golang` keep their `+`.
+pub fn subreddit_listing(subreddit: &str, sort: &str, limit: usize, after: Option<&str>) -> Url {
+    let limit = limit.to_string();
+    let mut query = vec![("limit", limit.as_str())];
+    if let Some(after) = after {
+        query.push(("after", after));
+    }
+    endpoint(&["r", subreddit.trim(), sort.trim()], &query)
+}
+
+// Site-wide search, the query is sent as typed so operators such as
+// `author:`, `subreddit:`, `self:yes` and quoted phrases keep working
+pub fn search(query: &str) -> Url {
+    endpoint(
+        &["search"],
+        &[("q", query), ("limit", "1000"), ("t", "all")],
+    )
+}
+
+// Newest results first, for saved searches that only look at what is new.
+// With a subreddit the search stays inside it.
+pub fn search_new(query: &str, subreddit: Option<&str>) -> Url {
+    let mut params = vec![("q", query), ("sort", "new"), ("limit", "100")];
+    match subreddit {
+        Some(subreddit) => {
+            params.push(("restrict_sr", "on"));
+            endpoint(&["r", subreddit.trim(), "search"], &params)
+        }
+        None => endpoint(&["search"], &params),
+    }
+}
+
+// Details of things by fullname, e.g. `t3_1abc2d` for a post
+pub fn info(fullname: &str) -> Url {
+    endpoint(&["api", "info"], &[("id", fullname.trim())])
+}
+
+// An empty /api/info lookup, a request that costs one call and returns the
+// rate limit headers
+pub fn rate_limit_probe() -> Url {
+    endpoint(&["api", "info"], &[])
+}
+
+// A post and its comment tree. `raw_json` asks for text without HTML entities.
+pub fn comments(post_id: &str, options: &CommentOptions, raw_json: bool) -> Url {
+    let limit = options.limit.to_string();
+    let mut query = vec![("sort", options.sort.as_param())];
+    if raw_json {
+        query.push(("raw_json", "1"));
+    }
+    if options.limit > 0 {
+        query.push(("limit", &limit));
+    }
+    endpoint(&["comments", post_id.trim()], &query)
+}
+
+// The replies under one comment, what a "continue this thread" link opens
+pub fn comment_replies(
+    post_id: &str,
+    comment_id: &str,
+    options: &CommentOptions,
+    raw_json: bool,
+) -> Url {
+    let mut url = comments(post_id, options, raw_json);
+    url.query_pairs_mut()
+        .append_pair("comment", comment_id.trim());
+    url
+}
+
+// What went wrong according to an error body. Reddit answers with
+// `{"message": "Forbidden", "error": 403}`, sometimes with a `reason` or an
+// `explanation`, with `{"json": {"errors": [[CODE, text, field]]}}` from
+// search and form endpoints and with `{"error": "invalid_grant"}` from OAuth.
+pub fn error_message(body: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(body).ok()?;
+
+    let errors: Vec<String> = value["json"]["errors"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|error| {
+            let code = error[0].as_str()?;
+            Some(match error[1].as_str().filter(|text| !text.is_empty()) {
+                Some(text) => format!("{} ({})", text, code),
+                None => code.to_string(),
+            })
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Some(errors.join("; "));
+    }
+
+    let text = |key: &str| value[key].as_str().map(str::trim).filter(|s| !s.is_empty());
+    let mut message = text("message")
+        .or(text("error_description"))
+        .or(text("error"))
+        .or(text("explanation"))?
+        .to_string();
+    if let Some(reason) = text("reason") {
+        message = format!("{} ({})", message, reason);
+    }
+    if let Some(explanation) = text("explanation")
+        && !message.starts_with(explanation)
+    {
+        message = format!("{}: {}", message, explanation);
+    }
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_query_with_spaces_and_quotes_is_encoded() {
+        let url = search("\"supply chain\" author:some_user");
+        assert_eq!(
+            url.as_str(),
+            "https://oauth.reddit.com/search?q=%22supply+chain%22+author%3Asome_user&limit=1000&t=all"
+        );
+    }
+
+    #[test]
+    fn search_query_cannot_inject_parameters() {
+        let url = search("wms&limit=1#top");
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(pairs[0], ("q".to_string(), "wms&limit=1#top".to_string()));
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(url.fragment(), None);
+    }
+
+    #[test]
+    fn search_query_keeps_unicode() {
+        let url = search("café self:yes");
+        let q = url.query_pairs().next().unwrap().1.into_owned();
+        assert_eq!(q, "café self:yes");
+    }
+
+    #[test]
+    fn subreddit_name_stays_in_its_segment() {
+        let url = subreddit_listing("supply chain/../../api", "hot", 100, None);
+        assert_eq!(
+            url.as_str(),
+            "https://oauth.reddit.com/r/supply%20chain%2F..%2F..%2Fapi/hot?limit=100"
+        );
+    }
+
+    #[test]
+    fn multireddit_keeps_plus() {
+        let url = subreddit_listing("rust+golang", "new", 100, None);
+        assert_eq!(
+            url.as_str(),
+            "https://oauth.reddit.com/r/rust+golang/new?limit=100"
+        );
+    }
+
+    #[test]
+    fn listing_page_continues_after_cursor() {
+        let url = subreddit_listing("rust", "hot", 25, Some("t3_1abc2d"));
+        assert_eq!(
+            url.as_str(),
+            "https://oauth.reddit.com/r/rust/hot?limit=25&after=t3_1abc2d"
+        );
+    }
+
+    #[test]
+    fn saved_search_stays_in_its_subreddit() {
+        let url = search_new("wms self:yes", Some("logistics"));
+        assert_eq!(
+            url.as_str(),
+            "https://oauth.reddit.com/r/logistics/search?q=wms+self%3Ayes&sort=new&limit=100&restrict_sr=on"
+        );
+    }
+
+    #[test]
+    fn rate_limit_probe_lists_nothing() {
+        assert_eq!(
+            rate_limit_probe().as_str(),
+            "https://oauth.reddit.com/api/info"
+        );
+    }
+
+    #[test]
+    fn info_url_takes_a_fullname() {
+        let url = info("t3_1abc2d");
+        assert_eq!(
+            url.as_str(),
+            "https://oauth.reddit.com/api/info?id=t3_1abc2d"
+        );
+    }
+
+    #[test]
+    fn comments_url_has_sort_and_limit() {
+        let options = CommentOptions::default();
+        let url = comments("1abc2d", &options, true);
+        assert_eq!(
+            url.as_str(),
+            format!(
+                "https://oauth.reddit.com/comments/1abc2d?sort={}&raw_json=1&limit={}",
+                options.sort.as_param(),
+                options.limit
+            )
+        );
+    }
+
+    #[test]
+    fn comment_replies_url_focuses_the_comment() {
+        let options = CommentOptions {
+            limit: 0,
+            ..CommentOptions::default()
+        };
+        let url = comment_replies("1abc2d", "k9x&y", &options, true);
+        assert_eq!(
+            url.as_str(),
+            format!(
+                "https://oauth.reddit.com/comments/1abc2d?sort={}&raw_json=1&comment=k9x%26y",
+                options.sort.as_param()
+            )
+        );
+    }
+
+    #[test]
+    fn comments_url_without_limit() {
+        let options = CommentOptions {
+            limit: 0,
+            ..CommentOptions::default()
+        };
+        let url = comments("1abc2d?x=1", &options, false);
+        assert_eq!(
+            url.as_str(),
+            format!(
+                "https://oauth.reddit.com/comments/1abc2d%3Fx=1?sort={}",
+                options.sort.as_param()
+            )
+        );
+    }
+
+    #[test]
+    fn error_message_reads_every_error_shape() {
+        assert_eq!(
+            error_message(r#"{"message": "Forbidden", "error": 403}"#).as_deref(),
+            Some("Forbidden")
+        );
+        assert_eq!(
+            error_message(r#"{"reason": "private", "message": "Forbidden", "error": 403}"#)
+                .as_deref(),
+            Some("Forbidden (private)")
+        );
+        assert_eq!(
+            error_message(
+                r#"{"reason": "banned", "message": "Not Found", "error": 404, "explanation": "This community has been banned"}"#
+            )
+            .as_deref(),
+            Some("Not Found (banned): This community has been banned")
+        );
+        assert_eq!(
+            error_message(
+                r#"{"json": {"errors": [["BAD_SEARCH_QUERY", "that search query is too long", "q"]]}}"#
+            )
+            .as_deref(),
+            Some("that search query is too long (BAD_SEARCH_QUERY)")
+        );
+        assert_eq!(
+            error_message(r#"{"error": "invalid_grant"}"#).as_deref(),
+            Some("invalid_grant")
+        );
+        assert_eq!(error_message(r#"{"error": 500}"#), None);
+        assert_eq!(error_message("<html>Too Many Requests</html>"), None);
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use reqwest::{Response, Url};
+
+use super::capture::{CapturedResponse, INDEX_FILE};
+
+// Responses of a --capture folder served instead of calling Reddit
+struct Replay {
+    folder: PathBuf,
+    // Captures of each URL in the order they were made, and how many of them
+    // were served
+    responses: Mutex<HashMap<String, (Vec<CapturedResponse>, usize)>>,
+}
+
+static REPLAY: OnceLock<Replay> = OnceLock::new();
+
+// Answer Reddit API requests from a capture folder for the rest of the run.
+// Returns how many responses it holds.
+pub fn enable(folder: &Path) -> Result<usize, String> {
+    let index = fs::read_to_string(folder.join(INDEX_FILE)).map_err(|e| {
+        format!(
+            "{} is not a capture folder, {} could not be read: {}",
+            folder.display(),
+            INDEX_FILE,
+            e
+        )
+    })?;
+
+    let mut responses: HashMap<String, (Vec<CapturedResponse>, usize)> = HashMap::new();
+    let mut count = 0;
+    for (number, line) in index.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: CapturedResponse = serde_json::from_str(line)
+            .map_err(|e| format!("Line {} of {}: {}", number + 1, INDEX_FILE, e))?;
+        responses
+            .entry(entry.url.clone())
+            .or_default()
+            .0
+            .push(entry);
+        count += 1;
+    }
+
+    REPLAY
+        .set(Replay {
+            folder: folder.to_path_buf(),
+            responses: Mutex::new(responses),
+        })
+        .map_err(|_| "Replay is already enabled".to_string())?;
+    Ok(count)
+}
+
+pub fn is_enabled() -> bool {
+    REPLAY.get().is_some()
+}
+
+// The captured response for `url`, None when not replaying. A URL captured
+// several times gets its responses in order, then the last one again. A URL
+// that was never captured gets a 404 so the run carries on as if the post
+// was gone.
+pub fn response(url: &Url) -> Option<Response> {
+    let replay = REPLAY.get()?;
+
+    let entry = replay.responses.lock().ok().and_then(|mut responses| {
+        let (entries, served) = responses.get_mut(url.as_str())?;
+        let entry = entries.get(*served).or(entries.last())?.clone();
+        *served += 1;
+        Some(entry)
+    });
+
+    let (status, body) = match entry {
+        Some(entry) => match fs::read(replay.folder.join(&entry.file)) {
+            Ok(body) => (entry.status, body),
+            Err(e) => (
+                500,
+                error_body(500, &format!("Failed to read {}: {}", entry.file, e)),
+            ),
+        },
+        None => (404, error_body(404, "Not in the replayed capture")),
+    };
+
+    super::client::stored_response(url, status, body, "replay")
+}
+
+// An error body shaped like Reddit's own
+fn error_body(status: u16, message: &str) -> Vec<u8> {
+    serde_json::json!({ "message": message, "error": status })
+        .to_string()
+        .into_bytes()
+}
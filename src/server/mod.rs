@@ -0,0 +1,194 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::status;
+use crate::database::adding::{CommentDataWrapper, DB, PostDataWrapper};
+use crate::database::leads::{LeadDataWrapper, REVIEW_ACCEPTED};
+use crate::database::queries::{DatabaseStats, PostFilter};
+use crate::settings::api_keys::ConfigDirs;
+
+type ApiResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+// Read-only HTTP API over the local database
+pub async fn serve(addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // Make sure every table exists before the first request comes in
+    DB::new()?.create_tables()?;
+
+    let app = Router::new()
+        .route("/posts", get(posts_handler))
+        .route("/posts/{id}/comments", get(comments_handler))
+        .route("/leads", get(leads_handler))
+        .route("/stats", get(stats_handler))
+        .route("/healthz", get(healthz_handler));
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Serving the Ruddit API on http://{}", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct PostsQuery {
+    subreddit: Option<String>,
+    q: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    #[serde(default)]
+    questions: bool,
+    intent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentsQuery {
+    min_score: Option<i32>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HealthQuery {
+    #[serde(default)]
+    deep: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct LeadsQuery {
+    // accepted (default), pending, rejected or all
+    status: Option<String>,
+    relevance: Option<String>,
+    subreddit: Option<String>,
+    // "none" for the unassigned leads
+    assignee: Option<String>,
+    limit: Option<usize>,
+}
+
+// Posts are returned with their Reddit id so it can be used in /posts/{id}/comments
+#[derive(Debug, Serialize)]
+struct PostResponse {
+    reddit_id: String,
+    #[serde(flatten)]
+    post: PostDataWrapper,
+}
+
+fn open_db() -> Result<DB, (StatusCode, String)> {
+    DB::new().map_err(internal_error)
+}
+
+fn internal_error<E: std::fmt::Display>(e: E) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn parse_date_param(value: &Option<String>) -> Result<Option<i64>, (StatusCode, String)> {
+    match value {
+        Some(date) => DB::parse_date(date).map(Some).ok_or((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid date '{}', expected YYYY-MM-DD", date),
+        )),
+        None => Ok(None),
+    }
+}
+
+async fn posts_handler(Query(query): Query<PostsQuery>) -> ApiResult<Vec<PostResponse>> {
+    let filter = PostFilter {
+        subreddit: query.subreddit,
+        contains: query.q,
+        since: parse_date_param(&query.since)?,
+        until: parse_date_param(&query.until)?,
+        limit: Some(query.limit.unwrap_or(100)),
+        offset: query.offset,
+        questions_only: query.questions,
+        intent: query.intent,
+        run: None,
+        keywords: None,
+        min_score: None,
+        noise: false,
+        saved_search: None,
+        tag: None,
+        stored_since: None,
+    };
+
+    let posts = open_db()?
+        .get_filtered_posts(&filter)
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|post| PostResponse {
+            reddit_id: DB::encode_reddit_id(post.id),
+            post,
+        })
+        .collect();
+
+    Ok(Json(posts))
+}
+
+async fn comments_handler(
+    Path(id): Path<String>,
+    Query(query): Query<CommentsQuery>,
+) -> ApiResult<Vec<CommentDataWrapper>> {
+    let mut comments = open_db()?.get_post_comments(&id).map_err(internal_error)?;
+
+    if let Some(min_score) = query.min_score {
+        comments.retain(|c| c.score >= min_score);
+    }
+    if let Some(limit) = query.limit {
+        comments.truncate(limit);
+    }
+
+    Ok(Json(comments))
+}
+
+async fn leads_handler(Query(query): Query<LeadsQuery>) -> ApiResult<Vec<LeadDataWrapper>> {
+    // Only human-approved leads are handed out unless asked otherwise
+    let db = open_db()?;
+    let mut leads = match query.status.as_deref() {
+        Some("all") => db.get_leads(),
+        Some(status) => db.get_leads_with_status(status),
+        None => db.get_leads_with_status(REVIEW_ACCEPTED),
+    }
+    .map_err(internal_error)?;
+
+    let min_relevance = ConfigDirs::read_config()
+        .map(|config| config.leads.min_relevance)
+        .unwrap_or_default();
+    leads.retain(|l| min_relevance.allows(&l.relevance));
+
+    if let Some(relevance) = &query.relevance {
+        leads.retain(|l| l.relevance.eq_ignore_ascii_case(relevance));
+    }
+    if let Some(subreddit) = &query.subreddit {
+        leads.retain(|l| l.subreddit.eq_ignore_ascii_case(subreddit));
+    }
+    match query.assignee.as_deref() {
+        Some("none") => leads.retain(|l| l.assignee.is_empty()),
+        Some(assignee) => leads.retain(|l| l.assignee.eq_ignore_ascii_case(assignee)),
+        None => {}
+    }
+    if let Some(limit) = query.limit {
+        leads.truncate(limit);
+    }
+
+    Ok(Json(leads))
+}
+
+async fn stats_handler() -> ApiResult<DatabaseStats> {
+    let stats = open_db()?.get_stats().map_err(internal_error)?;
+    Ok(Json(stats))
+}
+
+// Health report; ?deep=true also requests a Reddit token
+async fn healthz_handler(Query(query): Query<HealthQuery>) -> impl IntoResponse {
+    let report = status::build_report(query.deep).await;
+    let code = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (code, Json(report))
+}
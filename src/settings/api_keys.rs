@@ -2,6 +2,10 @@ use directories::{BaseDirs, UserDirs};
 use serde::{Deserialize, Serialize};
 use std::fs;
 
+use crate::actions::verbosity;
+use crate::settings::paths::Paths;
+use crate::settings::redact;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub struct ApiKeys {
@@ -23,6 +27,14 @@ pub struct ApiKeys {
     #[serde(default)]
     #[serde(rename = "MATCH")]
     pub match_keyword: String,
+
+    // Boolean expression replacing LEAD_KEYWORDS and MATCH when set
+    #[serde(default)]
+    pub lead_query: String,
+
+    // Extra keys used once GEMINI_API_KEY runs out of quota
+    #[serde(default)]
+    pub gemini_api_keys: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -36,7 +48,624 @@ pub struct ConfigDirs {
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct AppConfig {
+    // Database file used instead of ruddit.db in the data folder, e.g. in a
+    // synced folder or on a mounted volume
+    #[serde(default)]
+    pub db_path: String,
+
     pub api_keys: ApiKeys,
+
+    #[serde(default)]
+    pub exports: ExportSettings,
+
+    #[serde(default)]
+    pub schedule: ScheduleSettings,
+
+    #[serde(default)]
+    pub display: DisplaySettings,
+
+    #[serde(default)]
+    pub fetch: FetchSettings,
+
+    #[serde(default)]
+    pub leads: LeadSettings,
+
+    #[serde(default)]
+    pub matching: MatchingSettings,
+
+    #[serde(default)]
+    pub noise: NoiseSettings,
+
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+
+    #[serde(default)]
+    pub elasticsearch: ElasticsearchSettings,
+
+    #[serde(default)]
+    pub publish: PublishSettings,
+
+    // Intent taxonomy for posts, the built-in one is used when empty
+    #[serde(default)]
+    pub intents: Vec<IntentRule>,
+
+    // Rules checked by `ruddit watch` after every scheduled run
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+
+    // Extra Reddit apps to spread requests over, on top of the one in [api_keys]
+    #[serde(default)]
+    pub accounts: Vec<RedditAccount>,
+
+    // Named keyword sets selected with --group, LEAD_KEYWORDS is used without one
+    #[serde(default)]
+    pub keyword_groups: Vec<KeywordGroup>,
+
+    // Named workflows started with `ruddit run <name>`
+    #[serde(default)]
+    pub pipelines: Vec<PipelineSettings>,
+
+    // Reddit searches `ruddit watch` runs on their own schedule
+    #[serde(default)]
+    pub searches: Vec<SearchSettings>,
+}
+
+impl AppConfig {
+    // The keyword group called `name`, or LEAD_KEYWORDS and MATCH as an unnamed
+    // group when no name is given
+    pub fn keyword_group(&self, name: Option<&str>) -> Result<KeywordGroup, String> {
+        let Some(name) = name else {
+            return Ok(KeywordGroup {
+                name: String::new(),
+                keywords: self.api_keys.lead_keywords.clone(),
+                match_keyword: self.api_keys.match_keyword.clone(),
+                query: self.api_keys.lead_query.clone(),
+            });
+        };
+
+        self.keyword_groups
+            .iter()
+            .find(|group| group.name.eq_ignore_ascii_case(name.trim()))
+            .cloned()
+            .ok_or_else(|| {
+                let known: Vec<&str> = self
+                    .keyword_groups
+                    .iter()
+                    .map(|group| group.name.as_str())
+                    .collect();
+                if known.is_empty() {
+                    format!("No keyword group '{}', none are configured", name)
+                } else {
+                    format!(
+                        "No keyword group '{}', configured groups: {}",
+                        name,
+                        known.join(", ")
+                    )
+                }
+            })
+    }
+
+    // The [[pipelines]] entry called `name`
+    pub fn pipeline(&self, name: &str) -> Result<&PipelineSettings, String> {
+        self.pipelines
+            .iter()
+            .find(|pipeline| pipeline.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                let known: Vec<&str> = self
+                    .pipelines
+                    .iter()
+                    .map(|pipeline| pipeline.name.as_str())
+                    .collect();
+                if known.is_empty() {
+                    format!("No pipeline '{}', none are configured", name)
+                } else {
+                    format!(
+                        "No pipeline '{}', configured pipelines: {}",
+                        name,
+                        known.join(", ")
+                    )
+                }
+            })
+    }
+}
+
+// Stages and settings of a named `ruddit run` workflow. Empty subreddit and
+// relevance fall back to [api_keys].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipelineSettings {
+    pub name: String,
+
+    // Run in the order given: "fetch", "leads", "export" and "notify"
+    pub stages: Vec<String>,
+
+    #[serde(default)]
+    pub subreddit: String,
+
+    #[serde(default)]
+    pub relevance: String,
+
+    // Entry of [[keyword_groups]] used to filter and label the results
+    #[serde(default)]
+    pub group: String,
+
+    // Only posts from the last this many days
+    #[serde(default)]
+    pub since_days: Option<u32>,
+
+    #[serde(default)]
+    pub questions_only: bool,
+
+    #[serde(default)]
+    pub intent: String,
+
+    // Format of the export stage, xlsx when left out
+    #[serde(default)]
+    pub export: String,
+
+    // Webhook for the notify stage instead of the one under [notifications]
+    #[serde(default)]
+    pub webhook_url: String,
+}
+
+// A saved Reddit search. Posts it finds are stored tagged with its name, and
+// only the ones newer than its last run are announced.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchSettings {
+    pub name: String,
+
+    // Reddit search syntax, e.g. `"inventory software" self:yes`
+    pub query: String,
+
+    // Only search this subreddit
+    #[serde(default)]
+    pub subreddit: String,
+
+    // Cron expression, the fetch schedule under [schedule] when left out
+    #[serde(default)]
+    pub schedule: String,
+
+    #[serde(default)]
+    pub questions_only: bool,
+
+    // Announce new posts through [notifications]
+    #[serde(default = "default_true")]
+    pub notify: bool,
+
+    // Webhook used instead of the one under [notifications]
+    #[serde(default)]
+    pub webhook_url: String,
+}
+
+// Keywords matched together, labelling the leads and exports they produce
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeywordGroup {
+    pub name: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+
+    // "AND" or "OR" like MATCH, OR when left out
+    #[serde(default, rename = "match")]
+    pub match_keyword: String,
+
+    // Boolean expression replacing keywords and match when set
+    #[serde(default)]
+    pub query: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedditAccount {
+    pub name: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+// When to move on to the next Reddit account
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    // Every request goes to the next account
+    Request,
+    // Stay on one account until Reddit rate limits it
+    #[default]
+    RateLimit,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ExportSettings {
+    // Folder every export is written to, defaults to Reddit_data on the desktop
+    #[serde(default)]
+    pub folder: String,
+
+    // Folder the Obsidian notes are written to, defaults to Reddit_data/obsidian
+    #[serde(default)]
+    pub obsidian_vault: String,
+
+    // Replace usernames with salted-hash pseudonyms in exports and reports
+    #[serde(default)]
+    pub anonymize_authors: bool,
+
+    // Salt for the pseudonyms, one is generated and kept in the database when empty
+    #[serde(default)]
+    pub author_salt: String,
+
+    // Lead files are written inside a zip locked with this password when set
+    #[serde(default)]
+    pub leads_password: String,
+
+    // Order of day, month and year in exported dates: ymd (default), dmy or mdy
+    #[serde(default)]
+    pub date_order: String,
+
+    // "," writes decimals with a comma and separates CSV columns with ";"
+    #[serde(default)]
+    pub decimal_separator: String,
+
+    #[serde(default)]
+    pub columns: ExportColumns,
+}
+
+// Ordered fields of each export sheet, empty lists keep the default columns
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ExportColumns {
+    #[serde(default)]
+    pub posts: Vec<String>,
+
+    #[serde(default)]
+    pub comments: Vec<String>,
+}
+
+// Cron expressions used by `ruddit watch`, an empty string disables the job
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduleSettings {
+    #[serde(default = "default_fetch_schedule")]
+    pub fetch: String,
+
+    #[serde(default)]
+    pub leads: String,
+
+    // Reminders about HIGH leads nobody has contacted, see [leads] stale_days
+    #[serde(default)]
+    pub reminders: String,
+
+    // Export of the posts and comments stored since the previous one
+    #[serde(default)]
+    pub export: String,
+
+    // Format of the scheduled export, xlsx when left out
+    #[serde(default)]
+    pub export_format: String,
+}
+
+// How dates are shown in terminal listings
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+    Absolute,
+    Relative,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DisplaySettings {
+    #[serde(default)]
+    pub time_format: TimeFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FetchSettings {
+    // Comments kept per post when --comment-limit is not given, 0 keeps them all
+    #[serde(default = "default_comment_limit")]
+    pub comment_limit: usize,
+
+    #[serde(default)]
+    pub rotation: Rotation,
+
+    // Nested "continue this thread" links followed below a comment, 0 to skip them
+    #[serde(default = "default_continue_depth")]
+    pub continue_depth: usize,
+
+    // Extra requests spent following those links per post
+    #[serde(default = "default_continue_limit")]
+    pub continue_limit: usize,
+
+    // Posts from these subreddits are never stored by fetches and searches
+    #[serde(default)]
+    pub excluded_subreddits: Vec<String>,
+
+    // Nor link posts pointing to these domains or their subdomains
+    #[serde(default)]
+    pub excluded_domains: Vec<String>,
+
+    // Cached Reddit responses younger than this are reused without a request,
+    // 0 always asks Reddit whether they changed
+    #[serde(default)]
+    pub cache_fresh_seconds: u64,
+}
+
+pub fn default_comment_limit() -> usize {
+    100
+}
+
+pub fn default_continue_depth() -> usize {
+    3
+}
+
+pub fn default_continue_limit() -> usize {
+    10
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        FetchSettings {
+            comment_limit: default_comment_limit(),
+            rotation: Rotation::default(),
+            continue_depth: default_continue_depth(),
+            continue_limit: default_continue_limit(),
+            excluded_subreddits: Vec::new(),
+            excluded_domains: Vec::new(),
+            cache_fresh_seconds: 0,
+        }
+    }
+}
+
+// Lead relevance levels, in increasing order
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LeadRelevance {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+impl LeadRelevance {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "low" => Some(LeadRelevance::Low),
+            "medium" => Some(LeadRelevance::Medium),
+            "high" => Some(LeadRelevance::High),
+            _ => None,
+        }
+    }
+
+    // Whether a lead rated `relevance` clears this threshold. Unrated leads only
+    // pass when nothing is filtered out.
+    pub fn allows(&self, relevance: &str) -> bool {
+        LeadRelevance::parse(relevance).map_or(*self == LeadRelevance::Low, |level| level >= *self)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeadSettings {
+    // Leads below this stay in the database but are left out of exports and the API
+    #[serde(default)]
+    pub min_relevance: LeadRelevance,
+
+    // Days a lead can wait in pending or accepted without contact before
+    // the [schedule] reminders call it stale
+    #[serde(default = "default_stale_days")]
+    pub stale_days: u32,
+}
+
+fn default_stale_days() -> u32 {
+    7
+}
+
+impl Default for LeadSettings {
+    fn default() -> Self {
+        LeadSettings {
+            min_relevance: LeadRelevance::default(),
+            stale_days: default_stale_days(),
+        }
+    }
+}
+
+// How keywords are compared with the text
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    // Case-insensitive substring
+    #[default]
+    Exact,
+    // Also word forms, "forecasts" and "forecasting" match "forecast"
+    Stem,
+    // Also typos within `max_distance` edits
+    Fuzzy,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct MatchingSettings {
+    #[serde(default)]
+    pub mode: MatchMode,
+
+    // Edits allowed per word in fuzzy mode, 1 or 2
+    #[serde(default = "default_max_distance")]
+    pub max_distance: usize,
+}
+
+fn default_max_distance() -> usize {
+    1
+}
+
+impl Default for MatchingSettings {
+    fn default() -> Self {
+        MatchingSettings {
+            mode: MatchMode::default(),
+            max_distance: default_max_distance(),
+        }
+    }
+}
+
+// Quarantine of spam and noise posts, see filters::noise
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoiseSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    // Extra phrases that mark a post as noise, on top of the built-in ones
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    // Also ask Gemini to leave spam out of the leads it returns
+    #[serde(default)]
+    pub gemini_check: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for NoiseSettings {
+    fn default() -> Self {
+        NoiseSettings {
+            enabled: true,
+            patterns: Vec::new(),
+            gemini_check: false,
+        }
+    }
+}
+
+// An intent and the keywords that reveal it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntentRule {
+    pub name: String,
+    pub keywords: Vec<String>,
+}
+
+// Where alerts are sent besides the terminal
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct NotificationSettings {
+    // Receives a JSON {"text": ...} POST, which Slack and Discord-compatible hooks accept
+    #[serde(default)]
+    pub webhook_url: String,
+}
+
+// Elasticsearch or OpenSearch cluster new posts and comments are pushed to
+// after each fetch, an empty url disables it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ElasticsearchSettings {
+    #[serde(default)]
+    pub url: String,
+
+    // Prefix of the `<index>-posts` and `<index>-comments` indices
+    #[serde(default = "default_elasticsearch_index")]
+    pub index: String,
+
+    #[serde(default)]
+    pub username: String,
+
+    #[serde(default)]
+    pub password: String,
+
+    // Used instead of the username and password when set
+    #[serde(default)]
+    pub api_key: String,
+}
+
+fn default_elasticsearch_index() -> String {
+    "ruddit".to_string()
+}
+
+impl Default for ElasticsearchSettings {
+    fn default() -> Self {
+        ElasticsearchSettings {
+            url: String::new(),
+            index: default_elasticsearch_index(),
+            username: String::new(),
+            password: String::new(),
+            api_key: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Broker {
+    #[default]
+    None,
+    // Through a Kafka REST Proxy
+    Kafka,
+    Nats,
+}
+
+// Message queue each new post, comment and lead is published to as JSON
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishSettings {
+    #[serde(default)]
+    pub broker: Broker,
+
+    // REST Proxy address for Kafka, nats://host:port for NATS
+    #[serde(default)]
+    pub url: String,
+
+    // Messages go to `<topic>.posts`, `<topic>.comments` and `<topic>.leads`
+    #[serde(default = "default_publish_topic")]
+    pub topic: String,
+
+    // NATS auth token
+    #[serde(default)]
+    pub token: String,
+}
+
+fn default_publish_topic() -> String {
+    "ruddit".to_string()
+}
+
+impl Default for PublishSettings {
+    fn default() -> Self {
+        PublishSettings {
+            broker: Broker::None,
+            url: String::new(),
+            topic: default_publish_topic(),
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    // Posts and comments mentioning the keywords
+    Mentions,
+    // Percentage of new leads with a negative sentiment
+    NegativeShare,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    // Fires when the metric goes above this value
+    pub threshold: f64,
+
+    // Period the metric is measured over, also the minimum time between two alerts
+    #[serde(default = "default_alert_window")]
+    pub window_hours: u64,
+
+    // Keywords counted by `mentions`, defaults to BRANDED_KEYWORDS
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+fn default_alert_window() -> u64 {
+    24
+}
+
+fn default_fetch_schedule() -> String {
+    "*/15 * * * *".to_string()
+}
+
+impl Default for ScheduleSettings {
+    fn default() -> Self {
+        ScheduleSettings {
+            fetch: default_fetch_schedule(),
+            leads: String::new(),
+            reminders: String::new(),
+            export: String::new(),
+            export_format: String::new(),
+        }
+    }
 }
 
 impl Default for ApiKeys {
@@ -51,11 +680,41 @@ impl Default for ApiKeys {
             branded_keywords: vec![],
             sentiment: vec!["neutral".to_string()],
             match_keyword: "".to_string(),
+            lead_query: String::new(),
+            gemini_api_keys: vec![],
         }
     }
 }
 
-
+// Byte range of the `LEAD_KEYWORDS = [...]` assignment, which may span lines
+fn lead_keywords_span(content: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    let start = content.split_inclusive('\n').find_map(|line| {
+        let at = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        let rest = trimmed.strip_prefix("LEAD_KEYWORDS")?;
+        rest.trim_start()
+            .starts_with('=')
+            .then_some(at + line.len() - trimmed.len())
+    })?;
+
+    let open = start + content[start..].find('[')?;
+    let (mut in_string, mut escaped, mut in_comment) = (false, false, false);
+    for (index, c) in content[open..].char_indices() {
+        match c {
+            '\n' if in_comment => in_comment = false,
+            _ if in_comment => {}
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => in_comment = true,
+            ']' if !in_string => return Some((start, open + index + 1)),
+            _ => {}
+        }
+    }
+    None
+}
 
 impl ConfigDirs {
     pub fn new() -> Option<Self> {
@@ -72,30 +731,179 @@ impl ConfigDirs {
     }
 
     pub fn create_default_config() -> Result<(), Box<dyn std::error::Error>> {
-        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
-        let config_dir = base_dirs.config_dir();
+        let paths = Paths::get()?;
 
         // Create app-specific config directory
-        let app_config_dir = config_dir.join("ruddit");
+        let app_config_dir = &paths.config_dir;
 
-        println!("Creating config directory: {}", app_config_dir.display());
-        fs::create_dir_all(&app_config_dir)?;
+        verbosity::info(format!(
+            "Creating config directory: {}",
+            app_config_dir.display()
+        ));
+        fs::create_dir_all(app_config_dir)?;
 
         // Path to the config file
-        let config_path = app_config_dir.join("settings.toml");
+        let config_path = paths.config_file();
 
         // Default TOML content
         let toml_content = r#"
+# Database file, ruddit.db in the data folder when empty. A relative path is
+# taken from this folder. --db-path overrides it for one run.
+db_path = ""
+
 [api_keys]
-reddit_api_id = "your_api_id_here"
-reddit_api_secret = "your_api_secret_here"
-subreddit = "supplychain"
-relevance = "hot"
-gemini_api_key = "your_api_key_here"
-branded_keywords = ["keyword1", "keyword2"]
-lead_keywords = ["keyword1", "keyword2"]
-sentiment = ["keyword1", "keyword2"]
+REDDIT_API_ID = "your_api_id_here"
+REDDIT_API_SECRET = "your_api_secret_here"
+SUBREDDIT = "supplychain"
+RELEVANCE = "hot"
+GEMINI_API_KEY = "your_api_key_here"
+GEMINI_API_KEYS = []
+BRANDED_KEYWORDS = ["keyword1", "keyword2"]
+LEAD_KEYWORDS = ["keyword1", "keyword2"]
+SENTIMENT = ["keyword1", "keyword2"]
 MATCH = "OR"
+# Boolean expression used instead of LEAD_KEYWORDS and MATCH when set, e.g.
+# ("freight" OR "3PL") AND NOT "job"
+LEAD_QUERY = ""
+
+[exports]
+# Where exports are written, Reddit_data on the desktop when empty
+folder = ""
+obsidian_vault = ""
+anonymize_authors = false
+author_salt = ""
+leads_password = ""
+# Dates as ymd (2025-01-31), dmy (31/01/2025) or mdy (01/31/2025)
+# date_order = "dmy"
+# "," for decimals like 1,5 and CSV columns separated by ";" as Excel expects
+# in most of Europe
+# decimal_separator = ","
+
+# Columns of the post and comment exports, in order. Empty keeps the defaults.
+# posts: date, title, url, relevance, subreddit, intent, id, link, text, tags, note
+# comments: date, subreddit, post_title, author, comment, score, link, id, post_id, parent_id, note
+[exports.columns]
+posts = []
+comments = []
+
+[schedule]
+fetch = "*/15 * * * *"
+leads = "0 8 * * 1-5"
+# Remind about HIGH leads nobody has contacted for [leads] stale_days
+# reminders = "0 9 * * 1-5"
+# Export what was stored since the previous export, as xlsx, csv, ndjson,
+# obsidian or duckdb
+# export = "0 7 * * *"
+# export_format = "xlsx"
+
+[display]
+time_format = "both"
+
+[fetch]
+comment_limit = 100
+rotation = "rate_limit"
+# "continue this thread" links followed per post: how deep and how many requests
+continue_depth = 3
+continue_limit = 10
+# Posts from these subreddits, or linking to these domains, are never stored
+excluded_subreddits = []
+excluded_domains = []
+# Seconds a cached Reddit response is reused without asking Reddit, e.g. 60
+# while tuning filters. 0 checks every response with Reddit.
+cache_fresh_seconds = 0
+
+[leads]
+min_relevance = "low"
+stale_days = 7
+
+# "exact", "stem" to also match word forms or "fuzzy" to also allow typos
+[matching]
+mode = "exact"
+max_distance = 1
+
+# Giveaway, referral, crypto and link spam is quarantined as noise and left
+# out of leads and exports. Add your own phrases to patterns.
+[noise]
+enabled = true
+patterns = []
+gemini_check = false
+
+[notifications]
+webhook_url = ""
+
+# Push new posts and comments to Elasticsearch or OpenSearch after each fetch,
+# into the <index>-posts and <index>-comments indices
+[elasticsearch]
+url = ""
+index = "ruddit"
+username = ""
+password = ""
+api_key = ""
+
+# Publish each new post, comment and lead as JSON to "kafka" (through a Kafka
+# REST Proxy, e.g. http://localhost:8082) or "nats" (nats://localhost:4222)
+[publish]
+broker = "none"
+url = ""
+topic = "ruddit"
+token = ""
+
+# Intents assigned to posts, the first match wins. Without any block the
+# built-in hiring, comparison, seeking_recommendation, complaint and news are used
+# [[intents]]
+# name = "complaint"
+# keywords = ["frustrated", "doesn't work", "switching from"]
+
+# Alerts checked by `ruddit watch` after every run, one block per rule
+# [[alerts]]
+# name = "brand spike"
+# metric = "mentions"
+# threshold = 10
+# window_hours = 24
+#
+# [[alerts]]
+# name = "negative leads"
+# metric = "negative_share"
+# threshold = 40
+
+# Named keyword sets, pick one with --group to match, generate leads and
+# export for it alone. Results are labelled with the group name
+# [[keyword_groups]]
+# name = "pain-points"
+# keywords = ["stockout", "overstock", "manual spreadsheet"]
+# match = "OR"
+#
+# [[keyword_groups]]
+# name = "logistics"
+# query = '("freight" OR "3PL") AND NOT "job"'
+
+# Workflows started with `ruddit run <name>`, stages run in the order given
+# [[pipelines]]
+# name = "morning"
+# stages = ["fetch", "leads", "export", "notify"]
+# subreddit = "logistics"
+# relevance = "new"
+# group = "logistics"
+# since_days = 7
+# export = "csv"
+# webhook_url = ""
+
+# Searches run by `ruddit watch`, new posts are stored tagged with the search
+# name and announced through [notifications]
+# [[searches]]
+# name = "wms-questions"
+# query = '"warehouse management" OR wms'
+# subreddit = "logistics"
+# schedule = "0 * * * *"
+# questions_only = true
+# notify = true
+# webhook_url = ""
+
+# Extra Reddit apps used alongside the one above, one block per app
+# [[accounts]]
+# name = "second"
+# client_id = "your_api_id_here"
+# client_secret = "your_api_secret_here"
 
 "#
         .trim_start();
@@ -110,47 +918,86 @@ MATCH = "OR"
     }
 
     pub fn read_config() -> Result<AppConfig, Box<dyn std::error::Error>> {
-        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
-        let config_dir = base_dirs.config_dir();
-
         // Path to the config file
-        let config_path = config_dir.join("ruddit/settings.toml");
-        println!("Reading config file: {:#?}", config_path);
+        let config_path = Paths::get()?.config_file();
+        verbosity::info(format!("Reading config file: {:#?}", config_path));
+
+        Self::load_config()
+    }
+
+    // read_config without announcing the file, for lookups made on the side
+    pub fn load_config() -> Result<AppConfig, Box<dyn std::error::Error>> {
+        let config_path = Paths::get()?.config_file();
 
         // Read from file
         let toml_content = fs::read_to_string(config_path)?;
 
         // Try parsing; on failure, return the error instead of panicking
         let app_config: AppConfig = toml::from_str(&toml_content)?;
+        redact::register(&app_config);
 
         Ok(app_config)
     }
 
-    pub fn edit_config_file() -> Result<(), Box<dyn std::error::Error>> {
-        // get the config file path and edit natively.
-        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
-        let config_dir = base_dirs.config_dir();
-        let config_path = config_dir.join("ruddit/settings.toml");
-
-        #[cfg(target_os = "windows")]
-        {
-            use std::process::Command;
-
-            Command::new("cmd")
-                .args(&["/C", "start", "", &config_path.to_string_lossy()])
-                .spawn()?;
+    // Append keywords to LEAD_KEYWORDS in place, leaving the rest of the file and
+    // its comments untouched. Returns the new list.
+    pub fn add_lead_keywords(
+        keywords: &[String],
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let config_path = Paths::get()?.config_file();
+        let content = fs::read_to_string(&config_path)?;
+        let config: AppConfig = toml::from_str(&content)?;
+
+        let mut lead_keywords = config.api_keys.lead_keywords;
+        for keyword in keywords {
+            if !lead_keywords
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(keyword))
+            {
+                lead_keywords.push(keyword.clone());
+            }
         }
+        let line = format!(
+            "LEAD_KEYWORDS = {}",
+            toml::Value::Array(
+                lead_keywords
+                    .iter()
+                    .map(|keyword| toml::Value::String(keyword.clone()))
+                    .collect()
+            )
+        );
+
+        let content = match lead_keywords_span(&content) {
+            Some((start, end)) => format!("{}{}{}", &content[..start], line, &content[end..]),
+            None => {
+                let header = content
+                    .find("[api_keys]")
+                    .ok_or("No [api_keys] section in the config file")?;
+                let insert_at = content[header..]
+                    .find('\n')
+                    .map_or(content.len(), |newline| header + newline + 1);
+                format!(
+                    "{}{}\n{}",
+                    &content[..insert_at],
+                    line,
+                    &content[insert_at..]
+                )
+            }
+        };
+        fs::write(&config_path, content)?;
+
+        Ok(lead_keywords)
+    }
 
-        #[cfg(target_os = "macos")]
-        {
-            use std::process::Command;
-
-            Command::new("open").arg(config_path).spawn()?;
-        }
+    pub fn edit_config_file() -> Result<(), Box<dyn std::error::Error>> {
+        // get the config file path and edit natively.
+        let config_path = Paths::get()?.config_file();
 
-        #[cfg(target_os = "linux")]
-        {
-            Command::new("xdg-open").arg(config_path).spawn()?;
+        if !crate::actions::open::open_path(&config_path) {
+            println!(
+                "No desktop editor to open it with, edit {} in a terminal editor",
+                config_path.display()
+            );
         }
 
         Ok(())
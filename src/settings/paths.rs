@@ -0,0 +1,196 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use directories::{BaseDirs, UserDirs};
+
+// Puts the config, the database and the exports in this one folder
+pub const RUDDIT_HOME: &str = "RUDDIT_HOME";
+
+// Where ruddit keeps its files. Resolved once per process from RUDDIT_HOME or
+// the platform's directories, every path below comes from here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    // Holds settings.toml
+    pub config_dir: PathBuf,
+    // Holds ruddit.db and its lock file
+    pub data_dir: PathBuf,
+    // Holds files that can be deleted at any time, like captured responses
+    pub cache_dir: PathBuf,
+    // Reddit_data on the desktop, None without a desktop folder
+    pub desktop_exports: Option<PathBuf>,
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+impl Paths {
+    pub fn get() -> Result<&'static Paths, String> {
+        if let Some(paths) = PATHS.get() {
+            return Ok(paths);
+        }
+        let paths = Paths::from_env()?;
+        Ok(PATHS.get_or_init(|| paths))
+    }
+
+    fn from_env() -> Result<Paths, String> {
+        if let Some(home) = std::env::var_os(RUDDIT_HOME).filter(|home| !home.is_empty()) {
+            return Ok(Paths::under(Path::new(&home)));
+        }
+        let base_dirs = BaseDirs::new().ok_or("Failed to get base directories")?;
+        let desktop = UserDirs::new().and_then(|dirs| dirs.desktop_dir().map(PathBuf::from));
+        Ok(Paths::platform(
+            base_dirs.config_dir(),
+            base_dirs.data_dir(),
+            base_dirs.cache_dir(),
+            desktop.as_deref(),
+        ))
+    }
+
+    // Everything in `home`, exports and cache in their own folders
+    pub fn under(home: &Path) -> Paths {
+        Paths {
+            config_dir: home.to_path_buf(),
+            data_dir: home.to_path_buf(),
+            cache_dir: home.join("cache"),
+            desktop_exports: None,
+        }
+    }
+
+    // A ruddit folder in the platform's config, data and cache directories
+    pub fn platform(
+        config_dir: &Path,
+        data_dir: &Path,
+        cache_dir: &Path,
+        desktop: Option<&Path>,
+    ) -> Paths {
+        Paths {
+            config_dir: config_dir.join("ruddit"),
+            data_dir: data_dir.join("ruddit"),
+            cache_dir: cache_dir.join("ruddit"),
+            desktop_exports: desktop.map(|desktop| desktop.join("Reddit_data")),
+        }
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir.join("settings.toml")
+    }
+
+    pub fn database_file(&self) -> PathBuf {
+        self.data_dir.join("ruddit.db")
+    }
+
+    // Kept next to the database it guards, wherever that is
+    pub fn lock_file(database: &Path) -> PathBuf {
+        database.with_extension("lock")
+    }
+
+    pub fn watch_log(&self) -> PathBuf {
+        self.data_dir.join("watch.log")
+    }
+
+    // `folder` under [exports] when set, else Reddit_data on the desktop, else
+    // the exports folder next to the database
+    pub fn exports_dir(&self, configured: &str) -> PathBuf {
+        if !configured.trim().is_empty() {
+            return PathBuf::from(configured.trim());
+        }
+        self.desktop_exports
+            .clone()
+            .unwrap_or_else(|| self.data_dir.join("exports"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (config, data, cache, desktop) as the directories crate reports them
+    const PLATFORMS: [(&str, &str, &str, &str); 3] = [
+        (
+            "/home/ana/.config",
+            "/home/ana/.local/share",
+            "/home/ana/.cache",
+            "/home/ana/Desktop",
+        ),
+        (
+            "/Users/ana/Library/Application Support",
+            "/Users/ana/Library/Application Support",
+            "/Users/ana/Library/Caches",
+            "/Users/ana/Desktop",
+        ),
+        (
+            r"C:\Users\ana\AppData\Roaming",
+            r"C:\Users\ana\AppData\Roaming",
+            r"C:\Users\ana\AppData\Local",
+            r"C:\Users\ana\Desktop",
+        ),
+    ];
+
+    #[test]
+    fn platform_dirs_get_a_ruddit_folder() {
+        for (config, data, cache, desktop) in PLATFORMS {
+            let paths = Paths::platform(
+                Path::new(config),
+                Path::new(data),
+                Path::new(cache),
+                Some(Path::new(desktop)),
+            );
+            assert_eq!(
+                paths.config_file(),
+                Path::new(config).join("ruddit").join("settings.toml")
+            );
+            assert_eq!(
+                paths.database_file(),
+                Path::new(data).join("ruddit").join("ruddit.db")
+            );
+            assert_eq!(paths.cache_dir, Path::new(cache).join("ruddit"));
+            assert_eq!(
+                paths.exports_dir(""),
+                Path::new(desktop).join("Reddit_data")
+            );
+        }
+    }
+
+    #[test]
+    fn exports_fall_back_to_the_data_dir_without_a_desktop() {
+        for (config, data, cache, _) in PLATFORMS {
+            let paths = Paths::platform(Path::new(config), Path::new(data), Path::new(cache), None);
+            assert_eq!(
+                paths.exports_dir("  "),
+                Path::new(data).join("ruddit").join("exports")
+            );
+        }
+    }
+
+    #[test]
+    fn configured_exports_folder_wins() {
+        let paths = Paths::platform(
+            Path::new("/cfg"),
+            Path::new("/data"),
+            Path::new("/cache"),
+            Some(Path::new("/desk")),
+        );
+        assert_eq!(paths.exports_dir(" /srv/out "), PathBuf::from("/srv/out"));
+    }
+
+    #[test]
+    fn ruddit_home_holds_everything() {
+        let paths = Paths::under(Path::new("/opt/ruddit"));
+        assert_eq!(
+            paths.config_file(),
+            Path::new("/opt/ruddit").join("settings.toml")
+        );
+        assert_eq!(
+            paths.database_file(),
+            Path::new("/opt/ruddit").join("ruddit.db")
+        );
+        assert_eq!(
+            Paths::lock_file(&paths.database_file()),
+            Path::new("/opt/ruddit").join("ruddit.lock")
+        );
+        assert_eq!(
+            paths.exports_dir(""),
+            Path::new("/opt/ruddit").join("exports")
+        );
+        assert_eq!(paths.cache_dir, Path::new("/opt/ruddit").join("cache"));
+    }
+}
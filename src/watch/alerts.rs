@@ -0,0 +1,81 @@
+use chrono::Utc;
+
+use crate::database::adding::DB;
+use crate::filters::keywords::KeywordMatcher;
+use crate::notify;
+use crate::settings::api_keys::{AlertMetric, AlertRule, AppConfig};
+
+// Evaluate every [[alerts]] rule and notify for the ones above their threshold.
+// A rule fires at most once per window.
+pub async fn check_alerts(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    if config.alerts.is_empty() {
+        return Ok(());
+    }
+
+    let db = DB::new()?;
+    db.create_alerts_table()?;
+    let now = Utc::now().timestamp();
+
+    for rule in &config.alerts {
+        let since = now - rule.window_hours as i64 * 3600;
+        if db
+            .last_alert(&rule.name)?
+            .is_some_and(|fired| fired > since)
+        {
+            continue;
+        }
+
+        let Some(value) = measure(&db, config, rule, since)? else {
+            continue;
+        };
+
+        if value > rule.threshold {
+            notify::send(&config.notifications, &describe(rule, value)).await;
+            db.record_alert(&rule.name, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn measure(
+    db: &DB,
+    config: &AppConfig,
+    rule: &AlertRule,
+    since: i64,
+) -> Result<Option<f64>, Box<dyn std::error::Error>> {
+    match rule.metric {
+        AlertMetric::Mentions => {
+            let keywords = if rule.keywords.is_empty() {
+                &config.api_keys.branded_keywords
+            } else {
+                &rule.keywords
+            };
+            // An empty matcher would count every post
+            if keywords.is_empty() {
+                eprintln!(
+                    "Alert '{}' has no keywords, set keywords or BRANDED_KEYWORDS",
+                    rule.name
+                );
+                return Ok(None);
+            }
+
+            let matcher = KeywordMatcher::new(keywords, "OR");
+            Ok(Some(db.count_mentions(since, &matcher)? as f64))
+        }
+        AlertMetric::NegativeShare => Ok(db.negative_lead_share(since)?),
+    }
+}
+
+fn describe(rule: &AlertRule, value: f64) -> String {
+    match rule.metric {
+        AlertMetric::Mentions => format!(
+            "Ruddit alert '{}': {} mentions in the last {}h (threshold {})",
+            rule.name, value, rule.window_hours, rule.threshold
+        ),
+        AlertMetric::NegativeShare => format!(
+            "Ruddit alert '{}': {:.0}% of new leads are negative in the last {}h (threshold {}%)",
+            rule.name, value, rule.window_hours, rule.threshold
+        ),
+    }
+}
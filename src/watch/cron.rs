@@ -0,0 +1,218 @@
+use chrono::{Datelike, Timelike};
+
+// Five-field cron expression: minute hour day-of-month month day-of-week
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Invalid cron expression '{}': expected 5 fields (minute hour day month weekday)",
+                expression
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        // Both 0 and 7 mean Sunday
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(CronSchedule {
+            expression: expression.trim().to_string(),
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            // Like cron, `*/2` still counts as an unrestricted field
+            any_day_of_month: fields[2].starts_with('*'),
+            any_day_of_week: fields[4].starts_with('*'),
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn matches<T: Datelike + Timelike>(&self, time: &T) -> bool {
+        let day_of_month = self.days_of_month[time.day() as usize];
+        let day_of_week = self.days_of_week[time.weekday().num_days_from_sunday() as usize];
+
+        // Like cron, a restricted day-of-month and day-of-week match if either does
+        let day = if self.any_day_of_month || self.any_day_of_week {
+            day_of_month && day_of_week
+        } else {
+            day_of_month || day_of_week
+        };
+
+        self.minutes[time.minute() as usize]
+            && self.hours[time.hour() as usize]
+            && self.months[time.month() as usize]
+            && day
+    }
+}
+
+// Expand one field (`*`, `*/15`, `1-5`, `0,30`, `10-40/10`) into a lookup table
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or(format!("Invalid step in '{}'", part))?,
+            ),
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max)?, parse_value(end, min, max)?)
+        } else {
+            let value = parse_value(range, min, max)?;
+            // `5/10` means every 10 starting at 5
+            (value, if part.contains('/') { max } else { value })
+        };
+
+        if start > end {
+            return Err(format!("Invalid range '{}'", part));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+
+    Ok(allowed)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|v| *v >= min && *v <= max)
+        .ok_or(format!(
+            "Invalid value '{}', expected {}-{}",
+            value, min, max
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn allowed(field: &str, min: u32, max: u32) -> Vec<u32> {
+        parse_field(field, min, max)
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|(_, allowed)| **allowed)
+            .map(|(value, _)| value as u32)
+            .collect()
+    }
+
+    #[test]
+    fn expands_lists_ranges_and_steps() {
+        assert_eq!(allowed("0,30", 0, 59), vec![0, 30]);
+        assert_eq!(allowed("1-5", 0, 23), vec![1, 2, 3, 4, 5]);
+        assert_eq!(allowed("*/15", 0, 59), vec![0, 15, 30, 45]);
+        assert_eq!(allowed("10-40/10", 0, 59), vec![10, 20, 30, 40]);
+        assert_eq!(allowed("5/20", 0, 59), vec![5, 25, 45]);
+        assert_eq!(allowed("1-2,20-21", 1, 31), vec![1, 2, 20, 21]);
+    }
+
+    #[test]
+    fn rejects_bad_fields() {
+        assert!(parse_field("60", 0, 59).is_err());
+        assert!(parse_field("5-1", 0, 59).is_err());
+        assert!(parse_field("*/0", 0, 59).is_err());
+        assert!(CronSchedule::parse("0 * * *").is_err());
+    }
+
+    #[test]
+    fn matches_minute_hour_and_month() {
+        let schedule = CronSchedule::parse("*/15 9-17 * 1,7 *").unwrap();
+        assert!(schedule.matches(&at(2024, 1, 10, 9, 45)));
+        assert!(schedule.matches(&at(2024, 7, 10, 17, 0)));
+        assert!(!schedule.matches(&at(2024, 1, 10, 9, 50)));
+        assert!(!schedule.matches(&at(2024, 1, 10, 18, 0)));
+        assert!(!schedule.matches(&at(2024, 2, 10, 9, 45)));
+    }
+
+    #[test]
+    fn sunday_is_0_and_7() {
+        let schedule = CronSchedule::parse("0 0 * * 7").unwrap();
+        // 2024-01-07 is a Sunday
+        assert!(schedule.matches(&at(2024, 1, 7, 0, 0)));
+        assert!(!schedule.matches(&at(2024, 1, 8, 0, 0)));
+    }
+
+    #[test]
+    fn restricted_day_of_month_and_week_match_either() {
+        // The 1st of the month, or any Monday
+        let schedule = CronSchedule::parse("0 0 1 * 1").unwrap();
+        // 2024-02-01 is a Thursday, 2024-01-08 a Monday
+        assert!(schedule.matches(&at(2024, 2, 1, 0, 0)));
+        assert!(schedule.matches(&at(2024, 1, 8, 0, 0)));
+        assert!(!schedule.matches(&at(2024, 1, 9, 0, 0)));
+    }
+
+    #[test]
+    fn stepped_star_day_field_is_not_ored() {
+        // Odd days that are also Mondays, not odd days or Mondays
+        let schedule = CronSchedule::parse("0 0 */2 * 1").unwrap();
+        // 2024-01-01 is a Monday
+        assert!(schedule.matches(&at(2024, 1, 1, 0, 0)));
+        assert!(!schedule.matches(&at(2024, 1, 3, 0, 0)));
+        assert!(!schedule.matches(&at(2024, 1, 8, 0, 0)));
+
+        // Every other weekday counting from Sunday, on the 10th
+        let schedule = CronSchedule::parse("0 0 10 * */2").unwrap();
+        // 2024-01-10 is a Wednesday (3)
+        assert!(!schedule.matches(&at(2024, 1, 10, 0, 0)));
+        // 2024-02-10 is a Saturday (6)
+        assert!(schedule.matches(&at(2024, 2, 10, 0, 0)));
+        assert!(!schedule.matches(&at(2024, 2, 6, 0, 0)));
+    }
+
+    #[test]
+    fn shortcuts_expand() {
+        let schedule = CronSchedule::parse("@weekly").unwrap();
+        assert_eq!(schedule.expression(), "@weekly");
+        assert!(schedule.matches(&at(2024, 1, 7, 0, 0)));
+        assert!(!schedule.matches(&at(2024, 1, 8, 0, 0)));
+    }
+}
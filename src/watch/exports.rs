@@ -0,0 +1,69 @@
+use chrono::{Local, TimeZone, Utc};
+use clap::ValueEnum;
+
+use crate::actions::verbosity;
+use crate::arguments::modeling::ExportFormat;
+use crate::database::adding::DB;
+use crate::database::queries::PostFilter;
+use crate::exports;
+use crate::exports::table::TableFormat;
+use crate::settings::api_keys::AppConfig;
+
+// app_meta key holding when the last scheduled export started
+const LAST_EXPORT_KEY: &str = "watch_last_export";
+
+// Format of the [schedule] export, checked when the schedules are loaded
+pub fn export_format(config: &AppConfig) -> Result<ExportFormat, String> {
+    let format = config.schedule.export_format.trim();
+    if format.is_empty() {
+        return Ok(ExportFormat::Xlsx);
+    }
+    ExportFormat::from_str(format, true)
+        .map_err(|_| format!("Unknown export_format '{}' under [schedule]", format))
+}
+
+// Export the posts and comments stored since the previous scheduled export,
+// everything on the first one. The window only moves on once the export is
+// written, so a failed export is covered by the next.
+pub fn run_export(config: &AppConfig, db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    let format = export_format(config)?;
+    db.create_tables()?;
+    let started = Utc::now().timestamp();
+    let previous = db
+        .get_meta(LAST_EXPORT_KEY)?
+        .and_then(|value| value.parse::<i64>().ok());
+
+    match previous.and_then(|time| Local.timestamp_opt(time, 0).single()) {
+        Some(time) => verbosity::info(format!(
+            "Exporting what was stored since {}",
+            time.format("%Y-%m-%d %H:%M")
+        )),
+        None => verbosity::info("First scheduled export, exporting everything stored"),
+    }
+
+    let filter = PostFilter {
+        stored_since: previous,
+        ..Default::default()
+    };
+    let table = match format {
+        ExportFormat::Xlsx => TableFormat::Xlsx,
+        ExportFormat::Csv => TableFormat::Csv,
+        ExportFormat::Ndjson => TableFormat::Ndjson,
+        ExportFormat::Obsidian => {
+            exports::obsidian::export_obsidian_vault(&filter)?;
+            return finish(db, started);
+        }
+        ExportFormat::Duckdb => {
+            exports::duckdb::export_duckdb(&filter)?;
+            return finish(db, started);
+        }
+    };
+    exports::excel::export_posts(&filter, table, None)?;
+    exports::excel::export_comments(&filter, table, None)?;
+    finish(db, started)
+}
+
+fn finish(db: &DB, started: i64) -> Result<(), Box<dyn std::error::Error>> {
+    db.set_meta(LAST_EXPORT_KEY, &started.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,344 @@
+pub mod alerts;
+pub mod cron;
+pub mod exports;
+pub mod reload;
+pub mod searches;
+
+use std::time::Duration;
+
+use chrono::{Local, Timelike};
+
+use crate::actions::accounts::AccountPool;
+use crate::actions::interrupt;
+use crate::actions::verbosity;
+use crate::arguments::modeling::CommentOptions;
+use crate::database::adding::DB;
+use crate::database::jobs::{CommentJob, JobDataWrapper};
+use crate::database::queries::PostFilter;
+use crate::filters::keywords::KeywordMatcher;
+use crate::settings::api_keys::AppConfig;
+use crate::settings::redact::redact;
+use crate::{actions, ai, database, settings};
+use cron::CronSchedule;
+
+// Kinds of work stored in the jobs table
+const COMMENTS_JOB: &str = "comments";
+const LEADS_JOB: &str = "leads";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Job {
+    Fetch,
+    Leads,
+    Reminders,
+    Export,
+    // Entry of [[searches]] by name
+    Search(String),
+}
+
+impl Job {
+    fn name(&self) -> String {
+        match self {
+            Job::Fetch => "fetch".to_string(),
+            Job::Leads => "leads".to_string(),
+            Job::Reminders => "reminders".to_string(),
+            Job::Export => "export".to_string(),
+            Job::Search(name) => format!("search {}", name),
+        }
+    }
+}
+
+// Run the configured schedules until interrupted. With a --group, fetches
+// keep and leads are generated for that [[keyword_groups]] entry only.
+pub async fn run(group: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut watcher = reload::ConfigWatcher::new()?;
+    let config = settings::api_keys::ConfigDirs::read_config()?;
+    let mut jobs = scheduled_jobs(&config)?;
+
+    if jobs.is_empty() {
+        return Err(
+            "No schedules configured, set fetch, leads or export under [schedule] or add [[searches]]"
+                .into(),
+        );
+    }
+
+    verbosity::info(format!("Watching with {} scheduled job(s):", jobs.len()));
+    print_jobs(&jobs);
+
+    loop {
+        // Wake up at the start of every minute
+        let now = Local::now();
+        let wait = 60 - now.second() as u64;
+        if !interrupt::sleep(Duration::from_secs(wait)).await {
+            verbosity::info("Stopped watching");
+            return Ok(());
+        }
+
+        let now = Local::now();
+
+        // Jobs re-read the settings on every run, new schedules and searches
+        // take effect here
+        if let Some(changed) = watcher.changed() {
+            match changed.and_then(|config| scheduled_jobs(&config)) {
+                Ok(reloaded) if reloaded.is_empty() => eprintln!(
+                    "[{}] settings.toml has no schedules left, keeping the current ones",
+                    now.format("%Y-%m-%d %H:%M")
+                ),
+                Ok(reloaded) => {
+                    jobs = reloaded;
+                    verbosity::info(format!(
+                        "[{}] Reloaded settings.toml, {} scheduled job(s):",
+                        now.format("%Y-%m-%d %H:%M"),
+                        jobs.len()
+                    ));
+                    print_jobs(&jobs);
+                }
+                Err(e) => eprintln!(
+                    "[{}] Ignoring the changed settings.toml, keeping the current schedules: {}",
+                    now.format("%Y-%m-%d %H:%M"),
+                    e
+                ),
+            }
+        }
+
+        retry_due_jobs(group).await;
+
+        for (job, schedule) in &jobs {
+            if schedule.matches(&now) {
+                verbosity::info(format!(
+                    "[{}] Running {}",
+                    now.format("%Y-%m-%d %H:%M"),
+                    job.name()
+                ));
+                if let Err(e) = run_job(job, group).await {
+                    eprintln!(
+                        "[{}] {} failed: {}",
+                        now.format("%Y-%m-%d %H:%M"),
+                        job.name(),
+                        redact(&e.to_string())
+                    );
+                }
+
+                // Thresholds are checked against the freshly stored data
+                let checked = match settings::api_keys::ConfigDirs::read_config() {
+                    Ok(config) => alerts::check_alerts(&config).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = checked {
+                    eprintln!(
+                        "[{}] Alert check failed: {}",
+                        now.format("%Y-%m-%d %H:%M"),
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn print_jobs(jobs: &[(Job, CronSchedule)]) {
+    for (job, schedule) in jobs {
+        verbosity::info(format!("  {:<16} {}", job.name(), schedule.expression()));
+    }
+}
+
+fn scheduled_jobs(config: &AppConfig) -> Result<Vec<(Job, CronSchedule)>, String> {
+    let mut jobs = Vec::new();
+
+    for (job, expression) in [
+        (Job::Fetch, &config.schedule.fetch),
+        (Job::Leads, &config.schedule.leads),
+        (Job::Reminders, &config.schedule.reminders),
+        (Job::Export, &config.schedule.export),
+    ] {
+        if !expression.trim().is_empty() {
+            jobs.push((job, CronSchedule::parse(expression)?));
+        }
+    }
+
+    if !config.schedule.export.trim().is_empty() {
+        exports::export_format(config)?;
+    }
+
+    // Searches without a schedule of their own follow the fetch
+    for search in &config.searches {
+        let expression = if search.schedule.trim().is_empty() {
+            &config.schedule.fetch
+        } else {
+            &search.schedule
+        };
+        if expression.trim().is_empty() {
+            return Err(format!(
+                "Saved search '{}' has no schedule and [schedule] has no fetch to follow",
+                search.name
+            ));
+        }
+        let schedule = CronSchedule::parse(expression)
+            .map_err(|e| format!("Schedule of saved search '{}': {}", search.name, e))?;
+        jobs.push((Job::Search(search.name.clone()), schedule));
+    }
+
+    Ok(jobs)
+}
+
+async fn run_job(job: &Job, group: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    // Re-read the config so each run picks up the current settings
+    let config = settings::api_keys::ConfigDirs::read_config()?;
+    let filter = group_filter(&config, group)?;
+    // Only while the job runs, so ad-hoc commands get their turn between jobs
+    let _lock = if database::adding::is_ephemeral() {
+        None
+    } else {
+        Some(database::lock::WriteLock::acquire(&format!(
+            "watch {}",
+            job.name()
+        ))?)
+    };
+    let db = DB::new()?;
+    database::runs::begin("watch", &job.name());
+
+    match job {
+        Job::Fetch => {
+            let token = reddit_token(&config).await?;
+            let mut pool = AccountPool::with_primary(token, &config).await;
+
+            let (summary, failures) = crate::fetch_subreddit_with_comments(
+                &mut pool,
+                &config.api_keys.subreddit,
+                &config.api_keys.relevance,
+                &filter,
+                &CommentOptions::from_config(&config),
+            )
+            .await?;
+
+            summary.print();
+            db.record_run_summary(&summary)?;
+            crate::elastic::push_after_fetch(&config).await;
+
+            for (comment_job, error) in failures {
+                let payload = serde_json::to_string(&comment_job)?;
+                db.enqueue_job(COMMENTS_JOB, &payload, &error)?;
+            }
+        }
+        Job::Leads => {
+            if let Err(e) = ai::gemini::gemini_generate_leads(&filter, true).await {
+                db.enqueue_job(LEADS_JOB, "", &e.to_string())?;
+                return Err(e.into());
+            }
+        }
+        Job::Reminders => {
+            let reminded =
+                actions::leads::remind_stale_leads(&config, config.leads.stale_days).await?;
+            verbosity::info(format!("Reminded about {} stale HIGH leads", reminded));
+        }
+        Job::Export => exports::run_export(&config, &db)?,
+        Job::Search(name) => {
+            let search = config
+                .searches
+                .iter()
+                .find(|search| &search.name == name)
+                .ok_or_else(|| format!("Saved search '{}' is no longer configured", name))?;
+            searches::run_search(&config, search).await?;
+        }
+    }
+
+    crate::publish::publish_after_run(&config).await;
+    Ok(())
+}
+
+// Posts of the watched keyword group, all posts without one
+fn group_filter(config: &AppConfig, group: Option<&str>) -> Result<PostFilter, String> {
+    let Some(name) = group else {
+        return Ok(PostFilter::default());
+    };
+    let group = config.keyword_group(Some(name))?;
+    Ok(PostFilter {
+        keywords: Some(KeywordMatcher::from_group(&group, &config.matching)?),
+        ..Default::default()
+    })
+}
+
+async fn reddit_token(config: &AppConfig) -> Result<String, String> {
+    crate::get_access_token(
+        config.api_keys.reddit_api_id.clone(),
+        config.api_keys.reddit_api_secret.clone(),
+    )
+    .await
+    .map_err(|e| format!("Failed to retrieve access token: {}", e))
+}
+
+// Retry queued comment fetches and AI calls whose backoff has elapsed
+async fn retry_due_jobs(group: Option<&str>) {
+    let queued = match DB::new().and_then(|db| db.get_due_jobs()) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            eprintln!("Failed to read the job queue: {}", e);
+            return;
+        }
+    };
+
+    if queued.is_empty() {
+        return;
+    }
+
+    verbosity::info(format!("Retrying {} queued job(s)", queued.len()));
+    for queued_job in queued {
+        let result = retry_job(&queued_job, group).await;
+
+        let Ok(db) = DB::new() else {
+            continue;
+        };
+        let update = match result {
+            Ok(()) => db.complete_job(queued_job.id),
+            Err(e) => {
+                eprintln!(
+                    "Retry {} of {} job failed: {}",
+                    queued_job.attempts, queued_job.kind, e
+                );
+                db.retry_job(&queued_job, &e)
+            }
+        };
+        if let Err(e) = update {
+            eprintln!("Failed to update job {}: {}", queued_job.id, e);
+        }
+    }
+}
+
+async fn retry_job(queued_job: &JobDataWrapper, group: Option<&str>) -> Result<(), String> {
+    match queued_job.kind.as_str() {
+        COMMENTS_JOB => {
+            let comment_job: CommentJob =
+                serde_json::from_str(&queued_job.payload).map_err(|e| e.to_string())?;
+            let config =
+                settings::api_keys::ConfigDirs::read_config().map_err(|e| e.to_string())?;
+            let token = reddit_token(&config).await?;
+            let mut db = DB::new().map_err(|e| e.to_string())?;
+
+            crate::fetch_post_comments_into_db(
+                &token,
+                &mut db,
+                &comment_job,
+                &CommentOptions::from_config(&config),
+                None,
+                true,
+            )
+            .await
+            .map(|fetch| {
+                if let crate::CommentsFetch::Unavailable(reason) = fetch {
+                    verbosity::info(format!(
+                        "No comments for '{}': {}",
+                        comment_job.post_title, reason
+                    ));
+                }
+            })
+            .map_err(|e| format!("Failed to retrieve comments: {}", e))
+        }
+        LEADS_JOB => {
+            let config =
+                settings::api_keys::ConfigDirs::read_config().map_err(|e| e.to_string())?;
+            ai::gemini::gemini_generate_leads(&group_filter(&config, group)?, true)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        other => Err(format!("Unknown job kind '{}'", other)),
+    }
+}
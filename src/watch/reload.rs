@@ -0,0 +1,33 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::settings::api_keys::AppConfig;
+use crate::settings::paths::Paths;
+
+// Notices edits to settings.toml between two cycles of `ruddit watch`. The
+// content is compared rather than the modification time, so editors that
+// save through a temporary file and `touch` without a change are handled.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    content: Option<String>,
+}
+
+impl ConfigWatcher {
+    pub fn new() -> Result<ConfigWatcher, String> {
+        let path = Paths::get()?.config_file();
+        let content = fs::read_to_string(&path).ok();
+        Ok(ConfigWatcher { path, content })
+    }
+
+    // The new configuration when the file changed since the last call. A file
+    // that no longer parses is reported once and the current one kept.
+    pub fn changed(&mut self) -> Option<Result<AppConfig, String>> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        if self.content.as_ref() == Some(&content) {
+            return None;
+        }
+        self.content = Some(content);
+        let content = self.content.as_deref().unwrap_or_default();
+        Some(toml::from_str(content).map_err(|e| e.to_string()))
+    }
+}
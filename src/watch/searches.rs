@@ -0,0 +1,81 @@
+use crate::actions::outcome::RunSummary;
+use crate::actions::verbosity;
+use crate::database::adding::DB;
+use crate::database::queries::PostFilter;
+use crate::settings::api_keys::{AppConfig, SearchSettings};
+use crate::{database, notify, reddit};
+
+// Posts listed in a saved search notification, the rest are counted
+const NOTIFIED_POSTS: usize = 10;
+
+// Run one saved search: store the posts newer than its cursor, tag them with
+// the search name and announce them
+pub async fn run_search(
+    config: &AppConfig,
+    search: &SearchSettings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut db = DB::new()?;
+    db.create_tables()?;
+    let state = db.get_search_state(&search.name)?;
+
+    let token = super::reddit_token(config).await?;
+    let subreddit = (!search.subreddit.trim().is_empty()).then_some(search.subreddit.as_str());
+    let (mut posts, mut media) = crate::search_subreddit_posts(
+        &token,
+        reddit::search_new(&search.query, subreddit),
+        "search",
+    )
+    .await?;
+    crate::actions::display::print_fetched(&posts);
+
+    // Only what appeared since the last run counts as new
+    let filter = PostFilter {
+        since: (state.cursor > 0).then_some(state.cursor + 1),
+        questions_only: search.questions_only,
+        ..Default::default()
+    };
+    let fetched = posts.len();
+    crate::retain_matching(&filter, &mut posts, &mut media);
+
+    db.append_results(&posts)?;
+    db.append_media(&media)?;
+    db.record_sync(&format!("search: {}", search.name), posts.len())?;
+    let tagged = db.record_search_run(&search.name, &posts)?;
+
+    let mut summary = RunSummary::default();
+    summary.add(fetched, tagged);
+    db.record_run_summary(&summary)?;
+    verbosity::info(format!(
+        "Search '{}' found {} posts, {} new",
+        search.name, fetched, tagged
+    ));
+    crate::elastic::push_after_fetch(config).await;
+
+    if search.notify && tagged > 0 {
+        let mut notifications = config.notifications.clone();
+        if !search.webhook_url.trim().is_empty() {
+            notifications.webhook_url = search.webhook_url.trim().to_string();
+        }
+        notify::send(&notifications, &message(search, &posts)).await;
+    }
+
+    Ok(())
+}
+
+fn message(search: &SearchSettings, posts: &[database::adding::PostDataWrapper]) -> String {
+    let mut lines = vec![format!(
+        "Saved search '{}': {} new posts",
+        search.name,
+        posts.len()
+    )];
+    for post in posts.iter().take(NOTIFIED_POSTS) {
+        lines.push(format!(
+            "- r/{} {} {}",
+            post.subreddit, post.title, post.permalink
+        ));
+    }
+    if posts.len() > NOTIFIED_POSTS {
+        lines.push(format!("and {} more", posts.len() - NOTIFIED_POSTS));
+    }
+    lines.join("\n")
+}